@@ -0,0 +1,191 @@
+//! Pane monitors: alerts when a pane in the background prints, goes quiet,
+//! rings the bell or prints a line matching a pattern, shown on its border
+//! and optionally as a desktop notification.
+
+use super::{FocusArea, RgbApp};
+use crate::config::{DesktopNotify, MonitorConfig};
+use crate::ui::components::NotificationLevel;
+use crate::workspace::{Alert, AlertKind, PaneMonitor, TerminalId};
+use anyhow::Result;
+use regex::Regex;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Output lines searched for watched patterns
+const MATCH_SCAN_LINES: usize = 50;
+/// Silence watched by `:monitor silence` without a number
+const DEFAULT_SILENCE: Duration = Duration::from_secs(30);
+
+impl RgbApp {
+    /// Check every pane against its monitors and raise what is new. The
+    /// pane in front is never alerted, and looking at it clears its alert.
+    pub(super) fn check_monitors(&mut self) {
+        // Panes of every workspace, so parked ones alert too
+        let panes: Vec<_> = self
+            .workspaces()
+            .flat_map(|w| w.terminals().into_iter().filter_map(|t| Some((w.get_terminal_emulator(t.id)?, t))))
+            .collect();
+        self.monitors.retain(|id, _| panes.iter().any(|(_, t)| t.id == *id));
+        let in_front = self.workspace.active_terminal_id().filter(|_| self.focus == FocusArea::Terminal);
+
+        let mut raised = Vec::new();
+        for (emulator, terminal) in &panes {
+            let monitor = self
+                .monitors
+                .entry(terminal.id)
+                .or_insert_with(|| default_monitor(&self.config.monitor));
+            let emulator = emulator.read();
+            let alert = monitor.check(emulator.last_output(), emulator.last_bell(), || {
+                emulator.recent_output(MATCH_SCAN_LINES)
+            });
+            if in_front == Some(terminal.id) {
+                monitor.acknowledge();
+            } else if let Some(alert) = alert {
+                raised.push((terminal.title.clone(), alert));
+            }
+        }
+
+        for (title, alert) in raised {
+            self.announce(&title, &alert);
+        }
+        self.ui.set_alerts(
+            self.monitors
+                .iter()
+                .filter_map(|(id, m)| Some((*id, m.alert.as_ref()?.kind)))
+                .collect(),
+        );
+        self.refresh_tabs();
+    }
+
+    fn announce(&mut self, title: &str, alert: &Alert) {
+        let level = match alert.kind {
+            AlertKind::Activity | AlertKind::Silence => NotificationLevel::Info,
+            AlertKind::Bell | AlertKind::Match => NotificationLevel::Warning,
+        };
+        self.ui.notify(level, format!("{}: {}", title, alert.detail));
+
+        if let Some(sequence) = desktop_notification(self.config.monitor.desktop, title, &alert.detail) {
+            let mut stdout = io::stdout();
+            if let Err(e) = stdout.write_all(sequence.as_bytes()).and_then(|_| stdout.flush()) {
+                tracing::debug!("Failed to send a desktop notification: {}", e);
+            }
+        }
+    }
+
+    /// Warn about configured patterns that aren't valid regexes
+    pub(super) fn check_monitor_patterns(&mut self) {
+        for pattern in &self.config.monitor.patterns {
+            if let Err(e) = Regex::new(pattern) {
+                self.ui.notify(
+                    NotificationLevel::Warning,
+                    format!("Ignoring monitor pattern {}: {}", pattern, e),
+                );
+            }
+        }
+    }
+
+    /// `:monitor [activity|silence [secs]|bell|match <regex>|off]` on the
+    /// active pane
+    pub(super) fn run_monitor_command(&mut self, args: &[String]) -> Result<()> {
+        let Some(id) = self.workspace.active_terminal_id() else {
+            anyhow::bail!("No active pane");
+        };
+        let monitor = self
+            .monitors
+            .entry(id)
+            .or_insert_with(|| default_monitor(&self.config.monitor));
+
+        match args {
+            [] => {}
+            [kind] if kind == "activity" => monitor.activity = !monitor.activity,
+            [kind] if kind == "bell" => monitor.bell = !monitor.bell,
+            [kind] if kind == "silence" => {
+                monitor.silence = match monitor.silence {
+                    Some(_) => None,
+                    None => Some(DEFAULT_SILENCE),
+                };
+            }
+            [kind, seconds] if kind == "silence" => {
+                let seconds: u64 = seconds
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Not a number of seconds: {}", seconds))?;
+                monitor.silence = (seconds > 0).then(|| Duration::from_secs(seconds));
+            }
+            [kind, pattern @ ..] if kind == "match" && !pattern.is_empty() => {
+                let pattern = pattern.join(" ");
+                match monitor.patterns.iter().position(|p| p.as_str() == pattern) {
+                    Some(index) => {
+                        monitor.patterns.remove(index);
+                    }
+                    None => monitor.patterns.push(Regex::new(&pattern)?),
+                }
+            }
+            [kind] if kind == "off" => *monitor = PaneMonitor::default(),
+            _ => anyhow::bail!("Usage: :monitor [activity|silence [secs]|bell|match <regex>|off]"),
+        }
+        self.ui.show_status(&format!("Monitoring {}", monitor.describe()));
+        Ok(())
+    }
+
+    /// Focus the next pane with an alert, after the active one, looking in
+    /// the other workspaces once this one has none
+    pub(super) fn focus_next_alert(&mut self) -> Result<()> {
+        let terminals = self.workspace.terminals();
+        let start = self
+            .workspace
+            .active_terminal_id()
+            .and_then(|id| terminals.iter().position(|t| t.id == id))
+            .map_or(0, |i| i + 1);
+        let alerted = |id: &TerminalId| self.monitors.get(id).is_some_and(|m| m.alert.is_some());
+        let next = (0..terminals.len())
+            .map(|offset| terminals[(start + offset) % terminals.len()].id)
+            .find(alerted)
+            .or_else(|| {
+                self.workspaces()
+                    .skip(1)
+                    .flat_map(|w| w.terminals())
+                    .map(|t| t.id)
+                    .find(alerted)
+            });
+        let Some(id) = next else {
+            anyhow::bail!("No pane has an alert");
+        };
+        if let Some(tab) = self.tab_of(id) {
+            self.switch_workspace(tab);
+        }
+        self.workspace.set_active_terminal(id);
+        self.focus = FocusArea::Terminal;
+        Ok(())
+    }
+}
+
+/// Monitors a new pane starts with; patterns that don't compile are
+/// reported once at startup and skipped
+fn default_monitor(config: &MonitorConfig) -> PaneMonitor {
+    let mut monitor = PaneMonitor::default();
+    monitor.activity = config.activity;
+    monitor.silence = (config.silence > 0).then(|| Duration::from_secs(config.silence));
+    monitor.bell = config.bell;
+    monitor.patterns = config.patterns.iter().filter_map(|p| Regex::new(p).ok()).collect();
+    monitor
+}
+
+/// Escape sequence asking the host terminal for a desktop notification,
+/// wrapped for passthrough when running inside tmux
+fn desktop_notification(kind: DesktopNotify, title: &str, body: &str) -> Option<String> {
+    // Control characters would end the sequence early
+    let clean = |text: &str| -> String { text.chars().filter(|c| !c.is_control()).collect() };
+    let sequence = match kind {
+        DesktopNotify::Off => return None,
+        DesktopNotify::Osc9 => format!("\x1b]9;rgb: {}: {}\x07", clean(title), clean(body)),
+        DesktopNotify::Osc777 => {
+            // Fields are separated by semicolons
+            format!("\x1b]777;notify;rgb: {};{}\x07", clean(title).replace(';', ","), clean(body))
+        }
+    };
+    if std::env::var_os("TMUX").is_some() {
+        Some(format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b")))
+    } else {
+        Some(sequence)
+    }
+}
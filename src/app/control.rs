@@ -0,0 +1,320 @@
+//! Answers to control socket requests; see [`crate::control`]. Panes are
+//! named by id, 1-based number or title, like `:focus`.
+
+use super::{FocusArea, RgbApp};
+use crate::command::parse_key;
+use crate::control::{
+    LayoutInfo, PaneContent, PaneInfo, RpcError, WorktreeSummary, INVALID_PARAMS, METHOD_NOT_FOUND,
+    NO_SUCH_PANE,
+};
+use crate::git::{MergeStatus, WorktreeDisposition};
+use crate::layout::LayoutEngine;
+use crate::monitor::Signal;
+use crate::workspace::{TerminalId, TerminalInfo};
+use crossterm::event::KeyEvent;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct CreateParams {
+    command: Option<String>,
+    /// Run in this directory instead of a new worktree
+    cwd: Option<PathBuf>,
+    /// Attach to this existing worktree or branch
+    branch: Option<String>,
+    focus: bool,
+}
+
+#[derive(Deserialize)]
+struct PaneParams {
+    pane: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct CloseParams {
+    pane: Option<Value>,
+    /// keep, stash, commit or discard; without it the worktree is only
+    /// removed if that loses no work
+    worktree: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SignalParams {
+    pane: Option<Value>,
+    /// INT, TERM, KILL, STOP or CONT
+    signal: String,
+    /// Every process of the pane rather than its foreground job
+    #[serde(default)]
+    tree: bool,
+}
+
+#[derive(Deserialize)]
+struct TextParams {
+    pane: Option<Value>,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct KeysParams {
+    pane: Option<Value>,
+    keys: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ReadParams {
+    pane: Option<Value>,
+    /// Return this many lines of recent output instead of the screen
+    scrollback: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct LayoutParams {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CommandParams {
+    line: String,
+}
+
+impl RgbApp {
+    /// Answer one control request
+    pub(super) async fn handle_control(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "ping" => Ok(json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "pid": std::process::id(),
+                "socket": self.control.as_ref().map(|c| c.path().display().to_string()),
+                "project_dir": self.workspace.project_dir().display().to_string(),
+            })),
+            "pane.list" => {
+                let panes: Vec<PaneInfo> = self
+                    .workspace
+                    .terminals()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| self.pane_info(i, t))
+                    .collect();
+                to_value(panes)
+            }
+            "pane.create" => {
+                let params: CreateParams = parse(params)?;
+                let id = match (params.branch, params.cwd) {
+                    (Some(branch), _) => self.workspace.attach_terminal(&branch, params.command, None).await?,
+                    (None, Some(cwd)) => self.workspace.create_terminal_in(cwd, params.command).await?,
+                    (None, None) => self.workspace.create_terminal(params.command, None).await?,
+                };
+                if params.focus {
+                    self.workspace.set_active_terminal(id);
+                    self.focus = FocusArea::Terminal;
+                }
+                to_value(self.pane(id)?)
+            }
+            "pane.close" => {
+                let params: CloseParams = parse(params)?;
+                let id = self.resolve_pane(params.pane.as_ref())?;
+                match params.worktree.as_deref() {
+                    None => self.workspace.close_terminal(id).await?,
+                    Some(choice) => {
+                        let disposition = match choice {
+                            "keep" => WorktreeDisposition::Keep,
+                            "stash" => WorktreeDisposition::Stash,
+                            "commit" => WorktreeDisposition::Commit,
+                            "discard" => WorktreeDisposition::Discard,
+                            _ => return Err(RpcError::invalid_params(format!("Unknown worktree choice: {}", choice))),
+                        };
+                        self.workspace.close_terminal_with(id, disposition).await?;
+                    }
+                }
+                self.after_terminal_closed().await?;
+                Ok(Value::Null)
+            }
+            "pane.focus" => {
+                let params: PaneParams = parse(params)?;
+                let id = self.resolve_pane(params.pane.as_ref())?;
+                self.workspace.set_active_terminal(id);
+                self.focus = FocusArea::Terminal;
+                to_value(self.pane(id)?)
+            }
+            "pane.send_text" => {
+                let params: TextParams = parse(params)?;
+                let id = self.resolve_pane(params.pane.as_ref())?;
+                self.workspace.send_input(id, params.text.as_bytes())?;
+                Ok(Value::Null)
+            }
+            "pane.send_keys" => {
+                let params: KeysParams = parse(params)?;
+                let id = self.resolve_pane(params.pane.as_ref())?;
+                let keys = params
+                    .keys
+                    .iter()
+                    .map(|k| {
+                        parse_key(k)
+                            .map(|(code, modifiers)| KeyEvent::new(code, modifiers))
+                            .ok_or_else(|| RpcError::invalid_params(format!("Unknown key: {}", k)))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let emulator = self.workspace.get_terminal_emulator(id).ok_or_else(|| no_such_pane(id))?;
+                let mut emulator = emulator.write();
+                for key in keys {
+                    emulator.handle_key_event(key)?;
+                }
+                Ok(Value::Null)
+            }
+            "pane.read" => {
+                let params: ReadParams = parse(params)?;
+                let id = self.resolve_pane(params.pane.as_ref())?;
+                let emulator = self.workspace.get_terminal_emulator(id).ok_or_else(|| no_such_pane(id))?;
+                let emulator = emulator.read();
+                let content = match params.scrollback {
+                    Some(count) => PaneContent {
+                        id: id.to_string(),
+                        lines: emulator.recent_output(count),
+                        cursor: None,
+                    },
+                    None => {
+                        let (column, row) = emulator.get_cursor_position();
+                        PaneContent {
+                            id: id.to_string(),
+                            lines: emulator
+                                .get_visible_content()
+                                .iter()
+                                .map(|l| l.trim_end().to_string())
+                                .collect(),
+                            cursor: Some([row, column]),
+                        }
+                    }
+                };
+                to_value(content)
+            }
+            "pane.signal" => {
+                let params: SignalParams = parse(params)?;
+                let id = self.resolve_pane(params.pane.as_ref())?;
+                let signal = Signal::parse(&params.signal)
+                    .ok_or_else(|| RpcError::invalid_params(format!("Unknown signal: {}", params.signal)))?;
+                let message = self.signal_pane(id, signal, params.tree)?;
+                Ok(json!({ "message": message }))
+            }
+            "layout.get" => to_value(self.layout_info()),
+            "layout.set" => {
+                let params: LayoutParams = parse(params)?;
+                self.layout
+                    .apply_layout(&params.name)
+                    .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+                to_value(self.layout_info())
+            }
+            "worktree.list" => {
+                let worktrees: Vec<WorktreeSummary> = self
+                    .workspace
+                    .git_manager()
+                    .worktree_overviews()
+                    .into_iter()
+                    .map(|o| WorktreeSummary {
+                        pane: o.info.terminal_id.to_string(),
+                        name: o.info.name,
+                        branch: o.info.branch,
+                        path: o.info.path.display().to_string(),
+                        ahead: o.info.ahead,
+                        behind: o.info.behind,
+                        dirty_files: o.dirty_files,
+                        merge_status: merge_status_name(&o.info.merge_status).to_string(),
+                        last_commit: o.last_commit,
+                    })
+                    .collect();
+                to_value(worktrees)
+            }
+            "command.run" => {
+                let params: CommandParams = parse(params)?;
+                self.execute_command(&params.line).await?;
+                Ok(Value::Null)
+            }
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+        }
+    }
+
+    /// The pane `pane` names; the active one when it is absent
+    fn resolve_pane(&self, pane: Option<&Value>) -> Result<TerminalId, RpcError> {
+        let terminals = self.workspace.terminals();
+        let found = match pane {
+            None | Some(Value::Null) => self.workspace.active_terminal_id(),
+            Some(Value::Number(n)) => n
+                .as_u64()
+                .and_then(|n| terminals.get((n as usize).wrapping_sub(1)))
+                .map(|t| t.id),
+            Some(Value::String(name)) => match name.parse::<usize>() {
+                Ok(n) => terminals.get(n.wrapping_sub(1)).map(|t| t.id),
+                Err(_) => terminals
+                    .iter()
+                    .find(|t| t.id.to_string() == *name || t.title.eq_ignore_ascii_case(name))
+                    .map(|t| t.id),
+            },
+            Some(other) => return Err(RpcError::new(INVALID_PARAMS, format!("Not a pane: {}", other))),
+        };
+        found.ok_or_else(|| RpcError::new(NO_SUCH_PANE, format!("No pane {}", pane.unwrap_or(&Value::Null))))
+    }
+
+    fn pane(&self, id: TerminalId) -> Result<PaneInfo, RpcError> {
+        self.workspace
+            .terminals()
+            .iter()
+            .enumerate()
+            .find(|(_, t)| t.id == id)
+            .map(|(i, t)| self.pane_info(i, t))
+            .ok_or_else(|| no_such_pane(id))
+    }
+
+    fn pane_info(&self, index: usize, terminal: &TerminalInfo) -> PaneInfo {
+        PaneInfo {
+            id: terminal.id.to_string(),
+            index: index + 1,
+            title: terminal.title.clone(),
+            working_dir: terminal.working_dir.display().to_string(),
+            branch: terminal.branch.clone(),
+            worktree: terminal.has_worktree,
+            active: self.workspace.active_terminal_id() == Some(terminal.id),
+            alive: self
+                .workspace
+                .get_terminal_emulator(terminal.id)
+                .is_some_and(|e| e.read().is_alive()),
+            alert: self
+                .monitors
+                .get(&terminal.id)
+                .and_then(|m| m.alert.as_ref())
+                .map(|a| a.kind.label().to_string()),
+            synchronized: terminal.synchronized,
+            cpu_percent: self.usage.get(&terminal.id).map_or(0.0, |u| u.cpu_percent),
+            rss_bytes: self.usage.get(&terminal.id).map_or(0, |u| u.rss_bytes),
+        }
+    }
+
+    fn layout_info(&self) -> LayoutInfo {
+        LayoutInfo {
+            name: self.layout.layout_name().to_string(),
+            available: LayoutEngine::LAYOUT_NAMES.iter().map(|n| n.to_string()).collect(),
+        }
+    }
+}
+
+fn merge_status_name(status: &MergeStatus) -> &'static str {
+    match status {
+        MergeStatus::Unmerged => "unmerged",
+        MergeStatus::Merged => "merged",
+        MergeStatus::Conflict { .. } => "conflict",
+    }
+}
+
+fn no_such_pane(id: TerminalId) -> RpcError {
+    RpcError::new(NO_SUCH_PANE, format!("No pane {}", id))
+}
+
+fn parse<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    // Methods without parameters accept them being left out entirely
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::invalid_params(e.to_string()))
+}
+
+fn to_value(value: impl Serialize) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::from(anyhow::Error::from(e)))
+}
@@ -0,0 +1,227 @@
+//! Opening files in one editor pane per worktree.
+
+use crate::workspace::TerminalId;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Longest wait for an editor server to take a file
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorKind {
+    Neovim,
+    Vim,
+    Other,
+}
+
+/// An editor running in a pane, and how to reach it
+#[derive(Debug, Clone)]
+pub struct EditorPane {
+    pub terminal_id: TerminalId,
+    pub kind: EditorKind,
+    /// Neovim `--listen` socket, or Vim `--servername`
+    pub server: Option<String>,
+}
+
+/// Whether this vim can take `--remote-send`, which needs an X server;
+/// set by [`probe_vim`]
+static VIM_CLIENTSERVER: OnceLock<bool> = OnceLock::new();
+
+/// Find out in the background whether vim has a client-server, when
+/// `editor` is vim. Until it is known vim panes start without a server and
+/// files are typed into them.
+pub fn probe_vim(editor: &str) {
+    if kind_of(editor) != EditorKind::Vim || VIM_CLIENTSERVER.get().is_some() {
+        return;
+    }
+    tokio::task::spawn_blocking(|| {
+        let supported = std::env::var_os("DISPLAY").is_some()
+            && Command::new("vim")
+                .arg("--version")
+                .stdin(Stdio::null())
+                .output()
+                .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).contains("+clientserver"));
+        let _ = VIM_CLIENTSERVER.set(supported);
+    });
+}
+
+/// The editor to start: the configured command, then `$EDITOR`, then `vi`
+pub fn editor_command(configured: &str) -> String {
+    if !configured.trim().is_empty() {
+        return configured.to_string();
+    }
+    std::env::var("EDITOR")
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+pub fn kind_of(command: &str) -> EditorKind {
+    let program = command.split_whitespace().next().unwrap_or_default();
+    match Path::new(program).file_name().and_then(|n| n.to_str()) {
+        Some("nvim") => EditorKind::Neovim,
+        Some("vi" | "vim") => EditorKind::Vim,
+        _ => EditorKind::Other,
+    }
+}
+
+/// Command line that starts `editor` on a file, listening for later files
+/// when the editor supports it. Returns the command and the server name.
+pub fn launch(editor: &str, path: &Path, line: Option<usize>) -> (String, Option<String>) {
+    let name = format!("rgb-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+    let server = match kind_of(editor) {
+        EditorKind::Neovim => Some(std::env::temp_dir().join(format!("{}.sock", name)).display().to_string()),
+        EditorKind::Vim if VIM_CLIENTSERVER.get() == Some(&true) => Some(name),
+        _ => None,
+    };
+
+    let mut command = editor.to_string();
+    match (kind_of(editor), &server) {
+        (EditorKind::Neovim, Some(socket)) => command.push_str(&format!(" --listen {}", shell_quote(socket))),
+        (EditorKind::Vim, Some(name)) => command.push_str(&format!(" --servername {}", name)),
+        _ => {}
+    }
+    if let Some(line) = line {
+        command.push_str(&format!(" +{}", line));
+    }
+    command.push_str(&format!(" {}", shell_quote(&path.display().to_string())));
+    (command, server)
+}
+
+/// Open a file in a running editor through its server. False if the editor
+/// has no server or it didn't answer in time.
+pub async fn open_remote(pane: &EditorPane, path: &Path, line: Option<usize>, column: Option<usize>) -> bool {
+    let Some(server) = &pane.server else {
+        return false;
+    };
+    let (program, flag) = match pane.kind {
+        EditorKind::Neovim => ("nvim", "--server"),
+        EditorKind::Vim => ("vim", "--servername"),
+        EditorKind::Other => return false,
+    };
+
+    // In key notation `<` would start a key name
+    let mut keys = format!("<C-\\><C-N>:edit {}<CR>", fnameescape(path).replace('<', "<lt>"));
+    if let Some(line) = line {
+        keys.push_str(&format!(":call cursor({}, {})<CR>", line, column.unwrap_or(1)));
+    }
+
+    let status = tokio::process::Command::new(program)
+        .args([flag, server, "--remote-send", &keys])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .status();
+    tokio::time::timeout(REMOTE_TIMEOUT, status)
+        .await
+        .is_ok_and(|status| status.is_ok_and(|status| status.success()))
+}
+
+/// Keys that open a file in a vi-style editor by typing into its pane
+pub fn typed_keys(path: &Path, line: Option<usize>, column: Option<usize>) -> String {
+    let mut keys = format!("\x1b:edit {}\r", fnameescape(path));
+    if let Some(line) = line {
+        keys.push_str(&format!(":call cursor({}, {})\r", line, column.unwrap_or(1)));
+    }
+    keys
+}
+
+/// Run the configured open command with `{file}`, `{line}` and `{column}`
+/// filled in, without waiting for it
+pub fn run_open_command(
+    template: &str,
+    path: &Path,
+    line: Option<usize>,
+    column: Option<usize>,
+    dir: &Path,
+) -> std::io::Result<()> {
+    let command = template
+        .replace("{file}", &shell_quote(&path.display().to_string()))
+        .replace("{line}", &line.unwrap_or(1).to_string())
+        .replace("{column}", &column.unwrap_or(1).to_string());
+    Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+/// Where to edit `path` when the active pane works in `active_worktree`.
+/// Paths already inside a worktree stay there; project paths map to the same
+/// file in the active worktree. Returns the editor's root and the file.
+pub fn resolve(
+    path: &Path,
+    project_dir: &Path,
+    worktrees: &[PathBuf],
+    active_worktree: Option<&Path>,
+) -> (PathBuf, PathBuf) {
+    if let Some(worktree) = worktrees
+        .iter()
+        .filter(|w| path.starts_with(w))
+        .max_by_key(|w| w.components().count())
+    {
+        return (worktree.clone(), path.to_path_buf());
+    }
+
+    if let (Some(worktree), Ok(relative)) = (active_worktree, path.strip_prefix(project_dir)) {
+        let mapped = worktree.join(relative);
+        if mapped.exists() || mapped.parent().is_some_and(Path::exists) {
+            return (worktree.to_path_buf(), mapped);
+        }
+    }
+    (project_dir.to_path_buf(), path.to_path_buf())
+}
+
+/// `path` as the file argument of `:edit`, escaped as Vim's `fnameescape()`
+/// does so `%`, `#`, `|` and the like are taken literally
+fn fnameescape(path: &Path) -> String {
+    let path = path.display().to_string();
+    let mut escaped = String::with_capacity(path.len());
+    if path.starts_with(['+', '>']) || path == "-" {
+        escaped.push('\\');
+    }
+    for c in path.chars() {
+        if " \t\n*?[{`$\\%#'\"|!<".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_are_escaped_like_fnameescape() {
+        assert_eq!(fnameescape(Path::new("/src/main.rs")), "/src/main.rs");
+        assert_eq!(fnameescape(Path::new("/src/a%b.rs")), r"/src/a\%b.rs");
+        assert_eq!(fnameescape(Path::new("/notes/x#1.md")), r"/notes/x\#1.md");
+        assert_eq!(fnameescape(Path::new("/my file|rm")), r"/my\ file\|rm");
+        assert_eq!(fnameescape(Path::new(r#"/a"b\c"#)), r#"/a\"b\\c"#);
+        assert_eq!(fnameescape(Path::new("/what?[x]*")), r"/what\?\[x]\*");
+        assert_eq!(fnameescape(Path::new("+cmd")), r"\+cmd");
+        assert_eq!(fnameescape(Path::new("-")), r"\-");
+    }
+
+    #[test]
+    fn typed_keys_edit_the_escaped_file() {
+        assert_eq!(typed_keys(Path::new("/src/a%b.rs"), None, None), "\x1b:edit /src/a\\%b.rs\r");
+        assert_eq!(
+            typed_keys(Path::new("/x#1.md"), Some(3), None),
+            "\x1b:edit /x\\#1.md\r:call cursor(3, 1)\r"
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! `:fanout`: one task handed to several agents at once, each in a
+//! worktree of its own forked from the same commit, and the board that
+//! compares them and merges the winner.
+
+use super::editor::shell_quote;
+use super::{FocusArea, RgbApp};
+use crate::command;
+use crate::config::PromptInput;
+use crate::git::{DiffSide, IntegrationStrategy, WorktreeDisposition};
+use crate::layout::{LayoutMode, TileLayout};
+use crate::workspace::{test_status, Agent, AgentActivity, Fanout, TerminalId};
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use std::time::{Duration, Instant};
+
+/// Agents one `:fanout` may start
+const MAX_AGENTS: usize = 8;
+/// Output lines searched for a test run summary
+const TEST_SCAN_LINES: usize = 200;
+/// A stdin prompt is typed once the agent's startup output has been quiet
+/// this long...
+const PROMPT_AFTER_QUIET: Duration = Duration::from_secs(1);
+/// ...or after this long if it prints nothing at all
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Lines a page key scrolls the diff comparison
+const COMPARE_PAGE: usize = 20;
+
+impl RgbApp {
+    /// Start `count` agents of `profile` on `prompt`, tiled in a grid
+    pub(super) async fn start_fanout(&mut self, profile: &str, count: usize, prompt: String) -> Result<()> {
+        if !(1..=MAX_AGENTS).contains(&count) {
+            anyhow::bail!("A fanout runs 1 to {} agents", MAX_AGENTS);
+        }
+        let git = self.workspace.git_manager();
+        if !git.is_git_repo() {
+            anyhow::bail!("Fanout needs a git repository for the agents' worktrees");
+        }
+        let base = git.base_commit()?;
+
+        // A profile that isn't configured runs as a command
+        let (program, input, branch_profile) = match self.config.terminals.get(profile) {
+            Some(config) => (config.command.clone(), config.prompt, Some(profile)),
+            None => (command::quote(profile), PromptInput::Argument, None),
+        };
+        let command = match input {
+            PromptInput::Argument => format!("{} {}", program, shell_quote(&prompt)),
+            PromptInput::Stdin => program,
+        };
+
+        let mut fanout = Fanout {
+            profile: profile.to_string(),
+            prompt: prompt.clone(),
+            base: base.clone(),
+            started: Instant::now(),
+            agents: Vec::new(),
+            winner: None,
+        };
+        for _ in 0..count {
+            let id = match self.workspace.create_terminal_at(&base, Some(command.clone()), branch_profile).await {
+                Ok(id) => id,
+                Err(e) => {
+                    self.ui.show_error(&format!("Started {} of {} agents: {}", fanout.agents.len(), count, e));
+                    break;
+                }
+            };
+            let title = self
+                .workspace
+                .terminals()
+                .into_iter()
+                .find(|t| t.id == id)
+                .map(|t| t.title)
+                .unwrap_or_default();
+            let branch = git.worktree_info(id).map(|w| w.branch).unwrap_or_default();
+            self.record_if_configured(Some(profile), id);
+            let pending = (input == PromptInput::Stdin).then(|| prompt.clone());
+            fanout.agents.push(Agent::new(id, title, branch, pending));
+        }
+        let Some(first) = fanout.agents.first() else {
+            return Ok(());
+        };
+
+        let panes = self.workspace.terminals().len();
+        let cols = (panes as f64).sqrt().ceil() as usize;
+        self.layout.set_mode(LayoutMode::Tiled(TileLayout::Grid { cols: cols.max(1) }));
+        self.workspace.set_active_terminal(first.terminal_id);
+        self.focus = FocusArea::Terminal;
+
+        self.ui.show_status(&format!(
+            "Started {} {} agent(s); :fanout shows their progress",
+            fanout.agents.len(),
+            profile
+        ));
+        self.fanout = Some(fanout);
+        self.warn_if_overloaded();
+        Ok(())
+    }
+
+    /// Bring agent progress up to date, and type stdin prompts for agents
+    /// that are ready for them
+    pub(super) async fn refresh_fanout(&mut self) {
+        // Agents moved to another workspace are still agents
+        let open: Vec<TerminalId> = self.workspaces().flat_map(|w| w.terminals()).map(|t| t.id).collect();
+        let Some(fanout) = self.fanout.as_mut() else {
+            return;
+        };
+        fanout.agents.retain(|a| open.contains(&a.terminal_id));
+
+        for agent in &mut fanout.agents {
+            let Some(emulator) = self.workspace.get_terminal_emulator(agent.terminal_id) else {
+                continue;
+            };
+            let mut emulator = emulator.write();
+            agent.observe(emulator.is_alive(), emulator.last_output());
+            agent.tests = test_status(&emulator.recent_output(TEST_SCAN_LINES));
+
+            let ready = match agent.activity {
+                AgentActivity::Starting => fanout.started.elapsed() > PROMPT_TIMEOUT,
+                AgentActivity::Busy | AgentActivity::Idle => {
+                    agent.last_output.is_some_and(|at| at.elapsed() > PROMPT_AFTER_QUIET)
+                }
+                AgentActivity::Exited => false,
+            };
+            if ready {
+                if let Some(prompt) = agent.pending_prompt.take() {
+                    let typed = emulator.paste(&prompt).and_then(|_| emulator.write(b"\r"));
+                    if let Err(e) = typed {
+                        tracing::warn!("Failed to send the prompt to {}: {}", agent.title, e);
+                    }
+                }
+            }
+        }
+
+        let git = self.workspace.git_manager();
+        let ids: Vec<TerminalId> = fanout.agents.iter().map(|a| a.terminal_id).collect();
+        let scanned = tokio::task::spawn_blocking(move || {
+            ids.into_iter()
+                .map(|id| (id, git.changed_files(id)))
+                .collect::<Vec<_>>()
+        })
+        .await;
+        for (id, files) in scanned.unwrap_or_default() {
+            match (fanout.agent_mut(id), files) {
+                (Some(agent), Ok(files)) => agent.changed_files = files.into_iter().map(|f| f.path).collect(),
+                (_, Err(e)) => tracing::debug!("Failed to list changes of {}: {}", id, e),
+                (None, _) => {}
+            }
+        }
+
+        self.ui.refresh_fanout(fanout);
+    }
+
+    pub(super) async fn handle_fanout_key(&mut self, key: KeyEvent) -> Result<()> {
+        let board = self.ui.fanout_board_mut();
+        if board.is_comparing() {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => board.close_compare(),
+                KeyCode::Char('j') | KeyCode::Down => board.scroll_down(1),
+                KeyCode::Char('k') | KeyCode::Up => board.scroll_up(1),
+                KeyCode::PageDown | KeyCode::Char(' ') => board.scroll_down(COMPARE_PAGE),
+                KeyCode::PageUp => board.scroll_up(COMPARE_PAGE),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        let selected = board.selected_agent();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => board.close(),
+            KeyCode::Char('j') | KeyCode::Down => board.move_down(),
+            KeyCode::Char('k') | KeyCode::Up => board.move_up(),
+            KeyCode::Char(' ') => board.toggle_mark(),
+            KeyCode::Enter => {
+                if let Some(terminal_id) = selected {
+                    board.close();
+                    self.workspace.set_active_terminal(terminal_id);
+                    self.focus = FocusArea::Terminal;
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(terminal_id) = selected {
+                    board.close();
+                    self.workspace.set_active_terminal(terminal_id);
+                    self.open_diff(DiffSide::Main).await?;
+                }
+            }
+            KeyCode::Char('c') => self.compare_agents().await,
+            KeyCode::Char('m') => {
+                if let Some(terminal_id) = selected {
+                    self.merge_winner(terminal_id).await;
+                }
+            }
+            KeyCode::Char('X') => self.discard_losers().await?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Diffs of the marked agents (or the first few) side by side
+    async fn compare_agents(&mut self) {
+        let Some(fanout) = &self.fanout else {
+            return;
+        };
+        let git = self.workspace.git_manager();
+        let mut columns = Vec::new();
+        for id in self.ui.fanout_board_mut().compared_agents() {
+            let Some(index) = fanout.agents.iter().position(|a| a.terminal_id == id) else {
+                continue;
+            };
+            match git.get_diff(Some(id), DiffSide::Main).await {
+                Ok(hunks) => columns.push((format!("#{} {}", index + 1, fanout.agents[index].branch), hunks)),
+                Err(e) => {
+                    self.ui.show_error(&format!("Failed to read the diff of agent #{}: {}", index + 1, e));
+                    return;
+                }
+            }
+        }
+        self.ui.fanout_board_mut().show_compare(columns);
+    }
+
+    async fn merge_winner(&mut self, terminal_id: TerminalId) {
+        // Agents often leave their work uncommitted; it goes in as one commit
+        let git = self.workspace.git_manager();
+        let branch = git.worktree_info(terminal_id).map(|w| w.branch).unwrap_or_default();
+        let summary = self
+            .fanout
+            .as_ref()
+            .and_then(|f| f.prompt.lines().next().map(str::to_string))
+            .unwrap_or_default();
+        if let Err(e) = git.commit_all(terminal_id, &format!("fanout: {}", summary)) {
+            self.ui.show_error(&format!("Failed to commit {}: {}", branch, e));
+            return;
+        }
+
+        let merged = self.integrate_worktree(terminal_id, IntegrationStrategy::Merge).await;
+        if merged {
+            if let Some(fanout) = self.fanout.as_mut() {
+                fanout.winner = Some(terminal_id);
+                self.ui.refresh_fanout(fanout);
+            }
+            self.ui.show_status("Merged the winner; X discards the other agents");
+        } else if self.ui.is_conflict_view_visible() {
+            self.ui.fanout_board_mut().close();
+        }
+    }
+
+    /// Close every agent but the merged one, deleting their branches
+    async fn discard_losers(&mut self) -> Result<()> {
+        let Some(fanout) = self.fanout.as_mut() else {
+            return Ok(());
+        };
+        let Some(winner) = fanout.winner else {
+            self.ui.show_status("Merge the winning agent first (m)");
+            return Ok(());
+        };
+        let losers: Vec<TerminalId> = fanout
+            .agents
+            .iter()
+            .map(|a| a.terminal_id)
+            .filter(|id| *id != winner)
+            .collect();
+        fanout.agents.retain(|a| a.terminal_id == winner);
+        self.ui.refresh_fanout(fanout);
+
+        for id in &losers {
+            if let Err(e) = self.workspace.close_terminal_with(*id, WorktreeDisposition::Discard).await {
+                self.ui.show_error(&format!("Failed to discard an agent: {}", e));
+            }
+        }
+        self.after_terminal_closed().await?;
+        self.ui.show_status(&format!("Discarded {} agent(s)", losers.len()));
+        Ok(())
+    }
+}
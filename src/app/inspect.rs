@@ -0,0 +1,131 @@
+//! `:inspect` (Alt+P): the active pane's process tree, foreground job,
+//! listening ports and working directory, with signals for the job or the
+//! whole tree, so a hung agent can be stopped without closing its pane and
+//! worktree.
+
+use super::RgbApp;
+use crate::monitor::{signal_group, signal_processes, PaneProcesses, Signal};
+use crate::workspace::TerminalId;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+
+impl RgbApp {
+    pub(super) fn show_process_inspector(&mut self) -> Result<()> {
+        let Some(id) = self.workspace.active_terminal_id() else {
+            anyhow::bail!("No active pane");
+        };
+        let processes = self.inspect_pane(id)?;
+        let title = self
+            .workspace
+            .terminals()
+            .into_iter()
+            .find(|t| t.id == id)
+            .map(|t| t.title)
+            .unwrap_or_default();
+        self.ui.show_process_inspector(id, title, processes);
+        Ok(())
+    }
+
+    /// Re-read the inspected pane; closes the popup once the pane is gone
+    pub(super) fn refresh_process_inspector(&mut self) {
+        let Some(id) = self.ui.process_inspector_mut().terminal_id() else {
+            return;
+        };
+        match self.inspect_pane(id) {
+            Ok(processes) => self.ui.process_inspector_mut().refresh(processes),
+            Err(_) => self.ui.process_inspector_mut().close(),
+        }
+    }
+
+    fn inspect_pane(&self, id: TerminalId) -> Result<PaneProcesses> {
+        let emulator = self
+            .workspace
+            .get_terminal_emulator(id)
+            .ok_or_else(|| anyhow::anyhow!("No such pane"))?;
+        let (root, foreground) = {
+            let emulator = emulator.read();
+            (emulator.pid(), emulator.foreground_process_group())
+        };
+        Ok(PaneProcesses::inspect(root, foreground, |pid| self.resources.process_cpu(pid)))
+    }
+
+    /// Send `signal` to a pane's foreground job, or to every process in its
+    /// tree, and say what it reached
+    pub(super) fn signal_pane(&self, id: TerminalId, signal: Signal, whole_tree: bool) -> Result<String> {
+        let processes = self.inspect_pane(id)?;
+        if whole_tree {
+            // Children before their parents, which can't restart them in between
+            let pids: Vec<u32> = processes.processes.iter().rev().map(|p| p.pid).collect();
+            let reached = signal_processes(&pids, signal);
+            return Ok(format!("Sent {} to {} process(es) of the pane", signal.name(), reached));
+        }
+
+        // A stopped job is no longer in the foreground; its shell took over
+        let stopped = processes
+            .processes
+            .iter()
+            .find(|p| p.pid != processes.root && matches!(p.state, 'T' | 't'))
+            .map(|p| p.process_group);
+        let group = match signal {
+            Signal::Continue => stopped.or(processes.foreground),
+            _ => processes.foreground,
+        };
+        let Some(group) = group else {
+            anyhow::bail!("The pane's foreground job is unknown; signal the whole tree instead");
+        };
+        signal_group(group, signal).map_err(|e| anyhow::anyhow!("Cannot signal job {}: {}", group, e))?;
+        let job = processes
+            .processes
+            .iter()
+            .find(|p| p.process_group == group)
+            .map(|p| p.command.split_whitespace().next().unwrap_or_default().to_string())
+            .unwrap_or_default();
+        if Some(group) != processes.foreground {
+            return Ok(format!("Sent {} to job {} ({}); it runs in the background, fg brings it back", signal.name(), group, job));
+        }
+        Ok(format!("Sent {} to job {} ({})", signal.name(), group, job))
+    }
+
+    pub(super) fn handle_process_inspector_key(&mut self, key: KeyEvent) -> Result<()> {
+        let signal = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.ui.process_inspector_mut().close();
+                return Ok(());
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.ui.process_inspector_mut().move_down();
+                return Ok(());
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.ui.process_inspector_mut().move_up();
+                return Ok(());
+            }
+            KeyCode::Tab => {
+                self.ui.process_inspector_mut().toggle_target();
+                return Ok(());
+            }
+            KeyCode::Char('r') => {
+                self.refresh_process_inspector();
+                return Ok(());
+            }
+            KeyCode::Char('i') => Signal::Interrupt,
+            KeyCode::Char('t') => Signal::Terminate,
+            KeyCode::Char('K') => Signal::Kill,
+            KeyCode::Char('s') => Signal::Stop,
+            KeyCode::Char('c') => Signal::Continue,
+            _ => return Ok(()),
+        };
+
+        let inspector = self.ui.process_inspector_mut();
+        let Some(id) = inspector.terminal_id() else {
+            return Ok(());
+        };
+        let whole_tree = inspector.is_whole_tree();
+        match self.signal_pane(id, signal, whole_tree) {
+            Ok(message) => self.ui.show_status(&message),
+            Err(e) => self.ui.show_error(&e.to_string()),
+        }
+        self.refresh_process_inspector();
+        Ok(())
+    }
+}
@@ -1,8 +1,31 @@
-use crate::config::AppConfig;
+mod alerts;
+mod control;
+mod editor;
+mod fanout;
+mod inspect;
+mod output;
+mod recording;
+mod resources;
+mod settings;
+mod sync;
+mod tabs;
+
+use crate::command::{self, CommandId, CommandRegistry, CompletionContext};
+use crate::config::{AppConfig, ConfigWatcher};
+use crate::control::{ControlRequest, ControlServer};
+use crate::git::{
+    DiffSide, GitEvent, IntegrationOutcome, IntegrationStrategy, Resolution, SyncOutcome,
+    WorktreeDisposition,
+};
 use crate::layout::LayoutEngine;
-use crate::ui::Ui;
-use crate::workspace::WorkspaceManager;
+use crate::terminal::{LinkTarget, OutputLink};
+use crate::monitor::{ChangeType, ConflictKind, ConflictResolution, FileConflict, PaneUsage, ResourceMonitor, SystemLoad};
+use crate::ui::{components::{FinderChoice, FinderMode, NotificationLevel}, Ui};
+use crate::workspace::{Fanout, PaneMonitor, TerminalId, TimelineEvent, WorkspaceManager};
 use anyhow::Result;
+use base64::Engine;
+use editor::{EditorKind, EditorPane};
+use tabs::WorkspaceTab;
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind, MouseButton, EnableMouseCapture, DisableMouseCapture},
     execute,
@@ -12,9 +35,17 @@ use ratatui::{
     backend::CrosstermBackend,
     Terminal,
 };
-use std::{io, path::PathBuf};
-use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
+use std::{collections::{HashMap, HashSet}, io::{self, Write}, path::{Path, PathBuf}};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+const GIT_STATUS_REFRESH: Duration = Duration::from_millis(500);
+/// How often `:fanout` agent progress is re-read
+const FANOUT_REFRESH: Duration = Duration::from_secs(2);
+/// How often panes are checked against their monitors
+const MONITOR_CHECK: Duration = Duration::from_millis(250);
+/// How often the config files are checked for changes
+const CONFIG_CHECK: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum FocusArea {
@@ -23,15 +54,46 @@ enum FocusArea {
 }
 
 pub struct RgbApp {
+    /// The workspace on screen, and its layout
     workspace: WorkspaceManager,
     layout: LayoutEngine,
+    /// Every workspace in the tab bar, the others parked in theirs
+    tabs: Vec<WorkspaceTab>,
+    active_tab: usize,
+    /// Another workspace came to the front; the main loop follows its
+    /// file changes from now on
+    workspace_switched: bool,
+    /// Channels the main loop gives each workspace, for ones opened later
+    redraw_tx: Option<mpsc::UnboundedSender<()>>,
+    git_tx: Option<mpsc::UnboundedSender<GitEvent>>,
     ui: Ui,
     config: AppConfig,
+    /// The file given with `--config`, if any
+    config_path: Option<PathBuf>,
+    config_watcher: ConfigWatcher,
     terminal: Terminal<CrosstermBackend<io::Stdout>>,
     should_quit: bool,
     focus: FocusArea,
     command_mode: bool,
-    command_buffer: String,
+    commands: CommandRegistry,
+    /// Editor pane of each worktree, keyed by its root
+    editor_panes: HashMap<PathBuf, EditorPane>,
+    /// Agents of the last `:fanout`
+    fanout: Option<Fanout>,
+    /// Activity, silence, bell and pattern monitors of each pane
+    monitors: HashMap<TerminalId, PaneMonitor>,
+    /// CPU and memory of each pane's processes at the last sample, and of
+    /// the machine
+    resources: ResourceMonitor,
+    usage: HashMap<TerminalId, PaneUsage>,
+    system: SystemLoad,
+    /// The machine was over a `[resources]` limit at the last sample
+    overloaded: bool,
+    /// Digits typed after Alt+G
+    pane_number: Option<String>,
+    /// Control socket, kept for as long as the app runs
+    control: Option<ControlServer>,
+    control_rx: Option<mpsc::Receiver<ControlRequest>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -43,7 +105,7 @@ pub enum AppState {
 }
 
 impl RgbApp {
-    pub fn new(config: AppConfig, project_dir: PathBuf) -> Result<Self> {
+    pub fn new(config: AppConfig, config_path: Option<PathBuf>, project_dir: PathBuf) -> Result<Self> {
         tracing::info!("RgbApp::new called with project_dir: {:?}", project_dir);
 
         // Setup terminal
@@ -66,28 +128,62 @@ impl RgbApp {
         tracing::info!("Terminal created");
 
         // Initialize components
-        let workspace = WorkspaceManager::new(project_dir.clone())?;
+        let workspace = WorkspaceManager::new(project_dir.clone(), &config.git, config.general.max_terminals)?;
         tracing::info!("WorkspaceManager created");
 
         let layout = LayoutEngine::new();
-        let ui = Ui::new();
+        let ui = Ui::new(workspace.project_dir().to_path_buf());
         tracing::info!("Layout and UI created");
 
-        Ok(Self {
+        let (control, control_rx) = if config.general.control_socket {
+            match ControlServer::bind(crate::control::default_socket_path()) {
+                Ok((server, rx)) => {
+                    workspace.set_control_socket(server.path().to_path_buf());
+                    (Some(server), Some(rx))
+                }
+                Err(e) => {
+                    tracing::warn!("Control socket unavailable: {}", e);
+                    (None, None)
+                }
+            }
+        } else {
+            (None, None)
+        };
+
+        let mut app = Self {
+            tabs: vec![WorkspaceTab::first(workspace.project_dir())],
             workspace,
             layout,
+            active_tab: 0,
+            workspace_switched: false,
+            redraw_tx: None,
+            git_tx: None,
             ui,
             config,
+            config_watcher: ConfigWatcher::new(config_path.as_deref()),
+            config_path,
             terminal,
             should_quit: false,
             focus: FocusArea::Terminal,
             command_mode: false,
-            command_buffer: String::new(),
-        })
+            commands: CommandRegistry::new(),
+            editor_panes: HashMap::new(),
+            fanout: None,
+            monitors: HashMap::new(),
+            resources: ResourceMonitor::new(),
+            usage: HashMap::new(),
+            system: SystemLoad::default(),
+            overloaded: false,
+            pane_number: None,
+            control,
+            control_rx,
+        };
+        app.apply_config();
+        Ok(app)
     }
 
     pub async fn create_terminal_with_command(&mut self, command: &str) -> Result<()> {
-        self.workspace.create_terminal(Some(command.to_string())).await?;
+        self.workspace.create_terminal(Some(command.to_string()), None).await?;
         Ok(())
     }
 
@@ -97,9 +193,19 @@ impl RgbApp {
         // Create initial terminal if workspace is empty
         if self.workspace.terminals().is_empty() {
             tracing::info!("Creating initial terminal");
-            self.workspace.create_terminal(None).await?;
+            self.workspace.create_terminal(None, None).await?;
+        }
+
+        let available = self.workspace.git_manager().available_worktrees().len();
+        if available > 0 {
+            self.ui.notify(
+                NotificationLevel::Info,
+                format!("Found {} existing worktree(s); :worktree shows them", available),
+            );
         }
 
+        self.check_monitor_patterns();
+
         tracing::info!("Starting simplified main loop");
 
         // Do an initial update to get terminal content
@@ -114,6 +220,32 @@ impl RgbApp {
 
         // Give workspace a way to signal redraws
         self.workspace.set_redraw_sender(redraw_tx.clone());
+        self.redraw_tx = Some(redraw_tx.clone());
+
+        // Background worktree sync reports back through this channel
+        let (git_tx, mut git_rx) = mpsc::unbounded_channel::<GitEvent>();
+        self.workspace.start_git_worker(
+            Duration::from_secs(self.config.git.sync_interval),
+            Duration::from_secs(self.config.git.conflict_scan_interval),
+            git_tx.clone(),
+        );
+        self.git_tx = Some(git_tx);
+        self.refresh_tabs();
+
+        // Watcher changes feed the git panel and the file explorer; each is
+        // re-read at most every GIT_STATUS_REFRESH while they keep arriving
+        let mut file_rx = self.workspace.subscribe_file_changes();
+        let mut git_status_stale = true;
+        let mut file_tree_stale = false;
+        let mut last_status_refresh = std::time::Instant::now();
+        let mut last_tree_refresh = std::time::Instant::now();
+        let mut last_fanout_refresh = std::time::Instant::now();
+        let mut last_monitor_check = std::time::Instant::now();
+        let mut last_resource_sample = std::time::Instant::now();
+        let mut last_config_check = std::time::Instant::now();
+
+        // Without a control socket this channel stays empty
+        let mut control_rx = self.control_rx.take().unwrap_or_else(|| mpsc::channel(1).1);
 
         // Initial draw
         self.draw_ui();
@@ -130,6 +262,13 @@ impl RgbApp {
                 break;
             }
 
+            if self.workspace_switched {
+                file_rx = self.workspace.subscribe_file_changes();
+                git_status_stale = true;
+                file_tree_stale = true;
+                self.workspace_switched = false;
+            }
+
             // Check for events first (non-blocking)
             if event::poll(Duration::from_millis(0))? {
                 match event::read()? {
@@ -158,6 +297,8 @@ impl RgbApp {
             tokio::select! {
                 // Continuous terminal output monitoring
                 _ = update_interval.tick() => {
+                    self.ui.poll_finder();
+
                     // Update terminal buffers
                     match self.workspace.update().await {
                         Ok(_) => {
@@ -169,6 +310,65 @@ impl RgbApp {
                         },
                         Err(e) => tracing::error!("Workspace update error: {}", e),
                     }
+                    self.update_parked_workspaces().await;
+
+                    if git_status_stale
+                        && self.ui.is_git_panel_visible()
+                        && last_status_refresh.elapsed() > GIT_STATUS_REFRESH
+                    {
+                        match self.workspace.git_manager().get_status().await {
+                            Ok(status) => self.ui.git_status_mut().update_status(status),
+                            Err(e) => tracing::debug!("Git status refresh failed: {}", e),
+                        }
+                        git_status_stale = false;
+                        last_status_refresh = std::time::Instant::now();
+                    }
+
+                    if file_tree_stale
+                        && self.ui.is_file_explorer_visible()
+                        && last_tree_refresh.elapsed() > GIT_STATUS_REFRESH
+                    {
+                        self.ui.file_tree_mut().refresh();
+                        file_tree_stale = false;
+                        last_tree_refresh = std::time::Instant::now();
+                    }
+
+                    if self.fanout.is_some() && last_fanout_refresh.elapsed() > FANOUT_REFRESH {
+                        self.refresh_fanout().await;
+                        last_fanout_refresh = std::time::Instant::now();
+                    }
+
+                    if last_monitor_check.elapsed() > MONITOR_CHECK {
+                        self.check_monitors();
+                        last_monitor_check = std::time::Instant::now();
+                    }
+
+                    if last_resource_sample.elapsed() > Duration::from_secs(self.config.resources.interval.max(1)) {
+                        self.sample_resources();
+                        last_resource_sample = std::time::Instant::now();
+                    }
+
+                    if last_config_check.elapsed() > CONFIG_CHECK {
+                        if self.config_watcher.changed() {
+                            self.reload_config();
+                        }
+                        last_config_check = std::time::Instant::now();
+                    }
+                }
+
+                result = file_rx.recv() => {
+                    match result {
+                        Ok(change) => {
+                            self.ui.git_status_mut().record_change(change);
+                            self.ui.refresh_timeline(&self.workspace);
+                        }
+                        Err(broadcast::error::RecvError::Lagged(missed)) => {
+                            tracing::debug!("Dropped {} file change(s)", missed);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => {}
+                    }
+                    git_status_stale = true;
+                    file_tree_stale = true;
                 }
 
                 // Handle explicit redraw signals
@@ -178,6 +378,20 @@ impl RgbApp {
                     last_draw = std::time::Instant::now();
                 }
 
+                Some(event) = git_rx.recv() => {
+                    self.handle_git_event(event);
+                    self.ui.refresh_timeline(&self.workspace);
+                    self.draw_ui();
+                    last_draw = std::time::Instant::now();
+                }
+
+                Some(request) = control_rx.recv() => {
+                    let result = self.handle_control(&request.method, request.params).await;
+                    let _ = request.reply.send(result);
+                    self.draw_ui();
+                    last_draw = std::time::Instant::now();
+                }
+
                 // Small delay to prevent busy waiting
                 _ = tokio::time::sleep(Duration::from_millis(5)) => {
                     // Just yielding to other tasks
@@ -189,9 +403,84 @@ impl RgbApp {
         Ok(())
     }
 
+    fn handle_git_event(&mut self, event: GitEvent) {
+        match event {
+            GitEvent::SyncStarted { worktrees } => {
+                self.ui.show_status(&syncing_status(worktrees));
+            }
+            GitEvent::SyncFinished { worktrees } => {
+                self.ui.clear_status(&syncing_status(worktrees));
+            }
+            GitEvent::Synced { branch, outcome, .. } => {
+                self.ui.refresh_worktree_info(&self.workspace);
+                self.notify_sync(branch, outcome);
+            }
+            GitEvent::SyncFailed { branch, error, .. } => {
+                self.ui.notify(NotificationLevel::Error, format!("{}: sync failed: {}", branch, error));
+            }
+            GitEvent::ChangesScanned { changes } => {
+                // Each workspace's worker scans its own panes
+                let workspace = changes
+                    .first()
+                    .and_then(|(id, _)| self.workspaces().find(|w| w.get_terminal_emulator(*id).is_some()))
+                    .unwrap_or(&self.workspace);
+                let new_conflicts = workspace.apply_worktree_changes(changes);
+                let terminals = workspace.terminals();
+                for conflict in new_conflicts.iter().filter(|c| c.kind == ConflictKind::OverlappingLines) {
+                    let panes: Vec<String> = terminals
+                        .iter()
+                        .filter(|t| conflict.terminal_ids.contains(&t.id))
+                        .map(|t| t.title.clone())
+                        .collect();
+                    self.ui.notify(
+                        NotificationLevel::Warning,
+                        format!(
+                            "{} will conflict on merge: {}",
+                            conflict.file.display(),
+                            panes.join(", ")
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn notify_sync(&mut self, branch: String, outcome: SyncOutcome) {
+        match outcome {
+            SyncOutcome::UpToDate => {}
+            SyncOutcome::FastForwarded { commits } => self.ui.notify(
+                NotificationLevel::Info,
+                format!("{}: fast-forwarded {} commit(s) from main", branch, commits),
+            ),
+            SyncOutcome::Rebased { commits, onto } => self.ui.notify(
+                NotificationLevel::Info,
+                format!("{}: rebased {} commit(s) onto {} new commit(s) from main", branch, commits, onto),
+            ),
+            SyncOutcome::SkippedDirty { behind } => self.ui.notify(
+                NotificationLevel::Warning,
+                format!("{}: uncommitted changes, behind main by {}", branch, behind),
+            ),
+            SyncOutcome::SkippedMoved { behind } => self.ui.notify(
+                NotificationLevel::Warning,
+                format!("{}: new commit during sync, behind main by {}", branch, behind),
+            ),
+        }
+    }
+
     async fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Result<()> {
         match mouse.kind {
+            MouseEventKind::Moved => {
+                let hovered = self
+                    .link_under(mouse.column, mouse.row)
+                    .map(|(id, row, link)| (id, row, link.columns));
+                self.ui.set_hovered_link(hovered);
+            }
             MouseEventKind::Down(MouseButton::Left) => {
+                if let Some((id, _, link)) = self.link_under(mouse.column, mouse.row) {
+                    self.open_link(id, link.target).await?;
+                    return Ok(());
+                }
+
                 // Get terminal areas from layout
                 let terminal_areas = self.layout.get_terminal_areas();
 
@@ -216,17 +505,123 @@ impl RgbApp {
                     }
                 }
             }
-            MouseEventKind::ScrollDown => {
-                if self.focus == FocusArea::FileExplorer {
-                    self.ui.file_explorer_move_down();
+            MouseEventKind::ScrollDown if self.focus == FocusArea::FileExplorer => {
+                self.ui.file_explorer_move_down();
+            }
+            MouseEventKind::ScrollUp if self.focus == FocusArea::FileExplorer => {
+                self.ui.file_explorer_move_up();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The output link at a screen position, with its terminal and row
+    fn link_under(&self, column: u16, row: u16) -> Option<(TerminalId, u16, OutputLink)> {
+        let (id, area) = self.layout.get_terminal_areas().into_iter().find(|(_, area)| {
+            column > area.x
+                && column < area.x + area.width.saturating_sub(1)
+                && row > area.y
+                && row < area.y + area.height.saturating_sub(1)
+        })?;
+
+        // Content sits inside the pane border
+        let (column, row) = (column - area.x - 1, row - area.y - 1);
+        let emulator = self.workspace.get_terminal_emulator(id)?;
+        let link = emulator.try_read()?.link_at(column, row).cloned()?;
+        Some((id, row, link))
+    }
+
+    async fn open_link(&mut self, source: TerminalId, target: LinkTarget) -> Result<()> {
+        match target {
+            LinkTarget::File { path, line, column } => {
+                // The pane the link came from decides the worktree
+                self.workspace.set_active_terminal(source);
+                self.open_in_editor(&path, line, column).await
+            }
+            LinkTarget::Url(url) => {
+                let opener = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+                let spawned = std::process::Command::new(opener)
+                    .arg(&url)
+                    .stdin(std::process::Stdio::null())
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .spawn();
+                match spawned {
+                    Ok(_) => self.ui.show_status(&format!("Opened {}", url)),
+                    Err(e) => self.ui.show_error(&format!("Failed to open {}: {}", url, e)),
                 }
+                Ok(())
             }
-            MouseEventKind::ScrollUp => {
-                if self.focus == FocusArea::FileExplorer {
-                    self.ui.file_explorer_move_up();
+        }
+    }
+
+    /// Open a file in the editor pane of the worktree it belongs to,
+    /// starting that pane if needed, or hand it to the configured open
+    /// command
+    async fn open_in_editor(&mut self, path: &Path, line: Option<usize>, column: Option<usize>) -> Result<()> {
+        let worktrees: Vec<PathBuf> = self
+            .workspace
+            .terminals()
+            .into_iter()
+            .filter(|t| t.has_worktree)
+            .map(|t| t.working_dir)
+            .collect();
+        let active_worktree = self
+            .workspace
+            .active_terminal_id()
+            .and_then(|id| self.workspace.worktree_path(id));
+        let (root, path) = editor::resolve(path, self.workspace.project_dir(), &worktrees, active_worktree.as_deref());
+
+        let template = self.config.editor.open_command.clone();
+        if !template.trim().is_empty() {
+            match editor::run_open_command(&template, &path, line, column, &root) {
+                Ok(()) => self.ui.show_status(&format!("Opened {}", path.display())),
+                Err(e) => self.ui.show_error(&format!("Open command failed: {}", e)),
+            }
+            return Ok(());
+        }
+
+        let running = self.editor_panes.get(&root).cloned().filter(|pane| {
+            self.workspace
+                .get_terminal_emulator(pane.terminal_id)
+                .is_some_and(|em| em.read().is_alive())
+        });
+        if let Some(pane) = running {
+            let opened = editor::open_remote(&pane, &path, line, column).await || {
+                pane.kind != EditorKind::Other && {
+                    let keys = editor::typed_keys(&path, line, column);
+                    self.workspace.send_input(pane.terminal_id, keys.as_bytes())?;
+                    true
                 }
+            };
+            if opened {
+                self.workspace.set_active_terminal(pane.terminal_id);
+                self.focus = FocusArea::Terminal;
+                return Ok(());
             }
-            _ => {}
+        }
+
+        let command = editor::editor_command(&self.config.editor.command);
+        let (launch, server) = editor::launch(&command, &path, line);
+        let terminal_id = self.workspace.create_terminal_in(root.clone(), Some(launch)).await?;
+        self.workspace.set_active_terminal(terminal_id);
+        self.editor_panes.insert(root, EditorPane {
+            terminal_id,
+            kind: editor::kind_of(&command),
+            server,
+        });
+        self.focus = FocusArea::Terminal;
+        Ok(())
+    }
+
+    async fn handle_link_hint_key(&mut self, key: KeyEvent) -> Result<()> {
+        let picked = match key.code {
+            KeyCode::Char(c) => self.ui.pick_link_hint(c),
+            _ => self.ui.pick_link_hint('\0'),
+        };
+        if let Some((terminal_id, link)) = picked {
+            self.open_link(terminal_id, link.target).await?;
         }
         Ok(())
     }
@@ -236,28 +631,88 @@ impl RgbApp {
 
         // Handle command mode
         if self.command_mode {
-            match key.code {
-                KeyCode::Esc => {
-                    self.command_mode = false;
-                    self.command_buffer.clear();
-                    self.ui.clear_command();
-                }
-                KeyCode::Enter => {
-                    let command = self.command_buffer.clone();
-                    self.execute_command(&command).await?;
-                    self.command_mode = false;
-                    self.command_buffer.clear();
-                    self.ui.clear_command();
-                }
-                KeyCode::Backspace => {
-                    self.command_buffer.pop();
-                    self.ui.command_backspace();
-                }
-                KeyCode::Char(c) => {
-                    self.command_buffer.push(c);
-                    self.ui.command_push(c);
-                }
-                _ => {}
+            self.handle_command_line_key(key).await?;
+            return Ok(());
+        }
+
+        // Any key dismisses an error popup
+        if self.ui.has_error() {
+            self.ui.clear_error();
+            return Ok(());
+        }
+
+        if self.ui.is_link_hints_visible() {
+            self.handle_link_hint_key(key).await?;
+            return Ok(());
+        }
+
+        if self.pane_number.is_some() {
+            self.handle_pane_number_key(key);
+            return Ok(());
+        }
+
+        if self.ui.is_close_prompt_visible() {
+            self.handle_close_prompt_key(key).await?;
+            return Ok(());
+        }
+
+        if self.ui.is_worktree_info_visible() {
+            self.handle_worktree_dashboard_key(key).await?;
+            return Ok(());
+        }
+
+        if self.ui.is_fanout_visible() {
+            self.handle_fanout_key(key).await?;
+            return Ok(());
+        }
+
+        if self.ui.is_worktree_manager_visible() {
+            self.handle_worktree_manager_key(key).await?;
+            return Ok(());
+        }
+
+        if self.ui.is_timeline_visible() {
+            self.handle_timeline_key(key).await?;
+            return Ok(());
+        }
+
+        if self.ui.is_resources_visible() {
+            self.handle_resources_key(key)?;
+            return Ok(());
+        }
+
+        if self.ui.is_process_inspector_visible() {
+            self.handle_process_inspector_key(key)?;
+            return Ok(());
+        }
+
+        if self.ui.is_finder_visible() {
+            self.handle_finder_key(key).await?;
+            return Ok(());
+        }
+
+        // Conflict resolver takes all keys while open
+        if self.ui.is_conflict_view_visible() {
+            self.handle_conflict_key(key).await?;
+            return Ok(());
+        }
+
+        // Diff pane takes all keys while open
+        if self.ui.is_diff_visible() {
+            self.handle_diff_key(key).await?;
+            return Ok(());
+        }
+
+        // A file operation prompt takes typed keys before any shortcut
+        if self.focus == FocusArea::FileExplorer && self.ui.file_tree_mut().is_prompting() {
+            self.handle_file_prompt_key(key);
+            return Ok(());
+        }
+
+        // Commands bound in the config take precedence over built-in keys
+        if let Some(line) = self.bound_command(&key) {
+            if let Err(e) = self.execute_command(&line).await {
+                self.ui.show_error(&e.to_string());
             }
             return Ok(());
         }
@@ -266,27 +721,24 @@ impl RgbApp {
         match (key.code, key.modifiers) {
             // Quit application
             (KeyCode::Char('q') | KeyCode::Char('Q'), KeyModifiers::CONTROL) => {
-                self.should_quit = true;
+                self.run_command(CommandId::Quit, Vec::new()).await?;
             }
             // New terminal
-            (KeyCode::Char('t') | KeyCode::Char('T'), KeyModifiers::CONTROL) => {
-                self.workspace.create_terminal(None).await?;
+            _ if settings::is_bound(&self.config.keybindings.new_terminal, &key) => {
+                self.run_command(CommandId::New, Vec::new()).await?;
             }
             // Close terminal
-            (KeyCode::Char('w') | KeyCode::Char('W'), KeyModifiers::CONTROL) => {
+            _ if settings::is_bound(&self.config.keybindings.close_terminal, &key) => {
                 if self.focus == FocusArea::FileExplorer {
                     self.focus = FocusArea::Terminal;
                 } else {
-                    self.workspace.close_active_terminal().await?;
-                    if self.workspace.terminals().is_empty() {
-                        self.workspace.create_terminal(None).await?;
-                    }
+                    self.run_command(CommandId::Close, Vec::new()).await?;
                 }
             }
             // Enter command mode
             (KeyCode::Char(':'), KeyModifiers::NONE) => {
                 self.command_mode = true;
-                self.command_buffer.clear();
+                self.ui.command_line_mut().clear();
             }
             // Toggle help
             (KeyCode::Char('?'), KeyModifiers::NONE) => {
@@ -294,8 +746,7 @@ impl RgbApp {
             }
             // Toggle file explorer
             (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
-                self.ui.toggle_file_explorer();
-                self.focus = FocusArea::Terminal;
+                self.run_command(CommandId::Explorer, Vec::new()).await?;
             }
             // Switch focus
             (KeyCode::Char('f'), KeyModifiers::CONTROL) => {
@@ -307,7 +758,31 @@ impl RgbApp {
             }
             // Toggle git panel
             (KeyCode::Char('g'), KeyModifiers::CONTROL) => {
-                self.ui.toggle_git_panel();
+                self.run_command(CommandId::GitPanel, Vec::new()).await?;
+            }
+            // Fuzzy finders
+            (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                self.run_command(CommandId::Find, Vec::new()).await?;
+            }
+            (KeyCode::Char(' '), KeyModifiers::CONTROL) => {
+                self.run_command(CommandId::Panes, Vec::new()).await?;
+            }
+            // Label file references and URLs in the active terminal
+            (KeyCode::Char('o'), KeyModifiers::CONTROL) => {
+                if let Some(id) = self.workspace.active_terminal_id() {
+                    let links = self
+                        .workspace
+                        .get_terminal_emulator(id)
+                        .map(|em| {
+                            em.read()
+                                .visible_links()
+                                .into_iter()
+                                .map(|(row, link)| (row, link.clone()))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    self.ui.show_link_hints(id, links);
+                }
             }
             // Arrow keys for terminal navigation
             (KeyCode::Left, KeyModifiers::ALT) => {
@@ -316,6 +791,23 @@ impl RgbApp {
             (KeyCode::Right, KeyModifiers::ALT) => {
                 self.workspace.next_terminal();
             }
+            (KeyCode::Char('a'), KeyModifiers::ALT) => {
+                if let Err(e) = self.run_command(CommandId::NextAlert, Vec::new()).await {
+                    self.ui.show_status(&e.to_string());
+                }
+            }
+            (KeyCode::Char('s'), KeyModifiers::ALT) => {
+                self.run_command(CommandId::Sync, Vec::new()).await?;
+            }
+            (KeyCode::Char('p'), KeyModifiers::ALT) => {
+                self.run_command(CommandId::Inspect, Vec::new()).await?;
+            }
+            // Workspaces
+            (KeyCode::Char(n @ '1'..='9'), KeyModifiers::ALT) => {
+                self.switch_workspace(n as usize - '1' as usize);
+            }
+            (KeyCode::Char('['), KeyModifiers::ALT) => self.cycle_workspace(false),
+            (KeyCode::Char(']'), KeyModifiers::ALT) => self.cycle_workspace(true),
             // Tab switching
             (KeyCode::Tab, KeyModifiers::CONTROL) => {
                 self.workspace.next_terminal();
@@ -323,25 +815,29 @@ impl RgbApp {
             (KeyCode::BackTab, KeyModifiers::SHIFT) => {
                 self.workspace.previous_terminal();
             }
-            // Quick terminal switch (F1-F10)
-            (KeyCode::F(n), KeyModifiers::NONE) if n >= 1 && n <= 10 => {
+            // Quick terminal switch (F1-F10), and any pane by number
+            (KeyCode::F(n), KeyModifiers::NONE) if (1..=10).contains(&n) => {
                 self.workspace.switch_to_terminal(n as usize - 1);
             }
+            (KeyCode::Char('g'), KeyModifiers::ALT) => self.start_pane_number(),
             // File explorer navigation when focused
             _ if self.focus == FocusArea::FileExplorer => {
                 match key.code {
-                    KeyCode::Up => self.ui.file_explorer_move_up(),
-                    KeyCode::Down => self.ui.file_explorer_move_down(),
-                    KeyCode::Left => self.ui.file_explorer_toggle_expand(),
-                    KeyCode::Right => self.ui.file_explorer_toggle_expand(),
+                    KeyCode::Up | KeyCode::Char('k') => self.ui.file_explorer_move_up(),
+                    KeyCode::Down | KeyCode::Char('j') => self.ui.file_explorer_move_down(),
+                    KeyCode::Left | KeyCode::Char('h') => self.ui.file_explorer_toggle_expand(),
+                    KeyCode::Right | KeyCode::Char('l') => self.ui.file_explorer_toggle_expand(),
                     KeyCode::Enter => {
                         if let Some(path) = self.ui.file_explorer_open() {
-                            let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
-                            let command = format!("{} {}", editor, path);
-                            self.workspace.create_terminal(Some(command)).await?;
-                            self.focus = FocusArea::Terminal;
+                            self.open_in_editor(&path, None, None).await?;
                         }
                     }
+                    KeyCode::Char('a') => self.ui.file_tree_mut().start_create(),
+                    KeyCode::Char('r') => self.ui.file_tree_mut().start_rename(),
+                    KeyCode::Char('d') => self.ui.file_tree_mut().start_delete(),
+                    KeyCode::Char('.') => self.ui.file_tree_mut().toggle_hidden(),
+                    KeyCode::Char(c @ ('y' | 'Y')) => self.copy_explorer_path(c == 'Y')?,
+                    KeyCode::Char('w') => self.toggle_explorer_root(),
                     _ => {}
                 }
             }
@@ -349,9 +845,9 @@ impl RgbApp {
             _ => {
                 if self.focus == FocusArea::Terminal {
                     if let Some(emulator) = self.workspace.get_active_terminal_emulator() {
-                        if let Some(em) = emulator.try_read() {
-                            if em.is_alive() {
-                                drop(em);
+                        let alive = emulator.try_read().map(|em| em.is_alive());
+                        if let Some(alive) = alive {
+                            if alive {
                                 self.workspace.send_key_to_active_terminal(key).await?;
                             } else {
                                 // Terminal is dead, don't forward input but allow Ctrl+W to close
@@ -368,6 +864,539 @@ impl RgbApp {
         Ok(())
     }
 
+    async fn handle_diff_key(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.ui.diff_view_mut().close(),
+            KeyCode::Down | KeyCode::Char('j') => self.ui.diff_view_mut().cursor_down(),
+            KeyCode::Up | KeyCode::Char('k') => self.ui.diff_view_mut().cursor_up(),
+            KeyCode::PageDown => self.ui.diff_view_mut().page_down(20),
+            KeyCode::PageUp => self.ui.diff_view_mut().page_up(20),
+            KeyCode::Char('n') => self.ui.diff_view_mut().next_hunk(),
+            KeyCode::Char('p') => self.ui.diff_view_mut().previous_hunk(),
+            KeyCode::Char(' ') => self.ui.diff_view_mut().toggle_line(),
+            KeyCode::Tab => {
+                let side = match self.ui.diff_view().side() {
+                    DiffSide::Unstaged => DiffSide::Staged,
+                    DiffSide::Staged | DiffSide::Main => DiffSide::Unstaged,
+                };
+                self.open_diff(side).await?;
+            }
+            KeyCode::Char('s') | KeyCode::Char('u') => {
+                let staging = key.code == KeyCode::Char('s');
+                let side = self.ui.diff_view().side();
+                let expected = if staging { DiffSide::Unstaged } else { DiffSide::Staged };
+                if side != expected {
+                    return Ok(());
+                }
+
+                let Some(hunk) = self.ui.diff_view().selected_hunk().cloned() else {
+                    return Ok(());
+                };
+                let selection = self.ui.diff_view().line_selection();
+                let git = self.workspace.git_manager();
+                let terminal_id = self.workspace.active_terminal_id();
+
+                let result = if staging {
+                    git.stage_hunk(terminal_id, &hunk, selection.as_deref()).await
+                } else {
+                    git.unstage_hunk(terminal_id, &hunk, selection.as_deref()).await
+                };
+
+                match result {
+                    Ok(()) => {
+                        let hunks = git.get_diff(terminal_id, side).await?;
+                        self.ui.diff_view_mut().refresh(hunks);
+                    }
+                    Err(e) => self.ui.show_error(&format!("Failed to apply hunk: {}", e)),
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_conflict_key(&mut self, key: KeyEvent) -> Result<()> {
+        let view = self.ui.conflict_view_mut();
+        match key.code {
+            KeyCode::Esc => {
+                view.close();
+                self.ui.show_status("Merge aborted; main branch unchanged");
+            }
+            KeyCode::Down | KeyCode::Char('j') => view.next_conflict(),
+            KeyCode::Up | KeyCode::Char('k') => view.previous_conflict(),
+            KeyCode::Tab => view.next_file(),
+            KeyCode::Char('o') => view.resolve(Resolution::Ours),
+            KeyCode::Char('t') => view.resolve(Resolution::Theirs),
+            KeyCode::Char('b') => view.resolve(Resolution::Both),
+            KeyCode::Char('a') => view.resolve(Resolution::Base),
+            KeyCode::Enter => {
+                let Some(pending) = view.pending().cloned() else {
+                    return Ok(());
+                };
+                if !pending.is_resolved() {
+                    self.ui.show_error(&format!(
+                        "{} conflicts are still unresolved",
+                        pending.unresolved_count()
+                    ));
+                    return Ok(());
+                }
+
+                match self.workspace.git_manager().complete_merge(&pending).await {
+                    Ok(commit) => {
+                        self.ui.conflict_view_mut().close();
+                        self.ui.show_status(&format!(
+                            "Merged {} into {} ({})",
+                            pending.worktree_branch,
+                            pending.main_branch,
+                            &commit[..8]
+                        ));
+                    }
+                    Err(e) => self.ui.show_error(&format!("Failed to complete merge: {}", e)),
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Integrate the active terminal's worktree branch into the main branch
+    async fn integrate_active_worktree(&mut self, strategy: IntegrationStrategy) -> Result<()> {
+        if let Some(terminal_id) = self.workspace.active_terminal_id() {
+            self.integrate_worktree(terminal_id, strategy).await;
+        }
+        Ok(())
+    }
+
+    /// Land a terminal's branch on main; returns whether it is now merged
+    async fn integrate_worktree(&mut self, terminal_id: TerminalId, strategy: IntegrationStrategy) -> bool {
+        let resolution = self.workspace.conflict_resolution();
+        let conflicts: Vec<FileConflict> = self
+            .workspace
+            .file_conflicts()
+            .into_iter()
+            .filter(|c| c.kind == ConflictKind::OverlappingLines && c.terminal_ids.contains(&terminal_id))
+            .collect();
+
+        if resolution == ConflictResolution::Block && !conflicts.is_empty() {
+            let files: Vec<String> = conflicts.iter().map(|c| c.file.display().to_string()).collect();
+            self.ui.show_error(&format!(
+                "Integration blocked: {} will conflict with another worktree",
+                files.join(", ")
+            ));
+            return false;
+        }
+
+        let merged = self.run_integration(terminal_id, strategy).await;
+
+        // Bring the other worktrees onto the new main right away so they
+        // see the overlap now instead of at merge time
+        if merged && resolution == ConflictResolution::AutoResolve {
+            let others: HashSet<TerminalId> = self
+                .workspace
+                .file_conflicts()
+                .iter()
+                .filter(|c| c.terminal_ids.contains(&terminal_id))
+                .flat_map(|c| c.terminal_ids.iter().copied())
+                .filter(|&id| id != terminal_id)
+                .collect();
+
+            for other in others {
+                let git = self.workspace.git_manager();
+                let branch = git.worktree_info(other).map(|w| w.branch).unwrap_or_default();
+                match tokio::task::spawn_blocking(move || git.sync_worktree(other)).await {
+                    Ok(Ok(outcome)) => self.notify_sync(branch, outcome),
+                    Ok(Err(e)) => self.ui.notify(
+                        NotificationLevel::Error,
+                        format!("{}: sync failed: {}", branch, e),
+                    ),
+                    Err(e) => tracing::warn!("Sync task failed: {}", e),
+                }
+            }
+        }
+
+        merged
+    }
+
+    async fn run_integration(&mut self, terminal_id: TerminalId, strategy: IntegrationStrategy) -> bool {
+        match self.workspace.git_manager().integrate_worktree(terminal_id, strategy).await {
+            Ok(IntegrationOutcome::UpToDate) => {
+                self.ui.show_status("Already up to date");
+                true
+            }
+            Ok(IntegrationOutcome::FastForward { commit }) => {
+                self.ui.show_status(&format!("Fast-forwarded main to {}", &commit[..8]));
+                true
+            }
+            Ok(IntegrationOutcome::Committed { commit }) => {
+                self.ui.show_status(&format!("Integrated as {}", &commit[..8]));
+                true
+            }
+            Ok(IntegrationOutcome::Conflicts(pending)) => {
+                self.ui.show_conflicts(pending);
+                false
+            }
+            Err(e) => {
+                self.ui.show_error(&format!("Integration failed: {}", e));
+                false
+            }
+        }
+    }
+
+    /// Close the active terminal, asking first if its worktree holds work
+    async fn close_active_terminal(&mut self) -> Result<()> {
+        match self.workspace.active_terminal_id() {
+            Some(terminal_id) => self.close_terminal(terminal_id).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn close_terminal(&mut self, terminal_id: TerminalId) -> Result<()> {
+        let git = self.workspace.git_manager();
+        if let Some(info) = git.worktree_info(terminal_id).filter(|w| w.created) {
+            match git.worktree_state(terminal_id) {
+                Ok(state) if state.is_clean() => {}
+                Ok(state) => {
+                    self.ui.show_close_prompt(terminal_id, info.branch, state);
+                    return Ok(());
+                }
+                Err(e) => {
+                    self.ui.show_error(&format!("Cannot check worktree {}: {}", info.branch, e));
+                    return Ok(());
+                }
+            }
+        }
+
+        self.workspace.close_terminal(terminal_id).await?;
+        self.after_terminal_closed().await
+    }
+
+    async fn after_terminal_closed(&mut self) -> Result<()> {
+        if self.workspace.terminals().is_empty() {
+            self.workspace.create_terminal(None, None).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_close_prompt_key(&mut self, key: KeyEvent) -> Result<()> {
+        let disposition = match key.code {
+            KeyCode::Char('k') => WorktreeDisposition::Keep,
+            KeyCode::Char('s') => WorktreeDisposition::Stash,
+            KeyCode::Char('c') => WorktreeDisposition::Commit,
+            KeyCode::Char('D') => WorktreeDisposition::Discard,
+            KeyCode::Char('m') => {
+                let Some(terminal_id) = self.ui.close_prompt_mut().close() else {
+                    return Ok(());
+                };
+                let git = self.workspace.git_manager();
+                let branch = git.worktree_info(terminal_id).map(|w| w.branch).unwrap_or_default();
+                if let Err(e) = git.commit_all(terminal_id, &format!("WIP: uncommitted work from {}", branch)) {
+                    self.ui.show_error(&format!("Failed to commit {}: {}", branch, e));
+                    return Ok(());
+                }
+                if self.integrate_worktree(terminal_id, IntegrationStrategy::Merge).await {
+                    self.workspace.close_terminal(terminal_id).await?;
+                    self.after_terminal_closed().await?;
+                } else if self.ui.is_conflict_view_visible() {
+                    self.ui.show_status("Resolve the conflicts, then close the terminal again");
+                }
+                return Ok(());
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.ui.close_prompt_mut().close();
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+
+        let Some(terminal_id) = self.ui.close_prompt_mut().close() else {
+            return Ok(());
+        };
+        match self.workspace.close_terminal_with(terminal_id, disposition).await {
+            Ok(()) => {
+                if disposition == WorktreeDisposition::Keep {
+                    self.ui.show_status("Worktree kept; see :worktrees");
+                }
+                self.after_terminal_closed().await?;
+            }
+            Err(e) => self.ui.show_error(&format!("Failed to close terminal: {}", e)),
+        }
+        Ok(())
+    }
+
+    async fn handle_worktree_dashboard_key(&mut self, key: KeyEvent) -> Result<()> {
+        let selected = self.ui.worktree_dashboard_mut().selected_terminal();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.ui.worktree_dashboard_mut().close(),
+            KeyCode::Char('j') | KeyCode::Down => self.ui.worktree_dashboard_mut().move_down(),
+            KeyCode::Char('k') | KeyCode::Up => self.ui.worktree_dashboard_mut().move_up(),
+            KeyCode::Char('r') => self.ui.refresh_worktree_info(&self.workspace),
+            KeyCode::Enter => {
+                if let Some(terminal_id) = selected {
+                    self.workspace.set_active_terminal(terminal_id);
+                    self.focus = FocusArea::Terminal;
+                    self.ui.worktree_dashboard_mut().close();
+                } else if let Some(worktree) = self.ui.worktree_dashboard_mut().selected_unattached().cloned() {
+                    let id = self.workspace.attach_terminal(&worktree.name, None, None).await?;
+                    self.workspace.set_active_terminal(id);
+                    self.focus = FocusArea::Terminal;
+                    self.ui.worktree_dashboard_mut().close();
+                }
+            }
+            KeyCode::Char('s') => {
+                let Some(info) = selected.and_then(|id| self.workspace.git_manager().worktree_info(id)) else {
+                    return Ok(());
+                };
+                let id = self.workspace.create_terminal_in(info.path, None).await?;
+                self.workspace.set_active_terminal(id);
+                self.ui.worktree_dashboard_mut().close();
+            }
+            KeyCode::Char('d') => {
+                if let Some(terminal_id) = selected {
+                    self.workspace.set_active_terminal(terminal_id);
+                    self.ui.worktree_dashboard_mut().close();
+                    self.open_diff(DiffSide::Main).await?;
+                }
+            }
+            KeyCode::Char('m') => {
+                if let Some(terminal_id) = selected {
+                    self.integrate_worktree(terminal_id, IntegrationStrategy::Merge).await;
+                    self.ui.refresh_worktree_info(&self.workspace);
+                }
+            }
+            KeyCode::Char('x') => {
+                if let Some(terminal_id) = selected {
+                    self.close_terminal(terminal_id).await?;
+                    self.ui.refresh_worktree_info(&self.workspace);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_finder_key(&mut self, key: KeyEvent) -> Result<()> {
+        let finder = self.ui.finder_mut();
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) => finder.close(),
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => finder.move_up(),
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => finder.move_down(),
+            (KeyCode::Tab, _) => finder.toggle_mode(),
+            (KeyCode::Backspace, _) => finder.backspace(),
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => finder.push(c),
+            (KeyCode::Enter, _) => {
+                let choice = finder.selected_choice();
+                finder.close();
+                match choice {
+                    Some(FinderChoice::File(path)) => {
+                        self.open_in_editor(&path, None, None).await?;
+                    }
+                    Some(FinderChoice::Pane(id)) => {
+                        self.workspace.set_active_terminal(id);
+                        self.focus = FocusArea::Terminal;
+                    }
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_file_prompt_key(&mut self, key: KeyEvent) {
+        let tree = self.ui.file_tree_mut();
+        match key.code {
+            KeyCode::Esc => tree.cancel_prompt(),
+            KeyCode::Backspace => tree.prompt_backspace(),
+            KeyCode::Enter => match tree.confirm_prompt() {
+                Ok(Some(message)) => self.ui.show_status(&message),
+                Ok(None) => {}
+                Err(e) => self.ui.show_error(&format!("File operation failed: {}", e)),
+            },
+            KeyCode::Char(c) => {
+                tree.prompt_push(c);
+            }
+            _ => {}
+        }
+    }
+
+    /// Copy the selected path to the system clipboard through the outer
+    /// terminal (OSC 52), which also works over SSH
+    fn copy_explorer_path(&mut self, absolute: bool) -> Result<()> {
+        let tree = self.ui.file_tree_mut();
+        let Some(path) = tree.selected_path() else {
+            return Ok(());
+        };
+        let text = if absolute {
+            path.display().to_string()
+        } else {
+            tree.relative(path).display().to_string()
+        };
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&text);
+        let mut stdout = io::stdout();
+        write!(stdout, "\x1b]52;c;{}\x07", encoded)?;
+        stdout.flush()?;
+        self.ui.show_status(&format!("Copied {}", text));
+        Ok(())
+    }
+
+    /// Point the explorer at the active terminal's worktree, or back at the
+    /// project when it already shows that worktree
+    fn toggle_explorer_root(&mut self) {
+        let project = self.workspace.project_dir().to_path_buf();
+        let worktree = self
+            .workspace
+            .active_terminal_id()
+            .and_then(|id| self.workspace.worktree_path(id))
+            .and_then(|path| path.canonicalize().ok());
+
+        let root = match worktree {
+            Some(worktree) if worktree != self.ui.file_tree_mut().root() => worktree,
+            _ => project,
+        };
+        self.ui.show_status(&format!("Files: {}", root.display()));
+        self.ui.file_tree_mut().set_root(root);
+    }
+
+    async fn handle_timeline_key(&mut self, key: KeyEvent) -> Result<()> {
+        let selected = self.ui.timeline_mut().selected_entry().cloned();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.ui.timeline_mut().close(),
+            KeyCode::Char('j') | KeyCode::Down => self.ui.timeline_mut().move_down(),
+            KeyCode::Char('k') | KeyCode::Up => self.ui.timeline_mut().move_up(),
+            KeyCode::Char('f') => self.ui.timeline_mut().cycle_filter(),
+            KeyCode::Char('r') => self.ui.refresh_timeline(&self.workspace),
+            KeyCode::Char('d') => {
+                if let Some(entry) = selected {
+                    self.open_timeline_diff(entry.terminal_id).await?;
+                }
+            }
+            KeyCode::Enter => {
+                let Some(entry) = selected else {
+                    return Ok(());
+                };
+                match entry.event {
+                    TimelineEvent::FileChanged { path, change } => {
+                        let path = match change {
+                            ChangeType::Renamed { to, .. } => to,
+                            _ => path,
+                        };
+                        if path.is_file() {
+                            self.ui.timeline_mut().close();
+                            self.open_in_editor(&path, None, None).await?;
+                        } else {
+                            self.focus_timeline_pane(entry.terminal_id);
+                        }
+                    }
+                    TimelineEvent::Commit { .. } | TimelineEvent::Merged { .. } => {
+                        self.open_timeline_diff(entry.terminal_id).await?;
+                    }
+                    TimelineEvent::TerminalCreated { .. }
+                    | TimelineEvent::TerminalExited { .. }
+                    | TimelineEvent::TerminalClosed { .. } => {
+                        self.focus_timeline_pane(entry.terminal_id);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Focus the pane a timeline entry belongs to, if it is still open
+    fn focus_timeline_pane(&mut self, terminal_id: Option<TerminalId>) -> bool {
+        let open = terminal_id.filter(|id| self.workspace.terminals().iter().any(|t| t.id == *id));
+        match open {
+            Some(id) => {
+                self.workspace.set_active_terminal(id);
+                self.focus = FocusArea::Terminal;
+                self.ui.timeline_mut().close();
+                true
+            }
+            None => {
+                self.ui.show_status("That pane is closed");
+                false
+            }
+        }
+    }
+
+    /// Diff of the entry's pane: against main for worktrees, else unstaged
+    async fn open_timeline_diff(&mut self, terminal_id: Option<TerminalId>) -> Result<()> {
+        if !self.focus_timeline_pane(terminal_id) {
+            return Ok(());
+        }
+        let side = match terminal_id.and_then(|id| self.workspace.git_manager().worktree_info(id)) {
+            Some(_) => DiffSide::Main,
+            None => DiffSide::Unstaged,
+        };
+        self.open_diff(side).await
+    }
+
+    async fn handle_worktree_manager_key(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.ui.worktree_manager_mut().close(),
+            KeyCode::Char('j') | KeyCode::Down => self.ui.worktree_manager_mut().move_down(),
+            KeyCode::Char('k') | KeyCode::Up => self.ui.worktree_manager_mut().move_up(),
+            KeyCode::Char('r') => {
+                if let Err(e) = self.workspace.git_manager().discover_worktrees() {
+                    self.ui.show_error(&format!("Failed to list worktrees: {}", e));
+                }
+                let entries = self.workspace.git_manager().available_worktree_states();
+                self.ui.worktree_manager_mut().refresh(entries);
+            }
+            KeyCode::Enter => {
+                let Some(worktree) = self.ui.worktree_manager_mut().selected().cloned() else {
+                    return Ok(());
+                };
+                match self.workspace.attach_terminal(&worktree.name, None, None).await {
+                    Ok(id) => {
+                        self.workspace.set_active_terminal(id);
+                        self.ui.worktree_manager_mut().close();
+                    }
+                    Err(e) => self.ui.show_error(&format!("Failed to attach: {}", e)),
+                }
+            }
+            KeyCode::Char(c @ ('d' | 'D')) => {
+                let Some(worktree) = self.ui.worktree_manager_mut().selected().cloned() else {
+                    return Ok(());
+                };
+                let git = self.workspace.git_manager();
+                match git.remove_kept_worktree(&worktree.name, c == 'D').await {
+                    Ok(()) => self.ui.show_status(&format!("Removed worktree {}", worktree.name)),
+                    Err(e) => self.ui.show_error(&e.to_string()),
+                }
+                let entries = self.workspace.git_manager().available_worktree_states();
+                self.ui.worktree_manager_mut().refresh(entries);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Open the diff pane for the active terminal's worktree
+    async fn open_diff(&mut self, side: DiffSide) -> Result<()> {
+        let git = self.workspace.git_manager();
+        if !git.is_git_repo() {
+            self.ui.show_error("Not a git repository");
+            return Ok(());
+        }
+
+        let terminal_id = self.workspace.active_terminal_id();
+        let title = self
+            .workspace
+            .terminals()
+            .into_iter()
+            .find(|t| Some(t.id) == terminal_id)
+            .map(|t| t.working_dir.display().to_string())
+            .unwrap_or_else(|| "project".to_string());
+
+        match git.get_diff(terminal_id, side).await {
+            Ok(hunks) => self.ui.show_diff(title, side, hunks),
+            Err(e) => self.ui.show_error(&format!("Failed to read diff: {}", e)),
+        }
+        Ok(())
+    }
+
     fn draw_ui(&mut self) {
         match self.terminal.draw(|frame| {
             tracing::trace!("Drawing frame");
@@ -386,42 +1415,229 @@ impl RgbApp {
         }
     }
 
+    async fn handle_command_line_key(&mut self, key: KeyEvent) -> Result<()> {
+        let line = self.ui.command_line_mut();
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                self.command_mode = false;
+                line.clear();
+            }
+            (KeyCode::Enter, _) => {
+                let command = line.submit();
+                self.command_mode = false;
+                if let Err(e) = self.execute_command(&command).await {
+                    self.ui.show_error(&e.to_string());
+                }
+            }
+            (KeyCode::Tab, _) if line.is_cycling() => line.cycle(false),
+            (KeyCode::BackTab, _) if line.is_cycling() => line.cycle(true),
+            (KeyCode::Tab, _) => {
+                let context = self.completion_context();
+                let typed = self.ui.command_line_mut().before_cursor().to_string();
+                if let Some(completion) = command::complete(&self.commands, &typed, &context) {
+                    self.ui.command_line_mut().complete(completion);
+                }
+            }
+            (KeyCode::Backspace, _) => line.backspace(),
+            (KeyCode::Delete, _) => line.delete(),
+            (KeyCode::Left, _) | (KeyCode::Char('b'), KeyModifiers::CONTROL) => line.left(),
+            (KeyCode::Right, _) | (KeyCode::Char('f'), KeyModifiers::CONTROL) => line.right(),
+            (KeyCode::Home, _) | (KeyCode::Char('a'), KeyModifiers::CONTROL) => line.home(),
+            (KeyCode::End, _) | (KeyCode::Char('e'), KeyModifiers::CONTROL) => line.end(),
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => line.history_previous(),
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => line.history_next(),
+            (KeyCode::Char('w'), KeyModifiers::CONTROL) => line.delete_word(),
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => line.kill_to_start(),
+            (KeyCode::Char('k'), KeyModifiers::CONTROL) => line.kill_to_end(),
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => line.insert(c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn completion_context(&self) -> CompletionContext {
+        let terminals = self.workspace.terminals();
+        let dir = self
+            .workspace
+            .active_terminal_id()
+            .and_then(|id| terminals.iter().find(|t| t.id == id))
+            .map(|t| t.working_dir.clone())
+            .unwrap_or_else(|| self.workspace.project_dir().to_path_buf());
+        CompletionContext {
+            profiles: self.config.terminals.keys().cloned().collect(),
+            branches: self.workspace.git_manager().branch_names(),
+            layouts: LayoutEngine::LAYOUT_NAMES.iter().map(|l| l.to_string()).collect(),
+            panes: terminals.into_iter().map(|t| t.title).collect(),
+            dir,
+        }
+    }
+
+    /// Command line bound to `key` in the config, if any
+    fn bound_command(&self, key: &KeyEvent) -> Option<String> {
+        self.config
+            .keybindings
+            .commands
+            .iter()
+            .find(|(binding, _)| command::parse_key(binding) == Some((key.code, key.modifiers)))
+            .map(|(_, line)| line.clone())
+    }
+
     async fn execute_command(&mut self, command: &str) -> Result<()> {
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        if parts.is_empty() {
-            return Ok(());
+        if let Some((id, args)) = self.commands.parse(command)? {
+            self.run_command(id, args).await?;
         }
+        Ok(())
+    }
 
-        match parts[0] {
-            "quit" | "q" => self.should_quit = true,
-            "new" => {
-                let cmd = parts.get(1).map(|s| s.to_string());
-                self.workspace.create_terminal(cmd).await?;
+    /// Run a registered command; the command line and keybindings both
+    /// come through here
+    async fn run_command(&mut self, id: CommandId, args: Vec<String>) -> Result<()> {
+        match id {
+            CommandId::Quit => self.should_quit = true,
+            CommandId::New => {
+                let command = self.profile_command(&args);
+                let id = self.workspace.create_terminal(command, self.profile_key(&args)).await?;
+                self.record_if_configured(args.first().map(String::as_str), id);
+                self.warn_if_overloaded();
+            }
+            CommandId::Attach => match args.first() {
+                Some(target) => {
+                    let command = self.profile_command(&args[1..]);
+                    let id = self.workspace.attach_terminal(target, command, self.profile_key(&args[1..])).await?;
+                    self.record_if_configured(args.get(1).map(String::as_str), id);
+                    self.warn_if_overloaded();
+                }
+                None => {
+                    let available = self.workspace.git_manager().available_worktrees();
+                    if available.is_empty() {
+                        self.ui.show_status("No unattached worktrees; use :attach <branch>");
+                    } else {
+                        let names: Vec<String> = available
+                            .iter()
+                            .map(|w| w.branch.clone().unwrap_or_else(|| w.name.clone()))
+                            .collect();
+                        self.ui.show_status(&format!("Worktrees: {}", names.join(", ")));
+                    }
+                }
+            },
+            CommandId::Close => self.close_active_terminal().await?,
+            CommandId::Focus => {
+                let terminals = self.workspace.terminals();
+                let wanted = args.join(" ");
+                let found = match wanted.parse::<usize>() {
+                    Ok(n) => terminals.get(n.wrapping_sub(1)),
+                    Err(_) => terminals
+                        .iter()
+                        .find(|t| t.title.eq_ignore_ascii_case(&wanted))
+                        .or_else(|| terminals.iter().find(|t| t.title.to_lowercase().starts_with(&wanted.to_lowercase()))),
+                };
+                match found {
+                    Some(terminal) => {
+                        self.workspace.set_active_terminal(terminal.id);
+                        self.focus = FocusArea::Terminal;
+                    }
+                    None => anyhow::bail!("No pane {}", wanted),
+                }
+            }
+            CommandId::Edit => {
+                let dir = self.completion_context().dir;
+                let (path, line, column) = file_position(&args.join(" "), &dir);
+                self.open_in_editor(&path, line, column).await?;
+            }
+            CommandId::Find | CommandId::Panes => {
+                let mode = if id == CommandId::Find { FinderMode::Files } else { FinderMode::Panes };
+                self.ui.show_finder(mode, &self.workspace);
+                for c in args.join(" ").chars() {
+                    self.ui.finder_mut().push(c);
+                }
+            }
+            CommandId::Fanout => match args.as_slice() {
+                [] => match &self.fanout {
+                    Some(fanout) => self.ui.show_fanout(fanout),
+                    None => anyhow::bail!("No agents running; :fanout <profile> <n> <prompt>"),
+                },
+                [profile, count, prompt @ ..] if !prompt.is_empty() => {
+                    let count = count
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("Not a number of agents: {}", count))?;
+                    self.start_fanout(profile, count, prompt.join(" ")).await?;
+                }
+                _ => anyhow::bail!("Usage: :fanout <profile> <n> <prompt>"),
+            },
+            CommandId::Monitor => self.run_monitor_command(&args)?,
+            CommandId::NextAlert => self.focus_next_alert()?,
+            CommandId::Record => self.toggle_recording(args.first().map(String::as_str))?,
+            CommandId::Play => self.play_recording(args.first().map(String::as_str)).await?,
+            CommandId::SaveOutput => self.save_output(&args)?,
+            CommandId::PipePane => self.pipe_pane(&args)?,
+            CommandId::Sync => self.run_sync_command(&args)?,
+            CommandId::Broadcast => self.broadcast(&args)?,
+            CommandId::Workspace => self.run_workspace_command(&args).await?,
+            CommandId::Top => self.show_resources(),
+            CommandId::Inspect => self.show_process_inspector()?,
+            CommandId::Timeline => self.ui.show_timeline(&self.workspace),
+            CommandId::Worktrees => {
+                let entries = self.workspace.git_manager().available_worktree_states();
+                self.ui.show_worktree_manager(entries);
             }
-            "worktree" => {
+            CommandId::Worktree => {
                 // Show worktree info
                 self.ui.show_worktree_info(&self.workspace);
             }
-            "commit" => {
+            CommandId::Diff => {
+                let side = match args.first().map(String::as_str) {
+                    Some("staged" | "cached") => DiffSide::Staged,
+                    Some("main") => DiffSide::Main,
+                    _ => DiffSide::Unstaged,
+                };
+                self.open_diff(side).await?;
+            }
+            CommandId::Merge => self.integrate_active_worktree(IntegrationStrategy::Merge).await?,
+            CommandId::Rebase => self.integrate_active_worktree(IntegrationStrategy::Rebase).await?,
+            CommandId::Squash => self.integrate_active_worktree(IntegrationStrategy::Squash).await?,
+            CommandId::Commit => {
                 // Open commit interface
                 self.ui.show_commit_interface();
             }
-            "layout" => {
-                if let Some(layout_name) = parts.get(1) {
-                    self.layout.apply_layout(layout_name)?;
-                }
-            }
-            "config" => {
-                // Open configuration
-                self.ui.show_config_editor(&self.config);
+            CommandId::Layout => self.layout.apply_layout(&args[0])?,
+            CommandId::Explorer => {
+                self.ui.toggle_file_explorer();
+                self.focus = FocusArea::Terminal;
+                self.save_file_explorer();
             }
-            _ => {
-                self.ui.show_error(&format!("Unknown command: {}", parts[0]));
+            CommandId::GitPanel => {
+                self.ui.toggle_git_panel();
+                self.save_git_panel();
             }
+            CommandId::Config => self.run_config_command(&args).await?,
+            CommandId::Help => match args.first() {
+                Some(name) => match self.commands.lookup(name) {
+                    Some(spec) => self.ui.show_status(&format!(":{} - {}", spec.usage, spec.summary)),
+                    None => anyhow::bail!("Unknown command: {}", name),
+                },
+                None => self.ui.toggle_help(),
+            },
         }
         Ok(())
     }
 
+    /// Shell command for `:new` and `:attach` arguments: a profile name
+    /// expands to its command, anything else runs as typed
+    fn profile_command(&self, args: &[String]) -> Option<String> {
+        let (first, rest) = args.split_first()?;
+        let mut words: Vec<String> = rest.iter().map(|a| command::quote(a)).collect();
+        match self.config.terminals.get(first) {
+            Some(profile) => words.insert(0, profile.command.clone()),
+            None => words.insert(0, command::quote(first)),
+        }
+        Some(words.join(" "))
+    }
+
+    /// The configured profile `:new` and `:attach` arguments name, if any
+    fn profile_key<'a>(&self, args: &'a [String]) -> Option<&'a str> {
+        args.first().map(String::as_str).filter(|name| self.config.terminals.contains_key(*name))
+    }
+
     fn cleanup(&mut self) -> Result<()> {
         disable_raw_mode()?;
         execute!(
@@ -438,4 +1654,34 @@ impl Drop for RgbApp {
     fn drop(&mut self) {
         let _ = self.cleanup();
     }
-}
\ No newline at end of file
+}
+
+/// Footer status while the background worker syncs worktrees
+fn syncing_status(worktrees: usize) -> String {
+    format!("Syncing {} worktree(s)...", worktrees)
+}
+
+/// Split `path[:line[:col]]`, resolving the path against `dir`. A name
+/// that exists with its colons wins over a line suffix.
+fn file_position(arg: &str, dir: &Path) -> (PathBuf, Option<usize>, Option<usize>) {
+    let resolve = |p: &str| match p.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => dir.join(p),
+    };
+    let whole = resolve(arg);
+    if whole.exists() {
+        return (whole, None, None);
+    }
+
+    let mut parts = arg.rsplitn(3, ':');
+    let numbers: Vec<Option<usize>> = parts.by_ref().take(2).map(|p| p.parse().ok()).collect();
+    let rest: Vec<&str> = parts.collect();
+    match (numbers.as_slice(), rest.as_slice()) {
+        ([Some(column), Some(line)], [path]) => (resolve(path), Some(*line), Some(*column)),
+        ([Some(line), None], _) => {
+            let path = arg.rsplit_once(':').map_or(arg, |(p, _)| p);
+            (resolve(path), Some(*line), None)
+        }
+        _ => (whole, None, None),
+    }
+}
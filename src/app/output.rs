@@ -0,0 +1,74 @@
+//! `:save-output` and `:pipe-pane`: a pane's output saved to a file, all
+//! of it so far or everything it prints from now on.
+
+use super::RgbApp;
+use crate::terminal::TerminalEmulator;
+use crate::workspace::TerminalId;
+use anyhow::Result;
+use directories::ProjectDirs;
+use parking_lot::RwLock;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+impl RgbApp {
+    /// `:save-output [-e] [path]`: write the active pane's scrollback and
+    /// screen to a file, with colours and attributes kept given `-e`
+    pub(super) fn save_output(&mut self, args: &[String]) -> Result<()> {
+        let (id, emulator) = self.active_emulator()?;
+        let mut ansi = false;
+        let mut path = None;
+        for arg in args {
+            match arg.as_str() {
+                "-e" | "--ansi" => ansi = true,
+                _ if path.is_none() => path = Some(arg),
+                _ => anyhow::bail!("Usage: :save-output [-e] [path]"),
+            }
+        }
+
+        let path = match path {
+            Some(path) => self.completion_context().dir.join(path),
+            None => output_dir()?.join(self.pane_file_name(id, "txt")?),
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = emulator.read().capture(ansi);
+        std::fs::write(&path, &text).map_err(|e| anyhow::anyhow!("Cannot write {}: {}", path.display(), e))?;
+        self.ui.show_status(&format!("Saved {} lines to {}", text.lines().count(), path.display()));
+        Ok(())
+    }
+
+    /// `:pipe-pane [command...]`: feed the active pane's output from now on
+    /// to a shell command, such as `cat >> build.log`; without one, stop
+    pub(super) fn pipe_pane(&mut self, args: &[String]) -> Result<()> {
+        let (_, emulator) = self.active_emulator()?;
+        if args.is_empty() {
+            let stopped = emulator.write().stop_pipe();
+            match stopped {
+                Some(command) => self.ui.show_status(&format!("Stopped piping to `{}`", command)),
+                None => self.ui.show_status("Not piping; :pipe-pane <command> starts"),
+            }
+            return Ok(());
+        }
+
+        // As typed, so the shell sees its quotes and operators
+        let command = &args[0];
+        emulator.write().pipe_output(command)?;
+        self.ui.show_status(&format!("Piping output to `{}`; :pipe-pane alone stops", command));
+        Ok(())
+    }
+
+    fn active_emulator(&self) -> Result<(TerminalId, Arc<RwLock<TerminalEmulator>>)> {
+        self.workspace
+            .active_terminal_id()
+            .and_then(|id| Some((id, self.workspace.get_terminal_emulator(id)?)))
+            .ok_or_else(|| anyhow::anyhow!("No active pane"))
+    }
+}
+
+/// Where `:save-output` writes unless given a path
+fn output_dir() -> Result<PathBuf> {
+    ProjectDirs::from("com", "rgb", "rgb")
+        .map(|dirs| dirs.data_dir().join("output"))
+        .ok_or_else(|| anyhow::anyhow!("No home directory for saved output"))
+}
@@ -0,0 +1,133 @@
+//! `:record` and `:play`: pane output saved as asciicast files, and replayed
+//! in a pane by `rgb play`.
+
+use super::{FocusArea, RgbApp};
+use crate::command;
+use crate::workspace::TerminalId;
+use anyhow::Result;
+use directories::ProjectDirs;
+use std::path::{Path, PathBuf};
+
+impl RgbApp {
+    /// `:record [path]`: start recording the active pane, or stop if it is
+    /// being recorded
+    pub(super) fn toggle_recording(&mut self, path: Option<&str>) -> Result<()> {
+        let Some(id) = self.workspace.active_terminal_id() else {
+            anyhow::bail!("No active pane");
+        };
+        let emulator = self
+            .workspace
+            .get_terminal_emulator(id)
+            .ok_or_else(|| anyhow::anyhow!("No active pane"))?;
+
+        let stopped = emulator.write().stop_recording();
+        match stopped {
+            Some(saved) => self.ui.show_status(&format!("Saved recording to {}", saved.display())),
+            None => {
+                let path = match path {
+                    Some(path) => self.completion_context().dir.join(path),
+                    None => self.recording_path(id)?,
+                };
+                self.start_recording(id, &path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Record a pane opened from a profile with `record = true`
+    pub(super) fn record_if_configured(&mut self, profile: Option<&str>, id: TerminalId) {
+        let Some(profile) = profile.and_then(|p| self.config.terminals.get(p)) else {
+            return;
+        };
+        if !profile.record {
+            return;
+        }
+        let started = self.recording_path(id).and_then(|path| self.start_recording(id, &path));
+        if let Err(e) = started {
+            self.ui.show_error(&format!("Failed to start recording: {}", e));
+        }
+    }
+
+    fn start_recording(&mut self, id: TerminalId, path: &Path) -> Result<()> {
+        let title = self
+            .workspace
+            .terminals()
+            .into_iter()
+            .find(|t| t.id == id)
+            .map(|t| t.branch.unwrap_or(t.title))
+            .unwrap_or_default();
+        if let Some(emulator) = self.workspace.get_terminal_emulator(id) {
+            emulator.write().start_recording(path, &title)?;
+            self.ui.show_status(&format!("Recording to {}; :record again stops", path.display()));
+        }
+        Ok(())
+    }
+
+    fn recording_path(&self, id: TerminalId) -> Result<PathBuf> {
+        Ok(recordings_dir()?.join(self.pane_file_name(id, "cast")?))
+    }
+
+    /// `<project>-<branch or title>-<time>.<extension>`, for files saved
+    /// from a pane
+    pub(super) fn pane_file_name(&self, id: TerminalId, extension: &str) -> Result<String> {
+        let terminal = self
+            .workspace
+            .terminals()
+            .into_iter()
+            .find(|t| t.id == id)
+            .ok_or_else(|| anyhow::anyhow!("No such pane"))?;
+        let project = self
+            .workspace
+            .project_dir()
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "rgb".to_string());
+        let pane = terminal.branch.unwrap_or(terminal.title);
+        let time = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        Ok(format!("{}-{}-{}.{}", project, pane, time, extension).replace(['/', ' '], "-"))
+    }
+
+    /// `:play [file]`: replay a cast, or the newest recording, in a new pane
+    pub(super) async fn play_recording(&mut self, file: Option<&str>) -> Result<()> {
+        let path = match file {
+            Some(file) => self.completion_context().dir.join(file),
+            None => newest_recording()?,
+        };
+        if !path.is_file() {
+            anyhow::bail!("No recording at {}", path.display());
+        }
+        let rgb = std::env::current_exe()?;
+        let command = format!(
+            "{} play {}",
+            command::quote(&rgb.to_string_lossy()),
+            command::quote(&path.to_string_lossy())
+        );
+        let id = self
+            .workspace
+            .create_terminal_in(self.workspace.project_dir().to_path_buf(), Some(command))
+            .await?;
+        self.workspace.set_active_terminal(id);
+        self.focus = FocusArea::Terminal;
+        Ok(())
+    }
+}
+
+/// Where recordings go unless `:record` is given a path
+fn recordings_dir() -> Result<PathBuf> {
+    ProjectDirs::from("com", "rgb", "rgb")
+        .map(|dirs| dirs.data_dir().join("recordings"))
+        .ok_or_else(|| anyhow::anyhow!("No home directory for recordings"))
+}
+
+fn newest_recording() -> Result<PathBuf> {
+    let dir = recordings_dir()?;
+    std::fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "cast"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+        .ok_or_else(|| anyhow::anyhow!("No recordings in {}", dir.display()))
+}
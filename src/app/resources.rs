@@ -0,0 +1,162 @@
+//! `:top` and overload warnings: what each pane's processes use, sampled
+//! from /proc, and a warning once the machine is busier than `[resources]`
+//! allows. Warnings stand in for a hard cap on the number of panes.
+
+use super::{FocusArea, RgbApp};
+use crate::monitor::SystemLoad;
+use crate::ui::components::{NotificationLevel, ResourceRow};
+use crate::workspace::TerminalId;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use std::collections::HashMap;
+
+impl RgbApp {
+    /// Sample every pane's process tree and the machine's load, warning
+    /// when it becomes overloaded
+    pub(super) fn sample_resources(&mut self) {
+        let pids: HashMap<TerminalId, u32> = self
+            .workspaces()
+            .flat_map(|w| {
+                w.terminals()
+                    .into_iter()
+                    .filter_map(|t| Some((t.id, w.get_terminal_emulator(t.id)?.read().pid())))
+            })
+            .collect();
+        self.usage = self.resources.sample(&pids);
+        self.system = SystemLoad::read();
+
+        let overload = self.overload();
+        if let (Some(reason), false) = (&overload, self.overloaded) {
+            self.ui.notify(
+                NotificationLevel::Warning,
+                format!("Machine overloaded ({}); :top shows what each pane uses", reason),
+            );
+        }
+        self.overloaded = overload.is_some();
+        self.ui.set_overload(overload);
+        let rows = self.resource_rows();
+        self.ui.refresh_resources(rows, self.system);
+        // Its CPU figures come from this sample
+        if self.ui.is_process_inspector_visible() {
+            self.refresh_process_inspector();
+        }
+    }
+
+    /// What is over its `[resources]` limit, if anything
+    fn overload(&self) -> Option<String> {
+        let limits = &self.config.resources;
+        let mut reasons = Vec::new();
+        if limits.load_per_cpu > 0.0 && self.system.load_per_cpu() > limits.load_per_cpu {
+            reasons.push(format!("Load {:.1}/{} CPUs", self.system.load, self.system.cpus));
+        }
+        let memory = self.system.memory_used_percent();
+        if limits.memory_percent > 0.0 && memory > limits.memory_percent {
+            reasons.push(format!("Memory {:.0}%", memory));
+        }
+        (!reasons.is_empty()).then(|| reasons.join(", "))
+    }
+
+    /// Warn that a pane just opened adds to an overloaded machine
+    pub(super) fn warn_if_overloaded(&mut self) {
+        if let Some(reason) = self.overload().filter(|_| self.overloaded) {
+            self.ui.notify(
+                NotificationLevel::Warning,
+                format!("Machine overloaded ({}); more panes slow every agent down", reason),
+            );
+        }
+    }
+
+    /// `:top`
+    pub(super) fn show_resources(&mut self) {
+        if self.usage.is_empty() {
+            self.sample_resources();
+        }
+        let rows = self.resource_rows();
+        self.ui.show_resources(rows, self.system);
+    }
+
+    /// Every pane of every workspace, in tab and pane order
+    fn resource_rows(&self) -> Vec<ResourceRow> {
+        self.named_workspaces()
+            .flat_map(|(name, workspace)| {
+                workspace.terminals().into_iter().enumerate().map(move |(i, t)| ResourceRow {
+                    terminal_id: t.id,
+                    number: i + 1,
+                    usage: self.usage.get(&t.id).copied().unwrap_or_default(),
+                    title: t.title,
+                    workspace: name.to_string(),
+                    branch: t.branch,
+                })
+            })
+            .collect()
+    }
+
+    pub(super) fn handle_resources_key(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.ui.resource_monitor_mut().close(),
+            KeyCode::Char('j') | KeyCode::Down => self.ui.resource_monitor_mut().move_down(),
+            KeyCode::Char('k') | KeyCode::Up => self.ui.resource_monitor_mut().move_up(),
+            KeyCode::Char('s') => self.ui.resource_monitor_mut().cycle_sort(),
+            KeyCode::Char('r') => self.sample_resources(),
+            KeyCode::Enter => {
+                let Some(id) = self.ui.resource_monitor_mut().selected_terminal() else {
+                    return Ok(());
+                };
+                if let Some(tab) = self.tab_of(id) {
+                    self.switch_workspace(tab);
+                }
+                self.workspace.set_active_terminal(id);
+                self.focus = FocusArea::Terminal;
+                self.ui.resource_monitor_mut().close();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Alt+G: start picking a pane by number, for more panes than F1-F10
+    pub(super) fn start_pane_number(&mut self) {
+        self.pane_number = Some(String::new());
+        self.ui.set_pane_number(self.pane_number.clone());
+    }
+
+    /// Digits after Alt+G. The pane is picked as soon as no more digits
+    /// could name another one, or on Enter; Esc or any other key cancels.
+    pub(super) fn handle_pane_number_key(&mut self, key: KeyEvent) {
+        let Some(mut digits) = self.pane_number.take() else {
+            return;
+        };
+        let count = self.workspace.terminals().len();
+        let done = match key.code {
+            KeyCode::Char(c @ '0'..='9') => {
+                digits.push(c);
+                let number: usize = digits.parse().unwrap_or(0);
+                number == 0 || number.saturating_mul(10) > count
+            }
+            KeyCode::Backspace => {
+                digits.pop();
+                false
+            }
+            KeyCode::Enter => true,
+            _ => {
+                self.ui.set_pane_number(None);
+                return;
+            }
+        };
+
+        if !done {
+            self.pane_number = Some(digits);
+            self.ui.set_pane_number(self.pane_number.clone());
+            return;
+        }
+        self.ui.set_pane_number(None);
+        match digits.parse::<usize>() {
+            Ok(number) if (1..=count).contains(&number) => {
+                self.workspace.switch_to_terminal(number - 1);
+                self.focus = FocusArea::Terminal;
+            }
+            Ok(number) => self.ui.show_status(&format!("No pane {}; there are {}", number, count)),
+            Err(_) => {}
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! `:config` and live reloading: the config files are re-read whenever one
+//! is saved, and theme, border style, panels, keybindings and profiles
+//! take effect without a restart. Changes made in rgb, such as toggled
+//! panels, are saved back to the user's config file.
+
+use super::{editor, FocusArea, RgbApp};
+use crate::command;
+use crate::config::{self, KeybindingsConfig};
+use crate::ui::components::NotificationLevel;
+use crate::ui::theme::{border_type, Theme};
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Serialize;
+
+impl RgbApp {
+    /// Push the settings the UI draws with to it
+    pub(super) fn apply_config(&mut self) {
+        let theme = match Theme::named(&self.config.appearance.theme) {
+            Some(theme) => theme,
+            None => {
+                self.ui.notify(
+                    NotificationLevel::Warning,
+                    format!(
+                        "Unknown theme {}; use {}",
+                        self.config.appearance.theme,
+                        Theme::NAMES.join(", ")
+                    ),
+                );
+                Theme::default()
+            }
+        };
+        self.ui.set_theme(theme);
+        self.check_keybindings();
+        editor::probe_vim(&editor::editor_command(&self.config.editor.command));
+        self.ui.set_border_type(border_type(&self.config.layout.border_style));
+        self.ui.set_panels(self.config.layout.file_explorer, self.config.layout.git_panel);
+        if !self.config.layout.file_explorer && self.focus == FocusArea::FileExplorer {
+            self.focus = FocusArea::Terminal;
+        }
+    }
+
+    /// Warn about `[keybindings]` that name no key; the built-in ones fall
+    /// back to their defaults rather than leaving their action unbound
+    fn check_keybindings(&mut self) {
+        let defaults = KeybindingsConfig::default();
+        let bindings = &mut self.config.keybindings;
+        let mut problems = Vec::new();
+        for (name, binding, default) in [
+            ("new_terminal", &mut bindings.new_terminal, defaults.new_terminal),
+            ("close_terminal", &mut bindings.close_terminal, defaults.close_terminal),
+            ("switch_mode", &mut bindings.switch_mode, defaults.switch_mode),
+        ] {
+            if command::parse_key(binding).is_none() {
+                problems.push(format!("keybindings.{} = \"{}\" is not a key; using {}", name, binding, default));
+                *binding = default;
+            }
+        }
+        let mut unknown: Vec<&String> = bindings
+            .commands
+            .keys()
+            .filter(|binding| command::parse_key(binding).is_none())
+            .collect();
+        unknown.sort();
+        problems.extend(unknown.into_iter().map(|binding| format!("keybindings.commands: \"{}\" is not a key", binding)));
+        for problem in problems {
+            self.ui.notify(NotificationLevel::Warning, problem);
+        }
+    }
+
+    /// Re-read the config files, keeping the current settings if one of
+    /// them is invalid
+    pub(super) fn reload_config(&mut self) {
+        let config = match config::load_config(self.config_path.clone()) {
+            Ok(config) => config,
+            Err(e) => {
+                self.ui.notify(NotificationLevel::Error, format!("Config not applied: {}", e));
+                return;
+            }
+        };
+
+        // Read once at startup, by the workspace and its git worker
+        let mut restart = Vec::new();
+        if !same(&self.config.general, &config.general) {
+            restart.push("[general]");
+        }
+        if !same(&self.config.git, &config.git) {
+            restart.push("[git]");
+        }
+        self.config = config;
+        self.apply_config();
+
+        let message = if restart.is_empty() {
+            "Config reloaded".to_string()
+        } else {
+            format!("Config reloaded; {} changes apply after a restart", restart.join(" and "))
+        };
+        self.ui.notify(NotificationLevel::Info, message);
+    }
+
+    /// Save settings changed in rgb to the user's config file, which is
+    /// then not reloaded as if edited
+    fn save_settings(&mut self, settings: &[(&str, toml::Value)]) -> Result<()> {
+        let path = config::user_config_path(self.config_path.clone());
+        config::save_config(settings, Some(path.clone()))
+            .map_err(|e| anyhow::anyhow!("Cannot save {}: {}", path.display(), e))?;
+        self.config_watcher.changed();
+        Ok(())
+    }
+
+    /// Remember whether the file explorer is shown for the next start
+    pub(super) fn save_file_explorer(&mut self) {
+        self.config.layout.file_explorer = self.ui.is_file_explorer_visible();
+        let setting = toml::Value::Boolean(self.config.layout.file_explorer);
+        if let Err(e) = self.save_settings(&[("layout.file_explorer", setting)]) {
+            self.ui.show_status(&e.to_string());
+        }
+    }
+
+    /// Remember whether the git panel is shown for the next start
+    pub(super) fn save_git_panel(&mut self) {
+        self.config.layout.git_panel = self.ui.is_git_panel_visible();
+        let setting = toml::Value::Boolean(self.config.layout.git_panel);
+        if let Err(e) = self.save_settings(&[("layout.git_panel", setting)]) {
+            self.ui.show_status(&e.to_string());
+        }
+    }
+
+    /// `:config [edit|reload|set <key> <value>]`
+    pub(super) async fn run_config_command(&mut self, args: &[String]) -> Result<()> {
+        match args {
+            [] => self.edit_config().await,
+            [verb] if verb == "edit" => self.edit_config().await,
+            [verb] if verb == "reload" => {
+                self.reload_config();
+                Ok(())
+            }
+            [verb, key, value @ ..] if verb == "set" && !value.is_empty() => {
+                let value = value.join(" ");
+                let setting = self.config.set(key, &value)?;
+                self.apply_config();
+                self.save_settings(&[(key, setting)])?;
+                self.ui.show_status(&format!("Set {} = {}", key, value));
+                Ok(())
+            }
+            _ => anyhow::bail!("Usage: :config [edit|reload|set <key> <value>]"),
+        }
+    }
+
+    /// Open the user's config file in the editor in a new pane; it is
+    /// created empty first if it doesn't exist yet
+    async fn edit_config(&mut self) -> Result<()> {
+        let path = config::user_config_path(self.config_path.clone());
+        if !path.exists() {
+            self.save_settings(&[])?;
+        }
+        let dir = path.parent().map(|d| d.to_path_buf()).unwrap_or_default();
+        let program = editor::editor_command(&self.config.editor.command);
+        let launch = format!("{} {}", program, command::quote(&path.display().to_string()));
+        let id = self.workspace.create_terminal_in(dir, Some(launch)).await?;
+        self.workspace.set_active_terminal(id);
+        self.focus = FocusArea::Terminal;
+        self.ui.show_status(&format!("Editing {}; saved changes apply live", path.display()));
+        Ok(())
+    }
+}
+
+/// Whether two config sections hold the same settings
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    toml::Value::try_from(a).ok() == toml::Value::try_from(b).ok()
+}
+
+/// Whether `key` is the one a `[keybindings]` entry names; Ctrl with a
+/// letter matches either case
+pub(super) fn is_bound(binding: &str, key: &KeyEvent) -> bool {
+    let code = match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => KeyCode::Char(c.to_ascii_lowercase()),
+        code => code,
+    };
+    command::parse_key(binding) == Some((code, key.modifiers))
+}
@@ -0,0 +1,98 @@
+//! `:sync` and `:broadcast`: the same input typed into several panes, such
+//! as `git pull` in every agent's worktree.
+
+use super::RgbApp;
+use crate::workspace::TerminalId;
+use anyhow::Result;
+
+impl RgbApp {
+    /// `:sync [all|profile|off]`: toggle the active pane in the group whose
+    /// keys are mirrored, or make the group every pane, the panes running
+    /// the active pane's profile, or none
+    pub(super) fn run_sync_command(&mut self, args: &[String]) -> Result<()> {
+        let active = self.workspace.active_terminal_id();
+        match args.first().map(String::as_str) {
+            None => {
+                let Some(id) = active else {
+                    anyhow::bail!("No active pane");
+                };
+                if self.workspace.toggle_synchronized(id) {
+                    self.ui.show_status(&format!("Synchronized {} pane(s); :sync off stops", self.synchronized().len()));
+                } else {
+                    self.ui.show_status("Pane no longer synchronized");
+                }
+            }
+            Some("all") => {
+                let ids: Vec<TerminalId> = self.workspace.terminals().iter().map(|t| t.id).collect();
+                self.workspace.set_synchronized(ids);
+                self.ui.show_status(&format!("Synchronized all {} pane(s)", self.synchronized().len()));
+            }
+            Some("profile") => {
+                let Some(id) = active else {
+                    anyhow::bail!("No active pane");
+                };
+                self.workspace.set_synchronized(self.workspace.panes_with_profile_of(id));
+                self.ui.show_status(&format!("Synchronized {} pane(s) of this profile", self.synchronized().len()));
+            }
+            Some("off") => {
+                self.workspace.set_synchronized([]);
+                self.ui.show_status("Synchronized input off");
+            }
+            Some(other) => anyhow::bail!("Unknown group {}; :sync [all|profile|off]", other),
+        }
+        Ok(())
+    }
+
+    /// `:broadcast [-p] <text...>`: type a line and Enter into the
+    /// synchronized panes, or every pane if none are; `-p` keeps to panes
+    /// running the active pane's profile
+    pub(super) fn broadcast(&mut self, args: &[String]) -> Result<()> {
+        // The rest of the command line, byte for byte
+        let line = args.first().map(String::as_str).unwrap_or_default();
+        let (same_profile, text) = match line.split_once(char::is_whitespace) {
+            Some((flag, text)) if flag == "-p" || flag == "--profile" => (true, text.trim_start()),
+            _ if line == "-p" || line == "--profile" => (true, ""),
+            _ => (false, line),
+        };
+        if text.is_empty() {
+            anyhow::bail!("Usage: :broadcast [-p] <text...>");
+        }
+
+        let mut targets = self.synchronized();
+        if targets.is_empty() {
+            targets = self.workspace.terminals().iter().map(|t| t.id).collect();
+        }
+        if same_profile {
+            let Some(id) = self.workspace.active_terminal_id() else {
+                anyhow::bail!("No active pane");
+            };
+            let profile = self.workspace.panes_with_profile_of(id);
+            targets.retain(|t| profile.contains(t));
+        }
+
+        let mut sent = 0;
+        for id in targets {
+            let Some(emulator) = self.workspace.get_terminal_emulator(id) else {
+                continue;
+            };
+            let mut emulator = emulator.write();
+            if !emulator.is_alive() {
+                continue;
+            }
+            emulator.paste(text).and_then(|_| emulator.write(b"\r"))?;
+            sent += 1;
+        }
+        self.ui.show_status(&format!("Sent to {} pane(s)", sent));
+        Ok(())
+    }
+
+    /// Panes in the synchronized group, in pane order
+    fn synchronized(&self) -> Vec<TerminalId> {
+        self.workspace
+            .terminals()
+            .into_iter()
+            .filter(|t| t.synchronized)
+            .map(|t| t.id)
+            .collect()
+    }
+}
@@ -0,0 +1,262 @@
+//! Workspaces: named tabs, each with its own project directory, panes and
+//! layout, so several repositories can be worked on from one rgb. The
+//! shown workspace is the app's `workspace` and `layout`; the others are
+//! parked in their tab, their panes still running.
+
+use super::{FocusArea, RgbApp};
+use crate::layout::LayoutEngine;
+use crate::workspace::{TerminalId, WorkspaceManager};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub(super) struct WorkspaceTab {
+    name: String,
+    /// Manager and layout while the workspace is not shown
+    parked: Option<(WorkspaceManager, LayoutEngine)>,
+}
+
+impl WorkspaceTab {
+    /// The tab of the workspace rgb starts with, named after its directory
+    pub(super) fn first(project_dir: &Path) -> Self {
+        Self {
+            name: dir_name(project_dir),
+            parked: None,
+        }
+    }
+}
+
+impl RgbApp {
+    /// `:workspace [new <name> [dir]|move <workspace>|rename <name>|close|<workspace>]`
+    pub(super) async fn run_workspace_command(&mut self, args: &[String]) -> Result<()> {
+        match args {
+            [] => {
+                let names: Vec<String> = self
+                    .tabs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, tab)| {
+                        let marker = if i == self.active_tab { "*" } else { "" };
+                        format!("{}:{}{}", i + 1, tab.name, marker)
+                    })
+                    .collect();
+                self.ui.show_status(&format!("Workspaces: {}", names.join("  ")));
+            }
+            [verb, name, dir @ ..] if verb == "new" && dir.len() <= 1 => {
+                let dir = match dir.first() {
+                    Some(dir) => self.completion_context().dir.join(dir),
+                    None => self.workspace.project_dir().to_path_buf(),
+                };
+                self.new_workspace(name, dir).await?;
+            }
+            [verb, target] if verb == "move" => {
+                let index = self.find_workspace(target)?;
+                self.move_to_workspace(index).await?;
+            }
+            [verb, name] if verb == "rename" => {
+                self.check_workspace_name(name)?;
+                self.tabs[self.active_tab].name = name.clone();
+                self.refresh_tabs();
+            }
+            [verb] if verb == "close" => self.close_workspace().await?,
+            [target] => {
+                let index = self.find_workspace(target)?;
+                self.switch_workspace(index);
+            }
+            _ => anyhow::bail!("Usage: :workspace [new <name> [dir]|move <workspace>|rename <name>|close|<workspace>]"),
+        }
+        Ok(())
+    }
+
+    /// Open a workspace on `dir` with one pane, and show it
+    async fn new_workspace(&mut self, name: &str, dir: PathBuf) -> Result<()> {
+        self.check_workspace_name(name)?;
+        if !dir.is_dir() {
+            anyhow::bail!("No directory {}", dir.display());
+        }
+        let workspace = WorkspaceManager::new(dir, &self.config.git, self.config.general.max_terminals)?;
+        if let Some(tx) = &self.redraw_tx {
+            workspace.set_redraw_sender(tx.clone());
+        }
+        if let Some(server) = &self.control {
+            workspace.set_control_socket(server.path().to_path_buf());
+        }
+        if let Some(tx) = &self.git_tx {
+            workspace.start_git_worker(
+                Duration::from_secs(self.config.git.sync_interval),
+                Duration::from_secs(self.config.git.conflict_scan_interval),
+                tx.clone(),
+            );
+        }
+        workspace.create_terminal(None, None).await?;
+
+        self.tabs.push(WorkspaceTab {
+            name: name.to_string(),
+            parked: Some((workspace, LayoutEngine::new())),
+        });
+        self.switch_workspace(self.tabs.len() - 1);
+        Ok(())
+    }
+
+    /// Bring workspace `index` to the front, parking the shown one
+    pub(super) fn switch_workspace(&mut self, index: usize) {
+        if index == self.active_tab || index >= self.tabs.len() {
+            return;
+        }
+        let Some((workspace, layout)) = self.tabs[index].parked.take() else {
+            return;
+        };
+        let shown = std::mem::replace(&mut self.workspace, workspace);
+        let shown_layout = std::mem::replace(&mut self.layout, layout);
+        self.tabs[self.active_tab].parked = Some((shown, shown_layout));
+        self.active_tab = index;
+
+        self.ui.file_tree_mut().set_root(self.workspace.project_dir().to_path_buf());
+        self.focus = FocusArea::Terminal;
+        self.workspace_switched = true;
+        self.refresh_tabs();
+    }
+
+    /// Alt+[ and Alt+]: the workspace before or after the shown one
+    pub(super) fn cycle_workspace(&mut self, forward: bool) {
+        let count = self.tabs.len();
+        let index = if forward {
+            (self.active_tab + 1) % count
+        } else {
+            (self.active_tab + count - 1) % count
+        };
+        self.switch_workspace(index);
+    }
+
+    /// Move the active pane to workspace `index`, keeping this workspace
+    /// shown
+    async fn move_to_workspace(&mut self, index: usize) -> Result<()> {
+        if index == self.active_tab {
+            anyhow::bail!("The pane is already in this workspace");
+        }
+        let Some(id) = self.workspace.active_terminal_id() else {
+            anyhow::bail!("No active pane");
+        };
+        let Some((to, _)) = &self.tabs[index].parked else {
+            return Ok(());
+        };
+        let keeps_worktree = self.workspace.worktree_path(id).is_some();
+        self.workspace.move_terminal(id, to)?;
+
+        let name = &self.tabs[index].name;
+        if keeps_worktree && to.worktree_path(id).is_none() {
+            self.ui.show_status(&format!(
+                "Moved the pane to {}; its worktree belongs to another repository and is kept",
+                name
+            ));
+        } else {
+            self.ui.show_status(&format!("Moved the pane to {}", name));
+        }
+        self.after_terminal_closed().await
+    }
+
+    /// Close the shown workspace and its panes. Panes whose worktrees hold
+    /// work stay open, and so does the workspace, until they are closed
+    /// with a choice of what happens to it.
+    async fn close_workspace(&mut self) -> Result<()> {
+        if self.tabs.len() == 1 {
+            anyhow::bail!("This is the only workspace");
+        }
+        for terminal in self.workspace.terminals() {
+            if let Err(e) = self.workspace.close_terminal(terminal.id).await {
+                anyhow::bail!("{}: {}; :close it to choose what happens to its work", terminal.title, e);
+            }
+        }
+        let closing = self.active_tab;
+        self.switch_workspace(if closing == 0 { 1 } else { closing - 1 });
+        self.tabs.remove(closing);
+        if self.active_tab > closing {
+            self.active_tab -= 1;
+        }
+        self.refresh_tabs();
+        Ok(())
+    }
+
+    /// A workspace by number or name
+    fn find_workspace(&self, target: &str) -> Result<usize> {
+        if let Ok(n) = target.parse::<usize>() {
+            if (1..=self.tabs.len()).contains(&n) {
+                return Ok(n - 1);
+            }
+        }
+        self.tabs
+            .iter()
+            .position(|tab| tab.name.eq_ignore_ascii_case(target))
+            .ok_or_else(|| anyhow::anyhow!("No workspace {}", target))
+    }
+
+    fn check_workspace_name(&self, name: &str) -> Result<()> {
+        if name.parse::<usize>().is_ok() {
+            anyhow::bail!("A workspace name can't be a number");
+        }
+        if self.tabs.iter().any(|tab| tab.name.eq_ignore_ascii_case(name)) {
+            anyhow::bail!("There is already a workspace {}", name);
+        }
+        Ok(())
+    }
+
+    /// Every workspace, the shown one first
+    pub(super) fn workspaces(&self) -> impl Iterator<Item = &WorkspaceManager> {
+        std::iter::once(&self.workspace)
+            .chain(self.tabs.iter().filter_map(|tab| tab.parked.as_ref().map(|(workspace, _)| workspace)))
+    }
+
+    /// Every workspace with its name, in tab order
+    pub(super) fn named_workspaces(&self) -> impl Iterator<Item = (&str, &WorkspaceManager)> {
+        self.tabs.iter().enumerate().filter_map(|(i, tab)| {
+            let workspace = match &tab.parked {
+                Some((workspace, _)) => workspace,
+                None if i == self.active_tab => &self.workspace,
+                None => return None,
+            };
+            Some((tab.name.as_str(), workspace))
+        })
+    }
+
+    /// The workspace holding a pane
+    pub(super) fn tab_of(&self, id: TerminalId) -> Option<usize> {
+        if self.workspace.get_terminal_emulator(id).is_some() {
+            return Some(self.active_tab);
+        }
+        self.tabs.iter().position(|tab| {
+            tab.parked
+                .as_ref()
+                .is_some_and(|(workspace, _)| workspace.get_terminal_emulator(id).is_some())
+        })
+    }
+
+    /// Keep the panes of parked workspaces up to date
+    pub(super) async fn update_parked_workspaces(&self) {
+        for (workspace, _) in self.tabs.iter().filter_map(|tab| tab.parked.as_ref()) {
+            if let Err(e) = workspace.update().await {
+                tracing::error!("Workspace update error: {}", e);
+            }
+        }
+    }
+
+    /// Tab bar names, and whether a pane in each has an alert
+    pub(super) fn refresh_tabs(&mut self) {
+        let tabs = self
+            .named_workspaces()
+            .map(|(name, workspace)| {
+                let alerted = workspace
+                    .terminals()
+                    .iter()
+                    .any(|t| self.monitors.get(&t.id).is_some_and(|m| m.alert.is_some()));
+                (name.to_string(), alerted)
+            })
+            .collect();
+        self.ui.set_workspace_tabs(tabs, self.active_tab);
+    }
+}
+
+fn dir_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "rgb".to_string())
+}
@@ -0,0 +1,135 @@
+//! Tab completion for the command line.
+
+use super::{split_partial, ArgKind, CommandRegistry};
+use std::path::{Path, PathBuf};
+
+/// What completion can offer besides command names
+#[derive(Debug, Clone, Default)]
+pub struct CompletionContext {
+    pub profiles: Vec<String>,
+    pub branches: Vec<String>,
+    pub layouts: Vec<String>,
+    /// Titles of the open panes
+    pub panes: Vec<String>,
+    /// Directory relative paths complete against
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// Byte offset of the word being completed
+    pub start: usize,
+    /// Unquoted replacements for that word, sorted
+    pub candidates: Vec<String>,
+}
+
+/// Candidates for the last word of `line`
+pub fn complete(registry: &CommandRegistry, line: &str, context: &CompletionContext) -> Option<Completion> {
+    let words = split_partial(line);
+    let current = words.last()?;
+    let prefix = current.text.as_str();
+
+    let candidates: Vec<String> = if words.len() == 1 {
+        let names = registry.commands().iter().map(|c| c.name.to_string());
+        matching(names, prefix)
+    } else {
+        let spec = registry.lookup(&words[0].text)?;
+        let position = words.len() - 2;
+        let kind = spec.args.get(position).or(spec.args.last())?;
+        match kind {
+            ArgKind::Profile => matching(context.profiles.iter().cloned(), prefix),
+            ArgKind::Branch => matching(context.branches.iter().cloned(), prefix),
+            ArgKind::Layout => matching(context.layouts.iter().cloned(), prefix),
+            ArgKind::Pane => matching(context.panes.iter().cloned(), prefix),
+            ArgKind::Command => matching(registry.commands().iter().map(|c| c.name.to_string()), prefix),
+            ArgKind::Choice(choices) => matching(choices.iter().map(|c| c.to_string()), prefix),
+            ArgKind::Path => paths(prefix, &context.dir),
+            ArgKind::Text => Vec::new(),
+        }
+    };
+
+    (!candidates.is_empty()).then_some(Completion {
+        start: current.start,
+        candidates,
+    })
+}
+
+fn matching(options: impl Iterator<Item = String>, prefix: &str) -> Vec<String> {
+    let mut found: Vec<String> = options.filter(|o| o.starts_with(prefix)).collect();
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Entries of the directory `prefix` points into, as `prefix` would spell
+/// them; directories end in `/`
+fn paths(prefix: &str, dir: &Path) -> Vec<String> {
+    let (parent, name) = match prefix.rfind('/') {
+        Some(i) => prefix.split_at(i + 1),
+        None => ("", prefix),
+    };
+    let listed = match parent.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|home| home.join(rest)),
+        None => Some(dir.join(parent)),
+    };
+    let Some(entries) = listed.and_then(|d| std::fs::read_dir(d).ok()) else {
+        return Vec::new();
+    };
+
+    let mut found: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !file_name.starts_with(name) || (file_name.starts_with('.') && !name.starts_with('.')) {
+                return None;
+            }
+            let slash = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", parent, file_name, slash))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+/// Longest prefix all `candidates` share
+pub fn common_prefix(candidates: &[String]) -> String {
+    let Some(first) = candidates.first() else {
+        return String::new();
+    };
+    let mut end = first.len();
+    for other in &candidates[1..] {
+        end = first
+            .char_indices()
+            .zip(other.chars())
+            .take_while(|((_, a), b)| a == b)
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or(0)
+            .min(end);
+    }
+    first[..end].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn common_prefix_of_candidates() {
+        assert_eq!(common_prefix(&strings(&["worktree", "worktrees"])), "worktree");
+        assert_eq!(common_prefix(&strings(&["my file one.txt", "my file two.txt"])), "my file ");
+        assert_eq!(common_prefix(&strings(&["abc", "xyz"])), "");
+        assert_eq!(common_prefix(&strings(&["only"])), "only");
+        assert_eq!(common_prefix(&[]), "");
+    }
+
+    #[test]
+    fn common_prefix_stops_at_char_boundaries() {
+        // "é" and "è" share their first UTF-8 byte
+        assert_eq!(common_prefix(&strings(&["café", "cafè"])), "caf");
+    }
+}
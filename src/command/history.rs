@@ -0,0 +1,104 @@
+//! Command-line history, kept across sessions.
+
+use directories::ProjectDirs;
+use std::path::PathBuf;
+
+/// Lines kept in memory and on disk
+const MAX_HISTORY: usize = 1000;
+
+pub struct History {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+    /// Entry being shown while browsing, counted from the oldest
+    position: Option<usize>,
+    /// What was typed before browsing started
+    draft: String,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::load()
+    }
+}
+
+impl History {
+    /// History from the user's data directory; empty if there is none yet
+    pub fn load() -> Self {
+        let path = ProjectDirs::from("com", "rgb", "rgb").map(|dirs| dirs.data_dir().join("history"));
+        Self::load_from(path)
+    }
+
+    pub fn load_from(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|text| text.lines().filter(|l| !l.trim().is_empty()).map(str::to_string).collect())
+            .unwrap_or_default();
+        Self {
+            entries,
+            path,
+            position: None,
+            draft: String::new(),
+        }
+    }
+
+    /// Record a run command and write the history out
+    pub fn push(&mut self, line: &str) {
+        self.reset();
+        let line = line.trim();
+        if line.is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.entries.push(line.to_string());
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.drain(..self.entries.len() - MAX_HISTORY);
+        }
+        if let Err(e) = self.save() {
+            tracing::warn!("Failed to save command history: {}", e);
+        }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut text = self.entries.join("\n");
+        text.push('\n');
+        std::fs::write(path, text)
+    }
+
+    /// Step back to the previous entry starting with what was typed
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        if self.position.is_none() {
+            self.draft = current.to_string();
+        }
+        let end = self.position.unwrap_or(self.entries.len());
+        let found = self.entries[..end].iter().rposition(|e| e.starts_with(&self.draft))?;
+        self.position = Some(found);
+        Some(&self.entries[found])
+    }
+
+    /// Step forward again, back to what was typed after the newest entry
+    pub fn newer(&mut self) -> Option<&str> {
+        let position = self.position?;
+        match self.entries[position + 1..].iter().position(|e| e.starts_with(&self.draft)) {
+            Some(offset) => {
+                self.position = Some(position + 1 + offset);
+                Some(&self.entries[position + 1 + offset])
+            }
+            None => {
+                self.position = None;
+                Some(&self.draft)
+            }
+        }
+    }
+
+    /// Stop browsing
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+}
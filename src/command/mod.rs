@@ -0,0 +1,234 @@
+//! Command-line subsystem: parsing, the command registry, completion and
+//! history. Running a command is up to the app, which matches on
+//! [`CommandId`] so the command line and keybindings share one dispatcher.
+
+mod completion;
+mod history;
+mod parser;
+
+pub use completion::{common_prefix, complete, Completion, CompletionContext};
+pub use history::History;
+pub use parser::{quote, rest, split, split_partial, Word};
+
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyModifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandId {
+    Quit,
+    New,
+    Attach,
+    Close,
+    Focus,
+    Edit,
+    Find,
+    Panes,
+    Fanout,
+    Monitor,
+    NextAlert,
+    Record,
+    Play,
+    SaveOutput,
+    PipePane,
+    Sync,
+    Broadcast,
+    Workspace,
+    Top,
+    Inspect,
+    Timeline,
+    Worktree,
+    Worktrees,
+    Diff,
+    Merge,
+    Rebase,
+    Squash,
+    Commit,
+    Layout,
+    Explorer,
+    GitPanel,
+    Config,
+    Help,
+}
+
+/// What an argument position holds, for completion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    /// A terminal profile from the config, or any command
+    Profile,
+    Branch,
+    Layout,
+    Path,
+    /// An open pane, by number or title
+    Pane,
+    /// A registered command name
+    Command,
+    Choice(&'static [&'static str]),
+    /// Free text; not completed
+    Text,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandSpec {
+    pub id: CommandId,
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    /// Kinds of the positional arguments; later ones complete as the last
+    pub args: &'static [ArgKind],
+    /// Arguments that must be given
+    pub required: usize,
+    pub usage: &'static str,
+    pub summary: &'static str,
+    /// Takes the rest of the line as typed, quotes and all, as its one
+    /// argument: a shell command, or text for the panes
+    pub raw: bool,
+}
+
+/// Every command the command line and keybindings can run
+pub struct CommandRegistry {
+    commands: Vec<CommandSpec>,
+}
+
+impl Default for CommandRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        use ArgKind::*;
+        const DIFF_SIDES: &[&str] = &["unstaged", "staged", "main"];
+        const MONITORS: &[&str] = &["activity", "silence", "bell", "match", "off"];
+        const SYNC_GROUPS: &[&str] = &["all", "profile", "off"];
+        const WORKSPACE_VERBS: &[&str] = &["new", "move", "rename", "close"];
+        const CONFIG_VERBS: &[&str] = &["edit", "reload", "set"];
+
+        let commands = vec![
+            spec(CommandId::Quit, "quit", &["q"], &[], 0, "quit", "Quit rgb"),
+            spec(CommandId::New, "new", &[], &[Profile, Text], 0, "new [profile|command...]", "Open a terminal in a new worktree"),
+            spec(CommandId::Attach, "attach", &[], &[Branch, Profile, Text], 0, "attach [branch] [command...]", "Open a terminal in an existing worktree or branch"),
+            spec(CommandId::Close, "close", &[], &[], 0, "close", "Close the active terminal"),
+            spec(CommandId::Focus, "focus", &[], &[Pane], 1, "focus <number|title>", "Switch to a pane"),
+            spec(CommandId::Edit, "edit", &["e"], &[Path], 1, "edit <path[:line[:col]]>", "Open a file in the editor pane"),
+            spec(CommandId::Find, "find", &[], &[Text], 0, "find [query]", "Fuzzy-find a file"),
+            spec(CommandId::Panes, "panes", &[], &[Text], 0, "panes [query]", "Fuzzy-find a pane"),
+            spec(CommandId::Fanout, "fanout", &[], &[Profile, Text, Text], 0, "fanout [<profile> <n> <prompt>]", "Run one task in n agents side by side, or show their progress"),
+            spec(CommandId::Monitor, "monitor", &[], &[Choice(MONITORS), Text], 0, "monitor [activity|silence [secs]|bell|match <regex>|off]", "Toggle what the active pane alerts on"),
+            spec(CommandId::NextAlert, "nextalert", &[], &[], 0, "nextalert", "Focus the next pane with an alert (Alt+A)"),
+            spec(CommandId::Record, "record", &[], &[Path], 0, "record [path]", "Start or stop recording the active pane to an asciicast file"),
+            spec(CommandId::Play, "play", &[], &[Path], 0, "play [file]", "Replay a recording, or the newest one, in a new pane"),
+            spec(CommandId::SaveOutput, "save-output", &[], &[Path], 0, "save-output [-e] [path]", "Save the active pane's scrollback to a file, -e keeping colours"),
+            spec(CommandId::PipePane, "pipe-pane", &[], &[Text], 0, "pipe-pane [command...]", "Feed the active pane's output to a shell command, or stop").raw(),
+            spec(CommandId::Sync, "sync", &[], &[Choice(SYNC_GROUPS)], 0, "sync [all|profile|off]", "Mirror typed keys across panes: toggle the active one, or pick a group (Alt+S)"),
+            spec(CommandId::Broadcast, "broadcast", &[], &[Text], 1, "broadcast [-p] <text...>", "Type a line into the synchronized panes, or all; -p only the active pane's profile").raw(),
+            spec(CommandId::Workspace, "workspace", &["ws"], &[Choice(WORKSPACE_VERBS), Text, Path], 0, "workspace [new <name> [dir]|move <workspace>|rename <name>|close|<workspace>]", "Switch, open, rename or close workspaces, or move the active pane to one"),
+            spec(CommandId::Top, "top", &[], &[], 0, "top", "CPU and memory of each pane's processes, and of the machine"),
+            spec(CommandId::Inspect, "inspect", &[], &[], 0, "inspect", "The active pane's processes, ports and directory; signal its job or tree (Alt+P)"),
+            spec(CommandId::Timeline, "timeline", &[], &[], 0, "timeline", "File changes, commits and terminal events"),
+            spec(CommandId::Worktree, "worktree", &[], &[], 0, "worktree", "Dashboard of all worktrees"),
+            spec(CommandId::Worktrees, "worktrees", &[], &[], 0, "worktrees", "Manage worktrees kept after closing"),
+            spec(CommandId::Diff, "diff", &[], &[Choice(DIFF_SIDES)], 0, "diff [unstaged|staged|main]", "Review changes of the active pane"),
+            spec(CommandId::Merge, "merge", &[], &[], 0, "merge", "Merge the active worktree into main"),
+            spec(CommandId::Rebase, "rebase", &[], &[], 0, "rebase", "Rebase the active worktree onto main and fast-forward"),
+            spec(CommandId::Squash, "squash", &[], &[], 0, "squash", "Squash the active worktree into one commit on main"),
+            spec(CommandId::Commit, "commit", &[], &[], 0, "commit", "Commit the active worktree"),
+            spec(CommandId::Layout, "layout", &[], &[Layout], 1, "layout <name>", "Switch the pane layout"),
+            spec(CommandId::Explorer, "explorer", &[], &[], 0, "explorer", "Toggle the file explorer"),
+            spec(CommandId::GitPanel, "gitpanel", &[], &[], 0, "gitpanel", "Toggle the git status panel"),
+            spec(CommandId::Config, "config", &[], &[Choice(CONFIG_VERBS), Text], 0, "config [edit|reload|set <key> <value>]", "Edit the configuration; saved changes apply live"),
+            spec(CommandId::Help, "help", &["h"], &[Command], 0, "help [command]", "Show help, or a command's usage"),
+        ];
+        Self { commands }
+    }
+
+    /// Add a command, replacing one with the same name
+    pub fn register(&mut self, spec: CommandSpec) {
+        self.commands.retain(|c| c.name != spec.name);
+        self.commands.push(spec);
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<&CommandSpec> {
+        self.commands
+            .iter()
+            .find(|c| c.name == name || c.aliases.contains(&name))
+    }
+
+    pub fn get(&self, id: CommandId) -> Option<&CommandSpec> {
+        self.commands.iter().find(|c| c.id == id)
+    }
+
+    pub fn commands(&self) -> &[CommandSpec] {
+        &self.commands
+    }
+
+    /// Parse a command line into a command and its arguments; `None` for a
+    /// blank line
+    pub fn parse(&self, line: &str) -> Result<Option<(CommandId, Vec<String>)>> {
+        let mut words = split(line)?;
+        if words.is_empty() {
+            return Ok(None);
+        }
+        let name = words.remove(0);
+        let Some(spec) = self.lookup(&name) else {
+            anyhow::bail!("Unknown command: {}", name);
+        };
+        if words.len() < spec.required {
+            anyhow::bail!("Usage: :{}", spec.usage);
+        }
+        if spec.raw {
+            let rest = rest(line, 1);
+            words = if rest.trim().is_empty() { Vec::new() } else { vec![rest.to_string()] };
+        }
+        Ok(Some((spec.id, words)))
+    }
+}
+
+/// Parse a key binding such as `ctrl+b`, `alt+shift+x`, `f5` or
+/// `ctrl+space`
+pub fn parse_key(binding: &str) -> Option<(KeyCode, KeyModifiers)> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut code = None;
+    for part in binding.split('+').map(|p| p.trim().to_lowercase()) {
+        match part.as_str() {
+            "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+            "alt" | "meta" => modifiers |= KeyModifiers::ALT,
+            "shift" => modifiers |= KeyModifiers::SHIFT,
+            key => {
+                code = Some(match key {
+                    "space" => KeyCode::Char(' '),
+                    "tab" => KeyCode::Tab,
+                    "enter" => KeyCode::Enter,
+                    "esc" => KeyCode::Esc,
+                    "backspace" => KeyCode::Backspace,
+                    "up" => KeyCode::Up,
+                    "down" => KeyCode::Down,
+                    "left" => KeyCode::Left,
+                    "right" => KeyCode::Right,
+                    f if f.len() > 1 && f.starts_with('f') => KeyCode::F(f[1..].parse().ok()?),
+                    c if c.chars().count() == 1 => KeyCode::Char(c.chars().next()?),
+                    _ => return None,
+                });
+            }
+        }
+    }
+    Some((code?, modifiers))
+}
+
+fn spec(
+    id: CommandId,
+    name: &'static str,
+    aliases: &'static [&'static str],
+    args: &'static [ArgKind],
+    required: usize,
+    usage: &'static str,
+    summary: &'static str,
+) -> CommandSpec {
+    CommandSpec { id, name, aliases, args, required, usage, summary, raw: false }
+}
+
+impl CommandSpec {
+    fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+}
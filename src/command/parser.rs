@@ -0,0 +1,167 @@
+//! Shell-like splitting of command lines.
+
+use anyhow::Result;
+
+/// A word of a command line and where it starts, for completion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+    pub text: String,
+    /// Byte offset of the word's first char, quotes included
+    pub start: usize,
+}
+
+/// Split `line` into arguments the way a POSIX shell would: whitespace
+/// separates words, single quotes are literal, double quotes allow `\"`
+/// and `\\`, and a backslash outside quotes escapes the next char.
+pub fn split(line: &str) -> Result<Vec<String>> {
+    let (words, open, _) = scan(line);
+    if let Some(quote) = open {
+        anyhow::bail!("Unterminated {} quote", if quote == '\'' { "single" } else { "double" });
+    }
+    Ok(words.into_iter().map(|w| w.text).collect())
+}
+
+/// Like [`split`], but tolerant of an unfinished line. The last word is
+/// the one being typed; it is empty when the line ends in whitespace.
+pub fn split_partial(line: &str) -> Vec<Word> {
+    let (mut words, open, ends_in_word) = scan(line);
+    if open.is_none() && !ends_in_word {
+        words.push(Word { text: String::new(), start: line.len() });
+    }
+    words
+}
+
+/// Quote `word` so [`split`] reads it back unchanged
+pub fn quote(word: &str) -> String {
+    if !word.is_empty() && !word.contains(|c: char| c.is_whitespace() || "'\"\\".contains(c)) {
+        return word.to_string();
+    }
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// `line` as typed after its first `skip` words, quotes and all; empty
+/// when it has no more
+pub fn rest(line: &str, skip: usize) -> &str {
+    let (words, _, _) = scan(line);
+    words.get(skip).map(|w| &line[w.start..]).unwrap_or_default()
+}
+
+/// Words of `line`, the quote left open at the end, if any, and whether
+/// the line ends inside a word
+fn scan(line: &str) -> (Vec<Word>, Option<char>, bool) {
+    let mut words = Vec::new();
+    let mut current: Option<Word> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (None, c) if c.is_whitespace() => {
+                words.extend(current.take());
+                continue;
+            }
+            _ => {}
+        }
+        let word = current.get_or_insert_with(|| Word { text: String::new(), start: i });
+
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '\\') => {
+                if let Some((_, next)) = chars.next() {
+                    word.text.push(next);
+                }
+            }
+            (Some('"'), '\\') if matches!(chars.peek(), Some((_, '"' | '\\'))) => {
+                let (_, next) = chars.next().expect("peeked");
+                word.text.push(next);
+            }
+            (_, c) => word.text.push(c),
+        }
+    }
+    let ends_in_word = current.is_some();
+    words.extend(current);
+    (words, quote, ends_in_word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        split(line).unwrap()
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(words("  new  sh2\tmain "), ["new", "sh2", "main"]);
+        assert!(words("   ").is_empty());
+    }
+
+    #[test]
+    fn single_quotes_are_literal() {
+        assert_eq!(words(r#"edit 'my file.txt' 'a\b"c'"#), ["edit", "my file.txt", r#"a\b"c"#]);
+        assert_eq!(words("echo ''"), ["echo", ""]);
+    }
+
+    #[test]
+    fn double_quotes_escape_quote_and_backslash_only() {
+        assert_eq!(words(r#""say \"hi\"" "a\\b" "a\nb""#), [r#"say "hi""#, r"a\b", r"a\nb"]);
+    }
+
+    #[test]
+    fn backslash_escapes_outside_quotes() {
+        assert_eq!(words(r"my\ file.txt a\'b \\"), ["my file.txt", "a'b", "\\"]);
+    }
+
+    #[test]
+    fn quotes_join_adjacent_text() {
+        assert_eq!(words(r#"pre'mid dle'"post""#), ["premid dlepost"]);
+    }
+
+    #[test]
+    fn unterminated_quotes_are_errors() {
+        assert_eq!(split("edit 'my file").unwrap_err().to_string(), "Unterminated single quote");
+        assert_eq!(split(r#"edit "my file"#).unwrap_err().to_string(), "Unterminated double quote");
+        assert_eq!(split(r#"edit "a\""#).unwrap_err().to_string(), "Unterminated double quote");
+    }
+
+    #[test]
+    fn split_partial_keeps_the_open_quote_as_the_current_word() {
+        let words = split_partial("edit 'my fi");
+        assert_eq!(words.last(), Some(&Word { text: "my fi".into(), start: 5 }));
+        assert_eq!(words.len(), 2);
+    }
+
+    #[test]
+    fn split_partial_starts_a_word_after_whitespace() {
+        assert_eq!(split_partial("edit "), [
+            Word { text: "edit".into(), start: 0 },
+            Word { text: String::new(), start: 5 },
+        ]);
+        assert_eq!(split_partial(""), [Word { text: String::new(), start: 0 }]);
+        // An escaped space continues the word
+        assert_eq!(split_partial(r"edit my\ ").last(), Some(&Word { text: "my ".into(), start: 5 }));
+        // But not after an escaped backslash
+        assert_eq!(split_partial(r"edit my\\ ").last(), Some(&Word { text: String::new(), start: 10 }));
+        // A space inside an open quote continues it too
+        assert_eq!(split_partial("edit 'my ").last(), Some(&Word { text: "my ".into(), start: 5 }));
+    }
+
+    #[test]
+    fn quote_round_trips_through_split() {
+        for word in ["plain", "", "my file.txt", "it's", r#"say "hi""#, r"back\slash", "tab\there", "'", "ünï cødé"] {
+            assert_eq!(words(&quote(word)), [word], "{:?} quoted as {:?}", word, quote(word));
+        }
+        assert_eq!(quote("plain"), "plain");
+        assert_eq!(quote("my file"), "'my file'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn rest_keeps_the_line_as_typed() {
+        assert_eq!(rest(r#"broadcast  'a  b'  "c""#, 1), r#"'a  b'  "c""#);
+        assert_eq!(rest("broadcast", 1), "");
+        assert_eq!(rest("broadcast ", 1), "");
+    }
+}
@@ -1,30 +1,44 @@
-use anyhow::Result;
-use config::{Config, ConfigError, Environment, File};
+use crate::monitor::ConflictResolution;
+use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+/// Settings from the config files, each section and key defaulting when
+/// left out
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct AppConfig {
     pub general: GeneralConfig,
     pub appearance: AppearanceConfig,
     pub keybindings: KeybindingsConfig,
     pub layout: LayoutConfig,
     pub git: GitConfig,
+    pub editor: EditorConfig,
+    pub monitor: MonitorConfig,
+    pub resources: ResourcesConfig,
     pub terminals: HashMap<String, TerminalConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct GeneralConfig {
     pub project_dir: Option<PathBuf>,
+    /// Most panes one workspace holds; 0 for no limit beyond the
+    /// `[resources]` warnings
     pub max_terminals: usize,
     pub auto_save_layout: bool,
     pub default_shell: String,
+    /// Listen for `rgb ctl` on a Unix socket
+    pub control_socket: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct AppearanceConfig {
+    /// `dark`, `light` or `contrast`
     pub theme: String,
     pub font_size: u16,
     pub cursor_style: CursorStyle,
@@ -39,20 +53,29 @@ pub enum CursorStyle {
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct KeybindingsConfig {
     pub new_terminal: String,
     pub close_terminal: String,
     pub switch_mode: String,
+    /// Extra bindings from a key such as `ctrl+b` to a command line such
+    /// as `layout grid`
+    pub commands: HashMap<String, String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct LayoutConfig {
     pub default: String,
     pub min_pane_size: Size,
     pub border_style: BorderStyle,
+    /// Panels shown at startup; toggling one saves its state here
+    pub file_explorer: bool,
+    pub git_panel: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct Size {
     pub width: u16,
     pub height: u16,
@@ -67,57 +90,203 @@ pub enum BorderStyle {
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct GitConfig {
     pub auto_worktree: bool,
     pub sync_interval: u64,
     pub commit_template: String,
+    /// Directory holding terminal worktrees, relative to the project.
+    /// `{repo}` expands to the project directory name.
+    pub worktree_root: String,
+    /// Branch name for new worktrees; supports `{profile}`, `{date}`,
+    /// `{n}` and `{id}`
+    pub branch_template: String,
+    /// Ref new worktree branches start from; empty means HEAD
+    pub base_ref: String,
+    /// Seconds between scans for files changed in several worktrees; 0 disables
+    pub conflict_scan_interval: u64,
+    pub conflict_resolution: ConflictResolution,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct EditorConfig {
+    /// Editor started in a worktree's editor pane; empty means `$EDITOR`,
+    /// then `vi`
+    pub command: String,
+    /// Shell command that opens a file in an editor running elsewhere, with
+    /// `{file}`, `{line}` and `{column}` filled in; empty uses editor panes
+    pub open_command: String,
+}
+
+/// What every new pane is watched for, see `:monitor`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct MonitorConfig {
+    pub bell: bool,
+    pub activity: bool,
+    /// Seconds without output before alerting; 0 turns it off
+    pub silence: u64,
+    /// Regexes alerted on when a line of output matches
+    pub patterns: Vec<String>,
+    /// Also raise alerts as desktop notifications through the host terminal
+    pub desktop: DesktopNotify,
+}
+
+/// When the machine counts as overloaded, see `:top`
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ResourcesConfig {
+    /// One-minute load average per CPU above which to warn
+    pub load_per_cpu: f64,
+    /// Percent of memory in use above which to warn
+    pub memory_percent: f64,
+    /// Seconds between samples of the panes' processes
+    pub interval: u64,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum DesktopNotify {
+    #[default]
+    Off,
+    /// `OSC 9`, understood by iTerm2, kitty, WezTerm and Windows Terminal
+    Osc9,
+    /// `OSC 777`, understood by VTE terminals, foot and urxvt
+    Osc777,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TerminalConfig {
     pub command: String,
+    #[serde(default)]
     pub icon: String,
+    #[serde(default)]
     pub environment: HashMap<String, String>,
+    /// How `:fanout` hands the profile its task
+    #[serde(default)]
+    pub prompt: PromptInput,
+    /// Record every pane of this profile, as `:record` does
+    #[serde(default)]
+    pub record: bool,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum PromptInput {
+    /// Appended to the command as its last argument
+    #[default]
+    Argument,
+    /// Typed into the program once it has started
+    Stdin,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
-            general: GeneralConfig {
-                project_dir: None,
-                max_terminals: 10,
-                auto_save_layout: true,
-                default_shell: std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string()),
-            },
-            appearance: AppearanceConfig {
-                theme: "dark".to_string(),
-                font_size: 12,
-                cursor_style: CursorStyle::Block,
-                scrollback_lines: 10000,
-            },
-            keybindings: KeybindingsConfig {
-                new_terminal: "ctrl+t".to_string(),
-                close_terminal: "ctrl+w".to_string(),
-                switch_mode: "esc".to_string(),
-            },
-            layout: LayoutConfig {
-                default: "grid".to_string(),
-                min_pane_size: Size {
-                    width: 40,
-                    height: 10,
-                },
-                border_style: BorderStyle::Rounded,
-            },
-            git: GitConfig {
-                auto_worktree: true,
-                sync_interval: 300,
-                commit_template: "feat: {message}\n\nCo-authored-by: RGB".to_string(),
-            },
+            general: GeneralConfig::default(),
+            appearance: AppearanceConfig::default(),
+            keybindings: KeybindingsConfig::default(),
+            layout: LayoutConfig::default(),
+            git: GitConfig::default(),
+            editor: EditorConfig::default(),
+            monitor: MonitorConfig::default(),
+            resources: ResourcesConfig::default(),
             terminals: default_terminals(),
         }
     }
 }
 
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        Self {
+            project_dir: None,
+            max_terminals: 0,
+            auto_save_layout: true,
+            default_shell: std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string()),
+            control_socket: true,
+        }
+    }
+}
+
+impl Default for AppearanceConfig {
+    fn default() -> Self {
+        Self {
+            theme: "dark".to_string(),
+            font_size: 12,
+            cursor_style: CursorStyle::Block,
+            scrollback_lines: 10000,
+        }
+    }
+}
+
+impl Default for KeybindingsConfig {
+    fn default() -> Self {
+        Self {
+            new_terminal: "ctrl+t".to_string(),
+            close_terminal: "ctrl+w".to_string(),
+            switch_mode: "esc".to_string(),
+            commands: HashMap::new(),
+        }
+    }
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            default: "grid".to_string(),
+            min_pane_size: Size::default(),
+            border_style: BorderStyle::Rounded,
+            file_explorer: true,
+            git_panel: false,
+        }
+    }
+}
+
+impl Default for Size {
+    fn default() -> Self {
+        Self {
+            width: 40,
+            height: 10,
+        }
+    }
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            auto_worktree: true,
+            sync_interval: 300,
+            commit_template: "feat: {message}\n\nCo-authored-by: RGB".to_string(),
+            worktree_root: "../.rgb-worktrees/{repo}".to_string(),
+            branch_template: "rgb/{profile}/{date}/{n}".to_string(),
+            base_ref: String::new(),
+            conflict_scan_interval: 10,
+            conflict_resolution: ConflictResolution::Warn,
+        }
+    }
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            bell: true,
+            activity: false,
+            silence: 0,
+            patterns: Vec::new(),
+            desktop: DesktopNotify::Off,
+        }
+    }
+}
+
+impl Default for ResourcesConfig {
+    fn default() -> Self {
+        Self {
+            load_per_cpu: 2.0,
+            memory_percent: 90.0,
+            interval: 2,
+        }
+    }
+}
+
 fn default_terminals() -> HashMap<String, TerminalConfig> {
     let mut terminals = HashMap::new();
 
@@ -127,6 +296,8 @@ fn default_terminals() -> HashMap<String, TerminalConfig> {
             command: "claude".to_string(),
             icon: "🤖".to_string(),
             environment: HashMap::new(),
+            prompt: PromptInput::Argument,
+            record: false,
         },
     );
 
@@ -136,6 +307,8 @@ fn default_terminals() -> HashMap<String, TerminalConfig> {
             command: "vim".to_string(),
             icon: "📝".to_string(),
             environment: HashMap::new(),
+            prompt: PromptInput::Argument,
+            record: false,
         },
     );
 
@@ -145,70 +318,482 @@ fn default_terminals() -> HashMap<String, TerminalConfig> {
             command: std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string()),
             icon: ">".to_string(),
             environment: HashMap::new(),
+            prompt: PromptInput::Stdin,
+            record: false,
         },
     );
 
     terminals
 }
 
+/// Sections an `RGB_<SECTION>_<KEY>` environment variable can set
+const ENV_SECTIONS: [&str; 8] = ["general", "appearance", "keybindings", "layout", "git", "editor", "monitor", "resources"];
+
+/// Config files in the order they are layered, later ones winning: the
+/// platform config directory, `~/.config/rgb/config.toml`, then the file
+/// given with `--config`
+pub fn config_files(config_path: Option<&Path>) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Some(proj_dirs) = ProjectDirs::from("com", "rgb", "rgb") {
+        files.push(proj_dirs.config_dir().join("config.toml"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        files.push(home.join(".config").join("rgb").join("config.toml"));
+    }
+    files.extend(config_path.map(Path::to_path_buf));
+    // On Linux the first two are the same file
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|f| seen.insert(f.clone()));
+    files
+}
+
+/// The file changes made in rgb are saved to: the one given with
+/// `--config`, or `~/.config/rgb/config.toml`
+pub fn user_config_path(config_path: Option<PathBuf>) -> PathBuf {
+    config_path.unwrap_or_else(|| {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".config")
+            .join("rgb")
+            .join("config.toml")
+    })
+}
+
+/// A config file as read, for errors pointing into it
+struct Layer {
+    path: PathBuf,
+    text: String,
+    table: toml::Table,
+}
+
+/// Layer the config files and `RGB_` environment variables over the
+/// defaults. An error fails with the path, line and column of the file
+/// that set the offending key.
 pub fn load_config(config_path: Option<PathBuf>) -> Result<AppConfig> {
-    // If no config path is specified and no configs exist, just use defaults
-    let has_config = config_path.is_some() ||
-        dirs::home_dir().map(|h| h.join(".config").join("rgb").join("config.toml").exists()).unwrap_or(false);
+    let files = config_files(config_path.as_deref());
+    load_layers(&files, config_path.as_deref(), environment_overrides())
+}
+
+/// Merge `files`, in order, and then `environment` over the defaults;
+/// files other than `required` are skipped when missing
+fn load_layers(files: &[PathBuf], required: Option<&Path>, environment: toml::Table) -> Result<AppConfig> {
+    let mut merged = match toml::Value::try_from(AppConfig::default())? {
+        toml::Value::Table(table) => table,
+        _ => unreachable!("the config serializes to a table"),
+    };
 
-    if !has_config {
-        return Ok(AppConfig::default());
+    let mut layers = Vec::new();
+    for path in files {
+        // Only a file asked for by name has to exist
+        if !path.exists() && Some(path.as_path()) != required {
+            continue;
+        }
+        let text = std::fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+        let table = parse_file(path, &text)?;
+        merge(&mut merged, table.clone());
+        layers.push(Layer { path: path.clone(), text, table });
     }
+    merge(&mut merged, environment.clone());
 
-    let mut builder = Config::builder();
+    // Checked as a whole, as a file may set single keys of a profile
+    AppConfig::deserialize(toml::Value::Table(merged)).map_err(|e| locate(e, &layers, &environment))
+}
 
-    // Start with defaults
-    builder = builder.add_source(Config::try_from(&AppConfig::default())?);
+/// A config file's table; a syntax error fails with its line and column
+fn parse_file(path: &Path, text: &str) -> Result<toml::Table> {
+    text.parse::<toml::Table>().map_err(|error| match error.span() {
+        Some(span) => {
+            let (line, column) = line_column(text, span.start);
+            anyhow::anyhow!("{}:{}:{}: {}", path.display(), line, column, error.message())
+        }
+        None => anyhow::anyhow!("{}: {}", path.display(), error.message()),
+    })
+}
 
-    // Add system config if it exists
-    if let Some(proj_dirs) = ProjectDirs::from("com", "rgb", "rgb") {
-        let system_config = proj_dirs.config_dir().join("config.toml");
-        if system_config.exists() {
-            builder = builder.add_source(File::from(system_config));
+/// Point an error with the merged config at the layer that set the key
+/// it is about: the environment, or the last file that has the key or,
+/// failing that, the table holding it
+fn locate(error: toml::de::Error, layers: &[Layer], environment: &toml::Table) -> anyhow::Error {
+    // The message is followed by "in `section.key`"
+    let path = error
+        .to_string()
+        .lines()
+        .find_map(|line| Some(line.strip_prefix("in `")?.strip_suffix('`')?.to_string()))
+        .unwrap_or_default();
+    let keys: Vec<&str> = path.split('.').filter(|k| !k.is_empty()).collect();
+    if lookup(environment, &keys).is_some() {
+        return anyhow::anyhow!("RGB_ environment: {} in `{}`", error.message(), keys.join("."));
+    }
+    for depth in (1..=keys.len()).rev() {
+        for layer in layers.iter().rev() {
+            if lookup(&layer.table, &keys[..depth]).is_none() {
+                continue;
+            }
+            let (line, column) = key_offset(&layer.text, &keys[..depth])
+                .map(|offset| line_column(&layer.text, offset))
+                .unwrap_or((1, 1));
+            return anyhow::anyhow!(
+                "{}:{}:{}: {} in `{}`",
+                layer.path.display(),
+                line,
+                column,
+                error.message(),
+                keys.join(".")
+            );
         }
     }
+    anyhow::anyhow!("{}", error)
+}
 
-    // Add user config if it exists
-    if let Some(home) = dirs::home_dir() {
-        let user_config = home.join(".config").join("rgb").join("config.toml");
-        if user_config.exists() {
-            builder = builder.add_source(File::from(user_config));
+fn lookup<'a>(table: &'a toml::Table, keys: &[&str]) -> Option<&'a toml::Value> {
+    let (last, parents) = keys.split_last()?;
+    let mut table = table;
+    for key in parents {
+        table = table.get(*key)?.as_table()?;
+    }
+    table.get(*last)
+}
+
+/// Byte offset of the value, or table header, at `keys` in a file
+fn key_offset(text: &str, keys: &[&str]) -> Option<usize> {
+    let document = toml_edit::ImDocument::parse(text).ok()?;
+    let (last, parents) = keys.split_last()?;
+    let mut table: &dyn toml_edit::TableLike = document.as_table();
+    for key in parents {
+        table = table.get(key)?.as_table_like()?;
+    }
+    let item = table.get(last)?;
+    item.span()
+        .or_else(|| item.as_table().and_then(|t| t.span()))
+        .or_else(|| table.key(last).and_then(|k| k.span()))
+        .map(|span| span.start)
+}
+
+/// One-based line and column of a byte offset
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    (line, column)
+}
+
+/// Deep-merge `layer` into `base`, so a file sets single keys of a
+/// section, profile or binding table
+fn merge(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(below)), toml::Value::Table(above)) => merge(below, above),
+            (_, value) => {
+                base.insert(key, value);
+            }
         }
     }
+}
 
-    // Add specified config file
-    if let Some(path) = config_path {
-        builder = builder.add_source(File::from(path));
+/// `RGB_GIT_SYNC_INTERVAL=60` as `[git] sync_interval = 60`; a value that
+/// isn't valid TOML is taken as a string
+fn environment_overrides() -> toml::Table {
+    overrides_from(std::env::vars())
+}
+
+fn overrides_from(vars: impl IntoIterator<Item = (String, String)>) -> toml::Table {
+    let mut overrides = toml::Table::new();
+    for (name, raw) in vars {
+        let Some((section, key)) = name.strip_prefix("RGB_").and_then(|rest| rest.split_once('_')) else {
+            continue;
+        };
+        let section = section.to_lowercase();
+        if !ENV_SECTIONS.contains(&section.as_str()) {
+            continue;
+        }
+        let value = parse_value(&raw);
+        if let toml::Value::Table(table) = overrides
+            .entry(section)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        {
+            table.insert(key.to_lowercase(), value);
+        }
     }
+    overrides
+}
 
-    // Add environment variables with RGB_ prefix
-    builder = builder.add_source(Environment::with_prefix("RGB").separator("_"));
+/// A setting as typed on the command line or in the environment: TOML,
+/// such as `60`, `true` or `["a", "b"]`, or else a plain string
+pub fn parse_value(raw: &str) -> toml::Value {
+    format!("value = {}", raw)
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut t| t.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+impl AppConfig {
+    /// Change the setting at a dotted path such as `layout.border_style`,
+    /// or add an entry to a table such as `keybindings.commands`; returns
+    /// the value set
+    pub fn set(&mut self, key: &str, raw: &str) -> Result<toml::Value> {
+        let mut value = toml::Value::try_from(&*self)?;
+        let mut parts: Vec<&str> = key.split('.').collect();
+        let last = parts.pop().filter(|k| !k.is_empty()).ok_or_else(|| anyhow::anyhow!("No setting {}", key))?;
+        let mut table = value.as_table_mut();
+        for part in parts {
+            table = table.and_then(|t| t.get_mut(part)).and_then(toml::Value::as_table_mut);
+        }
+        let Some(table) = table else {
+            anyhow::bail!("No setting {}", key);
+        };
+        let setting = parse_value(raw);
+        table.insert(last.to_string(), setting.clone());
 
-    let config = builder.build()?;
-    Ok(config.try_deserialize()?)
+        let config = AppConfig::deserialize(value).map_err(|e| anyhow::anyhow!("{}: {}", key, e.message()))?;
+        // Fields the config doesn't have are dropped when it is read back
+        let written = toml::Value::try_from(&config)?;
+        if key.split('.').try_fold(&written, |v, part| v.get(part)).is_none() {
+            anyhow::bail!("No setting {}", key);
+        }
+        *self = config;
+        Ok(setting)
+    }
 }
 
-pub fn save_config(config: &AppConfig, path: Option<PathBuf>) -> Result<()> {
-    let config_path = path.unwrap_or_else(|| {
-        dirs::home_dir()
-            .unwrap()
-            .join(".config")
-            .join("rgb")
-            .join("config.toml")
-    });
+/// Persist settings changed in rgb, such as `layout.git_panel`, to the
+/// user's config file. Only those keys are written; the rest of the file,
+/// comments included, stays as it is.
+pub fn save_config(settings: &[(&str, toml::Value)], path: Option<PathBuf>) -> Result<()> {
+    let config_path = user_config_path(path);
 
     // Ensure directory exists
     if let Some(parent) = config_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
 
-    let toml_string = toml::to_string_pretty(config)?;
-    std::fs::write(config_path, toml_string)?;
+    let text = if config_path.exists() {
+        std::fs::read_to_string(&config_path)?
+    } else {
+        String::new()
+    };
+    parse_file(&config_path, &text)?;
+    let mut document: toml_edit::DocumentMut = text.parse()?;
+    for (key, value) in settings {
+        set_key(document.as_table_mut(), key, value)?;
+    }
+    std::fs::write(&config_path, document.to_string())?;
+    Ok(())
+}
 
+/// Set a dotted key in a file, creating the tables above it, and keeping
+/// the comment on a line it replaces
+fn set_key(table: &mut toml_edit::Table, key: &str, value: &toml::Value) -> Result<()> {
+    let parts: Vec<&str> = key.split('.').collect();
+    let (last, parents) = parts.split_last().ok_or_else(|| anyhow::anyhow!("No setting {}", key))?;
+    let mut table: &mut dyn toml_edit::TableLike = table;
+    for part in parents {
+        if !table.contains_key(part) {
+            let mut created = toml_edit::Table::new();
+            created.set_implicit(true);
+            table.insert(part, toml_edit::Item::Table(created));
+        }
+        table = table
+            .get_mut(part)
+            .and_then(|item| item.as_table_like_mut())
+            .ok_or_else(|| anyhow::anyhow!("{} is not a table in the config file", part))?;
+    }
+    let mut replacement: toml_edit::Value = value.to_string().parse()?;
+    if let Some(old) = table.get(last).and_then(toml_edit::Item::as_value) {
+        *replacement.decor_mut() = old.decor().clone();
+    }
+    table.insert(last, toml_edit::Item::Value(replacement));
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Notices when a config file is written, created or removed, by
+/// comparing modification times each time it is asked
+pub struct ConfigWatcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl ConfigWatcher {
+    pub fn new(config_path: Option<&Path>) -> Self {
+        let files = config_files(config_path)
+            .into_iter()
+            .map(|path| {
+                let modified = modified(&path);
+                (path, modified)
+            })
+            .collect();
+        Self { files }
+    }
+
+    /// Whether any file changed since the last call
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, seen) in &mut self.files {
+            let modified = modified(path);
+            if modified != *seen {
+                *seen = modified;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, text: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn env(vars: &[(&str, &str)]) -> toml::Table {
+        overrides_from(vars.iter().map(|(k, v)| (k.to_string(), v.to_string())))
+    }
+
+    #[test]
+    fn later_layers_win_key_by_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let system = write(dir.path(), "system.toml", r#"
+            [git]
+            sync_interval = 10
+            base_ref = "develop"
+
+            [terminals.claude]
+            command = "claude --system"
+            icon = "C"
+        "#);
+        let user = write(dir.path(), "user.toml", r#"
+            [git]
+            sync_interval = 20
+
+            [terminals.claude]
+            command = "claude --user"
+        "#);
+        let missing = dir.path().join("missing.toml");
+
+        let config = load_layers(&[system, missing, user], None, toml::Table::new()).unwrap();
+        assert_eq!(config.git.sync_interval, 20);
+        assert_eq!(config.git.base_ref, "develop");
+        assert_eq!(config.terminals["claude"].command, "claude --user");
+        assert_eq!(config.terminals["claude"].icon, "C");
+        // Untouched sections and profiles keep their defaults
+        assert_eq!(config.git.branch_template, GitConfig::default().branch_template);
+        assert!(config.terminals.contains_key("shell"));
+    }
+
+    #[test]
+    fn environment_wins_over_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = write(dir.path(), "config.toml", "[git]\nsync_interval = 20\nbase_ref = \"develop\"\n");
+        let config = load_layers(&[file], None, env(&[("RGB_GIT_SYNC_INTERVAL", "60")])).unwrap();
+        assert_eq!(config.git.sync_interval, 60);
+        assert_eq!(config.git.base_ref, "develop");
+    }
+
+    #[test]
+    fn a_missing_required_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.toml");
+        let error = load_layers(std::slice::from_ref(&missing), Some(&missing), toml::Table::new()).unwrap_err();
+        assert!(error.to_string().starts_with("Cannot read"), "{}", error);
+    }
+
+    #[test]
+    fn environment_variables_parse_as_toml_or_strings() {
+        let overrides = env(&[
+            ("RGB_GIT_SYNC_INTERVAL", "60"),
+            ("RGB_GENERAL_AUTO_SAVE_LAYOUT", "false"),
+            ("RGB_MONITOR_PATTERNS", r#"["error", "panic"]"#),
+            ("RGB_LAYOUT_DEFAULT", "grid"),
+            ("RGB_EDITOR_COMMAND", "nvim -u NONE"),
+            // Not settings
+            ("RGB_SOCKET", "/tmp/rgb.sock"),
+            ("RGB_TERMINALS_CLAUDE", "x"),
+            ("HOME", "/root"),
+        ]);
+        let expected: toml::Table = r#"
+            git = { sync_interval = 60 }
+            general = { auto_save_layout = false }
+            monitor = { patterns = ["error", "panic"] }
+            layout = { default = "grid" }
+            editor = { command = "nvim -u NONE" }
+        "#.parse().unwrap();
+        assert_eq!(overrides, expected);
+    }
+
+    #[test]
+    fn bad_values_point_at_the_file_and_line_that_set_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = write(dir.path(), "first.toml", "[git]\nsync_interval = \"soon\"\n");
+        let second = write(dir.path(), "second.toml", "# comment\n\n[git]\nbase_ref = \"main\"\nsync_interval = \"later\"\n");
+
+        let error = load_layers(&[first.clone(), second.clone()], None, toml::Table::new()).unwrap_err().to_string();
+        assert!(error.starts_with(&format!("{}:5:17: ", second.display())), "{}", error);
+        assert!(error.ends_with("in `git.sync_interval`"), "{}", error);
+
+        let error = load_layers(std::slice::from_ref(&first), None, toml::Table::new()).unwrap_err().to_string();
+        assert!(error.starts_with(&format!("{}:2:17: ", first.display())), "{}", error);
+
+        let error = load_layers(&[first], None, env(&[("RGB_GIT_SYNC_INTERVAL", "never")])).unwrap_err().to_string();
+        assert!(error.starts_with("RGB_ environment: "), "{}", error);
+    }
+
+    #[test]
+    fn syntax_errors_have_a_line_and_column() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = write(dir.path(), "config.toml", "[git]\nsync_interval = = 5\n");
+        let error = load_layers(std::slice::from_ref(&file), None, toml::Table::new()).unwrap_err().to_string();
+        assert!(error.starts_with(&format!("{}:2:", file.display())), "{}", error);
+    }
+
+    #[test]
+    fn save_keeps_comments_and_other_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(dir.path(), "config.toml", concat!(
+            "# My settings\n",
+            "[layout]\n",
+            "default = \"grid\" # tiled\n",
+            "git_panel = true # shown at startup\n",
+            "\n",
+            "[terminals.claude]\n",
+            "command = \"claude\"\n",
+        ));
+
+        save_config(
+            &[("layout.git_panel", toml::Value::Boolean(false)), ("git.sync_interval", toml::Value::Integer(60))],
+            Some(path.clone()),
+        )
+        .unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(saved, concat!(
+            "# My settings\n",
+            "[layout]\n",
+            "default = \"grid\" # tiled\n",
+            "git_panel = false # shown at startup\n",
+            "\n",
+            "[terminals.claude]\n",
+            "command = \"claude\"\n",
+            "\n",
+            "[git]\n",
+            "sync_interval = 60\n",
+        ));
+        let config = load_layers(&[path], None, toml::Table::new()).unwrap();
+        assert!(!config.layout.git_panel);
+        assert_eq!(config.git.sync_interval, 60);
+    }
+
+    #[test]
+    fn save_refuses_a_broken_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(dir.path(), "config.toml", "[layout\n");
+        assert!(save_config(&[("layout.git_panel", toml::Value::Boolean(false))], Some(path.clone())).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[layout\n");
+    }
+}
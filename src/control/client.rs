@@ -0,0 +1,65 @@
+//! The `rgb ctl` side of the socket.
+
+use super::protocol::{Request, Response};
+use super::{socket_dir, SOCKET_ENV};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+/// Socket to talk to: the one given, the one of the rgb this runs inside,
+/// or else the most recently started rgb that still answers
+pub fn resolve_socket(explicit: Option<PathBuf>) -> Result<PathBuf> {
+    if let Some(path) = explicit.or_else(|| std::env::var_os(SOCKET_ENV).map(PathBuf::from)) {
+        return Ok(path);
+    }
+
+    let dir = socket_dir();
+    let mut sockets: Vec<(std::time::SystemTime, PathBuf)> = std::fs::read_dir(&dir)
+        .with_context(|| format!("No running rgb found in {}", dir.display()))?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with("rgb-") && name.ends_with(".sock")
+        })
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    sockets.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    sockets
+        .into_iter()
+        .map(|(_, path)| path)
+        .find(|path| std::os::unix::net::UnixStream::connect(path).is_ok())
+        .with_context(|| format!("No running rgb found in {}", dir.display()))
+}
+
+/// Send one request and return its result; an error answer becomes an
+/// [`RpcError`](super::RpcError)
+pub async fn call(socket: &Path, method: &str, params: Value) -> Result<Value> {
+    let stream = UnixStream::connect(socket)
+        .await
+        .with_context(|| format!("Cannot connect to {}", socket.display()))?;
+    let (read, mut write) = stream.into_split();
+
+    let request = Request {
+        jsonrpc: "2.0".to_string(),
+        id: Value::from(1),
+        method: method.to_string(),
+        params,
+    };
+    let mut line = serde_json::to_string(&request)?;
+    line.push('\n');
+    write.write_all(line.as_bytes()).await?;
+
+    let answer = BufReader::new(read)
+        .lines()
+        .next_line()
+        .await?
+        .context("rgb closed the connection without answering")?;
+    let response: Response = serde_json::from_str(&answer)?;
+    match (response.result, response.error) {
+        (_, Some(error)) => Err(error.into()),
+        (result, None) => Ok(result.unwrap_or(Value::Null)),
+    }
+}
@@ -0,0 +1,32 @@
+//! Scriptable control over a Unix socket. Each running rgb listens on its
+//! own socket and speaks newline-delimited JSON-RPC 2.0; `rgb ctl` is the
+//! client. Requests are answered by the app's main loop, which owns the
+//! workspace, layout and git state, so the server here only moves messages.
+
+mod client;
+mod protocol;
+mod server;
+
+pub use client::{call, resolve_socket};
+pub use protocol::*;
+pub use server::{ControlRequest, ControlServer};
+
+use std::path::PathBuf;
+
+/// Environment variable holding the socket of the rgb a pane runs in, so
+/// `rgb ctl` inside a pane talks to its own instance
+pub const SOCKET_ENV: &str = "RGB_SOCKET";
+
+/// Directory the sockets of the current user live in
+pub fn socket_dir() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("rgb"),
+        // SAFETY: getuid has no preconditions and cannot fail
+        None => std::env::temp_dir().join(format!("rgb-{}", unsafe { libc::getuid() })),
+    }
+}
+
+/// Socket of this process
+pub fn default_socket_path() -> PathBuf {
+    socket_dir().join(format!("rgb-{}.sock", std::process::id()))
+}
@@ -0,0 +1,144 @@
+//! JSON-RPC 2.0 messages and the result types methods return. Field names
+//! here are the public interface; keep them stable.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// `pane.wait` gave up before the pattern appeared
+pub const TIMEOUT: i64 = -32000;
+/// The pane named in the params doesn't exist
+pub const NO_SUCH_PANE: i64 = -32001;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Request {
+    #[serde(default = "version")]
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::new(INTERNAL_ERROR, e.to_string())
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl Response {
+    pub fn new(id: Value, result: Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(value) => (Some(value), None),
+            Err(e) => (None, Some(e)),
+        };
+        Self { jsonrpc: version(), id, result, error }
+    }
+}
+
+fn version() -> String {
+    "2.0".to_string()
+}
+
+/// `pane.list`, `pane.create`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaneInfo {
+    pub id: String,
+    /// 1-based position, as shown on the pane and used by F1-F10 and Alt+G
+    pub index: usize,
+    pub title: String,
+    pub working_dir: String,
+    pub branch: Option<String>,
+    pub worktree: bool,
+    pub active: bool,
+    pub alive: bool,
+    /// Unseen alert of a `:monitor`: activity, silence, bell or match
+    pub alert: Option<String>,
+    /// In the `:sync` group, so typed keys reach it from its peers
+    #[serde(default)]
+    pub synchronized: bool,
+    /// CPU and resident memory of the pane's processes at the last sample
+    #[serde(default)]
+    pub cpu_percent: f64,
+    #[serde(default)]
+    pub rss_bytes: u64,
+}
+
+/// `pane.read`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaneContent {
+    pub id: String,
+    pub lines: Vec<String>,
+    /// Row and column, for visible content only
+    pub cursor: Option<[u16; 2]>,
+}
+
+/// `pane.wait`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitMatch {
+    pub id: String,
+    /// The line the pattern matched
+    pub line: String,
+    /// The pattern's capture groups, unmatched ones as null
+    pub captures: Vec<Option<String>>,
+}
+
+/// `layout.get`, `layout.set`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutInfo {
+    pub name: String,
+    pub available: Vec<String>,
+}
+
+/// `worktree.list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeSummary {
+    pub pane: String,
+    pub name: String,
+    pub branch: String,
+    pub path: String,
+    pub ahead: usize,
+    pub behind: usize,
+    pub dirty_files: usize,
+    pub merge_status: String,
+    pub last_commit: Option<String>,
+}
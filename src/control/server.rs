@@ -0,0 +1,179 @@
+//! Socket listener. Every line a client sends is one request; answers go
+//! back on the same connection in order.
+
+use super::protocol::{
+    PaneContent, Request, Response, RpcError, WaitMatch, INTERNAL_ERROR, PARSE_ERROR, TIMEOUT,
+};
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// How often `pane.wait` re-reads the pane
+const WAIT_POLL: Duration = Duration::from_millis(100);
+const DEFAULT_WAIT_TIMEOUT_MS: u64 = 10_000;
+
+/// A request for the app's main loop to answer
+pub struct ControlRequest {
+    pub method: String,
+    pub params: Value,
+    pub reply: oneshot::Sender<Result<Value, RpcError>>,
+}
+
+/// Listens on the socket until dropped, then removes it
+pub struct ControlServer {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl ControlServer {
+    /// Listen on `path`, taking over a socket left behind by an rgb that
+    /// is no longer running
+    pub fn bind(path: PathBuf) -> Result<(Self, mpsc::Receiver<ControlRequest>)> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Cannot create {}", dir.display()))?;
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+        }
+        if path.exists() {
+            if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+                anyhow::bail!("{} is in use by another rgb", path.display());
+            }
+            std::fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("Cannot listen on {}", path.display()))?;
+        let (tx, rx) = mpsc::channel(64);
+        let task = tokio::spawn(accept(listener, tx));
+        Ok((Self { path, task }, rx))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+async fn accept(listener: UnixListener, tx: mpsc::Sender<ControlRequest>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(serve(stream, tx.clone()));
+            }
+            Err(e) => {
+                tracing::warn!("Control socket accept failed: {}", e);
+                tokio::time::sleep(WAIT_POLL).await;
+            }
+        }
+    }
+}
+
+async fn serve(stream: UnixStream, tx: mpsc::Sender<ControlRequest>) {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let result = handle(&tx, &request.method, request.params).await;
+                Response::new(request.id, result)
+            }
+            Err(e) => Response::new(Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string()))),
+        };
+        let Ok(mut text) = serde_json::to_string(&response) else {
+            break;
+        };
+        text.push('\n');
+        if write.write_all(text.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn handle(tx: &mpsc::Sender<ControlRequest>, method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        // Waiting polls from here so the app never blocks on a client
+        "pane.wait" => wait(tx, params).await,
+        _ => forward(tx, method, params).await,
+    }
+}
+
+/// Hand a request to the app and wait for its answer
+async fn forward(tx: &mpsc::Sender<ControlRequest>, method: &str, params: Value) -> Result<Value, RpcError> {
+    let (reply, answer) = oneshot::channel();
+    let request = ControlRequest {
+        method: method.to_string(),
+        params,
+        reply,
+    };
+    let closed = || RpcError::new(INTERNAL_ERROR, "rgb is shutting down");
+    tx.send(request).await.map_err(|_| closed())?;
+    answer.await.map_err(|_| closed())?
+}
+
+#[derive(Deserialize)]
+struct WaitParams {
+    pane: Option<Value>,
+    pattern: String,
+    #[serde(default = "default_wait_timeout")]
+    timeout_ms: u64,
+    /// Also search this many lines of recent output, not just the screen
+    scrollback: Option<usize>,
+}
+
+fn default_wait_timeout() -> u64 {
+    DEFAULT_WAIT_TIMEOUT_MS
+}
+
+/// Poll a pane until a line matches the pattern
+async fn wait(tx: &mpsc::Sender<ControlRequest>, params: Value) -> Result<Value, RpcError> {
+    let params: WaitParams =
+        serde_json::from_value(params).map_err(|e| RpcError::invalid_params(e.to_string()))?;
+    let regex = Regex::new(&params.pattern).map_err(|e| RpcError::invalid_params(e.to_string()))?;
+    let deadline = Instant::now() + Duration::from_millis(params.timeout_ms);
+
+    loop {
+        let read = json!({ "pane": params.pane, "scrollback": params.scrollback });
+        let content: PaneContent = serde_json::from_value(forward(tx, "pane.read", read).await?)
+            .map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))?;
+
+        for line in content.lines.iter().rev() {
+            if let Some(captures) = regex.captures(line) {
+                let found = WaitMatch {
+                    id: content.id,
+                    line: line.clone(),
+                    captures: captures
+                        .iter()
+                        .skip(1)
+                        .map(|c| c.map(|m| m.as_str().to_string()))
+                        .collect(),
+                };
+                return serde_json::to_value(found).map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()));
+            }
+        }
+
+        if Instant::now() >= deadline {
+            return Err(RpcError::new(
+                TIMEOUT,
+                format!("No match for {} within {}ms", params.pattern, params.timeout_ms),
+            ));
+        }
+        tokio::time::sleep(WAIT_POLL).await;
+    }
+}
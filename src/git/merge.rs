@@ -0,0 +1,424 @@
+use anyhow::Result;
+use git2::{DiffOptions, Oid, Patch};
+use std::path::PathBuf;
+
+use crate::workspace::TerminalId;
+
+/// How a terminal's branch is landed on the main branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrationStrategy {
+    Merge,
+    Rebase,
+    Squash,
+}
+
+#[derive(Debug, Clone)]
+pub enum IntegrationOutcome {
+    UpToDate,
+    FastForward { commit: String },
+    Committed { commit: String },
+    Conflicts(PendingMerge),
+}
+
+/// A merge or squash that stopped on conflicts and waits for resolution
+#[derive(Debug, Clone)]
+pub struct PendingMerge {
+    pub terminal_id: TerminalId,
+    pub strategy: IntegrationStrategy,
+    pub main_branch: String,
+    pub worktree_branch: String,
+    pub ours: Oid,
+    pub theirs: Oid,
+    pub files: Vec<ConflictFile>,
+}
+
+impl PendingMerge {
+    pub fn is_resolved(&self) -> bool {
+        self.files.iter().all(|f| f.is_resolved())
+    }
+
+    pub fn unresolved_count(&self) -> usize {
+        self.files.iter().map(|f| f.unresolved_count()).sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConflictFile {
+    pub path: PathBuf,
+    pub regions: Vec<MergeRegion>,
+}
+
+#[derive(Debug, Clone)]
+pub enum MergeRegion {
+    Clean(Vec<String>),
+    Conflict(ConflictHunk),
+}
+
+#[derive(Debug, Clone)]
+pub struct ConflictHunk {
+    pub base: Vec<String>,
+    pub ours: Vec<String>,
+    pub theirs: Vec<String>,
+    /// Missing sides are deleted files in delete/modify conflicts
+    pub ours_present: bool,
+    pub theirs_present: bool,
+    pub resolution: Option<Resolution>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    Both,
+    Base,
+}
+
+impl ConflictHunk {
+    fn resolved_lines(&self) -> Option<Vec<String>> {
+        Some(match self.resolution? {
+            Resolution::Ours => self.ours.clone(),
+            Resolution::Theirs => self.theirs.clone(),
+            Resolution::Base => self.base.clone(),
+            Resolution::Both => self.ours.iter().chain(self.theirs.iter()).cloned().collect(),
+        })
+    }
+
+    /// Whether the chosen side deletes the file
+    fn resolves_to_deletion(&self) -> bool {
+        matches!(
+            (self.resolution, self.ours_present, self.theirs_present),
+            (Some(Resolution::Ours), false, _) | (Some(Resolution::Theirs), _, false)
+        )
+    }
+}
+
+impl ConflictFile {
+    pub fn is_resolved(&self) -> bool {
+        self.unresolved_count() == 0
+    }
+
+    pub fn unresolved_count(&self) -> usize {
+        self.regions
+            .iter()
+            .filter(|r| matches!(r, MergeRegion::Conflict(h) if h.resolution.is_none()))
+            .count()
+    }
+
+    pub fn conflicts_mut(&mut self) -> impl Iterator<Item = &mut ConflictHunk> {
+        self.regions.iter_mut().filter_map(|r| match r {
+            MergeRegion::Conflict(h) => Some(h),
+            MergeRegion::Clean(_) => None,
+        })
+    }
+
+    /// Final file content, `Ok(None)` when the resolution deletes the file
+    pub fn resolved_content(&self) -> Result<Option<String>> {
+        let mut content = String::new();
+        for region in &self.regions {
+            match region {
+                MergeRegion::Clean(lines) => content.extend(lines.iter().map(String::as_str)),
+                MergeRegion::Conflict(hunk) => {
+                    if hunk.resolves_to_deletion() {
+                        return Ok(None);
+                    }
+                    let lines = hunk.resolved_lines().ok_or_else(|| {
+                        anyhow::anyhow!("{} has unresolved conflicts", self.path.display())
+                    })?;
+                    content.extend(lines.iter().map(String::as_str));
+                }
+            }
+        }
+        Ok(Some(content))
+    }
+}
+
+/// A change from one side, expressed on base line indices `[start, end)`
+struct Change {
+    start: usize,
+    end: usize,
+    lines: Vec<String>,
+}
+
+/// Line-based three-way merge of `ours` and `theirs` against `base`,
+/// using libgit2's diff for each side.
+pub fn merge_texts(base: &str, ours: &str, theirs: &str) -> Result<Vec<MergeRegion>> {
+    let base_lines = split_lines(base);
+    let ours_changes = changes_between(base, ours)?;
+    let theirs_changes = changes_between(base, theirs)?;
+
+    let mut regions: Vec<MergeRegion> = Vec::new();
+    let push_clean = |regions: &mut Vec<MergeRegion>, lines: &[String]| {
+        if lines.is_empty() {
+            return;
+        }
+        if let Some(MergeRegion::Clean(existing)) = regions.last_mut() {
+            existing.extend_from_slice(lines);
+        } else {
+            regions.push(MergeRegion::Clean(lines.to_vec()));
+        }
+    };
+
+    let mut base_pos = 0;
+    let (mut oi, mut ti) = (0, 0);
+
+    while oi < ours_changes.len() || ti < theirs_changes.len() {
+        // Start a group with whichever change comes first on the base
+        let take_ours = match (ours_changes.get(oi), theirs_changes.get(ti)) {
+            (Some(o), Some(t)) => o.start <= t.start,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        let first = if take_ours { &ours_changes[oi] } else { &theirs_changes[ti] };
+        let (group_start, mut group_end) = (first.start, first.end);
+        let (ours_from, theirs_from) = (oi, ti);
+        if take_ours { oi += 1 } else { ti += 1 }
+
+        // Pull in every change from either side that overlaps or touches
+        // the group; like git, adjacent edits are treated as conflicting
+        loop {
+            if let Some(o) = ours_changes.get(oi).filter(|c| c.start <= group_end) {
+                group_end = group_end.max(o.end);
+                oi += 1;
+            } else if let Some(t) = theirs_changes.get(ti).filter(|c| c.start <= group_end) {
+                group_end = group_end.max(t.end);
+                ti += 1;
+            } else {
+                break;
+            }
+        }
+
+        push_clean(&mut regions, &base_lines[base_pos..group_start]);
+
+        let ours_group = &ours_changes[ours_from..oi];
+        let theirs_group = &theirs_changes[theirs_from..ti];
+        let base_slice = &base_lines[group_start..group_end];
+        let ours_text = apply_changes(&base_lines, group_start, group_end, ours_group);
+        let theirs_text = apply_changes(&base_lines, group_start, group_end, theirs_group);
+
+        if theirs_group.is_empty() || ours_text == theirs_text {
+            push_clean(&mut regions, &ours_text);
+        } else if ours_group.is_empty() {
+            push_clean(&mut regions, &theirs_text);
+        } else {
+            regions.push(MergeRegion::Conflict(ConflictHunk {
+                base: base_slice.to_vec(),
+                ours: ours_text,
+                theirs: theirs_text,
+                ours_present: true,
+                theirs_present: true,
+                resolution: None,
+            }));
+        }
+
+        base_pos = group_end;
+    }
+
+    push_clean(&mut regions, &base_lines[base_pos..]);
+    Ok(regions)
+}
+
+/// Whole-file conflict used when one side deleted the file or is binary
+pub fn whole_file_conflict(base: Option<&str>, ours: Option<&str>, theirs: Option<&str>) -> Vec<MergeRegion> {
+    vec![MergeRegion::Conflict(ConflictHunk {
+        base: base.map(split_lines).unwrap_or_default(),
+        ours: ours.map(split_lines).unwrap_or_default(),
+        theirs: theirs.map(split_lines).unwrap_or_default(),
+        ours_present: ours.is_some(),
+        theirs_present: theirs.is_some(),
+        resolution: None,
+    })]
+}
+
+fn apply_changes(base: &[String], start: usize, end: usize, changes: &[Change]) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut pos = start;
+    for change in changes {
+        result.extend_from_slice(&base[pos..change.start]);
+        result.extend(change.lines.iter().cloned());
+        pos = change.end;
+    }
+    result.extend_from_slice(&base[pos..end]);
+    result
+}
+
+fn changes_between(base: &str, other: &str) -> Result<Vec<Change>> {
+    let mut opts = DiffOptions::new();
+    opts.context_lines(0);
+    let patch = Patch::from_buffers(base.as_bytes(), None, other.as_bytes(), None, Some(&mut opts))?;
+
+    let mut changes = Vec::new();
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_idx)?;
+        let old_lines = hunk.old_lines() as usize;
+        // Pure insertions are addressed by the line before them
+        let start = if old_lines == 0 {
+            hunk.old_start() as usize
+        } else {
+            hunk.old_start() as usize - 1
+        };
+
+        let mut lines = Vec::new();
+        for line_idx in 0..line_count {
+            let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+            if line.origin() == '+' {
+                lines.push(String::from_utf8_lossy(line.content()).to_string());
+            }
+        }
+
+        changes.push(Change {
+            start,
+            end: start + old_lines,
+            lines,
+        });
+    }
+
+    Ok(changes)
+}
+
+fn split_lines(text: &str) -> Vec<String> {
+    text.split_inclusive('\n').map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged(base: &str, ours: &str, theirs: &str) -> Vec<MergeRegion> {
+        merge_texts(base, ours, theirs).unwrap()
+    }
+
+    fn content(regions: Vec<MergeRegion>) -> Option<String> {
+        ConflictFile { path: PathBuf::from("f"), regions }.resolved_content().unwrap()
+    }
+
+    fn conflicts(regions: &[MergeRegion]) -> Vec<&ConflictHunk> {
+        regions
+            .iter()
+            .filter_map(|r| match r {
+                MergeRegion::Conflict(h) => Some(h),
+                MergeRegion::Clean(_) => None,
+            })
+            .collect()
+    }
+
+    fn resolve(regions: Vec<MergeRegion>, resolution: Resolution) -> Option<String> {
+        let mut file = ConflictFile { path: PathBuf::from("f"), regions };
+        file.conflicts_mut().for_each(|h| h.resolution = Some(resolution));
+        file.resolved_content().unwrap()
+    }
+
+    const BASE: &str = "a\nb\nc\nd\ne\nf\n";
+
+    #[test]
+    fn non_overlapping_edits_merge_cleanly() {
+        let regions = merged(BASE, "A\nb\nc\nd\ne\nf\n", "a\nb\nc\nd\ne\nF\n");
+        assert!(conflicts(&regions).is_empty());
+        assert_eq!(content(regions).unwrap(), "A\nb\nc\nd\ne\nF\n");
+    }
+
+    #[test]
+    fn one_sided_edits_take_that_side() {
+        assert_eq!(content(merged(BASE, BASE, "a\nb\nX\nd\ne\nf\n")).unwrap(), "a\nb\nX\nd\ne\nf\n");
+        assert_eq!(content(merged(BASE, "a\nd\ne\nf\n", BASE)).unwrap(), "a\nd\ne\nf\n");
+    }
+
+    #[test]
+    fn the_same_edit_on_both_sides_is_clean() {
+        let edited = "a\nb\nX\nY\nd\ne\nf\n";
+        let regions = merged(BASE, edited, edited);
+        assert!(conflicts(&regions).is_empty());
+        assert_eq!(content(regions).unwrap(), edited);
+    }
+
+    #[test]
+    fn overlapping_edits_conflict() {
+        let regions = merged(BASE, "a\nb\nOURS\nd\ne\nf\n", "a\nb\nTHEIRS\nd\ne\nf\n");
+        let hunks = conflicts(&regions);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].base, ["c\n"]);
+        assert_eq!(hunks[0].ours, ["OURS\n"]);
+        assert_eq!(hunks[0].theirs, ["THEIRS\n"]);
+
+        let file = ConflictFile { path: PathBuf::from("f"), regions: regions.clone() };
+        assert_eq!(file.unresolved_count(), 1);
+        assert!(file.resolved_content().is_err());
+
+        assert_eq!(resolve(regions.clone(), Resolution::Ours).unwrap(), "a\nb\nOURS\nd\ne\nf\n");
+        assert_eq!(resolve(regions.clone(), Resolution::Theirs).unwrap(), "a\nb\nTHEIRS\nd\ne\nf\n");
+        assert_eq!(resolve(regions.clone(), Resolution::Both).unwrap(), "a\nb\nOURS\nTHEIRS\nd\ne\nf\n");
+        assert_eq!(resolve(regions, Resolution::Base).unwrap(), BASE);
+    }
+
+    #[test]
+    fn adjacent_edits_conflict_like_git() {
+        let regions = merged(BASE, "a\nB\nc\nd\ne\nf\n", "a\nb\nC\nd\ne\nf\n");
+        let hunks = conflicts(&regions);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].base, ["b\n", "c\n"]);
+        assert_eq!(hunks[0].ours, ["B\n", "c\n"]);
+        assert_eq!(hunks[0].theirs, ["b\n", "C\n"]);
+    }
+
+    #[test]
+    fn insertions_at_eof() {
+        // One side appends: clean
+        assert_eq!(content(merged(BASE, &format!("{}g\n", BASE), BASE)).unwrap(), format!("{}g\n", BASE));
+
+        // Both append different lines: conflict on an empty base slice
+        let regions = merged(BASE, &format!("{}ours\n", BASE), &format!("{}theirs\n", BASE));
+        let hunks = conflicts(&regions);
+        assert_eq!(hunks.len(), 1);
+        assert!(hunks[0].base.is_empty());
+        assert_eq!(hunks[0].ours, ["ours\n"]);
+        assert_eq!(hunks[0].theirs, ["theirs\n"]);
+        assert_eq!(resolve(regions, Resolution::Both).unwrap(), format!("{}ours\ntheirs\n", BASE));
+    }
+
+    #[test]
+    fn missing_trailing_newline() {
+        // Dropping the newline at EOF is an edit of the last line
+        let regions = merged("a\nb\nc\n", "A\nb\nc\n", "a\nb\nc");
+        assert!(conflicts(&regions).is_empty());
+        assert_eq!(content(regions).unwrap(), "A\nb\nc");
+
+        // Base without one; one side edits the start, the other appends
+        let regions = merged("a\nb\nc", "A\nb\nc", "a\nb\nc\nd");
+        assert!(conflicts(&regions).is_empty());
+        assert_eq!(content(regions).unwrap(), "A\nb\nc\nd");
+
+        // Both touch the unterminated last line
+        let regions = merged("a\nb", "a\nours", "a\ntheirs");
+        let hunks = conflicts(&regions);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].base, ["b"]);
+        assert_eq!(hunks[0].ours, ["ours"]);
+        assert_eq!(hunks[0].theirs, ["theirs"]);
+    }
+
+    #[test]
+    fn both_added_with_an_empty_base_is_a_whole_file_conflict() {
+        let regions = merged("", "ours\n", "theirs\n");
+        assert_eq!(regions.len(), 1);
+        let hunks = conflicts(&regions);
+        assert!(hunks[0].base.is_empty());
+        assert_eq!(hunks[0].ours, ["ours\n"]);
+        assert_eq!(hunks[0].theirs, ["theirs\n"]);
+    }
+
+    #[test]
+    fn whole_file_conflicts() {
+        // Added on both sides with no base
+        let regions = whole_file_conflict(None, Some("ours\n"), Some("theirs\n"));
+        assert_eq!(regions.len(), 1);
+        assert!(conflicts(&regions)[0].base.is_empty());
+        assert_eq!(resolve(regions, Resolution::Theirs).unwrap(), "theirs\n");
+
+        // Deleted on our side, modified on theirs
+        let regions = whole_file_conflict(Some("a\n"), None, Some("b\n"));
+        let hunk = conflicts(&regions)[0];
+        assert!(!hunk.ours_present && hunk.theirs_present);
+        assert_eq!(resolve(regions.clone(), Resolution::Ours), None);
+        assert_eq!(resolve(regions.clone(), Resolution::Theirs).unwrap(), "b\n");
+        assert_eq!(resolve(regions, Resolution::Base).unwrap(), "a\n");
+    }
+}
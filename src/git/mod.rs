@@ -1,31 +1,144 @@
+pub mod merge;
+pub mod worker;
+
+pub use merge::{
+    ConflictFile, ConflictHunk, IntegrationOutcome, IntegrationStrategy, MergeRegion,
+    PendingMerge, Resolution,
+};
+pub use worker::{GitEvent, GitWorker};
+
+use crate::config::GitConfig;
 use crate::workspace::TerminalId;
 use anyhow::Result;
 use git2::{
-    BranchType, DiffOptions, Repository, Status, StatusOptions, Worktree as Git2Worktree,
+    ApplyLocation, BranchType, Commit, Diff, DiffOptions, Index, Oid, Repository, Status,
+    StatusOptions,
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 
 pub struct GitManager {
-    repo: Option<Repository>,
+    repo: Option<Mutex<Repository>>,
     worktrees: Arc<RwLock<HashMap<TerminalId, WorktreeInfo>>>,
     status_cache: Arc<RwLock<GitStatus>>,
     project_dir: PathBuf,
+    /// Linked worktrees found on disk that no terminal is attached to
+    discovered: RwLock<Vec<DiscoveredWorktree>>,
+    worktree_root: PathBuf,
+    branch_template: String,
+    base_ref: Option<String>,
+    /// Commits and merges made through the manager, oldest first
+    activity: RwLock<Vec<GitActivity>>,
+}
+
+/// Activity entries kept for the timeline
+const MAX_ACTIVITY: usize = 1000;
+
+/// A commit or merge made through the manager
+#[derive(Debug, Clone)]
+pub struct GitActivity {
+    pub time: Instant,
+    pub terminal_id: Option<TerminalId>,
+    pub kind: GitActivityKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum GitActivityKind {
+    Commit { commit: String, summary: String },
+    /// A worktree branch landed on main
+    Merged { branch: String, commit: String },
 }
 
 #[derive(Debug, Clone)]
 pub struct WorktreeInfo {
+    /// Worktree name under `.git/worktrees`
+    pub name: String,
     pub path: PathBuf,
     pub branch: String,
     pub terminal_id: TerminalId,
     pub last_sync: Instant,
     pub merge_status: MergeStatus,
+    /// Commits on the worktree branch that main does not have
+    pub ahead: usize,
+    /// Commits on main that the worktree branch does not have
+    pub behind: usize,
+    /// Whether rgb created the worktree; attached ones are never removed
+    pub created: bool,
+}
+
+/// A linked worktree from `git worktree list`
+#[derive(Debug, Clone)]
+pub struct DiscoveredWorktree {
+    pub name: String,
+    pub path: PathBuf,
+    /// `None` when the worktree has a detached HEAD
+    pub branch: Option<String>,
+}
+
+/// Work in a worktree that removing it would lose
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WorktreeState {
+    /// Modified, staged and untracked files
+    pub dirty_files: usize,
+    /// Commits on the branch that main does not contain
+    pub unmerged_commits: usize,
 }
 
+impl WorktreeState {
+    pub fn is_clean(&self) -> bool {
+        self.dirty_files == 0 && self.unmerged_commits == 0
+    }
+}
+
+/// Snapshot of a terminal's worktree for the `:worktree` dashboard
+#[derive(Debug, Clone)]
+pub struct WorktreeOverview {
+    pub info: WorktreeInfo,
+    pub dirty_files: usize,
+    /// Subject line of the branch tip
+    pub last_commit: Option<String>,
+}
+
+/// A file a worktree changes relative to its fork point with main
+#[derive(Debug, Clone)]
+pub struct ChangedFile {
+    pub path: PathBuf,
+    /// Line ranges the changes touch, numbered as in main's current tip so
+    /// worktrees forked at different commits compare; pure insertions cover
+    /// the line they follow so adjacent edits count as overlapping, as in git
+    pub ranges: Vec<std::ops::Range<usize>>,
+    /// Added, deleted or binary files, where every change overlaps
+    pub whole_file: bool,
+}
+
+/// What happens to a worktree when its terminal closes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorktreeDisposition {
+    /// Leave the worktree and branch on disk for `:worktrees`/`:attach`
+    Keep,
+    /// Stash uncommitted changes, then remove the worktree
+    Stash,
+    /// Commit uncommitted changes on the branch, then remove the worktree
+    Commit,
+    /// Remove the worktree and delete its branch
+    Discard,
+}
+
+/// Result of one background sync of a worktree onto main
 #[derive(Debug, Clone)]
+pub enum SyncOutcome {
+    UpToDate,
+    FastForwarded { commits: usize },
+    Rebased { commits: usize, onto: usize },
+    SkippedDirty { behind: usize },
+    /// The branch got a new commit while the sync ran
+    SkippedMoved { behind: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum MergeStatus {
     Unmerged,
     Merged,
@@ -48,11 +161,137 @@ pub struct DiffHunk {
     pub old_lines: usize,
     pub new_start: usize,
     pub new_lines: usize,
-    pub content: String,
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+    pub side: DiffSide,
+    pub file_status: FileDiffStatus,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub old_lineno: Option<usize>,
+    pub new_lineno: Option<usize>,
+    /// Line as in the file, including its trailing newline if it has one
+    /// there; not necessarily UTF-8
+    pub content: Vec<u8>,
+}
+
+impl DiffLine {
+    /// The line for display, without its newline
+    pub fn text(&self) -> std::borrow::Cow<'_, str> {
+        let content = self.content.strip_suffix(b"\n").unwrap_or(&self.content);
+        let content = content.strip_suffix(b"\r").unwrap_or(content);
+        String::from_utf8_lossy(content)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+/// Which pair of trees a diff compares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffSide {
+    /// Index to working directory - changes that can be staged
+    Unstaged,
+    /// HEAD to index - changes that can be unstaged
+    Staged,
+    /// Fork point with main to working directory - everything the
+    /// branch would bring to main; read-only
+    Main,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileDiffStatus {
+    Added,
+    Deleted,
+    Modified,
+    Untracked,
+}
+
+impl DiffHunk {
+    /// Build a single-hunk patch for this hunk, keeping only the selected
+    /// line indices (all changed lines when `selection` is `None`).
+    ///
+    /// With `reverse` the patch undoes the hunk, which is what unstaging
+    /// applies to the index. Unselected lines on the "from" side become
+    /// context and unselected lines on the "to" side are dropped, the same
+    /// way `git add -p` edits a hunk.
+    pub fn to_patch(&self, selection: Option<&[usize]>, reverse: bool) -> Option<Vec<u8>> {
+        let old_start = if reverse { self.new_start } else { self.old_start };
+
+        let mut body = Vec::new();
+        let mut old_count = 0;
+        let mut new_count = 0;
+        let mut has_change = false;
+
+        for (idx, line) in self.lines.iter().enumerate() {
+            let selected = selection.is_none_or(|s| s.contains(&idx));
+            let kind = match (line.kind, reverse) {
+                (DiffLineKind::Added, true) => DiffLineKind::Removed,
+                (DiffLineKind::Removed, true) => DiffLineKind::Added,
+                (kind, _) => kind,
+            };
+
+            let prefix = match kind {
+                DiffLineKind::Context => ' ',
+                DiffLineKind::Removed if selected => '-',
+                DiffLineKind::Removed => ' ',
+                DiffLineKind::Added if selected => '+',
+                DiffLineKind::Added => continue,
+            };
+
+            if prefix != ' ' {
+                has_change = true;
+            }
+            if prefix != '+' {
+                old_count += 1;
+            }
+            if prefix != '-' {
+                new_count += 1;
+            }
+
+            body.push(prefix as u8);
+            body.extend_from_slice(&line.content);
+            if !line.content.ends_with(b"\n") {
+                body.extend_from_slice(b"\n\\ No newline at end of file\n");
+            }
+        }
+
+        if !has_change {
+            return None;
+        }
+
+        // A zero-length side is addressed by the line *before* it
+        let new_start = if old_count == 0 {
+            old_start + 1
+        } else if new_count == 0 {
+            old_start.saturating_sub(1)
+        } else {
+            old_start
+        };
+
+        let (a, b) = (quote_path("a/", &self.file), quote_path("b/", &self.file));
+        let mut patch = format!("diff --git {a} {b}\n--- {a}\n+++ {b}\n").into_bytes();
+        patch.extend_from_slice(format!("@@ -{},{} +{},{} @@\n", old_start, old_count, new_start, new_count).as_bytes());
+        patch.extend_from_slice(&body);
+        Some(patch)
+    }
+
+    pub fn is_changed_line(&self, idx: usize) -> bool {
+        self.lines
+            .get(idx)
+            .map(|l| l.kind != DiffLineKind::Context)
+            .unwrap_or(false)
+    }
 }
 
 impl GitManager {
-    pub fn new(project_dir: &Path) -> Result<Self> {
+    pub fn new(project_dir: &Path, config: &GitConfig) -> Result<Self> {
         let repo = Repository::open(project_dir).ok();
 
         if let Some(ref r) = repo {
@@ -61,159 +300,650 @@ impl GitManager {
             tracing::info!("No git repository found at {:?}", project_dir);
         }
 
-        Ok(Self {
-            repo,
+        let canonical_dir = project_dir.canonicalize().unwrap_or_else(|_| project_dir.to_path_buf());
+        let repo_name = canonical_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "repo".to_string());
+        let worktree_root = normalize_path(
+            &canonical_dir.join(config.worktree_root.replace("{repo}", &repo_name)),
+        );
+
+        let manager = Self {
+            repo: repo.map(Mutex::new),
             worktrees: Arc::new(RwLock::new(HashMap::new())),
             status_cache: Arc::new(RwLock::new(GitStatus::default())),
             project_dir: project_dir.to_path_buf(),
-        })
+            discovered: RwLock::new(Vec::new()),
+            activity: RwLock::new(Vec::new()),
+            worktree_root,
+            branch_template: config.branch_template.clone(),
+            base_ref: Some(config.base_ref.clone()).filter(|r| !r.is_empty()),
+        };
+
+        if let Err(e) = manager.discover_worktrees() {
+            tracing::warn!("Failed to list existing worktrees: {}", e);
+        }
+
+        Ok(manager)
     }
 
     pub fn is_git_repo(&self) -> bool {
         self.repo.is_some()
     }
 
-    pub async fn create_worktree(&self, terminal_id: TerminalId) -> Result<PathBuf> {
+    /// Commit new worktree branches start from now: `base_ref`, or HEAD
+    pub fn base_commit(&self) -> Result<String> {
+        let repo = self.repo.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Not a git repository"))?
+            .lock();
+        let commit = match &self.base_ref {
+            Some(base_ref) => repo.revparse_single(base_ref)?.peel_to_commit()?,
+            None => repo.head()?.peel_to_commit()?,
+        };
+        Ok(commit.id().to_string())
+    }
+
+    /// Create a worktree on a new branch for a terminal, starting from
+    /// `base` if given and otherwise from [`base_commit`](Self::base_commit)
+    pub async fn create_worktree(&self, terminal_id: TerminalId, profile: &str, base: Option<&str>) -> Result<PathBuf> {
         let repo = self.repo.as_ref()
             .ok_or_else(|| anyhow::anyhow!("Not a git repository"))?;
 
-        // Generate unique branch name
-        let branch_name = format!("terminal-{}", &terminal_id.to_string()[..8]);
+        // Ensure the root exists; git creates the worktree directory itself
+        tokio::fs::create_dir_all(&self.worktree_root).await?;
 
-        // Create worktree path in temp directory
-        let worktree_dir = std::env::temp_dir()
-            .join("rgb-worktrees")
-            .join(terminal_id.to_string());
+        let repo = repo.lock();
 
-        // Ensure directory exists
-        tokio::fs::create_dir_all(&worktree_dir).await?;
+        let (branch_name, name) = self.next_branch_name(&repo, terminal_id, profile);
+        let worktree_dir = self.worktree_root.join(&name);
 
-        // Get current branch
-        let head = repo.head()?;
-        // Create new branch from current
-        let oid = head.target()
-            .ok_or_else(|| anyhow::anyhow!("HEAD has no target"))?;
-        let commit = repo.find_commit(oid)?;
-        repo.branch(&branch_name, &commit, false)?;
+        let base = match (base, &self.base_ref) {
+            (Some(base), _) => repo.revparse_single(base)?.peel_to_commit()?,
+            (None, Some(base_ref)) => repo.revparse_single(base_ref)?.peel_to_commit()?,
+            (None, None) => repo.head()?.peel_to_commit()?,
+        };
+        let branch = repo.branch(&branch_name, &base, false)?;
 
-        // Add worktree
-        let _worktree = repo.worktree(
-            &branch_name,
+        // Add worktree checked out on the new branch
+        repo.worktree(
+            &name,
             &worktree_dir,
-            Some(&git2::WorktreeAddOptions::new()),
+            Some(git2::WorktreeAddOptions::new().reference(Some(branch.get()))),
         )?;
 
-        // Store worktree info
-        let info = WorktreeInfo {
+        self.worktrees.write().insert(terminal_id, WorktreeInfo {
+            name,
             path: worktree_dir.clone(),
             branch: branch_name,
             terminal_id,
             last_sync: Instant::now(),
             merge_status: MergeStatus::Unmerged,
+            ahead: 0,
+            behind: 0,
+            created: true,
+        });
+
+        Ok(worktree_dir)
+    }
+
+    /// Attach a terminal to an existing worktree (by name, branch or path)
+    /// or check out an existing local branch in a new worktree
+    pub async fn attach_worktree(&self, terminal_id: TerminalId, target: &str) -> Result<PathBuf> {
+        let repo = self.repo.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Not a git repository"))?;
+
+        let target_path = self.project_dir.join(target);
+        let existing = {
+            let mut discovered = self.discovered.write();
+            let position = discovered.iter().position(|w| {
+                w.name == target
+                    || w.branch.as_deref() == Some(target)
+                    || same_path(&w.path, &target_path)
+            });
+            position.map(|idx| discovered.remove(idx))
         };
 
-        self.worktrees.write().insert(terminal_id, info);
+        let (name, path, branch, created) = if let Some(worktree) = existing {
+            let branch = worktree.branch.ok_or_else(|| {
+                anyhow::anyhow!("Worktree '{}' has a detached HEAD", worktree.name)
+            })?;
+            (worktree.name, worktree.path, branch, false)
+        } else {
+            if self.worktrees.read().values().any(|w| {
+                w.branch == target || w.name == target || same_path(&w.path, &target_path)
+            }) {
+                anyhow::bail!("'{}' is already attached to another terminal", target);
+            }
 
-        Ok(worktree_dir)
+            tokio::fs::create_dir_all(&self.worktree_root).await?;
+            let repo = repo.lock();
+            let branch = repo.find_branch(target, BranchType::Local).map_err(|_| {
+                anyhow::anyhow!("No worktree or local branch named '{}'", target)
+            })?;
+            let name = worktree_name(target);
+            let path = self.worktree_root.join(&name);
+            repo.worktree(
+                &name,
+                &path,
+                Some(git2::WorktreeAddOptions::new().reference(Some(branch.get()))),
+            )?;
+            (name, path, target.to_string(), false)
+        };
+
+        self.worktrees.write().insert(terminal_id, WorktreeInfo {
+            name,
+            path: path.clone(),
+            branch,
+            terminal_id,
+            last_sync: Instant::now(),
+            merge_status: MergeStatus::Unmerged,
+            ahead: 0,
+            behind: 0,
+            created,
+        });
+
+        Ok(path)
     }
 
-    pub async fn cleanup_worktree(&self, terminal_id: TerminalId) -> Result<()> {
-        if let Some(info) = self.worktrees.write().remove(&terminal_id) {
-            if let Some(repo) = &self.repo {
-                // Prune worktree
-                if let Ok(worktree) = repo.find_worktree(&info.branch) {
-                    worktree.prune(Some(&mut git2::WorktreePruneOptions::new()))?;
-                }
+    /// Local branch names, for completion
+    pub fn branch_names(&self) -> Vec<String> {
+        let Some(repo) = &self.repo else {
+            return Vec::new();
+        };
+        let repo = repo.lock();
+        let Ok(branches) = repo.branches(Some(git2::BranchType::Local)) else {
+            return Vec::new();
+        };
+        branches
+            .flatten()
+            .filter_map(|(branch, _)| branch.name().ok().flatten().map(str::to_string))
+            .collect()
+    }
 
-                // Delete branch
-                if let Ok(mut branch) = repo.find_branch(&info.branch, BranchType::Local) {
-                    branch.delete()?;
+    /// Worktrees from `git worktree list` that no terminal is using
+    pub fn available_worktrees(&self) -> Vec<DiscoveredWorktree> {
+        self.discovered.read().clone()
+    }
+
+    /// Refresh the list of linked worktrees not attached to a terminal
+    pub fn discover_worktrees(&self) -> Result<()> {
+        let Some(repo) = &self.repo else {
+            return Ok(());
+        };
+        let repo = repo.lock();
+
+        let attached: Vec<String> = self.worktrees.read().values().map(|w| w.name.clone()).collect();
+        let mut discovered = Vec::new();
+
+        for name in repo.worktrees()?.iter().flatten() {
+            if attached.iter().any(|a| a == name) {
+                continue;
+            }
+            let worktree = repo.find_worktree(name)?;
+            if worktree.validate().is_err() {
+                tracing::debug!("Skipping stale worktree {}", name);
+                continue;
+            }
+
+            let branch = Repository::open(worktree.path()).ok().and_then(|r| {
+                let head = r.head().ok()?;
+                if head.is_branch() {
+                    head.shorthand().map(str::to_string)
+                } else {
+                    None
                 }
+            });
+
+            discovered.push(DiscoveredWorktree {
+                name: name.to_string(),
+                path: worktree.path().to_path_buf(),
+                branch,
+            });
+        }
+
+        discovered.sort_by(|a, b| a.name.cmp(&b.name));
+        tracing::info!("Discovered {} existing worktree(s)", discovered.len());
+        *self.discovered.write() = discovered;
+        Ok(())
+    }
+
+    /// Expand the branch template, bumping `{n}` past existing branches
+    /// and worktrees
+    fn next_branch_name(&self, repo: &Repository, terminal_id: TerminalId, profile: &str) -> (String, String) {
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let id = terminal_id.to_string()[..8].to_string();
+        let expand = |n: usize| {
+            self.branch_template
+                .replace("{profile}", profile)
+                .replace("{date}", &date)
+                .replace("{id}", &id)
+                .replace("{n}", &n.to_string())
+        };
+
+        let taken = |branch: &str, name: &str| {
+            repo.find_branch(branch, BranchType::Local).is_ok()
+                || repo.find_worktree(name).is_ok()
+                || self.worktree_root.join(name).exists()
+        };
+
+        let mut n = 1;
+        loop {
+            let mut branch = expand(n);
+            // Templates without {n} fall back to a numeric suffix
+            if n > 1 && !self.branch_template.contains("{n}") {
+                branch = format!("{}-{}", branch, n);
+            }
+            let name = worktree_name(&branch);
+            if !taken(&branch, &name) {
+                return (branch, name);
             }
+            n += 1;
+        }
+    }
 
-            // Remove directory
-            if info.path.exists() {
-                tokio::fs::remove_dir_all(&info.path).await?;
+    /// Remove a terminal's worktree if doing so loses nothing; worktrees
+    /// with uncommitted or unmerged work are refused
+    pub async fn cleanup_worktree(&self, terminal_id: TerminalId) -> Result<()> {
+        let Some(info) = self.worktree_info(terminal_id) else {
+            return Ok(());
+        };
+
+        if info.created {
+            let state = self.worktree_state(terminal_id)?;
+            if !state.is_clean() {
+                anyhow::bail!(
+                    "Worktree {} has {} uncommitted file(s) and {} unmerged commit(s)",
+                    info.branch,
+                    state.dirty_files,
+                    state.unmerged_commits,
+                );
             }
         }
 
-        Ok(())
+        self.release_worktree(terminal_id, WorktreeDisposition::Discard).await
     }
 
-    pub async fn sync_worktree(&self, terminal_id: TerminalId) -> Result<()> {
-        let info = self.worktrees.read().get(&terminal_id).cloned();
+    /// Detach a worktree from its terminal, handling its work as chosen.
+    /// Worktrees rgb did not create are always kept.
+    pub async fn release_worktree(
+        &self,
+        terminal_id: TerminalId,
+        disposition: WorktreeDisposition,
+    ) -> Result<()> {
+        let Some(info) = self.worktree_info(terminal_id) else {
+            return Ok(());
+        };
+        let disposition = if info.created { disposition } else { WorktreeDisposition::Keep };
 
-        if let Some(mut info) = info {
-            if info.last_sync.elapsed().as_secs() < 300 {
-                // Skip if synced recently
+        let keep_branch = match disposition {
+            WorktreeDisposition::Keep => {
+                self.worktrees.write().remove(&terminal_id);
+                self.discovered.write().push(DiscoveredWorktree {
+                    name: info.name,
+                    path: info.path,
+                    branch: Some(info.branch),
+                });
                 return Ok(());
             }
+            WorktreeDisposition::Stash => {
+                let mut worktree_repo = Repository::open(&info.path)?;
+                let signature = worktree_repo.signature()?;
+                match worktree_repo.stash_save(
+                    &signature,
+                    &format!("rgb: {}", info.branch),
+                    Some(git2::StashFlags::INCLUDE_UNTRACKED),
+                ) {
+                    Ok(_) => {}
+                    // Nothing to stash
+                    Err(e) if e.code() == git2::ErrorCode::NotFound => {}
+                    Err(e) => return Err(e.into()),
+                }
+                self.worktree_state(terminal_id)?.unmerged_commits > 0
+            }
+            WorktreeDisposition::Commit => {
+                self.commit_all(terminal_id, &format!("WIP: uncommitted work from {}", info.branch))?;
+                true
+            }
+            WorktreeDisposition::Discard => false,
+        };
 
-            if let Some(repo) = &self.repo {
-                // Open worktree repository
-                let worktree_repo = Repository::open(&info.path)?;
+        self.worktrees.write().remove(&terminal_id);
+        self.remove_worktree(&info.name, &info.path, (!keep_branch).then_some(info.branch.as_str()))
+            .await
+    }
 
-                // Get current branch in main repo
-                let main_head = repo.head()?;
-                let main_branch = main_head.shorthand().unwrap_or("main");
+    /// Pass a terminal's worktree to the manager of the workspace it moved
+    /// to. Only a manager of the same repository can take it; otherwise it
+    /// is kept, as if its terminal had closed, and false is returned.
+    pub fn hand_over_worktree(&self, terminal_id: TerminalId, to: &GitManager) -> bool {
+        let Some(info) = self.worktrees.write().remove(&terminal_id) else {
+            return false;
+        };
+        if self.project_dir == to.project_dir {
+            to.worktrees.write().insert(terminal_id, info);
+            return true;
+        }
+        self.discovered.write().push(DiscoveredWorktree {
+            name: info.name,
+            path: info.path,
+            branch: Some(info.branch),
+        });
+        false
+    }
 
-                // Check for uncommitted changes
-                let statuses = worktree_repo.statuses(Some(
-                    StatusOptions::new()
-                        .include_untracked(true)
-                        .include_ignored(false),
-                ))?;
+    /// Uncommitted files and unmerged commits in a terminal's worktree
+    pub fn worktree_state(&self, terminal_id: TerminalId) -> Result<WorktreeState> {
+        let info = self.worktree_info(terminal_id)
+            .ok_or_else(|| anyhow::anyhow!("Terminal has no worktree"))?;
+        self.state_at(&info.path, Some(&info.branch))
+    }
 
-                if !statuses.is_empty() {
-                    // Has uncommitted changes, skip sync
-                    return Ok(());
+    /// Fresh ahead/behind, dirty and last-commit details for every
+    /// terminal worktree, ordered by branch
+    pub fn worktree_overviews(&self) -> Vec<WorktreeOverview> {
+        let mut overviews: Vec<WorktreeOverview> = self
+            .worktree_ids()
+            .into_iter()
+            .filter_map(|terminal_id| match self.worktree_overview(terminal_id) {
+                Ok(overview) => Some(overview),
+                Err(e) => {
+                    tracing::warn!("Failed to inspect worktree for {}: {}", terminal_id, e);
+                    self.worktree_info(terminal_id).map(|info| WorktreeOverview {
+                        info,
+                        dirty_files: 0,
+                        last_commit: None,
+                    })
                 }
+            })
+            .collect();
+        overviews.sort_by(|a, b| a.info.branch.cmp(&b.info.branch));
+        overviews
+    }
 
-                // Attempt to merge main branch
-                let main_oid = main_head.target()
-                    .ok_or_else(|| anyhow::anyhow!("Main HEAD has no target"))?;
-                let main_commit = repo.find_commit(main_oid)?;
-
-                let worktree_head = worktree_repo.head()?;
-                let worktree_oid = worktree_head.target()
-                    .ok_or_else(|| anyhow::anyhow!("Worktree HEAD has no target"))?;
-
-                // Check if merge is needed
-                let merge_base = repo.merge_base(main_oid, worktree_oid)?;
-
-                if merge_base != main_oid {
-                    // Merge is needed
-                    let annotated_commit = worktree_repo.find_annotated_commit(main_oid)?;
-                    let (merge_analysis, _) = worktree_repo.merge_analysis(&[&annotated_commit])?;
-
-                    if merge_analysis.is_fast_forward() {
-                        // Fast-forward merge
-                        worktree_repo.checkout_tree(
-                            main_commit.as_object(),
-                            Some(&mut git2::build::CheckoutBuilder::new()),
-                        )?;
-                        worktree_repo.set_head_detached(main_oid)?;
-                        info.merge_status = MergeStatus::Merged;
-                    } else if merge_analysis.is_normal() {
-                        // Regular merge needed
-                        // TODO: Implement proper merge
-                        info.merge_status = MergeStatus::Conflict {
-                            main_branch: main_branch.to_string(),
-                            worktree_branch: info.branch.clone(),
-                        };
+    fn worktree_overview(&self, terminal_id: TerminalId) -> Result<WorktreeOverview> {
+        let repo = self.repo.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Not a git repository"))?;
+        let info = self.worktree_info(terminal_id)
+            .ok_or_else(|| anyhow::anyhow!("Terminal has no worktree"))?;
+
+        let (ahead, behind, last_commit) = {
+            let repo = repo.lock();
+            let main = repo.head()?.peel_to_commit()?.id();
+            let tip = repo.find_branch(&info.branch, BranchType::Local)?.get().peel_to_commit()?;
+            let (ahead, behind) = repo.graph_ahead_behind(tip.id(), main)?;
+            (ahead, behind, tip.summary().map(str::to_string))
+        };
+        self.update_worktree(terminal_id, |w| {
+            w.ahead = ahead;
+            w.behind = behind;
+        });
+
+        let dirty_files = self.state_at(&info.path, None)?.dirty_files;
+        let info = self.worktree_info(terminal_id).unwrap_or(info);
+
+        Ok(WorktreeOverview { info, dirty_files, last_commit })
+    }
+
+    /// Files a terminal's worktree changes against its merge base with main,
+    /// committed or not, with the lines each change touches mapped onto
+    /// main's tip
+    pub fn changed_files(&self, terminal_id: TerminalId) -> Result<Vec<ChangedFile>> {
+        let info = self.worktree_info(terminal_id)
+            .ok_or_else(|| anyhow::anyhow!("Terminal has no worktree"))?;
+        let main = self.main_head()?;
+        let worktree_repo = Repository::open(&info.path)?;
+
+        // No context so hunks cover only the changed lines
+        let diff = Self::diff_for_side(&worktree_repo, DiffSide::Main, Some(main), 0)?;
+
+        // What main changed since the fork point moves the base lines
+        let head = worktree_repo.head()?.peel_to_commit()?.id();
+        let base = worktree_repo.merge_base(head, main)?;
+        let main_hunks = if base == main {
+            HashMap::new()
+        } else {
+            let base_tree = worktree_repo.find_commit(base)?.tree()?;
+            let main_tree = worktree_repo.find_commit(main)?.tree()?;
+            let main_diff = worktree_repo.diff_tree_to_tree(
+                Some(&base_tree),
+                Some(&main_tree),
+                Some(DiffOptions::new().context_lines(0)),
+            )?;
+            line_hunks(&main_diff)?
+        };
+
+        let mut files: Vec<ChangedFile> = Vec::new();
+        for (idx, delta) in diff.deltas().enumerate() {
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                continue;
+            };
+            let whole_file = !matches!(delta.status(), git2::Delta::Modified)
+                || delta.flags().is_binary();
+
+            let mut ranges = Vec::new();
+            if !whole_file {
+                if let Some(patch) = git2::Patch::from_diff(&diff, idx)? {
+                    let moved = main_hunks.get(path).map(Vec::as_slice).unwrap_or_default();
+                    for hunk_idx in 0..patch.num_hunks() {
+                        let (hunk, _) = patch.hunk(hunk_idx)?;
+                        let start = hunk.old_start() as usize;
+                        let end = start + (hunk.old_lines() as usize).max(1);
+                        let mapped = map_line(moved, start).start..map_line(moved, end - 1).end;
+                        ranges.push(mapped);
                     }
                 }
+            }
+
+            files.push(ChangedFile { path: path.to_path_buf(), ranges, whole_file });
+        }
+
+        Ok(files)
+    }
+
+    /// `available_worktrees` with the work each holds, where it can be read
+    pub fn available_worktree_states(&self) -> Vec<(DiscoveredWorktree, Option<WorktreeState>)> {
+        self.available_worktrees()
+            .into_iter()
+            .map(|w| {
+                let state = self.kept_worktree_state(&w).ok();
+                (w, state)
+            })
+            .collect()
+    }
+
+    /// State of a kept worktree, as shown by the `:worktrees` manager
+    pub fn kept_worktree_state(&self, worktree: &DiscoveredWorktree) -> Result<WorktreeState> {
+        self.state_at(&worktree.path, worktree.branch.as_deref())
+    }
+
+    fn state_at(&self, path: &Path, branch: Option<&str>) -> Result<WorktreeState> {
+        let repo = self.repo.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Not a git repository"))?;
+
+        let worktree_repo = Repository::open(path)?;
+        let dirty_files = worktree_repo
+            .statuses(Some(
+                StatusOptions::new()
+                    .include_untracked(true)
+                    .recurse_untracked_dirs(true)
+                    .include_ignored(false),
+            ))?
+            .len();
+
+        let unmerged_commits = match branch {
+            Some(branch) => {
+                let repo = repo.lock();
+                let main = repo.head()?.peel_to_commit()?.id();
+                let tip = repo.find_branch(branch, BranchType::Local)?.get().peel_to_commit()?.id();
+                repo.graph_ahead_behind(tip, main)?.0
+            }
+            None => 0,
+        };
+
+        Ok(WorktreeState { dirty_files, unmerged_commits })
+    }
+
+    /// Remove a kept worktree from `:worktrees`. Unless `force` is set,
+    /// worktrees holding uncommitted or unmerged work are refused.
+    pub async fn remove_kept_worktree(&self, name: &str, force: bool) -> Result<()> {
+        let worktree = self.discovered.read().iter().find(|w| w.name == name).cloned()
+            .ok_or_else(|| anyhow::anyhow!("No kept worktree named '{}'", name))?;
+
+        if !force {
+            let state = self.kept_worktree_state(&worktree)?;
+            if !state.is_clean() {
+                anyhow::bail!(
+                    "{} has {} uncommitted file(s) and {} unmerged commit(s); use force to remove it",
+                    worktree.name,
+                    state.dirty_files,
+                    state.unmerged_commits,
+                );
+            }
+        }
+
+        self.discovered.write().retain(|w| w.name != name);
+        self.remove_worktree(&worktree.name, &worktree.path, worktree.branch.as_deref()).await
+    }
+
+    /// Commit every change in a terminal's worktree; `None` when clean
+    pub fn commit_all(&self, terminal_id: TerminalId, message: &str) -> Result<Option<String>> {
+        let info = self.worktree_info(terminal_id)
+            .ok_or_else(|| anyhow::anyhow!("Terminal has no worktree"))?;
+        let worktree_repo = Repository::open(&info.path)?;
+
+        let mut index = worktree_repo.index()?;
+        index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)?;
+        index.update_all(["*"].iter(), None)?;
+        index.write()?;
+
+        let tree = worktree_repo.find_tree(index.write_tree()?)?;
+        let parent = worktree_repo.head()?.peel_to_commit()?;
+        if parent.tree_id() == tree.id() {
+            return Ok(None);
+        }
+
+        let signature = worktree_repo.signature()?;
+        let commit_id = worktree_repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &[&parent],
+        )?;
+
+        self.record_activity(Some(terminal_id), GitActivityKind::Commit {
+            commit: commit_id.to_string(),
+            summary: summary(message),
+        });
+        Ok(Some(commit_id.to_string()))
+    }
+
+    async fn remove_worktree(&self, name: &str, path: &Path, delete_branch: Option<&str>) -> Result<()> {
+        if let Some(repo) = &self.repo {
+            let repo = repo.lock();
+
+            // Prune worktree
+            if let Ok(worktree) = repo.find_worktree(name) {
+                worktree.prune(Some(
+                    git2::WorktreePruneOptions::new().valid(true).working_tree(true),
+                ))?;
+            }
 
-                info.last_sync = Instant::now();
-                self.worktrees.write().insert(terminal_id, info);
+            // Delete branch
+            if let Some(branch) = delete_branch {
+                if let Ok(mut branch) = repo.find_branch(branch, BranchType::Local) {
+                    branch.delete()?;
+                }
             }
         }
 
+        // Remove directory
+        if path.exists() {
+            tokio::fs::remove_dir_all(path).await?;
+        }
+
         Ok(())
     }
 
+    /// Bring a clean worktree up to date with the main branch: fast-forward
+    /// when it has no commits of its own, otherwise rebase them onto main.
+    ///
+    /// This does blocking git work; the background worker runs it on
+    /// `spawn_blocking`.
+    pub fn sync_worktree(&self, terminal_id: TerminalId) -> Result<SyncOutcome> {
+        let repo = self.repo.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Not a git repository"))?;
+        let info = self.worktree_info(terminal_id)
+            .ok_or_else(|| anyhow::anyhow!("Terminal has no worktree"))?;
+
+        // Only the lookups hold the shared repository; the checkout and
+        // rebase below use a handle of their own so the UI isn't kept
+        // waiting on it
+        let (main_id, branch_id, ahead, behind) = {
+            let repo = repo.lock();
+            let main_id = repo.head()?.peel_to_commit()?.id();
+            let branch_id = repo
+                .find_branch(&info.branch, BranchType::Local)?
+                .get()
+                .peel_to_commit()?
+                .id();
+            let (ahead, behind) = repo.graph_ahead_behind(branch_id, main_id)?;
+            (main_id, branch_id, ahead, behind)
+        };
+
+        self.update_worktree(terminal_id, |w| {
+            w.ahead = ahead;
+            w.behind = behind;
+        });
+
+        // The worktree shares the project's objects and branches
+        let worktree_repo = Repository::open(&info.path)?;
+        let outcome = if behind == 0 {
+            SyncOutcome::UpToDate
+        } else if ensure_clean(&worktree_repo, "Worktree").is_err() {
+            SyncOutcome::SkippedDirty { behind }
+        } else {
+            let main_commit = worktree_repo.find_commit(main_id)?;
+            let branch_commit = worktree_repo.find_commit(branch_id)?;
+            let advanced = if ahead == 0 {
+                advance_branch(&worktree_repo, &info.branch, &branch_commit, &main_commit, "rgb: sync fast-forward")?
+            } else {
+                rebase_onto(&worktree_repo, &info, &main_commit, &branch_commit)?
+            };
+            if !advanced {
+                SyncOutcome::SkippedMoved { behind }
+            } else if ahead == 0 {
+                SyncOutcome::FastForwarded { commits: behind }
+            } else {
+                SyncOutcome::Rebased { commits: ahead, onto: behind }
+            }
+        };
+
+        self.update_worktree(terminal_id, |w| {
+            w.last_sync = Instant::now();
+            if matches!(outcome, SyncOutcome::FastForwarded { .. } | SyncOutcome::Rebased { .. }) {
+                w.behind = 0;
+            }
+        });
+
+        Ok(outcome)
+    }
+
+    pub fn worktree_ids(&self) -> Vec<TerminalId> {
+        self.worktrees.read().keys().copied().collect()
+    }
+
+    fn update_worktree(&self, terminal_id: TerminalId, f: impl FnOnce(&mut WorktreeInfo)) {
+        if let Some(info) = self.worktrees.write().get_mut(&terminal_id) {
+            f(info);
+        }
+    }
+
     pub async fn get_status(&self) -> Result<GitStatus> {
         if let Some(repo) = &self.repo {
+            let repo = repo.lock();
             let mut status = GitStatus::default();
 
             let statuses = repo.statuses(Some(
@@ -253,50 +983,385 @@ impl GitManager {
         }
     }
 
-    pub async fn get_diff(&self, terminal_id: Option<TerminalId>) -> Result<Vec<DiffHunk>> {
-        let mut hunks = Vec::new();
+    /// Full line-level diff for a terminal's worktree (or the project when
+    /// `terminal_id` is `None`), one entry per hunk.
+    pub async fn get_diff(
+        &self,
+        terminal_id: Option<TerminalId>,
+        side: DiffSide,
+    ) -> Result<Vec<DiffHunk>> {
+        if self.repo.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let repo_to_use = self.open_repo_for(terminal_id)?;
+        let main = match side {
+            DiffSide::Main => Some(self.main_head()?),
+            _ => None,
+        };
+        let diff = Self::diff_for_side(&repo_to_use, side, main, 3)?;
 
-        if let Some(_repo) = &self.repo {
-            let repo_to_use = if let Some(tid) = terminal_id {
-                if let Some(info) = self.worktrees.read().get(&tid).cloned() {
-                    Repository::open(&info.path)?
-                } else {
-                    Repository::open(&self.project_dir)?
-                }
-            } else {
-                Repository::open(&self.project_dir)?
+        let mut hunks: Vec<DiffHunk> = Vec::new();
+        diff.print(git2::DiffFormat::Patch, |delta, hunk, line| {
+            let Some(hunk) = hunk else {
+                return true;
+            };
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                return true;
             };
 
-            let head = repo_to_use.head()?.peel_to_tree()?;
-            let mut diff_options = DiffOptions::new();
-            let diff = repo_to_use.diff_tree_to_workdir(Some(&head), Some(&mut diff_options))?;
-
-            diff.foreach(
-                &mut |delta, _| {
-                    tracing::debug!("Diff file: {:?}", delta.new_file().path());
-                    true
-                },
-                None,
-                Some(&mut |delta, hunk| {
-                    if let Some(path) = delta.new_file().path() {
-                        hunks.push(DiffHunk {
-                            file: path.to_path_buf(),
-                            old_start: hunk.old_start() as usize,
-                            old_lines: hunk.old_lines() as usize,
-                            new_start: hunk.new_start() as usize,
-                            new_lines: hunk.new_lines() as usize,
-                            content: String::from_utf8_lossy(hunk.header()).to_string(),
-                        });
-                    }
-                    true
-                }),
-                None,
-            )?;
-        }
+            let header = String::from_utf8_lossy(hunk.header()).trim_end().to_string();
+            let is_new_hunk = hunks
+                .last()
+                .map(|h| h.file != path || h.header != header)
+                .unwrap_or(true);
+
+            if is_new_hunk {
+                hunks.push(DiffHunk {
+                    file: path.to_path_buf(),
+                    old_start: hunk.old_start() as usize,
+                    old_lines: hunk.old_lines() as usize,
+                    new_start: hunk.new_start() as usize,
+                    new_lines: hunk.new_lines() as usize,
+                    header,
+                    lines: Vec::new(),
+                    side,
+                    file_status: match delta.status() {
+                        git2::Delta::Added => FileDiffStatus::Added,
+                        git2::Delta::Deleted => FileDiffStatus::Deleted,
+                        git2::Delta::Untracked => FileDiffStatus::Untracked,
+                        _ => FileDiffStatus::Modified,
+                    },
+                });
+            }
+
+            let kind = match line.origin() {
+                ' ' => DiffLineKind::Context,
+                '+' => DiffLineKind::Added,
+                '-' => DiffLineKind::Removed,
+                // File headers, hunk headers and EOF newline markers
+                _ => return true,
+            };
+
+            if let Some(current) = hunks.last_mut() {
+                current.lines.push(DiffLine {
+                    kind,
+                    old_lineno: line.old_lineno().map(|n| n as usize),
+                    new_lineno: line.new_lineno().map(|n| n as usize),
+                    content: line.content().to_vec(),
+                });
+            }
+            true
+        })?;
 
         Ok(hunks)
     }
 
+    /// Stage a hunk (or only the selected line indices of it) by applying
+    /// a partial patch to the index.
+    pub async fn stage_hunk(
+        &self,
+        terminal_id: Option<TerminalId>,
+        hunk: &DiffHunk,
+        selection: Option<&[usize]>,
+    ) -> Result<()> {
+        let repo_to_use = self.open_repo_for(terminal_id)?;
+
+        // New and deleted files have no base in the index to patch against
+        match hunk.file_status {
+            FileDiffStatus::Untracked | FileDiffStatus::Added if selection.is_none() => {
+                let mut index = repo_to_use.index()?;
+                index.add_path(&hunk.file)?;
+                index.write()?;
+                return Ok(());
+            }
+            FileDiffStatus::Deleted if selection.is_none() => {
+                let mut index = repo_to_use.index()?;
+                index.remove_path(&hunk.file)?;
+                index.write()?;
+                return Ok(());
+            }
+            FileDiffStatus::Untracked => {
+                anyhow::bail!("Stage the whole file before staging lines of an untracked file");
+            }
+            _ => {}
+        }
+
+        self.apply_hunk_to_index(&repo_to_use, hunk, selection, false)
+    }
+
+    /// Remove a staged hunk (or only the selected lines of it) from the index.
+    pub async fn unstage_hunk(
+        &self,
+        terminal_id: Option<TerminalId>,
+        hunk: &DiffHunk,
+        selection: Option<&[usize]>,
+    ) -> Result<()> {
+        let repo_to_use = self.open_repo_for(terminal_id)?;
+
+        if hunk.file_status == FileDiffStatus::Added && selection.is_none() {
+            // Newly added file: drop it from the index entirely
+            let mut index = repo_to_use.index()?;
+            index.remove_path(&hunk.file)?;
+            index.write()?;
+            return Ok(());
+        }
+
+        self.apply_hunk_to_index(&repo_to_use, hunk, selection, true)
+    }
+
+    fn apply_hunk_to_index(
+        &self,
+        repo: &Repository,
+        hunk: &DiffHunk,
+        selection: Option<&[usize]>,
+        reverse: bool,
+    ) -> Result<()> {
+        let patch = hunk
+            .to_patch(selection, reverse)
+            .ok_or_else(|| anyhow::anyhow!("No changed lines selected"))?;
+
+        tracing::debug!("Applying patch to index:\n{}", String::from_utf8_lossy(&patch));
+
+        let diff = Diff::from_buffer(&patch)?;
+        repo.apply(&diff, ApplyLocation::Index, None)?;
+        Ok(())
+    }
+
+    fn main_head(&self) -> Result<Oid> {
+        let repo = self.repo.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Not a git repository"))?;
+        let oid = repo.lock().head()?.peel_to_commit()?.id();
+        Ok(oid)
+    }
+
+    fn diff_for_side(
+        repo: &Repository,
+        side: DiffSide,
+        main: Option<Oid>,
+        context_lines: u32,
+    ) -> Result<Diff<'_>> {
+        let mut diff_options = DiffOptions::new();
+        diff_options.context_lines(context_lines);
+
+        let diff = match side {
+            DiffSide::Unstaged => {
+                diff_options
+                    .include_untracked(true)
+                    .recurse_untracked_dirs(true)
+                    .show_untracked_content(true);
+                repo.diff_index_to_workdir(None, Some(&mut diff_options))?
+            }
+            DiffSide::Staged => {
+                let head = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+                repo.diff_tree_to_index(head.as_ref(), None, Some(&mut diff_options))?
+            }
+            DiffSide::Main => {
+                let main = main.ok_or_else(|| anyhow::anyhow!("Main branch has no commits"))?;
+                let head = repo.head()?.peel_to_commit()?.id();
+                let base = repo.find_commit(repo.merge_base(head, main)?)?.tree()?;
+                diff_options
+                    .include_untracked(true)
+                    .recurse_untracked_dirs(true)
+                    .show_untracked_content(true);
+                repo.diff_tree_to_workdir_with_index(Some(&base), Some(&mut diff_options))?
+            }
+        };
+
+        Ok(diff)
+    }
+
+    pub fn worktree_info(&self, terminal_id: TerminalId) -> Option<WorktreeInfo> {
+        self.worktrees.read().get(&terminal_id).cloned()
+    }
+
+    fn set_merge_status(&self, terminal_id: TerminalId, status: MergeStatus) {
+        self.update_worktree(terminal_id, |info| info.merge_status = status);
+    }
+
+    /// Land a terminal's worktree branch on the main branch (the branch
+    /// checked out in the project directory).
+    pub async fn integrate_worktree(
+        &self,
+        terminal_id: TerminalId,
+        strategy: IntegrationStrategy,
+    ) -> Result<IntegrationOutcome> {
+        let repo = self.repo.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Not a git repository"))?;
+        let info = self.worktree_info(terminal_id)
+            .ok_or_else(|| anyhow::anyhow!("Terminal has no worktree"))?;
+
+        let outcome = {
+            let repo = repo.lock();
+            Self::integrate_locked(&repo, &info, strategy)?
+        };
+
+        match &outcome {
+            IntegrationOutcome::FastForward { commit } | IntegrationOutcome::Committed { commit } => {
+                self.record_activity(Some(terminal_id), GitActivityKind::Merged {
+                    branch: info.branch.clone(),
+                    commit: commit.clone(),
+                });
+            }
+            IntegrationOutcome::UpToDate | IntegrationOutcome::Conflicts(_) => {}
+        }
+
+        match &outcome {
+            IntegrationOutcome::Conflicts(pending) => {
+                self.set_merge_status(terminal_id, MergeStatus::Conflict {
+                    main_branch: pending.main_branch.clone(),
+                    worktree_branch: pending.worktree_branch.clone(),
+                });
+            }
+            _ => self.set_merge_status(terminal_id, MergeStatus::Merged),
+        }
+
+        Ok(outcome)
+    }
+
+    fn integrate_locked(
+        repo: &Repository,
+        info: &WorktreeInfo,
+        strategy: IntegrationStrategy,
+    ) -> Result<IntegrationOutcome> {
+        let main_ref = repo.head()?;
+        if !main_ref.is_branch() {
+            anyhow::bail!("Project checkout is on a detached HEAD");
+        }
+        let main_branch = main_ref.shorthand().unwrap_or("main").to_string();
+        if main_branch == info.branch {
+            anyhow::bail!("Worktree branch is the main branch");
+        }
+
+        ensure_clean(repo, "Project directory")?;
+        ensure_clean(&Repository::open(&info.path)?, "Worktree")?;
+
+        let main_commit = main_ref.peel_to_commit()?;
+        let branch_commit = repo
+            .find_branch(&info.branch, BranchType::Local)?
+            .get()
+            .peel_to_commit()?;
+
+        if main_commit.id() == branch_commit.id()
+            || repo.graph_descendant_of(main_commit.id(), branch_commit.id())?
+        {
+            return Ok(IntegrationOutcome::UpToDate);
+        }
+
+        let can_fast_forward = repo.merge_base(main_commit.id(), branch_commit.id())? == main_commit.id();
+
+        match strategy {
+            IntegrationStrategy::Merge | IntegrationStrategy::Rebase if can_fast_forward => {
+                advance_main(repo, &branch_commit, "rgb: fast-forward")?;
+                Ok(IntegrationOutcome::FastForward { commit: branch_commit.id().to_string() })
+            }
+            IntegrationStrategy::Rebase => {
+                if !rebase_onto(repo, info, &main_commit, &branch_commit)? {
+                    anyhow::bail!("{} moved during the rebase; run it again", info.branch);
+                }
+                let rebased = repo.find_branch(&info.branch, BranchType::Local)?
+                    .get()
+                    .peel_to_commit()?;
+                advance_main(repo, &rebased, "rgb: rebase")?;
+                Ok(IntegrationOutcome::FastForward { commit: rebased.id().to_string() })
+            }
+            IntegrationStrategy::Merge | IntegrationStrategy::Squash => {
+                let mut index = repo.merge_commits(&main_commit, &branch_commit, None)?;
+
+                if index.has_conflicts() {
+                    return Ok(IntegrationOutcome::Conflicts(PendingMerge {
+                        terminal_id: info.terminal_id,
+                        strategy,
+                        main_branch,
+                        worktree_branch: info.branch.clone(),
+                        ours: main_commit.id(),
+                        theirs: branch_commit.id(),
+                        files: collect_conflicts(repo, &index)?,
+                    }));
+                }
+
+                let tree_id = index.write_tree_to(repo)?;
+                let commit = commit_integration(
+                    repo, strategy, &main_branch, &info.branch, tree_id, &main_commit, &branch_commit,
+                )?;
+                Ok(IntegrationOutcome::Committed { commit: commit.to_string() })
+            }
+        }
+    }
+
+    /// Finish a conflicted merge or squash with the user's resolutions
+    pub async fn complete_merge(&self, pending: &PendingMerge) -> Result<String> {
+        let repo = self.repo.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Not a git repository"))?;
+
+        let commit = {
+            let repo = repo.lock();
+
+            let main_commit = repo.head()?.peel_to_commit()?;
+            if main_commit.id() != pending.ours {
+                anyhow::bail!("{} moved since the merge started; run it again", pending.main_branch);
+            }
+            ensure_clean(&repo, "Project directory")?;
+
+            let branch_commit = repo.find_commit(pending.theirs)?;
+            let mut index = repo.merge_commits(&main_commit, &branch_commit, None)?;
+
+            for file in &pending.files {
+                let content = file.resolved_content()?;
+                let template = index
+                    .conflicts()?
+                    .filter_map(|c| c.ok())
+                    .find(|c| {
+                        [&c.our, &c.their, &c.ancestor]
+                            .into_iter()
+                            .flatten()
+                            .any(|e| e.path == path_bytes(&file.path))
+                    })
+                    .and_then(|c| c.our.or(c.their).or(c.ancestor));
+
+                // Drops the conflict stages along with any stage-0 entry
+                index.remove_path(&file.path)?;
+                if let (Some(content), Some(mut entry)) = (content, template) {
+                    entry.id = repo.blob(content.as_bytes())?;
+                    entry.file_size = content.len() as u32;
+                    entry.flags &= !INDEX_ENTRY_STAGE_MASK;
+                    index.add(&entry)?;
+                }
+            }
+
+            if index.has_conflicts() {
+                anyhow::bail!("Merge index still has unresolved conflicts");
+            }
+
+            let tree_id = index.write_tree_to(&repo)?;
+            commit_integration(
+                &repo,
+                pending.strategy,
+                &pending.main_branch,
+                &pending.worktree_branch,
+                tree_id,
+                &main_commit,
+                &branch_commit,
+            )?
+        };
+
+        self.set_merge_status(pending.terminal_id, MergeStatus::Merged);
+        self.record_activity(Some(pending.terminal_id), GitActivityKind::Merged {
+            branch: pending.worktree_branch.clone(),
+            commit: commit.to_string(),
+        });
+        Ok(commit.to_string())
+    }
+
+    /// Open the repository a terminal works in: its worktree if it has one,
+    /// otherwise the project repository.
+    fn open_repo_for(&self, terminal_id: Option<TerminalId>) -> Result<Repository> {
+        let worktree_path = terminal_id
+            .and_then(|tid| self.worktrees.read().get(&tid).map(|info| info.path.clone()));
+
+        Ok(Repository::open(worktree_path.as_deref().unwrap_or(&self.project_dir))?)
+    }
+
     pub async fn commit(
         &self,
         message: &str,
@@ -306,15 +1371,7 @@ impl GitManager {
         let _repo = self.repo.as_ref()
             .ok_or_else(|| anyhow::anyhow!("Not a git repository"))?;
 
-        let repo_to_use = if let Some(tid) = terminal_id {
-            if let Some(info) = self.worktrees.read().get(&tid).cloned() {
-                Repository::open(&info.path)?
-            } else {
-                Repository::open(&self.project_dir)?
-            }
-        } else {
-            Repository::open(&self.project_dir)?
-        };
+        let repo_to_use = self.open_repo_for(terminal_id)?;
 
         // Add files to index
         let mut index = repo_to_use.index()?;
@@ -338,6 +1395,672 @@ impl GitManager {
             &[&parent_commit],
         )?;
 
+        self.record_activity(terminal_id, GitActivityKind::Commit {
+            commit: commit_id.to_string(),
+            summary: summary(message),
+        });
         Ok(commit_id.to_string())
     }
-}
\ No newline at end of file
+
+    fn record_activity(&self, terminal_id: Option<TerminalId>, kind: GitActivityKind) {
+        let mut activity = self.activity.write();
+        activity.push(GitActivity { time: Instant::now(), terminal_id, kind });
+        let excess = activity.len().saturating_sub(MAX_ACTIVITY);
+        activity.drain(..excess);
+    }
+
+    /// Commits and merges made through the manager, oldest first
+    pub fn activity(&self) -> Vec<GitActivity> {
+        self.activity.read().clone()
+    }
+}
+
+/// First line of a commit message
+fn summary(message: &str) -> String {
+    message.lines().next().unwrap_or_default().to_string()
+}
+
+/// Stage bits of `IndexEntry::flags` (GIT_INDEX_ENTRY_STAGEMASK)
+const INDEX_ENTRY_STAGE_MASK: u16 = 0x3000;
+
+fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().as_bytes().to_vec()
+}
+
+/// Refuse to touch a checkout with uncommitted changes to tracked files
+fn ensure_clean(repo: &Repository, what: &str) -> Result<()> {
+    let statuses = repo.statuses(Some(
+        StatusOptions::new()
+            .include_untracked(false)
+            .include_ignored(false),
+    ))?;
+
+    if !statuses.is_empty() {
+        anyhow::bail!("{} has uncommitted changes; commit or stash them first", what);
+    }
+    Ok(())
+}
+
+/// Check out `commit` in the project directory and move the main branch to it.
+/// The checkout runs first so it is computed against the old HEAD.
+fn advance_main(repo: &Repository, commit: &Commit, reflog: &str) -> Result<()> {
+    repo.checkout_tree(
+        commit.as_object(),
+        Some(git2::build::CheckoutBuilder::new().safe()),
+    )?;
+    repo.head()?.set_target(commit.id(), reflog)?;
+    Ok(())
+}
+
+fn commit_integration(
+    repo: &Repository,
+    strategy: IntegrationStrategy,
+    main_branch: &str,
+    worktree_branch: &str,
+    tree_id: Oid,
+    main_commit: &Commit,
+    branch_commit: &Commit,
+) -> Result<Oid> {
+    let tree = repo.find_tree(tree_id)?;
+    let signature = repo.signature()?;
+
+    let (message, parents) = match strategy {
+        IntegrationStrategy::Squash => {
+            let mut message = format!("Squash branch '{}'\n\n", worktree_branch);
+            let mut revwalk = repo.revwalk()?;
+            revwalk.push(branch_commit.id())?;
+            revwalk.hide(main_commit.id())?;
+            revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+            for oid in revwalk {
+                let commit = repo.find_commit(oid?)?;
+                message.push_str(&format!("* {}\n", commit.summary().unwrap_or("")));
+            }
+            (message, vec![main_commit])
+        }
+        _ => (
+            format!("Merge branch '{}' into {}", worktree_branch, main_branch),
+            vec![main_commit, branch_commit],
+        ),
+    };
+
+    let oid = repo.commit(None, &signature, &signature, &message, &tree, &parents)?;
+    advance_main(repo, &repo.find_commit(oid)?, "rgb: merge")?;
+    Ok(oid)
+}
+
+/// A hunk's old start and length and new start and length, as git numbers
+/// them
+type LineHunk = (usize, usize, usize, usize);
+
+/// Each modified file's hunks in a zero-context diff, by old path
+fn line_hunks(diff: &Diff) -> Result<HashMap<PathBuf, Vec<LineHunk>>> {
+    let mut hunks: HashMap<PathBuf, Vec<LineHunk>> = HashMap::new();
+    for (idx, delta) in diff.deltas().enumerate() {
+        let Some(path) = delta.old_file().path() else {
+            continue;
+        };
+        let Some(patch) = git2::Patch::from_diff(diff, idx)? else {
+            continue;
+        };
+        let file = hunks.entry(path.to_path_buf()).or_default();
+        for hunk_idx in 0..patch.num_hunks() {
+            let (hunk, _) = patch.hunk(hunk_idx)?;
+            file.push((
+                hunk.old_start() as usize,
+                hunk.old_lines() as usize,
+                hunk.new_start() as usize,
+                hunk.new_lines() as usize,
+            ));
+        }
+    }
+    Ok(hunks)
+}
+
+/// Where `line` of the old side ends up on the new side of `hunks`; a line
+/// the hunks replaced maps to everything that replaced it
+fn map_line(hunks: &[LineHunk], line: usize) -> std::ops::Range<usize> {
+    let mut shift = 0isize;
+    for &(old_start, old_lines, new_start, new_lines) in hunks {
+        if old_lines == 0 {
+            // A pure insertion follows line `old_start`
+            if line <= old_start {
+                break;
+            }
+        } else if line < old_start {
+            break;
+        } else if line < old_start + old_lines {
+            return new_start..new_start + new_lines.max(1);
+        }
+        shift += new_lines as isize - old_lines as isize;
+    }
+    let line = line.saturating_add_signed(shift);
+    line..line + 1
+}
+
+/// Replay the worktree branch's commits on top of `onto`, moving the branch
+/// and its worktree checkout. Stops without changes on the first conflict;
+/// returns false, leaving the branch alone, if it moved meanwhile.
+fn rebase_onto(
+    repo: &Repository,
+    info: &WorktreeInfo,
+    onto: &Commit,
+    branch_commit: &Commit,
+) -> Result<bool> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(branch_commit.id())?;
+    revwalk.hide(onto.id())?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+
+    let committer = repo.signature()?;
+    let mut head = onto.id();
+
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() > 1 {
+            anyhow::bail!("Cannot rebase merge commit {}; use :merge instead", commit.id());
+        }
+
+        let parent = repo.find_commit(head)?;
+        let mut index = repo.cherrypick_commit(&commit, &parent, 0, None)?;
+        if index.has_conflicts() {
+            anyhow::bail!(
+                "Rebase stopped on conflicts in {} ({}); use :merge to resolve them",
+                &commit.id().to_string()[..8],
+                commit.summary().unwrap_or("")
+            );
+        }
+
+        let tree = repo.find_tree(index.write_tree_to(repo)?)?;
+        head = repo.commit(
+            None,
+            &commit.author(),
+            &committer,
+            commit.message().unwrap_or(""),
+            &tree,
+            &[&parent],
+        )?;
+    }
+
+    let worktree_repo = Repository::open(&info.path)?;
+    let rebased = worktree_repo.find_commit(head)?;
+    let branch_commit = worktree_repo.find_commit(branch_commit.id())?;
+    advance_branch(&worktree_repo, &info.branch, &branch_commit, &rebased, "rgb: rebase")
+}
+
+/// Check out `to` in a worktree and move its branch there from `from`.
+/// The checkout runs first so it is computed against the old HEAD; the
+/// branch only moves if it still points at `from`, and false is returned
+/// otherwise.
+fn advance_branch(
+    worktree_repo: &Repository,
+    branch: &str,
+    from: &Commit,
+    to: &Commit,
+    reflog: &str,
+) -> Result<bool> {
+    let name = format!("refs/heads/{}", branch);
+    if worktree_repo.refname_to_id(&name)? != from.id() {
+        return Ok(false);
+    }
+    worktree_repo.checkout_tree(
+        to.as_object(),
+        Some(git2::build::CheckoutBuilder::new().safe()),
+    )?;
+
+    match worktree_repo.reference_matching(&name, to.id(), true, from.id(), reflog) {
+        Ok(_) => Ok(true),
+        Err(e) if e.code() == git2::ErrorCode::Modified => {
+            restore_checkout(worktree_repo, to)?;
+            Ok(false)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Undo a checkout of `checked_out` that HEAD didn't follow: every file
+/// where the two differ is put back as HEAD has it
+fn restore_checkout(worktree_repo: &Repository, checked_out: &Commit) -> Result<()> {
+    let head = worktree_repo.head()?.peel_to_tree()?;
+    let diff = worktree_repo.diff_tree_to_tree(Some(&checked_out.tree()?), Some(&head), None)?;
+
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.force().remove_untracked(true);
+    let mut touched = false;
+    for delta in diff.deltas() {
+        for path in [delta.old_file().path(), delta.new_file().path()].into_iter().flatten() {
+            checkout.path(path);
+            touched = true;
+        }
+    }
+    // Without paths the checkout would cover the whole tree
+    if touched {
+        worktree_repo.checkout_head(Some(&mut checkout))?;
+    }
+    Ok(())
+}
+
+fn collect_conflicts(repo: &Repository, index: &Index) -> Result<Vec<ConflictFile>> {
+    let read_blob = |entry: &Option<git2::IndexEntry>| -> Result<Option<(String, bool)>> {
+        match entry {
+            Some(entry) => {
+                let blob = repo.find_blob(entry.id)?;
+                Ok(Some((String::from_utf8_lossy(blob.content()).to_string(), blob.is_binary())))
+            }
+            None => Ok(None),
+        }
+    };
+
+    let mut files = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        let path = [&conflict.our, &conflict.their, &conflict.ancestor]
+            .into_iter()
+            .flatten()
+            .next()
+            .map(|e| PathBuf::from(String::from_utf8_lossy(&e.path).to_string()))
+            .unwrap_or_default();
+
+        let base = read_blob(&conflict.ancestor)?;
+        let ours = read_blob(&conflict.our)?;
+        let theirs = read_blob(&conflict.their)?;
+        let is_binary = [&base, &ours, &theirs].into_iter().flatten().any(|(_, binary)| *binary);
+
+        let regions = match (&ours, &theirs) {
+            (Some((ours, _)), Some((theirs, _))) if !is_binary => {
+                let base = base.as_ref().map(|(b, _)| b.as_str()).unwrap_or("");
+                merge::merge_texts(base, ours, theirs)?
+            }
+            _ => merge::whole_file_conflict(
+                base.as_ref().map(|(b, _)| b.as_str()),
+                ours.as_ref().map(|(o, _)| o.as_str()),
+                theirs.as_ref().map(|(t, _)| t.as_str()),
+            ),
+        };
+
+        files.push(ConflictFile { path, regions });
+    }
+
+    Ok(files)
+}
+
+/// `prefix` and `path` as git writes a path in a patch: in double quotes
+/// with C escapes when it has control characters, quotes, backslashes or
+/// non-ASCII bytes
+fn quote_path(prefix: &str, path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let bytes = path.as_os_str().as_bytes();
+    let plain = |b: &u8| (b' '..=b'~').contains(b) && *b != b'"' && *b != b'\\';
+    if bytes.iter().all(plain) {
+        return format!("{}{}", prefix, path.display());
+    }
+    let mut quoted = format!("\"{}", prefix);
+    for &b in bytes {
+        match b {
+            b'\x07' => quoted.push_str("\\a"),
+            b'\x08' => quoted.push_str("\\b"),
+            b'\t' => quoted.push_str("\\t"),
+            b'\n' => quoted.push_str("\\n"),
+            b'\x0b' => quoted.push_str("\\v"),
+            b'\x0c' => quoted.push_str("\\f"),
+            b'\r' => quoted.push_str("\\r"),
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            b' '..=b'~' => quoted.push(b as char),
+            b => quoted.push_str(&format!("\\{:03o}", b)),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Worktree names live in `.git/worktrees` and cannot contain slashes
+fn worktree_name(branch: &str) -> String {
+    branch.replace('/', "-")
+}
+
+/// Resolve `..` lexically, since the worktree root may not exist yet
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            std::path::Component::CurDir => {}
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A repository with a committer and one commit of `files`
+    fn repo_with<P: AsRef<Path>, C: AsRef<[u8]>>(dir: &Path, files: &[(P, C)]) -> Repository {
+        let repo = Repository::init(dir).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        commit_files(&repo, files, "initial");
+        repo
+    }
+
+    fn commit_files<P: AsRef<Path>, C: AsRef<[u8]>>(repo: &Repository, files: &[(P, C)], message: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            std::fs::write(workdir.join(path), content).unwrap();
+            index.add_path(path.as_ref()).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = repo.signature().unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents).unwrap()
+    }
+
+    fn numbered(lines: std::ops::RangeInclusive<usize>) -> String {
+        lines.map(|n| format!("line {}\n", n)).collect()
+    }
+
+    #[test]
+    fn map_line_around_an_insertion() {
+        // Two lines inserted after line 10
+        let hunks = [(10, 0, 11, 2)];
+        assert_eq!(map_line(&hunks, 1), 1..2);
+        assert_eq!(map_line(&hunks, 10), 10..11);
+        assert_eq!(map_line(&hunks, 11), 13..14);
+
+        // Five lines inserted at the top
+        let hunks = [(0, 0, 1, 5)];
+        assert_eq!(map_line(&hunks, 1), 6..7);
+    }
+
+    #[test]
+    fn map_line_around_a_deletion() {
+        // Lines 3 and 4 deleted
+        let hunks = [(3, 2, 2, 0)];
+        assert_eq!(map_line(&hunks, 2), 2..3);
+        // A deleted line maps to where it was
+        assert_eq!(map_line(&hunks, 3), 2..3);
+        assert_eq!(map_line(&hunks, 4), 2..3);
+        assert_eq!(map_line(&hunks, 5), 3..4);
+    }
+
+    #[test]
+    fn map_line_inside_a_replacement() {
+        // Line 5 replaced by three lines, then one line added after 8
+        let hunks = [(5, 1, 5, 3), (8, 0, 11, 1)];
+        assert_eq!(map_line(&hunks, 4), 4..5);
+        assert_eq!(map_line(&hunks, 5), 5..8);
+        assert_eq!(map_line(&hunks, 6), 8..9);
+        assert_eq!(map_line(&hunks, 8), 10..11);
+        assert_eq!(map_line(&hunks, 9), 12..13);
+    }
+
+    #[test]
+    fn map_line_without_hunks_is_identity() {
+        assert_eq!(map_line(&[], 7), 7..8);
+    }
+
+    #[test]
+    fn line_hunks_of_a_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = repo_with(dir.path(), &[("a.txt", &numbered(1..=10))]);
+        let old = repo.head().unwrap().peel_to_tree().unwrap();
+        let edited = numbered(1..=2) + &numbered(5..=5) + "new\n" + &numbered(6..=10) + "end\n";
+        commit_files(&repo, &[("a.txt", &edited)], "edit");
+        let new = repo.head().unwrap().peel_to_tree().unwrap();
+
+        let diff = repo.diff_tree_to_tree(Some(&old), Some(&new), Some(DiffOptions::new().context_lines(0))).unwrap();
+        let hunks = line_hunks(&diff).unwrap();
+        assert_eq!(hunks[Path::new("a.txt")], [(3, 2, 2, 0), (5, 0, 4, 1), (10, 0, 10, 1)]);
+    }
+
+    #[tokio::test]
+    async fn changed_lines_are_compared_in_mains_numbering() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let repo = repo_with(&project, &[("a.txt", &numbered(1..=20))]);
+        let manager = GitManager::new(&project, &GitConfig::default()).unwrap();
+
+        // Forked before main inserted five lines at the top; edits line 10
+        let early = TerminalId::new_v4();
+        let path = manager.create_worktree(early, "early", None).await.unwrap();
+        let edited = numbered(1..=9) + "early\n" + &numbered(11..=20);
+        std::fs::write(path.join("a.txt"), edited).unwrap();
+        manager.commit_all(early, "early edit").unwrap();
+
+        // Also forked before; edits line 3, which main moves to line 8
+        let other = TerminalId::new_v4();
+        let path = manager.create_worktree(other, "other", None).await.unwrap();
+        let edited = numbered(1..=2) + "other
+" + &numbered(4..=20);
+        std::fs::write(path.join("a.txt"), edited).unwrap();
+        manager.commit_all(other, "other edit").unwrap();
+
+        let top: String = (1..=5).map(|n| format!("top {}\n", n)).collect();
+        commit_files(&repo, &[("a.txt", &(top.clone() + &numbered(1..=20)))], "main inserts");
+
+        // Forked after; edits the same line, now line 15, and one of the new lines
+        let late = TerminalId::new_v4();
+        let path = manager.create_worktree(late, "late", None).await.unwrap();
+        let edited = "top 1\ntop 2\nlate top\ntop 4\ntop 5\n".to_string() + &numbered(1..=9) + "late\n" + &numbered(11..=20);
+        std::fs::write(path.join("a.txt"), edited).unwrap();
+        manager.commit_all(late, "late edit").unwrap();
+
+        let early_changes = manager.changed_files(early).unwrap();
+        let other_changes = manager.changed_files(other).unwrap();
+        let late_changes = manager.changed_files(late).unwrap();
+        let lines = |changes: &[ChangedFile]| -> Vec<(usize, usize)> {
+            changes[0].ranges.iter().map(|r| (r.start, r.end)).collect()
+        };
+        assert_eq!(lines(&early_changes), [(15, 16)]);
+        assert_eq!(lines(&other_changes), [(8, 9)]);
+        assert_eq!(lines(&late_changes), [(3, 4), (15, 16)]);
+
+        // Line 3 before main's insertion isn't line 3 after it
+        let detector = crate::monitor::ConflictDetector::new();
+        detector.set_worktree_changes(other, other_changes);
+        detector.set_worktree_changes(late, late_changes.clone());
+        assert_eq!(detector.get_conflicts()[0].kind, crate::monitor::ConflictKind::SameFile);
+
+        let detector = crate::monitor::ConflictDetector::new();
+        detector.set_worktree_changes(early, early_changes);
+        detector.set_worktree_changes(late, late_changes);
+        let conflicts = detector.get_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, crate::monitor::ConflictKind::OverlappingLines);
+    }
+
+    /// What the index holds for `path`, read fresh from disk
+    fn staged(dir: &Path, path: &Path) -> Vec<u8> {
+        let repo = Repository::open(dir).unwrap();
+        let entry = repo.index().unwrap().get_path(path, 0).unwrap();
+        let content = repo.find_blob(entry.id).unwrap().content().to_vec();
+        content
+    }
+
+    /// The one hunk `path` has on `side`
+    async fn hunk_of(manager: &GitManager, side: DiffSide, path: &Path) -> DiffHunk {
+        let mut hunks: Vec<DiffHunk> = manager
+            .get_diff(None, side)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|h| h.file == path)
+            .collect();
+        assert_eq!(hunks.len(), 1, "{}", path.display());
+        hunks.pop().unwrap()
+    }
+
+    /// Indices of the changed lines of `hunk` that mention `text`, in any case
+    fn changed_lines(hunk: &DiffHunk, text: &str) -> Vec<usize> {
+        (0..hunk.lines.len())
+            .filter(|&i| hunk.is_changed_line(i) && hunk.lines[i].text().to_lowercase().contains(text))
+            .collect()
+    }
+
+    const BEFORE: &str = "one\ntwo\nthree\nfour\nfive\nsix\n";
+    const AFTER: &str = "one\nTWO\nthree\nfour\nFIVE\nsix\nseven\n";
+
+    #[tokio::test]
+    async fn staging_whole_hunks() {
+        let dir = tempfile::tempdir().unwrap();
+        repo_with(dir.path(), &[("a.txt", BEFORE)]);
+        let manager = GitManager::new(dir.path(), &GitConfig::default()).unwrap();
+        let path = Path::new("a.txt");
+        std::fs::write(dir.path().join(path), AFTER).unwrap();
+
+        let hunk = hunk_of(&manager, DiffSide::Unstaged, path).await;
+        manager.stage_hunk(None, &hunk, None).await.unwrap();
+        assert_eq!(staged(dir.path(), path), AFTER.as_bytes());
+
+        let hunk = hunk_of(&manager, DiffSide::Staged, path).await;
+        manager.unstage_hunk(None, &hunk, None).await.unwrap();
+        assert_eq!(staged(dir.path(), path), BEFORE.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn staging_selected_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        repo_with(dir.path(), &[("a.txt", BEFORE)]);
+        let manager = GitManager::new(dir.path(), &GitConfig::default()).unwrap();
+        let path = Path::new("a.txt");
+        std::fs::write(dir.path().join(path), AFTER).unwrap();
+
+        // Only the second line's change; the rest stays unstaged
+        let hunk = hunk_of(&manager, DiffSide::Unstaged, path).await;
+        let selection = changed_lines(&hunk, "wo");
+        assert_eq!(selection.len(), 2);
+        manager.stage_hunk(None, &hunk, Some(&selection)).await.unwrap();
+        assert_eq!(staged(dir.path(), path), b"one\nTWO\nthree\nfour\nfive\nsix\n");
+
+        // Then the appended line alone
+        let hunk = hunk_of(&manager, DiffSide::Unstaged, path).await;
+        manager.stage_hunk(None, &hunk, Some(&changed_lines(&hunk, "seven"))).await.unwrap();
+        assert_eq!(staged(dir.path(), path), b"one\nTWO\nthree\nfour\nfive\nsix\nseven\n");
+
+        // Nothing selected makes no patch
+        assert!(hunk.to_patch(Some(&[]), false).is_none());
+    }
+
+    #[tokio::test]
+    async fn unstaging_selected_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        repo_with(dir.path(), &[("a.txt", BEFORE)]);
+        let manager = GitManager::new(dir.path(), &GitConfig::default()).unwrap();
+        let path = Path::new("a.txt");
+        std::fs::write(dir.path().join(path), AFTER).unwrap();
+        let hunk = hunk_of(&manager, DiffSide::Unstaged, path).await;
+        manager.stage_hunk(None, &hunk, None).await.unwrap();
+
+        // Reversing only the fifth line's change and the added line
+        let hunk = hunk_of(&manager, DiffSide::Staged, path).await;
+        let mut selection = changed_lines(&hunk, "ive");
+        selection.extend(changed_lines(&hunk, "seven"));
+        manager.unstage_hunk(None, &hunk, Some(&selection)).await.unwrap();
+        assert_eq!(staged(dir.path(), path), b"one\nTWO\nthree\nfour\nfive\nsix\n");
+    }
+
+    #[tokio::test]
+    async fn staging_keeps_bytes_of_odd_paths_and_content() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let paths = [
+            PathBuf::from("tab\there.txt"),
+            PathBuf::from("say \"hi\".txt"),
+            PathBuf::from("back\\slash.txt"),
+            PathBuf::from("ünïcødé.txt"),
+            PathBuf::from(std::ffi::OsStr::from_bytes(b"latin1-caf\xe9.txt")),
+        ];
+        let before: &[u8] = b"caf\xe9\nline\nlast";
+        let after: &[u8] = b"caf\xe9\nLINE \xff\nlast";
+
+        let dir = tempfile::tempdir().unwrap();
+        let files: Vec<(&PathBuf, &[u8])> = paths.iter().map(|p| (p, before)).collect();
+        repo_with(dir.path(), &files);
+        let manager = GitManager::new(dir.path(), &GitConfig::default()).unwrap();
+
+        for path in &paths {
+            std::fs::write(dir.path().join(path), after).unwrap();
+            let hunk = hunk_of(&manager, DiffSide::Unstaged, path).await;
+            manager.stage_hunk(None, &hunk, None).await.unwrap();
+            assert_eq!(staged(dir.path(), path), after, "{}", path.display());
+
+            let hunk = hunk_of(&manager, DiffSide::Staged, path).await;
+            manager.unstage_hunk(None, &hunk, None).await.unwrap();
+            assert_eq!(staged(dir.path(), path), before, "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn paths_are_quoted_like_git() {
+        use std::os::unix::ffi::OsStrExt;
+
+        assert_eq!(quote_path("a/", Path::new("src/main.rs")), "a/src/main.rs");
+        assert_eq!(quote_path("a/", Path::new("my file.txt")), "a/my file.txt");
+        assert_eq!(quote_path("b/", Path::new("tab\there")), r#""b/tab\there""#);
+        assert_eq!(quote_path("a/", Path::new("say \"hi\"")), r#""a/say \"hi\"""#);
+        assert_eq!(quote_path("a/", Path::new("back\\slash")), r#""a/back\\slash""#);
+        assert_eq!(quote_path("a/", Path::new("é")), r#""a/\303\251""#);
+        assert_eq!(
+            quote_path("a/", Path::new(std::ffi::OsStr::from_bytes(b"caf\xe9\x7f"))),
+            r#""a/caf\351\177""#
+        );
+    }
+
+    #[tokio::test]
+    async fn sync_leaves_a_branch_that_moved_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let repo = repo_with(&project, &[("a.txt", "base\n")]);
+        let manager = GitManager::new(&project, &GitConfig::default()).unwrap();
+
+        let id = TerminalId::new_v4();
+        let path = manager.create_worktree(id, "shell", None).await.unwrap();
+        let worktree_repo = Repository::open(&path).unwrap();
+        let forked = worktree_repo.head().unwrap().peel_to_commit().unwrap();
+
+        commit_files(&repo, &[("b.txt", "main\n")], "main moves on");
+        let main = worktree_repo.find_commit(repo.head().unwrap().peel_to_commit().unwrap().id()).unwrap();
+
+        // The agent commits after the sync read the branch
+        std::fs::write(path.join("a.txt"), "agent\n").unwrap();
+        let agent = manager.commit_all(id, "agent work").unwrap().unwrap();
+
+        let branch = manager.worktree_info(id).unwrap().branch;
+        assert!(!advance_branch(&worktree_repo, &branch, &forked, &main, "test").unwrap());
+
+        // Had it moved during the checkout, the checkout is undone
+        worktree_repo
+            .checkout_tree(main.as_object(), Some(git2::build::CheckoutBuilder::new().safe()))
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(path.join("a.txt")).unwrap(), "base\n");
+        restore_checkout(&worktree_repo, &main).unwrap();
+
+        let head = worktree_repo.head().unwrap().peel_to_commit().unwrap().id();
+        assert_eq!(head.to_string(), agent);
+        assert_eq!(std::fs::read_to_string(path.join("a.txt")).unwrap(), "agent\n");
+        assert!(!path.join("b.txt").exists());
+        assert!(ensure_clean(&worktree_repo, "Worktree").is_ok());
+
+        // Read afresh, the same sync goes through
+        let outcome = manager.sync_worktree(id).unwrap();
+        assert!(matches!(outcome, SyncOutcome::Rebased { commits: 1, onto: 1 }), "{:?}", outcome);
+        assert_eq!(std::fs::read_to_string(path.join("b.txt")).unwrap(), "main\n");
+    }
+}
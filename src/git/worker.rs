@@ -0,0 +1,140 @@
+use super::{ChangedFile, GitManager, SyncOutcome};
+use crate::workspace::TerminalId;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+
+/// Progress and results reported by the background git worker
+#[derive(Debug, Clone)]
+pub enum GitEvent {
+    SyncStarted { worktrees: usize },
+    /// Every worktree of a `SyncStarted` has been reported
+    SyncFinished { worktrees: usize },
+    Synced { terminal_id: TerminalId, branch: String, outcome: SyncOutcome },
+    SyncFailed { terminal_id: TerminalId, branch: String, error: String },
+    /// What every worktree changes against main, for conflict detection
+    ChangesScanned { changes: Vec<(TerminalId, Vec<ChangedFile>)> },
+}
+
+/// Periodically syncs every worktree onto the main branch and scans what
+/// each worktree changes.
+///
+/// git2 calls block, so each step runs on tokio's blocking pool and the UI
+/// loop only ever sees `GitEvent`s.
+pub struct GitWorker {
+    handle: JoinHandle<()>,
+}
+
+impl GitWorker {
+    /// A zero interval disables that part of the work
+    pub fn spawn(
+        git_manager: Arc<GitManager>,
+        sync_interval: Duration,
+        scan_interval: Duration,
+        events: mpsc::UnboundedSender<GitEvent>,
+    ) -> Self {
+        let handle = tokio::spawn(async move {
+            // First run one full interval after startup
+            let mut sync_ticker = ticker(sync_interval);
+            let mut scan_ticker = ticker(scan_interval);
+
+            loop {
+                let keep_running = tokio::select! {
+                    _ = tick(&mut sync_ticker) => sync_all(&git_manager, &events).await,
+                    _ = tick(&mut scan_ticker) => scan_all(&git_manager, &events).await,
+                };
+                if !keep_running {
+                    break;
+                }
+            }
+
+            tracing::debug!("Git worker stopped");
+        });
+
+        Self { handle }
+    }
+}
+
+impl Drop for GitWorker {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+fn ticker(interval: Duration) -> Option<Interval> {
+    if interval.is_zero() {
+        return None;
+    }
+    let mut ticker = tokio::time::interval_at(Instant::now() + interval, interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    Some(ticker)
+}
+
+async fn tick(ticker: &mut Option<Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Returns false once the receiver is gone
+async fn sync_all(git_manager: &Arc<GitManager>, events: &mpsc::UnboundedSender<GitEvent>) -> bool {
+    let terminal_ids = git_manager.worktree_ids();
+    if terminal_ids.is_empty() {
+        return true;
+    }
+    let worktrees = terminal_ids.len();
+    if events.send(GitEvent::SyncStarted { worktrees }).is_err() {
+        return false;
+    }
+
+    for terminal_id in terminal_ids {
+        let branch = git_manager
+            .worktree_info(terminal_id)
+            .map(|w| w.branch)
+            .unwrap_or_default();
+
+        let git = git_manager.clone();
+        let result = tokio::task::spawn_blocking(move || git.sync_worktree(terminal_id)).await;
+
+        let event = match result {
+            Ok(Ok(outcome)) => GitEvent::Synced { terminal_id, branch, outcome },
+            Ok(Err(e)) => GitEvent::SyncFailed { terminal_id, branch, error: e.to_string() },
+            Err(e) => GitEvent::SyncFailed { terminal_id, branch, error: e.to_string() },
+        };
+
+        if events.send(event).is_err() {
+            return false;
+        }
+    }
+    events.send(GitEvent::SyncFinished { worktrees }).is_ok()
+}
+
+async fn scan_all(git_manager: &Arc<GitManager>, events: &mpsc::UnboundedSender<GitEvent>) -> bool {
+    let git = git_manager.clone();
+    let scanned = tokio::task::spawn_blocking(move || {
+        git.worktree_ids()
+            .into_iter()
+            .filter_map(|terminal_id| match git.changed_files(terminal_id) {
+                Ok(files) => Some((terminal_id, files)),
+                Err(e) => {
+                    tracing::debug!("Failed to scan worktree for {}: {}", terminal_id, e);
+                    None
+                }
+            })
+            .collect()
+    })
+    .await;
+
+    match scanned {
+        Ok(changes) => events.send(GitEvent::ChangesScanned { changes }).is_ok(),
+        Err(e) => {
+            tracing::warn!("Worktree scan panicked: {}", e);
+            true
+        }
+    }
+}
@@ -21,7 +21,9 @@ pub enum TileLayout {
 
 pub struct LayoutEngine {
     mode: LayoutMode,
+    #[allow(dead_code)]
     containers: Vec<Container>,
+    #[allow(dead_code)]
     focus_stack: Vec<ContainerId>,
     terminal_positions: HashMap<TerminalId, Rect>,
 }
@@ -53,6 +55,12 @@ pub struct Size {
     pub height: u16,
 }
 
+impl Default for LayoutEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LayoutEngine {
     pub fn new() -> Self {
         Self {
@@ -115,7 +123,7 @@ impl LayoutEngine {
             }
             TileLayout::Grid { cols } => {
                 let cols = *cols.min(&terminals.len()).max(&1);
-                let rows = (terminals.len() + cols - 1) / cols;
+                let rows = terminals.len().div_ceil(cols);
 
                 let row_chunks = Layout::default()
                     .direction(Direction::Vertical)
@@ -246,6 +254,24 @@ impl LayoutEngine {
         self.terminal_positions.iter().map(|(id, rect)| (*id, *rect)).collect()
     }
 
+    /// Names [`apply_layout`](Self::apply_layout) accepts
+    pub const LAYOUT_NAMES: &'static [&'static str] =
+        &["vertical", "horizontal", "grid", "spiral", "floating", "tabbed", "stacked"];
+
+    /// Name of the current layout, as [`apply_layout`](Self::apply_layout)
+    /// takes it
+    pub fn layout_name(&self) -> &'static str {
+        match self.mode {
+            LayoutMode::Tiled(TileLayout::Vertical) => "vertical",
+            LayoutMode::Tiled(TileLayout::Horizontal) => "horizontal",
+            LayoutMode::Tiled(TileLayout::Grid { .. }) => "grid",
+            LayoutMode::Tiled(TileLayout::Spiral) => "spiral",
+            LayoutMode::Floating => "floating",
+            LayoutMode::Tabbed => "tabbed",
+            LayoutMode::Stacked => "stacked",
+        }
+    }
+
     pub fn apply_layout(&mut self, layout_name: &str) -> Result<()> {
         let mode = match layout_name {
             "vertical" => LayoutMode::Tiled(TileLayout::Vertical),
@@ -1,8 +1,11 @@
 pub mod app;
+pub mod command;
 pub mod config;
+pub mod control;
 pub mod git;
 pub mod layout;
 pub mod monitor;
+pub mod player;
 pub mod terminal;
 pub mod ui;
 pub mod workspace;
\ No newline at end of file
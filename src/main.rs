@@ -1,14 +1,6 @@
-mod app;
-mod config;
-mod git;
-mod layout;
-mod monitor;
-mod terminal;
-mod ui;
-mod workspace;
-
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use rgb::{app, config, control, player};
 use std::path::PathBuf;
 use tracing_subscriber::EnvFilter;
 
@@ -31,12 +23,72 @@ struct Args {
     /// Command to execute in new terminal
     #[arg(short = 'e', long)]
     execute: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Call a method of a running rgb over its control socket and print
+    /// the result as JSON
+    #[command(visible_alias = "msg")]
+    Ctl {
+        /// Method to call, e.g. pane.list, pane.send_text or layout.set
+        method: String,
+
+        /// Parameters as key=value; values that are valid JSON are passed
+        /// as JSON, anything else as a string. A single JSON object is
+        /// passed as is.
+        params: Vec<String>,
+
+        /// Socket of the rgb to talk to; defaults to the one this runs
+        /// inside, then the newest running rgb
+        #[arg(short, long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+    },
+
+    /// Replay an asciicast recording; Space pauses, arrows seek and
+    /// change speed, q quits
+    Play {
+        /// Cast file, e.g. one written by :record
+        file: PathBuf,
+
+        /// Playback speed
+        #[arg(short, long, default_value_t = 1.0, value_parser = positive)]
+        speed: f64,
+
+        /// Shorten pauses longer than this many seconds
+        #[arg(short, long, value_name = "SECONDS", value_parser = positive)]
+        idle_limit: Option<f64>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
+    match args.command {
+        Some(Command::Ctl { method, params, socket }) => {
+            match ctl(&method, &params, socket).await {
+                Ok(result) => println!("{}", serde_json::to_string(&result)?),
+                Err(e) => {
+                    eprintln!("rgb ctl {}: {:#}", method, e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        Some(Command::Play { file, speed, idle_limit }) => {
+            if let Err(e) = player::play(&file, speed, idle_limit) {
+                eprintln!("rgb play {}: {:#}", file.display(), e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        None => {}
+    }
+
     // Initialize logging
     let filter = if args.debug {
         EnvFilter::new("debug")
@@ -62,7 +114,7 @@ async fn main() -> Result<()> {
     }
 
     // Load configuration
-    let config = config::load_config(args.config)?;
+    let config = config::load_config(args.config.clone())?;
 
     // Determine project directory
     let project_dir = args.directory
@@ -70,7 +122,7 @@ async fn main() -> Result<()> {
         .unwrap_or_else(|| PathBuf::from("."));
 
     // Create and run the application
-    match app::RgbApp::new(config, project_dir) {
+    match app::RgbApp::new(config, args.config, project_dir) {
         Ok(mut app) => {
             // If execute command is provided, create initial terminal with it
             if let Some(cmd) = args.execute {
@@ -91,3 +143,35 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// `rgb ctl`: send one request and return its result
+async fn ctl(method: &str, params: &[String], socket: Option<PathBuf>) -> Result<serde_json::Value> {
+    let params = match params {
+        [] => serde_json::Value::Null,
+        [object] if object.trim_start().starts_with('{') => serde_json::from_str(object)?,
+        pairs => {
+            let mut map = serde_json::Map::new();
+            for pair in pairs {
+                let Some((key, value)) = pair.split_once('=') else {
+                    anyhow::bail!("Expected key=value, got {}", pair);
+                };
+                let value = serde_json::from_str(value)
+                    .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+                map.insert(key.to_string(), value);
+            }
+            serde_json::Value::Object(map)
+        }
+    };
+
+    let socket = control::resolve_socket(socket)?;
+    control::call(&socket, method, params).await
+}
+
+/// A number of seconds or a speed: finite and above zero
+fn positive(arg: &str) -> Result<f64, String> {
+    match arg.parse::<f64>() {
+        Ok(value) if value.is_finite() && value > 0.0 => Ok(value),
+        Ok(_) => Err("must be a number above 0".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
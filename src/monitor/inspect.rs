@@ -0,0 +1,285 @@
+//! What runs in a pane, for the process inspector: its process tree with
+//! command lines, the foreground job, listening ports and working
+//! directory, and signals for the job or the whole tree. The tree and
+//! ports come from /proc and are empty elsewhere; signals work on any Unix.
+
+use super::process::{cwd, descendants, parent_map};
+use super::resources::process_stat;
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+/// One process of a pane's tree
+#[derive(Debug, Clone)]
+pub struct InspectedProcess {
+    pub pid: u32,
+    /// Levels below the pane's own process
+    pub depth: usize,
+    pub state: char,
+    pub process_group: u32,
+    /// Command line, or the bare name for kernel-hidden ones
+    pub command: String,
+    /// Percent of one CPU since the previous resource sample
+    pub cpu_percent: Option<f64>,
+    pub rss_bytes: u64,
+}
+
+/// A socket a process of the pane listens on
+#[derive(Debug, Clone)]
+pub struct ListeningPort {
+    pub protocol: &'static str,
+    pub address: String,
+    pub port: u16,
+    pub pid: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PaneProcesses {
+    /// The process the pane started
+    pub root: u32,
+    /// Process group in the foreground of the pane's terminal
+    pub foreground: Option<u32>,
+    /// Working directory of the foreground job, or of the root process
+    pub cwd: Option<PathBuf>,
+    /// The tree, each process followed by its children
+    pub processes: Vec<InspectedProcess>,
+    pub ports: Vec<ListeningPort>,
+}
+
+impl PaneProcesses {
+    /// Read the tree under `root`; `cpu` gives each process's CPU use from
+    /// the resource monitor's last samples
+    pub fn inspect(root: u32, foreground: Option<u32>, cpu: impl Fn(u32) -> Option<f64>) -> Self {
+        let parents = parent_map();
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for pid in descendants(root, &parents).into_iter().skip(1) {
+            if let Some(&parent) = parents.get(&pid) {
+                children.entry(parent).or_default().push(pid);
+            }
+        }
+
+        let mut processes = Vec::new();
+        let mut stack = vec![(root, 0)];
+        while let Some((pid, depth)) = stack.pop() {
+            let Some(stat) = process_stat(pid) else {
+                continue;
+            };
+            processes.push(InspectedProcess {
+                pid,
+                depth,
+                state: stat.state,
+                process_group: stat.process_group,
+                command: command_line(pid),
+                cpu_percent: cpu(pid),
+                rss_bytes: stat.rss_bytes,
+            });
+            if let Some(below) = children.get_mut(&pid) {
+                // Popped lowest pid first
+                below.sort_unstable_by(|a, b| b.cmp(a));
+                stack.extend(below.iter().map(|&child| (child, depth + 1)));
+            }
+        }
+
+        let sockets = listening_sockets();
+        let ports = processes
+            .iter()
+            .flat_map(|p| {
+                socket_inodes(p.pid)
+                    .into_iter()
+                    .filter_map(|inode| sockets.get(&inode))
+                    .map(|&(protocol, ref address, port)| ListeningPort {
+                        protocol,
+                        address: address.clone(),
+                        port,
+                        pid: p.pid,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let leader = foreground.filter(|&group| processes.iter().any(|p| p.pid == group));
+        Self {
+            root,
+            foreground,
+            cwd: cwd(leader.unwrap_or(root)),
+            processes,
+            ports,
+        }
+    }
+
+    /// Processes of the foreground job
+    pub fn foreground_job(&self) -> impl Iterator<Item = &InspectedProcess> {
+        self.processes
+            .iter()
+            .filter(move |p| Some(p.process_group) == self.foreground)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Interrupt,
+    Terminate,
+    Kill,
+    Stop,
+    Continue,
+}
+
+impl Signal {
+    pub fn name(self) -> &'static str {
+        match self {
+            Signal::Interrupt => "SIGINT",
+            Signal::Terminate => "SIGTERM",
+            Signal::Kill => "SIGKILL",
+            Signal::Stop => "SIGSTOP",
+            Signal::Continue => "SIGCONT",
+        }
+    }
+
+    /// `INT`, `SIGINT` or `int`
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.to_ascii_uppercase();
+        match name.strip_prefix("SIG").unwrap_or(&name) {
+            "INT" => Some(Signal::Interrupt),
+            "TERM" => Some(Signal::Terminate),
+            "KILL" => Some(Signal::Kill),
+            "STOP" => Some(Signal::Stop),
+            "CONT" => Some(Signal::Continue),
+            _ => None,
+        }
+    }
+
+    fn number(self) -> libc::c_int {
+        match self {
+            Signal::Interrupt => libc::SIGINT,
+            Signal::Terminate => libc::SIGTERM,
+            Signal::Kill => libc::SIGKILL,
+            Signal::Stop => libc::SIGSTOP,
+            Signal::Continue => libc::SIGCONT,
+        }
+    }
+}
+
+/// Signal every process in a process group, as the terminal does for
+/// Ctrl+C
+pub fn signal_group(group: u32, signal: Signal) -> io::Result<()> {
+    // SAFETY: kill has no memory effects; a negative pid names a group
+    if unsafe { libc::kill(-(group as libc::pid_t), signal.number()) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Signal each process, returning how many it reached. Ones that exited
+/// meanwhile are skipped.
+pub fn signal_processes(pids: &[u32], signal: Signal) -> usize {
+    pids.iter()
+        // SAFETY: kill has no memory effects
+        .filter(|&&pid| unsafe { libc::kill(pid as libc::pid_t, signal.number()) } == 0)
+        .count()
+}
+
+#[cfg(target_os = "linux")]
+fn command_line(pid: u32) -> String {
+    let args = std::fs::read(format!("/proc/{}/cmdline", pid)).unwrap_or_default();
+    let line = args
+        .split(|&b| b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(String::from_utf8_lossy)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if !line.is_empty() {
+        return line;
+    }
+    std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        .map(|name| format!("[{}]", name.trim()))
+        .unwrap_or_default()
+}
+
+/// Listening TCP and bound UDP sockets by inode: protocol, address, port
+#[cfg(target_os = "linux")]
+fn listening_sockets() -> HashMap<u64, (&'static str, String, u16)> {
+    // TCP_LISTEN, and UDP sockets that aren't connected anywhere
+    const TABLES: [(&str, &str, &str); 4] = [
+        ("/proc/net/tcp", "tcp", "0A"),
+        ("/proc/net/tcp6", "tcp6", "0A"),
+        ("/proc/net/udp", "udp", "07"),
+        ("/proc/net/udp6", "udp6", "07"),
+    ];
+
+    let mut sockets = HashMap::new();
+    for (path, protocol, listening) in TABLES {
+        let Ok(table) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        for line in table.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (Some(local), Some(&state), Some(inode)) = (fields.get(1), fields.get(3), fields.get(9)) else {
+                continue;
+            };
+            if state != listening {
+                continue;
+            }
+            let Some((address, port)) = local.split_once(':') else {
+                continue;
+            };
+            let (Some(address), Ok(port), Ok(inode)) =
+                (socket_address(address), u16::from_str_radix(port, 16), inode.parse())
+            else {
+                continue;
+            };
+            sockets.insert(inode, (protocol, address, port));
+        }
+    }
+    sockets
+}
+
+/// An address from /proc/net: 32-bit words in hex, each in host byte order
+#[cfg(target_os = "linux")]
+fn socket_address(hex: &str) -> Option<String> {
+    let bytes: Vec<u8> = (0..hex.len())
+        .step_by(8)
+        .map(|i| u32::from_str_radix(hex.get(i..i + 8)?, 16).ok())
+        .collect::<Option<Vec<u32>>>()?
+        .into_iter()
+        .flat_map(u32::to_ne_bytes)
+        .collect();
+    match bytes.len() {
+        4 => Some(std::net::Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]).to_string()),
+        16 => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(format!("[{}]", std::net::Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn socket_inodes(pid: u32) -> Vec<u64> {
+    let Ok(entries) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| std::fs::read_link(entry.path()).ok())
+        .filter_map(|target| {
+            let target = target.to_str()?;
+            target.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn command_line(_pid: u32) -> String {
+    String::new()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn listening_sockets() -> HashMap<u64, (&'static str, String, u16)> {
+    HashMap::new()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn socket_inodes(_pid: u32) -> Vec<u64> {
+    Vec::new()
+}
@@ -1,24 +1,61 @@
+mod inspect;
+mod process;
+mod resources;
+
+use crate::git::ChangedFile;
 use crate::workspace::TerminalId;
 use anyhow::Result;
-use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use parking_lot::RwLock;
+use git2::Repository;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, DebouncedEvent, Debouncer, FileIdMap};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
+use tokio::sync::broadcast;
+
+use process::ProcessSnapshot;
+
+pub use inspect::{signal_group, signal_processes, InspectedProcess, ListeningPort, PaneProcesses, Signal};
+pub use resources::{PaneUsage, ResourceMonitor, SystemLoad};
+
+/// Changes kept for `get_file_changes`
+const MAX_RECENT_CHANGES: usize = 1000;
 
 pub struct FileTracker {
-    terminal_files: Arc<RwLock<HashMap<TerminalId, HashSet<PathBuf>>>>,
+    ownership: Arc<Ownership>,
     file_changes: Arc<RwLock<Vec<FileChange>>>,
-    conflict_detector: Arc<ConflictDetector>,
-    monitor: Arc<RwLock<Option<FileMonitor>>>,
+    monitor: Mutex<Option<FileMonitor>>,
+    events: broadcast::Sender<FileChange>,
 }
 
+/// Debounced watcher over the project and every worktree root
 pub struct FileMonitor {
-    watcher: RecommendedWatcher,
-    event_rx: mpsc::UnboundedReceiver<FileEvent>,
-    tracked_paths: Arc<RwLock<HashSet<PathBuf>>>,
+    debouncer: Debouncer<RecommendedWatcher, FileIdMap>,
+    roots: Arc<RwLock<Vec<WatchRoot>>>,
+}
+
+/// Which files each terminal works on, shared with the watcher thread.
+/// Files are kept relative to their watch root, so the same file in two
+/// worktrees counts as one.
+struct Ownership {
+    terminal_files: RwLock<HashMap<TerminalId, HashSet<PathBuf>>>,
+    /// Root process of each terminal, for changes in the shared project dir
+    terminal_pids: RwLock<HashMap<TerminalId, u32>>,
+    conflict_detector: ConflictDetector,
+}
+
+/// A watched directory and the repository whose ignore rules apply to it
+struct WatchRoot {
+    path: PathBuf,
+    repo: Option<Mutex<Repository>>,
+    /// Terminal whose worktree this is; `None` for the shared project dir
+    owner: Option<TerminalId>,
+    /// Covered by an enclosing root's recursive watch
+    nested: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -37,29 +74,23 @@ pub enum ChangeType {
     Renamed { from: PathBuf, to: PathBuf },
 }
 
-#[derive(Debug, Clone)]
-pub struct FileEvent {
-    pub path: PathBuf,
-    pub kind: FileEventKind,
-    pub timestamp: Instant,
-}
-
-#[derive(Debug, Clone)]
-pub enum FileEventKind {
-    Create,
-    Modify,
-    Delete,
-}
-
 pub struct ConflictDetector {
     overlaps: Arc<RwLock<HashMap<PathBuf, Vec<TerminalId>>>>,
-    resolution_strategy: ConflictResolution,
+    /// Per-terminal changes against main, from the git worker's scans
+    worktree_changes: Arc<RwLock<HashMap<TerminalId, Vec<ChangedFile>>>>,
+    resolution_strategy: RwLock<ConflictResolution>,
+    /// `get_conflicts` as of the last change to the two maps above
+    conflicts: RwLock<Option<Vec<FileConflict>>>,
 }
 
-#[derive(Debug, Clone)]
+/// How cross-worktree conflicts are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub enum ConflictResolution {
+    /// Badge the panes and files involved
     Warn,
+    /// Also refuse to integrate a branch that will conflict with another
     Block,
+    /// Also sync the other worktrees right after a branch lands on main
     AutoResolve,
 }
 
@@ -67,143 +98,174 @@ impl FileTracker {
     pub fn new_disabled() -> Self {
         // Create a disabled file tracker that doesn't monitor anything
         Self {
-            terminal_files: Arc::new(RwLock::new(HashMap::new())),
+            ownership: Arc::new(Ownership {
+                terminal_files: RwLock::new(HashMap::new()),
+                terminal_pids: RwLock::new(HashMap::new()),
+                conflict_detector: ConflictDetector::new(),
+            }),
             file_changes: Arc::new(RwLock::new(Vec::new())),
-            conflict_detector: Arc::new(ConflictDetector::new()),
-            monitor: Arc::new(RwLock::new(None)),
+            monitor: Mutex::new(None),
+            events: broadcast::channel(1024).0,
         }
     }
 
     pub fn new(project_dir: &Path) -> Result<Self> {
-        let conflict_detector = Arc::new(ConflictDetector::new());
-        let mut tracker = Self {
-            terminal_files: Arc::new(RwLock::new(HashMap::new())),
-            file_changes: Arc::new(RwLock::new(Vec::new())),
-            conflict_detector,
-            monitor: Arc::new(RwLock::new(None)),
-        };
+        let tracker = Self::new_disabled();
 
         // Start monitoring the project directory
-        tracker.start_monitoring(project_dir)?;
+        tracker.start_monitoring()?;
+        tracker.watch_root(project_dir, None)?;
 
         Ok(tracker)
     }
 
-    fn start_monitoring(&mut self, path: &Path) -> Result<()> {
-        let (event_tx, event_rx) = mpsc::unbounded_channel();
-        let event_tx_clone = event_tx.clone();
-
-        let mut watcher = RecommendedWatcher::new(
-            move |res: Result<Event, notify::Error>| {
-                if let Ok(event) = res {
-                    let file_event = match event.kind {
-                        EventKind::Create(_) => Some(FileEventKind::Create),
-                        EventKind::Modify(_) => Some(FileEventKind::Modify),
-                        EventKind::Remove(_) => Some(FileEventKind::Delete),
-                        _ => None,
+    fn start_monitoring(&self) -> Result<()> {
+        let roots: Arc<RwLock<Vec<WatchRoot>>> = Arc::new(RwLock::new(Vec::new()));
+        let handler_roots = roots.clone();
+        let ownership = self.ownership.clone();
+        let file_changes = self.file_changes.clone();
+        let events = self.events.clone();
+
+        // Runs on the debouncer's own thread, once per batch
+        let debouncer = new_debouncer(
+            Duration::from_millis(250),
+            None,
+            move |result: DebounceEventResult| match result {
+                Ok(batch) => {
+                    let mut changes: Vec<FileChange> = {
+                        let roots = handler_roots.read();
+                        batch.iter().filter_map(|event| to_change(event, &roots)).collect()
                     };
+                    if changes.is_empty() {
+                        return;
+                    }
 
-                    if let Some(kind) = file_event {
-                        for path in event.paths {
-                            let _ = event_tx_clone.send(FileEvent {
-                                path,
-                                kind: kind.clone(),
-                                timestamp: Instant::now(),
-                            });
-                        }
+                    ownership.attribute(&mut changes);
+                    ownership.record(&changes, &handler_roots.read());
+                    {
+                        let mut recent = file_changes.write();
+                        recent.extend(changes.iter().cloned());
+                        let excess = recent.len().saturating_sub(MAX_RECENT_CHANGES);
+                        recent.drain(..excess);
+                    }
+                    for change in changes {
+                        // No subscribers is fine
+                        let _ = events.send(change);
+                    }
+                }
+                Err(errors) => {
+                    for error in errors {
+                        tracing::warn!("File watch error: {}", error);
                     }
                 }
             },
-            Config::default()
-                .with_poll_interval(Duration::from_secs(1))
-                .with_compare_contents(false),
         )?;
 
-        watcher.watch(path, RecursiveMode::Recursive)?;
+        *self.monitor.lock() = Some(FileMonitor { debouncer, roots });
+        Ok(())
+    }
 
-        let monitor = FileMonitor {
-            watcher,
-            event_rx,
-            tracked_paths: Arc::new(RwLock::new(HashSet::new())),
+    /// Watch a directory tree; changes under a terminal's worktree are
+    /// attributed to that terminal
+    pub fn watch_root(&self, path: &Path, owner: Option<TerminalId>) -> Result<()> {
+        let mut monitor = self.monitor.lock();
+        let Some(monitor) = monitor.as_mut() else {
+            return Ok(());
         };
 
-        *self.monitor.write() = Some(monitor);
+        let path = path.canonicalize()?;
+        let mut roots = monitor.roots.write();
+        if let Some(root) = roots.iter_mut().find(|r| r.path == path) {
+            root.owner = owner;
+            return Ok(());
+        }
 
-        // Start processing events
-        self.spawn_event_processor();
+        let nested = roots.iter().any(|r| path.starts_with(&r.path));
+        if !nested {
+            monitor.debouncer.watcher().watch(&path, RecursiveMode::Recursive)?;
+            monitor.debouncer.cache().add_root(&path, RecursiveMode::Recursive);
+        }
 
+        roots.push(WatchRoot {
+            repo: Repository::open(&path).ok().map(Mutex::new),
+            path,
+            owner,
+            nested,
+        });
+        // Longest first so events match their innermost root
+        roots.sort_by_key(|r| std::cmp::Reverse(r.path.components().count()));
         Ok(())
     }
 
-    fn spawn_event_processor(&self) {
-        let file_changes = self.file_changes.clone();
-        let monitor = self.monitor.clone();
-
-        tokio::spawn(async move {
-            loop {
-                if let Some(ref mut mon) = *monitor.write() {
-                    if let Ok(event) = mon.event_rx.try_recv() {
-                        // Process file event
-                        let change = FileChange {
-                            terminal_id: None, // Will be determined by tracking
-                            file_path: event.path,
-                            change_type: match event.kind {
-                                FileEventKind::Create => ChangeType::Created,
-                                FileEventKind::Modify => ChangeType::Modified,
-                                FileEventKind::Delete => ChangeType::Deleted,
-                            },
-                            timestamp: event.timestamp,
-                        };
-
-                        file_changes.write().push(change);
-
-                        // Keep only recent changes (last 1000)
-                        if file_changes.read().len() > 1000 {
-                            let mut changes = file_changes.write();
-                            let drain_count = changes.len().saturating_sub(1000);
-                            changes.drain(0..drain_count);
-                        }
-                    }
-                } else {
-                    break;
-                }
+    pub fn unwatch_root(&self, path: &Path) {
+        let mut monitor = self.monitor.lock();
+        let Some(monitor) = monitor.as_mut() else {
+            return;
+        };
 
-                tokio::time::sleep(Duration::from_millis(100)).await;
-            }
-        });
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let removed: Vec<WatchRoot> = {
+            let mut roots = monitor.roots.write();
+            let (removed, kept) = roots.drain(..).partition(|r| r.path == path);
+            *roots = kept;
+            removed
+        };
+
+        for root in removed.into_iter().filter(|r| !r.nested) {
+            // The watch is already gone if the directory was deleted
+            let _ = monitor.debouncer.watcher().unwatch(&root.path);
+            monitor.debouncer.cache().remove_root(&root.path);
+        }
     }
 
-    pub fn start_tracking_terminal(&self, terminal_id: TerminalId) {
-        self.terminal_files.write().insert(terminal_id, HashSet::new());
+    /// Stream of debounced, ignore-filtered changes
+    pub fn subscribe(&self) -> broadcast::Receiver<FileChange> {
+        self.events.subscribe()
+    }
+
+    /// `pid` is the terminal's root process, used to attribute changes
+    /// outside any worktree
+    pub fn start_tracking_terminal(&self, terminal_id: TerminalId, pid: Option<u32>) {
+        self.ownership.terminal_files.write().insert(terminal_id, HashSet::new());
+        if let Some(pid) = pid {
+            self.ownership.terminal_pids.write().insert(terminal_id, pid);
+        }
     }
 
     pub fn stop_tracking_terminal(&self, terminal_id: TerminalId) {
-        self.terminal_files.write().remove(&terminal_id);
-        self.conflict_detector.remove_terminal(terminal_id);
+        self.ownership.terminal_files.write().remove(&terminal_id);
+        self.ownership.terminal_pids.write().remove(&terminal_id);
+        self.ownership.conflict_detector.remove_terminal(terminal_id);
     }
 
     pub fn track_file(&self, terminal_id: TerminalId, file: PathBuf) {
-        if let Some(files) = self.terminal_files.write().get_mut(&terminal_id) {
-            files.insert(file.clone());
-            self.conflict_detector.add_file_terminal(file, terminal_id);
-        }
+        self.ownership.track(terminal_id, file);
     }
 
     pub fn untrack_file(&self, terminal_id: TerminalId, file: &Path) {
-        if let Some(files) = self.terminal_files.write().get_mut(&terminal_id) {
-            files.remove(file);
-            self.conflict_detector.remove_file_terminal(file, terminal_id);
-        }
+        self.ownership.untrack(terminal_id, file);
     }
 
     pub fn get_terminal_files(&self, terminal_id: TerminalId) -> HashSet<PathBuf> {
-        self.terminal_files
+        self.ownership
+            .terminal_files
             .read()
             .get(&terminal_id)
             .cloned()
             .unwrap_or_default()
     }
 
+    /// Terminals working on each tracked file, by root-relative path
+    pub fn file_owners(&self) -> HashMap<PathBuf, Vec<TerminalId>> {
+        let mut owners: HashMap<PathBuf, Vec<TerminalId>> = HashMap::new();
+        for (&terminal_id, files) in self.ownership.terminal_files.read().iter() {
+            for file in files {
+                owners.entry(file.clone()).or_default().push(terminal_id);
+            }
+        }
+        owners
+    }
+
     pub fn get_file_changes(&self, since: Option<Instant>) -> Vec<FileChange> {
         let changes = self.file_changes.read();
         if let Some(since_time) = since {
@@ -218,13 +280,160 @@ impl FileTracker {
     }
 
     pub fn detect_conflicts(&self) -> Vec<FileConflict> {
-        self.conflict_detector.get_conflicts()
+        self.ownership.conflict_detector.get_conflicts()
     }
 
-    pub fn update(&self) -> Result<()> {
-        // Process any pending file events
-        // This is handled by the background processor
-        Ok(())
+    pub fn set_worktree_changes(&self, terminal_id: TerminalId, files: Vec<ChangedFile>) {
+        self.ownership.conflict_detector.set_worktree_changes(terminal_id, files);
+    }
+
+    pub fn conflict_resolution(&self) -> ConflictResolution {
+        self.ownership.conflict_detector.resolution_strategy()
+    }
+
+    pub fn set_conflict_resolution(&self, strategy: ConflictResolution) {
+        self.ownership.conflict_detector.set_resolution_strategy(strategy);
+    }
+}
+
+impl Ownership {
+    fn track(&self, terminal_id: TerminalId, file: PathBuf) {
+        if let Some(files) = self.terminal_files.write().get_mut(&terminal_id) {
+            if files.insert(file.clone()) {
+                self.conflict_detector.add_file_terminal(file, terminal_id);
+            }
+        }
+    }
+
+    fn untrack(&self, terminal_id: TerminalId, file: &Path) {
+        if let Some(files) = self.terminal_files.write().get_mut(&terminal_id) {
+            if files.remove(file) {
+                self.conflict_detector.remove_file_terminal(file, terminal_id);
+            }
+        }
+    }
+
+    /// Attribute changes outside any worktree by process tree. /proc is
+    /// only read when a batch has such changes.
+    fn attribute(&self, changes: &mut [FileChange]) {
+        if changes.iter().all(|c| c.terminal_id.is_some()) {
+            return;
+        }
+
+        let snapshot = ProcessSnapshot::capture(&self.terminal_pids.read());
+        for change in changes.iter_mut().filter(|c| c.terminal_id.is_none()) {
+            change.terminal_id = snapshot.owner_of(&change.file_path);
+        }
+    }
+
+    fn record(&self, changes: &[FileChange], roots: &[WatchRoot]) {
+        for change in changes {
+            let Some(terminal_id) = change.terminal_id else {
+                continue;
+            };
+            match &change.change_type {
+                ChangeType::Created | ChangeType::Modified => {
+                    if let Some(file) = root_relative(&change.file_path, roots) {
+                        self.track(terminal_id, file);
+                    }
+                }
+                ChangeType::Deleted => {
+                    if let Some(file) = root_relative(&change.file_path, roots) {
+                        self.untrack(terminal_id, &file);
+                    }
+                }
+                ChangeType::Renamed { from, to } => {
+                    if let Some(file) = root_relative(from, roots) {
+                        self.untrack(terminal_id, &file);
+                    }
+                    if let Some(file) = root_relative(to, roots) {
+                        self.track(terminal_id, file);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Map a debounced event to a change, dropping ignored and `.git` paths.
+/// The debouncer already pairs renames into `RenameMode::Both`.
+fn to_change(event: &DebouncedEvent, roots: &[WatchRoot]) -> Option<FileChange> {
+    let visible = |path: &Path| !is_ignored(path, roots);
+    let first = event.paths.first()?;
+
+    let change_type = match event.kind {
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            let (from, to) = (&event.paths[0], &event.paths[1]);
+            match (visible(from), visible(to)) {
+                (true, true) => ChangeType::Renamed { from: from.clone(), to: to.clone() },
+                (false, true) => ChangeType::Created,
+                (true, false) => ChangeType::Deleted,
+                (false, false) => return None,
+            }
+        }
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => ChangeType::Created,
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => ChangeType::Deleted,
+        // Unpaired rename halves from backends that cannot tell which side they are
+        EventKind::Modify(ModifyKind::Name(_)) => {
+            if first.exists() {
+                ChangeType::Created
+            } else {
+                ChangeType::Deleted
+            }
+        }
+        // Directory mtime updates just echo changes to their entries
+        EventKind::Modify(_) if first.is_dir() => return None,
+        EventKind::Modify(_) => ChangeType::Modified,
+        _ => return None,
+    };
+
+    let file_path = match &change_type {
+        ChangeType::Renamed { to, .. } => to.clone(),
+        ChangeType::Created if event.paths.len() == 2 => event.paths[1].clone(),
+        _ => first.clone(),
+    };
+    if !matches!(change_type, ChangeType::Renamed { .. }) && !visible(&file_path) {
+        return None;
+    }
+
+    Some(FileChange {
+        terminal_id: roots
+            .iter()
+            .find(|r| file_path.starts_with(&r.path))
+            .and_then(|r| r.owner),
+        file_path,
+        change_type,
+        timestamp: event.time,
+    })
+}
+
+fn root_relative(path: &Path, roots: &[WatchRoot]) -> Option<PathBuf> {
+    roots
+        .iter()
+        .find_map(|r| path.strip_prefix(&r.path).ok())
+        .map(Path::to_path_buf)
+}
+
+fn is_ignored(path: &Path, roots: &[WatchRoot]) -> bool {
+    if path.components().any(|c| c.as_os_str() == ".git") {
+        return true;
+    }
+
+    let Some(root) = roots.iter().find(|r| path.starts_with(&r.path)) else {
+        return false;
+    };
+    let Some(repo) = &root.repo else {
+        return false;
+    };
+    let Ok(relative) = path.strip_prefix(&root.path) else {
+        return false;
+    };
+    repo.lock().is_path_ignored(relative).unwrap_or(false)
+}
+
+impl Default for ConflictDetector {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -232,7 +441,9 @@ impl ConflictDetector {
     pub fn new() -> Self {
         Self {
             overlaps: Arc::new(RwLock::new(HashMap::new())),
-            resolution_strategy: ConflictResolution::Warn,
+            worktree_changes: Arc::new(RwLock::new(HashMap::new())),
+            resolution_strategy: RwLock::new(ConflictResolution::Warn),
+            conflicts: RwLock::new(None),
         }
     }
 
@@ -240,53 +451,187 @@ impl ConflictDetector {
         self.overlaps
             .write()
             .entry(file)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(terminal_id);
+        self.invalidate();
     }
 
     pub fn remove_file_terminal(&self, file: &Path, terminal_id: TerminalId) {
-        if let Some(terminals) = self.overlaps.write().get_mut(file) {
+        let mut overlaps = self.overlaps.write();
+        if let Some(terminals) = overlaps.get_mut(file) {
             terminals.retain(|&id| id != terminal_id);
             if terminals.is_empty() {
-                self.overlaps.write().remove(file);
+                overlaps.remove(file);
             }
         }
+        drop(overlaps);
+        self.invalidate();
     }
 
     pub fn remove_terminal(&self, terminal_id: TerminalId) {
-        let mut overlaps = self.overlaps.write();
-        overlaps.retain(|_, terminals| {
+        self.overlaps.write().retain(|_, terminals| {
             terminals.retain(|&id| id != terminal_id);
             !terminals.is_empty()
         });
+        self.worktree_changes.write().remove(&terminal_id);
+        self.invalidate();
+    }
+
+    /// Replace what a terminal's worktree changes against main
+    pub fn set_worktree_changes(&self, terminal_id: TerminalId, files: Vec<ChangedFile>) {
+        self.worktree_changes.write().insert(terminal_id, files);
+        self.invalidate();
+    }
+
+    /// Called after changing `overlaps` or `worktree_changes`, with
+    /// neither of them locked
+    fn invalidate(&self) {
+        *self.conflicts.write() = None;
     }
 
+    /// Files touched by more than one terminal, ordered by path. Files
+    /// whose changed line ranges overlap will conflict on merge.
+    ///
+    /// The UI asks several times a frame, so the result is kept until
+    /// ownership or a worktree scan changes.
     pub fn get_conflicts(&self) -> Vec<FileConflict> {
-        self.overlaps
+        // Held while computing so an invalidation can't be overwritten
+        // with a result from before it
+        let mut cached = self.conflicts.write();
+        if let Some(conflicts) = cached.as_ref() {
+            return conflicts.clone();
+        }
+        let conflicts = self.compute_conflicts();
+        *cached = Some(conflicts.clone());
+        conflicts
+    }
+
+    fn compute_conflicts(&self) -> Vec<FileConflict> {
+        let mut conflicts: HashMap<PathBuf, FileConflict> = self
+            .overlaps
             .read()
             .iter()
             .filter(|(_, terminals)| terminals.len() > 1)
-            .map(|(file, terminals)| FileConflict {
-                file: file.clone(),
-                terminal_ids: terminals.clone(),
+            .map(|(file, terminals)| {
+                (file.clone(), FileConflict {
+                    file: file.clone(),
+                    terminal_ids: terminals.clone(),
+                    kind: ConflictKind::SameFile,
+                })
             })
-            .collect()
+            .collect();
+
+        let worktree_changes = self.worktree_changes.read();
+        let mut by_path: HashMap<&Path, Vec<(TerminalId, &ChangedFile)>> = HashMap::new();
+        for (&terminal_id, files) in worktree_changes.iter() {
+            for file in files {
+                by_path.entry(file.path.as_path()).or_default().push((terminal_id, file));
+            }
+        }
+
+        for (path, changes) in by_path.into_iter().filter(|(_, c)| c.len() > 1) {
+            let overlapping = changes.iter().enumerate().any(|(i, (_, a))| {
+                changes[i + 1..].iter().any(|(_, b)| changes_overlap(a, b))
+            });
+
+            let conflict = conflicts.entry(path.to_path_buf()).or_insert_with(|| FileConflict {
+                file: path.to_path_buf(),
+                terminal_ids: Vec::new(),
+                kind: ConflictKind::SameFile,
+            });
+            for (terminal_id, _) in &changes {
+                if !conflict.terminal_ids.contains(terminal_id) {
+                    conflict.terminal_ids.push(*terminal_id);
+                }
+            }
+            if overlapping {
+                conflict.kind = ConflictKind::OverlappingLines;
+            }
+        }
+
+        let mut conflicts: Vec<FileConflict> = conflicts.into_values().collect();
+        conflicts.sort_by(|a, b| a.file.cmp(&b.file));
+        conflicts
+    }
+
+    pub fn resolution_strategy(&self) -> ConflictResolution {
+        *self.resolution_strategy.read()
     }
 
-    pub fn set_resolution_strategy(&mut self, strategy: ConflictResolution) {
-        self.resolution_strategy = strategy;
+    pub fn set_resolution_strategy(&self, strategy: ConflictResolution) {
+        *self.resolution_strategy.write() = strategy;
     }
 }
 
+fn changes_overlap(a: &ChangedFile, b: &ChangedFile) -> bool {
+    a.whole_file
+        || b.whole_file
+        || a.ranges.iter().any(|ra| b.ranges.iter().any(|rb| ra.start < rb.end && rb.start < ra.end))
+}
+
 #[derive(Debug, Clone)]
 pub struct FileConflict {
     pub file: PathBuf,
     pub terminal_ids: Vec<TerminalId>,
+    pub kind: ConflictKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// Several terminals change the file in separate places
+    SameFile,
+    /// Changed line ranges overlap, so merging both will conflict
+    OverlappingLines,
 }
 
 impl Drop for FileMonitor {
     fn drop(&mut self) {
-        // Watcher will be dropped automatically
+        // The debouncer stops its thread and watcher when dropped
         tracing::info!("File monitor stopped");
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A change to `a.txt` of the `[start, end)` line ranges
+    fn changed(ranges: &[(usize, usize)], whole_file: bool) -> ChangedFile {
+        ChangedFile {
+            path: PathBuf::from("a.txt"),
+            ranges: ranges.iter().map(|&(start, end)| start..end).collect(),
+            whole_file,
+        }
+    }
+
+    #[test]
+    fn line_ranges_overlap_when_they_share_a_line() {
+        assert!(changes_overlap(&changed(&[(3, 6)], false), &changed(&[(5, 7)], false)));
+        assert!(changes_overlap(&changed(&[(1, 2), (10, 12)], false), &changed(&[(11, 12)], false)));
+        assert!(!changes_overlap(&changed(&[(3, 5)], false), &changed(&[(5, 7)], false)));
+        assert!(!changes_overlap(&changed(&[], false), &changed(&[(1, 100)], false)));
+    }
+
+    #[test]
+    fn whole_file_changes_overlap_everything() {
+        assert!(changes_overlap(&changed(&[], true), &changed(&[(50, 51)], false)));
+        assert!(changes_overlap(&changed(&[(1, 2)], false), &changed(&[], true)));
+    }
+
+    #[test]
+    fn conflicts_are_cached_until_changes_are_replaced() {
+        let detector = ConflictDetector::new();
+        let (a, b) = (TerminalId::new_v4(), TerminalId::new_v4());
+        detector.set_worktree_changes(a, vec![changed(&[(1, 3)], false)]);
+        detector.set_worktree_changes(b, vec![changed(&[(5, 6)], false)]);
+        assert_eq!(detector.get_conflicts()[0].kind, ConflictKind::SameFile);
+
+        detector.set_worktree_changes(b, vec![changed(&[(2, 3)], false)]);
+        let conflicts = detector.get_conflicts();
+        assert_eq!(conflicts[0].kind, ConflictKind::OverlappingLines);
+        assert_eq!(conflicts[0].terminal_ids.len(), 2);
+
+        detector.set_worktree_changes(b, Vec::new());
+        assert!(detector.get_conflicts().is_empty());
+    }
+}
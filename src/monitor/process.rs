@@ -0,0 +1,142 @@
+//! Process-tree lookups used to attribute file changes in the shared
+//! project directory to the terminal whose processes made them.
+//!
+//! Only Linux exposes what we need (through /proc); elsewhere every lookup
+//! comes back empty and such changes stay unattributed.
+
+use crate::workspace::TerminalId;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Open files and working directories of every terminal's process tree,
+/// taken once per batch of events
+#[derive(Debug, Default)]
+pub struct ProcessSnapshot {
+    terminals: Vec<TerminalProcesses>,
+}
+
+#[derive(Debug)]
+struct TerminalProcesses {
+    terminal_id: TerminalId,
+    open_files: HashSet<PathBuf>,
+    cwds: Vec<PathBuf>,
+}
+
+impl ProcessSnapshot {
+    pub fn capture(terminal_pids: &HashMap<TerminalId, u32>) -> Self {
+        if terminal_pids.is_empty() {
+            return Self::default();
+        }
+
+        let parents = parent_map();
+        let terminals = terminal_pids
+            .iter()
+            .map(|(&terminal_id, &root)| {
+                let pids = descendants(root, &parents);
+                TerminalProcesses {
+                    terminal_id,
+                    open_files: pids.iter().flat_map(|&pid| open_files(pid)).collect(),
+                    cwds: pids.iter().filter_map(|&pid| cwd(pid)).collect(),
+                }
+            })
+            .collect();
+
+        Self { terminals }
+    }
+
+    /// The terminal holding `path` open, or failing that the one whose
+    /// processes work closest above it. Ties stay unattributed.
+    pub fn owner_of(&self, path: &Path) -> Option<TerminalId> {
+        let holders: Vec<TerminalId> = self
+            .terminals
+            .iter()
+            .filter(|t| t.open_files.contains(path))
+            .map(|t| t.terminal_id)
+            .collect();
+        if let [owner] = holders[..] {
+            return Some(owner);
+        }
+
+        let depth = |t: &TerminalProcesses| {
+            t.cwds
+                .iter()
+                .filter(|cwd| path.starts_with(cwd))
+                .map(|cwd| cwd.components().count())
+                .max()
+        };
+        let deepest = self.terminals.iter().filter_map(depth).max()?;
+        let closest: Vec<TerminalId> = self
+            .terminals
+            .iter()
+            .filter(|t| depth(t) == Some(deepest))
+            .map(|t| t.terminal_id)
+            .collect();
+        match closest[..] {
+            [owner] => Some(owner),
+            _ => None,
+        }
+    }
+}
+
+/// `pid` and everything started below it
+pub(super) fn descendants(root: u32, parents: &HashMap<u32, u32>) -> Vec<u32> {
+    let mut pids = vec![root];
+    let mut i = 0;
+    while i < pids.len() {
+        let parent = pids[i];
+        pids.extend(parents.iter().filter(|(_, &p)| p == parent).map(|(&pid, _)| pid));
+        i += 1;
+    }
+    pids
+}
+
+#[cfg(target_os = "linux")]
+pub(super) fn parent_map() -> HashMap<u32, u32> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return HashMap::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let stat = std::fs::read_to_string(entry.path().join("stat")).ok()?;
+            // The command name may contain spaces; fields resume after its ')'
+            let ppid = stat.rsplit_once(')')?.1.split_whitespace().nth(1)?.parse().ok()?;
+            Some((pid, ppid))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn open_files(pid: u32) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| std::fs::read_link(entry.path()).ok())
+        .filter(|target| target.is_absolute())
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+pub(super) fn cwd(pid: u32) -> Option<PathBuf> {
+    std::fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(super) fn parent_map() -> HashMap<u32, u32> {
+    HashMap::new()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_files(_pid: u32) -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(super) fn cwd(_pid: u32) -> Option<PathBuf> {
+    None
+}
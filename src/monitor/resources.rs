@@ -0,0 +1,197 @@
+//! CPU and memory use of each terminal's process tree, and how loaded the
+//! machine is, read from /proc. Elsewhere every reading comes back empty.
+
+use super::process::{descendants, parent_map};
+use crate::workspace::TerminalId;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// What one terminal's processes use
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PaneUsage {
+    /// Percent of one CPU since the last sample; above 100 on several
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    pub processes: usize,
+}
+
+/// Load average and memory of the whole machine
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemLoad {
+    /// One-minute load average
+    pub load: f64,
+    pub cpus: usize,
+    pub memory_total: u64,
+    pub memory_available: u64,
+}
+
+impl SystemLoad {
+    pub fn read() -> Self {
+        let (memory_total, memory_available) = memory();
+        Self {
+            load: load_average(),
+            cpus: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            memory_total,
+            memory_available,
+        }
+    }
+
+    pub fn load_per_cpu(&self) -> f64 {
+        self.load / self.cpus.max(1) as f64
+    }
+
+    pub fn memory_used_percent(&self) -> f64 {
+        if self.memory_total == 0 {
+            return 0.0;
+        }
+        100.0 * (self.memory_total - self.memory_available.min(self.memory_total)) as f64 / self.memory_total as f64
+    }
+}
+
+/// Samples terminals' process trees; CPU use is measured between samples
+#[derive(Debug, Default)]
+pub struct ResourceMonitor {
+    /// CPU ticks each process had used at the last sample
+    ticks: HashMap<u32, u64>,
+    /// Percent of one CPU each process used between the last two samples
+    process_cpu: HashMap<u32, f64>,
+    sampled: Option<Instant>,
+}
+
+impl ResourceMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Usage of each terminal's tree, given its root process. The first
+    /// sample has no CPU figures to compare with and reports none.
+    pub fn sample(&mut self, terminal_pids: &HashMap<TerminalId, u32>) -> HashMap<TerminalId, PaneUsage> {
+        let elapsed = self.sampled.map(|at| at.elapsed().as_secs_f64());
+        self.sampled = Some(Instant::now());
+        let parents = parent_map();
+        let ticks_per_second = ticks_per_second();
+
+        let mut ticks = HashMap::new();
+        let mut process_cpu = HashMap::new();
+        let usage = terminal_pids
+            .iter()
+            .map(|(&terminal_id, &root)| {
+                let mut usage = PaneUsage::default();
+                let mut used = 0;
+                for pid in descendants(root, &parents) {
+                    let Some(process) = process_stat(pid) else {
+                        continue;
+                    };
+                    usage.processes += 1;
+                    usage.rss_bytes += process.rss_bytes;
+                    // A process started since the last sample used all its ticks since
+                    let since = process.ticks - self.ticks.get(&pid).copied().unwrap_or(0).min(process.ticks);
+                    if let Some(elapsed) = elapsed.filter(|e| *e > 0.0) {
+                        process_cpu.insert(pid, 100.0 * since as f64 / ticks_per_second / elapsed);
+                    }
+                    used += since;
+                    ticks.insert(pid, process.ticks);
+                }
+                if let Some(elapsed) = elapsed.filter(|e| *e > 0.0) {
+                    usage.cpu_percent = 100.0 * used as f64 / ticks_per_second / elapsed;
+                }
+                (terminal_id, usage)
+            })
+            .collect();
+        self.ticks = ticks;
+        self.process_cpu = process_cpu;
+        usage
+    }
+
+    /// CPU use of one process of a terminal between the last two samples
+    pub fn process_cpu(&self, pid: u32) -> Option<f64> {
+        self.process_cpu.get(&pid).copied()
+    }
+}
+
+pub(super) struct ProcessStat {
+    /// `R` running, `S` sleeping, `T` stopped, `Z` zombie and so on
+    pub(super) state: char,
+    pub(super) process_group: u32,
+    /// User and system CPU ticks
+    pub(super) ticks: u64,
+    pub(super) rss_bytes: u64,
+}
+
+#[cfg(target_os = "linux")]
+pub(super) fn process_stat(pid: u32) -> Option<ProcessStat> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // Fields resume after the command name's ')' at field 3, the state
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let field = |n: usize| fields.get(n - 3)?.parse::<u64>().ok();
+    Some(ProcessStat {
+        state: fields.first()?.chars().next()?,
+        process_group: field(5)? as u32,
+        ticks: field(14)? + field(15)?,
+        rss_bytes: field(24)? * page_size(),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn ticks_per_second() -> f64 {
+    // SAFETY: sysconf only reads a system constant
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 {
+        ticks as f64
+    } else {
+        100.0
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn page_size() -> u64 {
+    // SAFETY: sysconf only reads a system constant
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 {
+        size as u64
+    } else {
+        4096
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn load_average() -> f64 {
+    std::fs::read_to_string("/proc/loadavg")
+        .ok()
+        .and_then(|text| text.split_whitespace().next()?.parse().ok())
+        .unwrap_or(0.0)
+}
+
+/// Total and available memory in bytes
+#[cfg(target_os = "linux")]
+fn memory() -> (u64, u64) {
+    let Ok(text) = std::fs::read_to_string("/proc/meminfo") else {
+        return (0, 0);
+    };
+    let kilobytes = |key: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(key)?.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    (kilobytes("MemTotal:") * 1024, kilobytes("MemAvailable:") * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(super) fn process_stat(_pid: u32) -> Option<ProcessStat> {
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn ticks_per_second() -> f64 {
+    100.0
+}
+
+#[cfg(not(target_os = "linux"))]
+fn load_average() -> f64 {
+    0.0
+}
+
+#[cfg(not(target_os = "linux"))]
+fn memory() -> (u64, u64) {
+    (0, 0)
+}
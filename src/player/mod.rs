@@ -0,0 +1,237 @@
+//! `rgb play`: replays an asciicast in the terminal it runs in, typically
+//! an rgb pane, with pause, seek and speed controls. The output goes
+//! through an emulator at the recorded size, which follows the cast's
+//! resizes, and its screen is drawn cut to the terminal. Seeking back
+//! resets the emulator and replays everything up to the new position at
+//! once.
+
+use crate::terminal::{Cast, Replay};
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    execute,
+    terminal::{self, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Seconds the arrow keys seek
+const SEEK_STEP: f64 = 5.0;
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 16.0;
+/// Longest wait for a key before the clock is advanced again
+const FRAME: Duration = Duration::from_millis(50);
+
+/// Play `path` at `speed`, shortening pauses to `idle_limit` seconds
+pub fn play(path: &Path, speed: f64, idle_limit: Option<f64>) -> Result<()> {
+    let mut cast = Cast::read(path)?;
+    if let Some(limit) = idle_limit {
+        cast.limit_idle(limit);
+    }
+
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut player = Player::new(cast, speed);
+    let result = player.run();
+    execute!(io::stdout(), LeaveAlternateScreen, cursor::Show)?;
+    disable_raw_mode()?;
+    result
+}
+
+struct Player {
+    cast: Cast,
+    screen: Replay,
+    /// Next event to write
+    next: usize,
+    /// Position in cast seconds
+    clock: f64,
+    speed: f64,
+    paused: bool,
+    out: io::Stdout,
+}
+
+impl Player {
+    fn new(cast: Cast, speed: f64) -> Self {
+        Self {
+            screen: Replay::new((cast.header.width, cast.header.height)),
+            cast,
+            next: 0,
+            clock: 0.0,
+            speed: speed.clamp(MIN_SPEED, MAX_SPEED),
+            paused: false,
+            out: io::stdout(),
+        }
+    }
+
+    fn run(&mut self) -> Result<()> {
+        let mut last_tick = Instant::now();
+        loop {
+            if !self.paused {
+                self.clock += last_tick.elapsed().as_secs_f64() * self.speed;
+                if self.write_until(self.clock) {
+                    self.draw()?;
+                }
+                if self.next >= self.cast.events.len() {
+                    self.clock = self.cast.duration();
+                    self.pause()?;
+                }
+            }
+            last_tick = Instant::now();
+
+            let wait = match self.cast.events.get(self.next) {
+                Some(event) if !self.paused => {
+                    let due = (event.time() - self.clock).max(0.0) / self.speed;
+                    Duration::from_secs_f64(due).min(FRAME)
+                }
+                _ => FRAME,
+            };
+            if !event::poll(wait)? {
+                continue;
+            }
+            match event::read()? {
+                Event::Key(key) if !self.handle_key(key)? => return Ok(()),
+                Event::Resize(..) => self.draw()?,
+                _ => {}
+            }
+        }
+    }
+
+    /// Act on a key; false to quit
+    fn handle_key(&mut self, key: KeyEvent) -> Result<bool> {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(false),
+            KeyCode::Char(' ') => {
+                if self.paused {
+                    self.resume()?;
+                } else {
+                    self.pause()?;
+                }
+            }
+            KeyCode::Right | KeyCode::Char('l') => self.seek(self.clock + SEEK_STEP)?,
+            KeyCode::Left | KeyCode::Char('h') => self.seek(self.clock - SEEK_STEP)?,
+            KeyCode::Home | KeyCode::Char('0') => self.seek(0.0)?,
+            KeyCode::End | KeyCode::Char('$') => self.seek(self.cast.duration())?,
+            // Next event while paused
+            KeyCode::Char('.') if self.paused => {
+                if let Some(event) = self.cast.events.get(self.next) {
+                    let time = event.time();
+                    self.seek(time)?;
+                }
+            }
+            KeyCode::Up | KeyCode::Char('+') | KeyCode::Char('=') => self.set_speed(self.speed * 2.0)?,
+            KeyCode::Down | KeyCode::Char('-') => self.set_speed(self.speed / 2.0)?,
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    /// Feed output and resize events up to `time` to the emulator; true if
+    /// the screen may have changed
+    fn write_until(&mut self, time: f64) -> bool {
+        let mut wrote = false;
+        while let Some(event) = self.cast.events.get(self.next) {
+            if event.time() > time {
+                break;
+            }
+            if event.is_output() {
+                self.screen.feed(event.data().as_bytes());
+                wrote = true;
+            } else if let Some(size) = event.resize() {
+                self.screen.resize(size);
+                wrote = true;
+            }
+            self.next += 1;
+        }
+        wrote
+    }
+
+    /// Draw the emulator's screen, cut to the terminal; rows past the
+    /// recording are cleared
+    fn draw(&mut self) -> Result<()> {
+        let (columns, rows) = terminal::size()?;
+        let mut frame = String::from("\x1b[?25l");
+        let screen = self.screen.rows(columns as usize);
+        for (row, line) in screen.iter().take(rows as usize).enumerate() {
+            frame.push_str(&format!("\x1b[{};1H\x1b[0m{}\x1b[0m\x1b[K", row + 1, line));
+        }
+        if screen.len() < rows as usize {
+            frame.push_str(&format!("\x1b[{};1H\x1b[0J", screen.len() + 1));
+        }
+        if let Some((column, row)) = self.screen.cursor().filter(|&(c, r)| c < columns && r < rows) {
+            frame.push_str(&format!("\x1b[{};{}H\x1b[?25h", row + 1, column + 1));
+        }
+        self.out.write_all(frame.as_bytes())?;
+        if self.paused {
+            self.draw_status()?;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+
+    fn seek(&mut self, time: f64) -> Result<()> {
+        let time = time.clamp(0.0, self.cast.duration());
+        self.clock = time;
+        self.redraw()
+    }
+
+    /// Reset the emulator and replay everything up to the clock
+    fn redraw(&mut self) -> Result<()> {
+        self.screen = Replay::new((self.cast.header.width, self.cast.header.height));
+        self.next = 0;
+        self.write_until(self.clock);
+        self.draw()
+    }
+
+    fn pause(&mut self) -> Result<()> {
+        self.paused = true;
+        self.draw_status()
+    }
+
+    fn resume(&mut self) -> Result<()> {
+        if self.next >= self.cast.events.len() {
+            self.clock = 0.0;
+        }
+        self.paused = false;
+        // The status line covered part of the recording
+        self.redraw()
+    }
+
+    fn set_speed(&mut self, speed: f64) -> Result<()> {
+        self.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        if self.paused {
+            self.draw_status()?;
+        }
+        Ok(())
+    }
+
+    /// Position and controls on the bottom row, shown while paused
+    fn draw_status(&mut self) -> Result<()> {
+        let (columns, rows) = terminal::size()?;
+        let state = if self.next >= self.cast.events.len() { "end" } else { "paused" };
+        let status = format!(
+            " {} {} / {}  x{}  space play  ←/→ seek  +/- speed  q quit",
+            state,
+            clock(self.clock),
+            clock(self.cast.duration()),
+            self.speed
+        );
+        let status: String = status.chars().take(columns as usize).collect();
+        write!(
+            self.out,
+            "\x1b7\x1b[{};1H\x1b[0;7m\x1b[2K{}\x1b[0m\x1b8",
+            rows,
+            status
+        )?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Seconds as `m:ss`
+fn clock(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -0,0 +1,188 @@
+//! A pane's whole history, scrollback and screen, as text for
+//! `:save-output`: plain, or with SGR escapes that reproduce its colours
+//! and attributes when printed to a terminal, like `tmux capture-pane -e`.
+
+use alacritty_terminal::grid::{Dimensions, Grid};
+use alacritty_terminal::index::{Column, Line};
+use alacritty_terminal::term::cell::{Cell, Flags};
+use alacritty_terminal::vte::ansi::{Color, NamedColor};
+
+/// Attributes that have an SGR parameter
+const STYLE_FLAGS: Flags = Flags::BOLD
+    .union(Flags::DIM)
+    .union(Flags::ITALIC)
+    .union(Flags::UNDERLINE)
+    .union(Flags::INVERSE)
+    .union(Flags::HIDDEN)
+    .union(Flags::STRIKEOUT);
+
+/// Every line from the top of the scrollback to the bottom of the screen.
+/// Lines the terminal wrapped are joined back up, and trailing blanks and
+/// empty lines are left out.
+pub(super) fn capture(grid: &Grid<Cell>, ansi: bool) -> String {
+    let columns = grid.columns();
+    let mut out = String::new();
+    let mut style = Style::default();
+
+    let mut line = grid.topmost_line();
+    while line <= grid.bottommost_line() {
+        let row = &grid[line];
+        let wrapped = row[Column(columns - 1)].flags.contains(Flags::WRAPLINE);
+        // A wrapped row's trailing blanks are real spaces in the middle of its line
+        let end = if wrapped {
+            columns
+        } else {
+            (0..columns)
+                .rposition(|col| !is_blank(&row[Column(col)], ansi))
+                .map_or(0, |col| col + 1)
+        };
+
+        for col in 0..end {
+            let cell = &row[Column(col)];
+            if cell.flags.intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER) {
+                continue;
+            }
+            if ansi {
+                let cell_style = Style::of(cell);
+                if cell_style != style {
+                    out.push_str(&cell_style.sgr());
+                    style = cell_style;
+                }
+            }
+            out.push(cell.c);
+            out.extend(cell.zerowidth().into_iter().flatten());
+        }
+        if !wrapped {
+            if style != Style::default() {
+                out.push_str("\x1b[0m");
+                style = Style::default();
+            }
+            out.push('\n');
+        }
+        line += 1;
+    }
+    if style != Style::default() {
+        out.push_str("\x1b[0m");
+    }
+
+    // The screen below the last output is empty rows
+    let kept = out.trim_end_matches('\n').len();
+    out.truncate(kept);
+    out.push('\n');
+    out
+}
+
+/// The rows on screen with their escapes, each cut to `columns` cells and
+/// ending in the default style
+pub(super) fn screen(grid: &Grid<Cell>, columns: usize) -> Vec<String> {
+    let columns = columns.min(grid.columns());
+    (0..grid.screen_lines())
+        .map(|line| {
+            let row = &grid[Line(line as i32)];
+            let mut out = String::new();
+            let mut style = Style::default();
+            for col in 0..columns {
+                let cell = &row[Column(col)];
+                if cell.flags.intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER) {
+                    continue;
+                }
+                let cell_style = Style::of(cell);
+                if cell_style != style {
+                    out.push_str(&cell_style.sgr());
+                    style = cell_style;
+                }
+                // Half of a wide character doesn't fit
+                if cell.flags.contains(Flags::WIDE_CHAR) && col + 1 == columns {
+                    out.push(' ');
+                    continue;
+                }
+                out.push(cell.c);
+                out.extend(cell.zerowidth().into_iter().flatten());
+            }
+            if style != Style::default() {
+                out.push_str("\x1b[0m");
+            }
+            out
+        })
+        .collect()
+}
+
+/// A cell that shows nothing; coloured backgrounds count when colours are kept
+fn is_blank(cell: &Cell, ansi: bool) -> bool {
+    let empty = cell.c == ' ' && cell.zerowidth().is_none();
+    if !ansi {
+        return empty;
+    }
+    empty
+        && !cell.flags.intersects(Flags::INVERSE | Flags::UNDERLINE | Flags::STRIKEOUT)
+        && sgr_color(cell.bg, 40).is_none()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    flags: Flags,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            flags: Flags::empty(),
+        }
+    }
+}
+
+impl Style {
+    fn of(cell: &Cell) -> Self {
+        Self {
+            fg: sgr_color(cell.fg, 30).is_some().then_some(cell.fg),
+            bg: sgr_color(cell.bg, 40).is_some().then_some(cell.bg),
+            flags: cell.flags & STYLE_FLAGS,
+        }
+    }
+
+    /// The escape that switches to this style from any other
+    fn sgr(&self) -> String {
+        let mut params = vec!["0".to_string()];
+        for (flag, param) in [
+            (Flags::BOLD, "1"),
+            (Flags::DIM, "2"),
+            (Flags::ITALIC, "3"),
+            (Flags::UNDERLINE, "4"),
+            (Flags::INVERSE, "7"),
+            (Flags::HIDDEN, "8"),
+            (Flags::STRIKEOUT, "9"),
+        ] {
+            if self.flags.contains(flag) {
+                params.push(param.to_string());
+            }
+        }
+        params.extend(self.fg.and_then(|fg| sgr_color(fg, 30)));
+        params.extend(self.bg.and_then(|bg| sgr_color(bg, 40)));
+        format!("\x1b[{}m", params.join(";"))
+    }
+}
+
+/// SGR parameters for a foreground (`base` 30) or background (`base` 40)
+/// colour; none for the terminal's default
+fn sgr_color(color: Color, base: u16) -> Option<String> {
+    match color {
+        Color::Named(named) => {
+            let index = named as u16;
+            match index {
+                0..=7 => Some((base + index).to_string()),
+                8..=15 => Some((base + 60 + index - 8).to_string()),
+                _ => {
+                    let dim = NamedColor::DimBlack as u16..=NamedColor::DimWhite as u16;
+                    dim.contains(&index)
+                        .then(|| (base + index - NamedColor::DimBlack as u16).to_string())
+                }
+            }
+        }
+        Color::Indexed(index) => Some(format!("{};5;{}", base + 8, index)),
+        Color::Spec(rgb) => Some(format!("{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b)),
+    }
+}
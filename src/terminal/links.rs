@@ -0,0 +1,89 @@
+//! Recognizes file references and URLs in terminal output.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// A clickable span of one screen row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputLink {
+    /// Columns the match covers
+    pub columns: Range<usize>,
+    pub target: LinkTarget,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    File {
+        path: PathBuf,
+        line: Option<usize>,
+        column: Option<usize>,
+    },
+    Url(String),
+}
+
+static URL: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\bhttps?://[^\s<>"'`]+"#).unwrap());
+
+/// Python and pytest tracebacks: `File "app/x.py", line 12`
+static PYTHON: Lazy<Regex> = Lazy::new(|| Regex::new(r#"File "([^"]+)", line (\d+)"#).unwrap());
+
+/// tsc: `src/app.ts(12,5): error TS2322`
+static TSC: Lazy<Regex> = Lazy::new(|| Regex::new(r"([\w~./@+-]+\.\w+)\((\d+),(\d+)\)").unwrap());
+
+/// `path:line[:col]`, as printed by rustc (`--> src/main.rs:3:5`), gcc,
+/// grep -n, pytest summaries and most linters
+static PATH_LINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"([\w~./@+-]+):(\d+)(?::(\d+))?").unwrap());
+
+/// Every link on `text`, in column order. Relative paths resolve against
+/// `working_dir` and only count if the file exists there.
+pub fn scan_line(text: &str, working_dir: &Path) -> Vec<OutputLink> {
+    let mut links: Vec<(Range<usize>, LinkTarget)> = Vec::new();
+    let overlaps = |links: &[(Range<usize>, LinkTarget)], range: &Range<usize>| {
+        links.iter().any(|(r, _)| r.start < range.end && range.start < r.end)
+    };
+
+    for m in URL.find_iter(text) {
+        let url = m.as_str().trim_end_matches(['.', ',', ';', ':', ')', ']', '}', '\'']);
+        links.push((m.start()..m.start() + url.len(), LinkTarget::Url(url.to_string())));
+    }
+
+    let file_patterns: [&Regex; 3] = [&PYTHON, &TSC, &PATH_LINE];
+    for pattern in file_patterns {
+        for caps in pattern.captures_iter(text) {
+            let whole = caps.get(0).expect("group 0 always matches");
+            let range = whole.start()..whole.end();
+            if overlaps(&links, &range) {
+                continue;
+            }
+
+            let Some(path) = resolve(&caps[1], working_dir) else {
+                continue;
+            };
+            let number = |i: usize| caps.get(i).and_then(|m| m.as_str().parse().ok());
+            links.push((range, LinkTarget::File {
+                path,
+                line: number(2),
+                column: number(3),
+            }));
+        }
+    }
+
+    links.sort_by_key(|(range, _)| range.start);
+    links
+        .into_iter()
+        .map(|(bytes, target)| OutputLink {
+            // Grid rows hold one char per cell, so columns count chars
+            columns: text[..bytes.start].chars().count()..text[..bytes.end].chars().count(),
+            target,
+        })
+        .collect()
+}
+
+fn resolve(raw: &str, working_dir: &Path) -> Option<PathBuf> {
+    let path = match raw.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()?.join(rest),
+        None => working_dir.join(raw),
+    };
+    path.is_file().then_some(path)
+}
@@ -1,19 +1,31 @@
+mod capture;
+mod links;
+mod recording;
+mod replay;
+mod tap;
+
+pub use links::{LinkTarget, OutputLink};
+pub use recording::{Cast, CastEvent, CastHeader, Recorder};
+pub use replay::Replay;
+
 use alacritty_terminal::{
     event::{Event as AlacEvent, EventListener, WindowSize},
-    event_loop::{EventLoop, EventLoopSender, Msg, Notifier},
+    event_loop::{EventLoop, EventLoopSender, Msg},
     grid::{Dimensions, Scroll},
     index::{Column, Line, Point},
     sync::FairMutex,
-    term::{Config, Term},
-    tty::{self, Pty},
+    term::{Config, Term, TermMode},
+    tty,
 };
 use anyhow::Result;
 use crossterm::event::KeyEvent;
 use ratatui::style::Color;
+use tap::{OutputPipe, Taps, TappedPty};
 use std::{
     borrow::Cow,
-    path::Path,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
+    time::Instant,
 };
 
 struct TermSize {
@@ -61,11 +73,29 @@ pub struct TerminalEmulator {
     size: (u16, u16),
     active_files: Vec<String>,
     is_alive: Arc<Mutex<bool>>,
+    /// When the program last wrote anything
+    last_output: Arc<Mutex<Option<Instant>>>,
+    /// When the program last rang the bell
+    last_bell: Arc<Mutex<Option<Instant>>>,
+    /// Recorder and pipe the output is copied to while on
+    taps: Taps,
+    pid: u32,
+    /// Our copy of the PTY master, to ask which job is in the foreground
+    master: Option<std::fs::File>,
+    working_dir: PathBuf,
+    /// Text of each visible row as last scanned, and the links found on it
+    scanned_rows: Vec<String>,
+    links: Vec<Vec<OutputLink>>,
 }
 
+/// Files referenced in output that `get_active_files` remembers
+const MAX_ACTIVE_FILES: usize = 100;
+
 #[derive(Clone)]
 struct EventProxy {
     is_alive: Arc<Mutex<bool>>,
+    last_output: Arc<Mutex<Option<Instant>>>,
+    last_bell: Arc<Mutex<Option<Instant>>>,
 }
 
 impl EventListener for EventProxy {
@@ -113,13 +143,16 @@ impl EventListener for EventProxy {
             }
             AlacEvent::Bell => {
                 tracing::debug!("Terminal bell!");
+                *self.last_bell.lock().unwrap() = Some(Instant::now());
             }
             AlacEvent::ChildExit(_) => {
                 tracing::info!("Child process exit event");
                 *self.is_alive.lock().unwrap() = false;
             }
             AlacEvent::Wakeup => {
+                // Sent after the event loop parses output from the pty
                 tracing::trace!("Wakeup event");
+                *self.last_output.lock().unwrap() = Some(Instant::now());
             }
             AlacEvent::TextAreaSizeRequest(_) => {
                 tracing::trace!("Text area size request");
@@ -132,7 +165,13 @@ impl EventListener for EventProxy {
 }
 
 impl TerminalEmulator {
-    pub fn new(command: &str, working_dir: &Path, size: (u16, u16)) -> Result<Self> {
+    /// `env` adds to the variables the pane's process starts with
+    pub fn new(
+        command: &str,
+        working_dir: &Path,
+        size: (u16, u16),
+        mut env: std::collections::HashMap<String, String>,
+    ) -> Result<Self> {
         let window_size = WindowSize {
             num_lines: size.1,
             num_cols: size.0,
@@ -151,10 +190,8 @@ impl TerminalEmulator {
             } else {
                 let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string());
                 // Force interactive mode for shells
-                let args = if shell.ends_with("zsh") {
-                    vec!["-i".to_string()]  // Interactive mode for zsh
-                } else if shell.ends_with("bash") {
-                    vec!["-i".to_string()]  // Interactive mode for bash
+                let args = if shell.ends_with("zsh") || shell.ends_with("bash") {
+                    vec!["-i".to_string()]  // Interactive mode for zsh/bash
                 } else {
                     vec![]
                 };
@@ -170,7 +207,6 @@ impl TerminalEmulator {
         };
 
         // Set up environment variables for proper terminal operation
-        let mut env = std::collections::HashMap::new();
         env.insert("TERM".to_string(), "xterm-256color".to_string());
 
         // Force interactive shell behavior
@@ -204,12 +240,20 @@ impl TerminalEmulator {
             shell.ends_with("zsh"), shell.ends_with("bash"));
 
         let pty = tty::new(&options, window_size, 0)?;
-        tracing::info!("PTY created successfully - child PID: {:?}", pty.child().id());
+        let pid = pty.child().id();
+        tracing::info!("PTY created successfully - child PID: {:?}", pid);
+        let master = pty.file().try_clone().ok();
+        let taps: Taps = Arc::default();
+        let pty = TappedPty::new(pty, taps.clone())?;
 
         let is_alive = Arc::new(Mutex::new(true));
 
+        let last_output = Arc::new(Mutex::new(None));
+        let last_bell = Arc::new(Mutex::new(None));
         let event_proxy = EventProxy {
             is_alive: is_alive.clone(),
+            last_output: last_output.clone(),
+            last_bell: last_bell.clone(),
         };
 
         let config = Config::default();
@@ -258,6 +302,14 @@ impl TerminalEmulator {
             size,
             active_files: Vec::new(),
             is_alive,
+            last_output,
+            last_bell,
+            taps,
+            pid,
+            master,
+            working_dir: working_dir.to_path_buf(),
+            scanned_rows: Vec::new(),
+            links: Vec::new(),
         })
     }
 
@@ -274,6 +326,19 @@ impl TerminalEmulator {
         Ok(())
     }
 
+    /// Type `text` as a paste: bracketed if the program asked for that, so
+    /// newlines don't submit it line by line, otherwise with newlines as
+    /// Enter
+    pub fn paste(&mut self, text: &str) -> Result<()> {
+        let bracketed = self.term.lock().mode().contains(TermMode::BRACKETED_PASTE);
+        if bracketed {
+            let text = text.replace('\x1b', "");
+            self.write(format!("\x1b[200~{}\x1b[201~", text).as_bytes())
+        } else {
+            self.write(text.replace("\r\n", "\r").replace('\n', "\r").as_bytes())
+        }
+    }
+
     pub fn resize(&mut self, size: (u16, u16)) -> Result<()> {
         if self.size == size {
             return Ok(());
@@ -293,6 +358,10 @@ impl TerminalEmulator {
         self.term.lock().resize(&term_size);
         let _ = self.sender.send(Msg::Resize(window_size));
 
+        if let Some(recorder) = self.taps.lock().unwrap().recorder.as_mut() {
+            recorder.resize(size)?;
+        }
+
         Ok(())
     }
 
@@ -300,6 +369,16 @@ impl TerminalEmulator {
         *self.is_alive.lock().unwrap()
     }
 
+    /// When the program last produced output, if it has
+    pub fn last_output(&self) -> Option<Instant> {
+        *self.last_output.lock().unwrap()
+    }
+
+    /// When the program last rang the bell, if it has
+    pub fn last_bell(&self) -> Option<Instant> {
+        *self.last_bell.lock().unwrap()
+    }
+
     pub fn update(&mut self) -> Result<bool> {
         if !self.is_alive() {
             tracing::trace!("Skipping update for dead terminal");
@@ -354,6 +433,8 @@ impl TerminalEmulator {
 
         drop(term);
 
+        self.scan_links();
+
         // Force a UI update to show any new terminal content
         Ok(true)  // Return true to trigger redraw
     }
@@ -415,6 +496,26 @@ impl TerminalEmulator {
         content
     }
 
+    /// The last `count` non-blank lines of scrollback and screen, oldest first
+    pub fn recent_output(&self, count: usize) -> Vec<String> {
+        let term = self.term.lock();
+        let grid = term.grid();
+
+        let mut lines = Vec::new();
+        let mut line = grid.bottommost_line();
+        while line >= grid.topmost_line() && lines.len() < count {
+            let text: String = (0..grid.columns())
+                .map(|col| grid[Point::new(line, Column(col))].c)
+                .collect();
+            if !text.trim().is_empty() {
+                lines.push(text.trim_end().to_string());
+            }
+            line -= 1;
+        }
+        lines.reverse();
+        lines
+    }
+
     pub fn get_display_colors(&self) -> Vec<Vec<(Color, Color)>> {
         let term = self.term.lock();
         let mut colors = Vec::new();
@@ -456,6 +557,123 @@ impl TerminalEmulator {
         term.scroll_display(scroll);
     }
 
+    /// Rescan visible rows whose text changed for file references and URLs
+    fn scan_links(&mut self) {
+        let rows = self.get_visible_content();
+        self.links.resize(rows.len(), Vec::new());
+        self.scanned_rows.resize(rows.len(), String::new());
+
+        for (index, row) in rows.into_iter().enumerate() {
+            if self.scanned_rows[index] == row {
+                continue;
+            }
+
+            let links = links::scan_line(&row, &self.working_dir);
+            for link in &links {
+                if let LinkTarget::File { path, .. } = &link.target {
+                    let path = path.display().to_string();
+                    self.active_files.retain(|f| *f != path);
+                    self.active_files.push(path);
+                }
+            }
+            let excess = self.active_files.len().saturating_sub(MAX_ACTIVE_FILES);
+            self.active_files.drain(..excess);
+
+            self.links[index] = links;
+            self.scanned_rows[index] = row;
+        }
+    }
+
+    /// The link under a cell of the visible screen
+    pub fn link_at(&self, column: u16, row: u16) -> Option<&OutputLink> {
+        self.links
+            .get(row as usize)?
+            .iter()
+            .find(|link| link.columns.contains(&(column as usize)))
+    }
+
+    /// Every link on screen with its row, top to bottom
+    pub fn visible_links(&self) -> Vec<(u16, &OutputLink)> {
+        self.links
+            .iter()
+            .enumerate()
+            .flat_map(|(row, links)| links.iter().map(move |link| (row as u16, link)))
+            .collect()
+    }
+
+    /// Record output from now on to an asciicast file at `path`, starting
+    /// with what is on screen
+    pub fn start_recording(&mut self, path: &Path, title: &str) -> Result<()> {
+        let mut recorder = Recorder::create(path, self.size, title)?;
+        let screen: Vec<String> = self
+            .get_visible_content()
+            .iter()
+            .map(|line| line.trim_end().to_string())
+            .collect();
+        if screen.iter().any(|line| !line.is_empty()) {
+            let (column, row) = self.get_cursor_position();
+            let snapshot = format!(
+                "\x1b[2J\x1b[H{}\x1b[{};{}H",
+                screen.join("\r\n"),
+                row + 1,
+                column + 1
+            );
+            recorder.output(snapshot.as_bytes())?;
+        }
+        self.taps.lock().unwrap().recorder = Some(recorder);
+        Ok(())
+    }
+
+    /// Stop recording; the path of the finished cast, if there was one
+    pub fn stop_recording(&mut self) -> Option<PathBuf> {
+        let recorder = self.taps.lock().unwrap().recorder.take()?;
+        Some(recorder.path().to_path_buf())
+    }
+
+    pub fn recording_path(&self) -> Option<PathBuf> {
+        self.taps.lock().unwrap().recorder.as_ref().map(|r| r.path().to_path_buf())
+    }
+
+    /// Feed output from now on to a shell command run in the pane's
+    /// directory, replacing any command already fed
+    pub fn pipe_output(&mut self, command: &str) -> Result<()> {
+        let pipe = OutputPipe::spawn(command, &self.working_dir)?;
+        self.taps.lock().unwrap().pipe = Some(pipe);
+        Ok(())
+    }
+
+    /// Stop piping; the command that was fed, if there was one
+    pub fn stop_pipe(&mut self) -> Option<String> {
+        let pipe = self.taps.lock().unwrap().pipe.take()?;
+        Some(pipe.command().to_string())
+    }
+
+    pub fn pipe_command(&self) -> Option<String> {
+        self.taps.lock().unwrap().pipe.as_ref().map(|p| p.command().to_string())
+    }
+
+    /// All output still held, scrollback included, optionally with the
+    /// escapes for its colours and attributes
+    pub fn capture(&self, ansi: bool) -> String {
+        capture::capture(self.term.lock().grid(), ansi)
+    }
+
+    /// Process id of the shell or command running in the terminal
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Process group in the foreground of the pane's terminal: the shell
+    /// at its prompt, or the job it is running
+    pub fn foreground_process_group(&self) -> Option<u32> {
+        use std::os::fd::AsRawFd;
+        let master = self.master.as_ref()?;
+        // SAFETY: the descriptor stays open for as long as `master` lives
+        let group = unsafe { libc::tcgetpgrp(master.as_raw_fd()) };
+        (group > 0).then_some(group as u32)
+    }
+
+    /// Files referenced in recent output, oldest first
     pub fn get_active_files(&self) -> &[String] {
         &self.active_files
     }
@@ -527,17 +745,16 @@ fn convert_alacritty_color(color: alacritty_terminal::vte::ansi::Color) -> Color
                 16..=231 => {
                     // 216 color cube
                     let idx = idx - 16;
-                    let r = ((idx / 36) * 51) as u8;
-                    let g = (((idx % 36) / 6) * 51) as u8;
-                    let b = ((idx % 6) * 51) as u8;
+                    let r = (idx / 36) * 51;
+                    let g = ((idx % 36) / 6) * 51;
+                    let b = (idx % 6) * 51;
                     Color::Rgb(r, g, b)
                 },
                 232..=255 => {
                     // Grayscale
-                    let gray = ((idx - 232) * 10 + 8) as u8;
+                    let gray = (idx - 232) * 10 + 8;
                     Color::Rgb(gray, gray, gray)
                 },
-                _ => Color::Reset,
             }
         },
     }
@@ -549,9 +766,9 @@ fn convert_key_to_bytes(key: KeyEvent) -> Vec<u8> {
     match (key.code, key.modifiers) {
         (KeyCode::Char(c), KeyModifiers::NONE) => c.to_string().into_bytes(),
         (KeyCode::Char(c), KeyModifiers::CONTROL) => {
-            if c >= 'a' && c <= 'z' {
+            if c.is_ascii_lowercase() {
                 vec![(c as u8) - b'a' + 1]
-            } else if c >= 'A' && c <= 'Z' {
+            } else if c.is_ascii_uppercase() {
                 vec![(c as u8) - b'A' + 1]
             } else if c == ' ' {
                 vec![0]  // Ctrl+Space
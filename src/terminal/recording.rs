@@ -0,0 +1,317 @@
+//! Pane output recorded with its timing to asciicast v2 files, the format
+//! of asciinema: a JSON header line, then one `[time, kind, data]` line per
+//! event. The pane's [`Taps`](super::tap::Taps) feed the recorder.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// First line of a cast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CastHeader {
+    pub version: u32,
+    pub width: u16,
+    pub height: u16,
+    /// Unix time the recording started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+}
+
+/// One event: seconds since the start, `o` for output, `i` for input, `r`
+/// for a resize to `COLSxROWS` or `m` for a marker, and its data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CastEvent(pub f64, pub String, pub String);
+
+impl CastEvent {
+    pub fn time(&self) -> f64 {
+        self.0
+    }
+
+    pub fn is_output(&self) -> bool {
+        self.1 == "o"
+    }
+
+    /// Columns and rows of an `r` event
+    pub fn resize(&self) -> Option<(u16, u16)> {
+        if self.1 != "r" {
+            return None;
+        }
+        let (columns, rows) = self.2.split_once('x')?;
+        Some((columns.trim().parse().ok()?, rows.trim().parse().ok()?))
+    }
+
+    pub fn data(&self) -> &str {
+        &self.2
+    }
+}
+
+/// A whole cast, read back for playback
+#[derive(Debug, Clone)]
+pub struct Cast {
+    pub header: CastHeader,
+    pub events: Vec<CastEvent>,
+}
+
+impl Cast {
+    pub fn read(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Cannot open {}", path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+        let header: CastHeader = serde_json::from_str(&lines.next().context("Empty cast file")??)
+            .context("Not an asciicast header")?;
+        if header.version != 2 {
+            anyhow::bail!("Unsupported asciicast version {}", header.version);
+        }
+
+        let mut events = Vec::new();
+        for (number, line) in lines.enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: CastEvent = serde_json::from_str(&line)
+                .with_context(|| format!("Bad event on line {}", number + 2))?;
+            events.push(event);
+        }
+        Ok(Self { header, events })
+    }
+
+    /// Length in seconds
+    pub fn duration(&self) -> f64 {
+        self.events.last().map_or(0.0, CastEvent::time)
+    }
+
+    /// Shorten pauses longer than `limit` seconds to `limit`
+    pub fn limit_idle(&mut self, limit: f64) {
+        let mut removed = 0.0;
+        let mut previous = 0.0;
+        for event in &mut self.events {
+            let gap = event.0 - previous;
+            previous = event.0;
+            if gap > limit {
+                removed += gap - limit;
+            }
+            event.0 -= removed;
+        }
+    }
+}
+
+/// Writes one pane's output to a cast file as it arrives
+pub struct Recorder {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    started: Instant,
+    /// Start of a UTF-8 character split across two reads
+    partial: Vec<u8>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, size: (u16, u16), title: &str) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = File::create(path).with_context(|| format!("Cannot create {}", path.display()))?;
+
+        // Panes always run with this TERM, whatever the host's is
+        let mut env = HashMap::from([("TERM".to_string(), "xterm-256color".to_string())]);
+        if let Ok(shell) = std::env::var("SHELL") {
+            env.insert("SHELL".to_string(), shell);
+        }
+        let header = CastHeader {
+            version: 2,
+            width: size.0,
+            height: size.1,
+            timestamp: Some(chrono::Utc::now().timestamp()),
+            title: Some(title.to_string()),
+            env,
+        };
+
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", serde_json::to_string(&header)?)?;
+        writer.flush()?;
+        Ok(Self {
+            writer,
+            path: path.to_path_buf(),
+            started: Instant::now(),
+            partial: Vec::new(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record bytes the program wrote
+    pub fn output(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let mut data = std::mem::take(&mut self.partial);
+        data.extend_from_slice(bytes);
+
+        // Hold back a character cut off at the end until the rest arrives;
+        // invalid bytes before it are passed on
+        let mut checked = 0;
+        let complete = loop {
+            match std::str::from_utf8(&data[checked..]) {
+                Ok(_) => break data.len(),
+                Err(e) => match e.error_len() {
+                    Some(invalid) => checked += e.valid_up_to() + invalid,
+                    None => break checked + e.valid_up_to(),
+                },
+            }
+        };
+        self.partial = data.split_off(complete);
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.event("o", &String::from_utf8_lossy(&data))
+    }
+
+    pub fn resize(&mut self, size: (u16, u16)) -> io::Result<()> {
+        self.event("r", &format!("{}x{}", size.0, size.1))
+    }
+
+    fn event(&mut self, kind: &str, data: &str) -> io::Result<()> {
+        let event = CastEvent(self.started.elapsed().as_secs_f64(), kind.to_string(), data.to_string());
+        writeln!(self.writer, "{}", serde_json::to_string(&event)?)?;
+        // Flushed per event so a cast can be watched while it is recorded
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(time: f64, data: &str) -> CastEvent {
+        CastEvent(time, "o".to_string(), data.to_string())
+    }
+
+    #[test]
+    fn multibyte_char_split_across_reads_is_held_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("split.cast");
+        let mut recorder = Recorder::create(&path, (80, 24), "split").unwrap();
+
+        let bytes = "añb€".as_bytes();
+        // 'ñ' is two bytes, '€' three; cut inside each
+        recorder.output(&bytes[..2]).unwrap();
+        recorder.output(&bytes[2..5]).unwrap();
+        recorder.output(&bytes[5..6]).unwrap();
+        recorder.output(&bytes[6..]).unwrap();
+
+        let cast = Cast::read(&path).unwrap();
+        let data: Vec<&str> = cast.events.iter().map(CastEvent::data).collect();
+        assert_eq!(data, ["a", "ñb", "€"]);
+    }
+
+    #[test]
+    fn invalid_bytes_are_not_held_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("invalid.cast");
+        let mut recorder = Recorder::create(&path, (80, 24), "invalid").unwrap();
+        recorder.output(b"a\xffb").unwrap();
+        // An invalid byte before a split character doesn't stop the hold-back
+        recorder.output(b"\xfe\xc3").unwrap();
+        recorder.output(b"\xb1").unwrap();
+
+        let cast = Cast::read(&path).unwrap();
+        let data: Vec<&str> = cast.events.iter().map(CastEvent::data).collect();
+        assert_eq!(data, ["a\u{fffd}b", "\u{fffd}", "ñ"]);
+    }
+
+    #[test]
+    fn recorded_cast_reads_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("round.cast");
+        let mut recorder = Recorder::create(&path, (100, 30), "claude: fix \"tests\"").unwrap();
+        assert_eq!(recorder.path(), path);
+        recorder.output(b"$ ls\r\n").unwrap();
+        recorder.resize((120, 40)).unwrap();
+        recorder.output(b"\x1b[1mbold\x1b[0m\n").unwrap();
+        drop(recorder);
+
+        let cast = Cast::read(&path).unwrap();
+        assert_eq!(cast.header.version, 2);
+        assert_eq!((cast.header.width, cast.header.height), (100, 30));
+        assert_eq!(cast.header.title.as_deref(), Some("claude: fix \"tests\""));
+        assert_eq!(cast.header.env["TERM"], "xterm-256color");
+        assert!(cast.header.timestamp.is_some());
+
+        let events = &cast.events;
+        assert_eq!(events.len(), 3);
+        assert!(events[0].is_output());
+        assert_eq!(events[0].data(), "$ ls\r\n");
+        assert_eq!(events[1].resize(), Some((120, 40)));
+        assert!(!events[1].is_output());
+        assert_eq!(events[2].data(), "\x1b[1mbold\x1b[0m\n");
+        assert!(events.windows(2).all(|w| w[0].time() <= w[1].time()));
+    }
+
+    #[test]
+    fn reads_casts_from_asciinema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("asciinema.cast");
+        std::fs::write(&path, concat!(
+            r#"{"version": 2, "width": 80, "height": 24, "idle_time_limit": 2.0}"#, "\n",
+            r#"[0.5, "o", "hi"]"#, "\n",
+            "\n",
+            r#"[1.0, "m", ""]"#, "\n",
+            r#"[1.5, "r", "90x20"]"#, "\n",
+        )).unwrap();
+
+        let cast = Cast::read(&path).unwrap();
+        assert_eq!(cast.header.title, None);
+        assert_eq!(cast.events.len(), 3);
+        assert_eq!(cast.events[2].resize(), Some((90, 20)));
+        assert_eq!(cast.duration(), 1.5);
+    }
+
+    #[test]
+    fn rejects_bad_casts() {
+        let dir = tempfile::tempdir().unwrap();
+        let read = |text: &str| {
+            let path = dir.path().join("bad.cast");
+            std::fs::write(&path, text).unwrap();
+            Cast::read(&path).unwrap_err().to_string()
+        };
+        assert_eq!(read(""), "Empty cast file");
+        assert_eq!(read("[0.1, \"o\", \"x\"]\n"), "Not an asciicast header");
+        assert_eq!(read("{\"version\": 1, \"width\": 80, \"height\": 24}\n"), "Unsupported asciicast version 1");
+        assert_eq!(
+            read("{\"version\": 2, \"width\": 80, \"height\": 24}\n[0.1, \"o\", \"x\"]\n[oops]\n"),
+            "Bad event on line 3"
+        );
+    }
+
+    #[test]
+    fn limit_idle_compresses_long_pauses() {
+        let mut cast = Cast {
+            header: CastHeader {
+                version: 2,
+                width: 80,
+                height: 24,
+                timestamp: None,
+                title: None,
+                env: HashMap::new(),
+            },
+            events: vec![event(0.5, "a"), event(10.5, "b"), event(11.0, "c"), event(14.0, "d")],
+        };
+        cast.limit_idle(2.0);
+
+        let times: Vec<f64> = cast.events.iter().map(CastEvent::time).collect();
+        // 10s and 3s gaps drop to 2s; shorter ones, the first included, stay
+        assert_eq!(times, [0.5, 2.5, 3.0, 5.0]);
+        assert_eq!(cast.duration(), 5.0);
+
+        // A limit longer than every pause changes nothing
+        cast.limit_idle(10.0);
+        let again: Vec<f64> = cast.events.iter().map(CastEvent::time).collect();
+        assert_eq!(again, times);
+    }
+}
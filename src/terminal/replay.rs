@@ -0,0 +1,53 @@
+//! A terminal emulator with no process behind it, for `rgb play`: a cast's
+//! output is fed in at the size it was recorded at, and the screen is read
+//! back out to draw in whatever terminal the player runs in.
+
+use super::{capture, TermSize};
+use alacritty_terminal::{
+    event::VoidListener,
+    term::{Config, Term, TermMode},
+    vte::ansi::Processor,
+};
+
+pub struct Replay {
+    term: Term<VoidListener>,
+    parser: Processor,
+}
+
+impl Replay {
+    pub fn new(size: (u16, u16)) -> Self {
+        // Only the screen is drawn, so no scrollback is kept
+        let config = Config { scrolling_history: 0, ..Config::default() };
+        let term_size = TermSize::new(size.0.max(1) as usize, size.1.max(1) as usize);
+        Self {
+            term: Term::new(config, &term_size, VoidListener),
+            parser: Processor::new(),
+        }
+    }
+
+    pub fn resize(&mut self, size: (u16, u16)) {
+        let term_size = TermSize::new(size.0.max(1) as usize, size.1.max(1) as usize);
+        self.term.resize(&term_size);
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.parser.advance(&mut self.term, *byte);
+        }
+    }
+
+    /// The screen's rows, cut to `columns` cells, with the escapes for
+    /// their colours and attributes
+    pub fn rows(&self, columns: usize) -> Vec<String> {
+        capture::screen(self.term.grid(), columns)
+    }
+
+    /// Column and row of the cursor; `None` while the program hides it
+    pub fn cursor(&self) -> Option<(u16, u16)> {
+        if !self.term.mode().contains(TermMode::SHOW_CURSOR) {
+            return None;
+        }
+        let point = self.term.grid().cursor.point;
+        Some((point.column.0 as u16, point.line.0 as u16))
+    }
+}
@@ -0,0 +1,171 @@
+//! Every pane's PTY is read through [`TappedPty`], which hands what it reads
+//! to the pane's [`Taps`] on its way to the emulator: the `:record`
+//! recorder and the `:pipe-pane` process, while either is set.
+
+use super::recording::Recorder;
+use alacritty_terminal::event::{OnResize, WindowSize};
+use alacritty_terminal::tty::{ChildEvent, EventedPty, EventedReadWrite, Pty};
+use anyhow::{Context, Result};
+use polling::{Event, PollMode, Poller};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+
+/// Where a pane's output goes besides its screen; shared with its PTY reader
+pub type Taps = Arc<Mutex<OutputTaps>>;
+
+#[derive(Default)]
+pub struct OutputTaps {
+    pub recorder: Option<Recorder>,
+    pub pipe: Option<OutputPipe>,
+}
+
+impl OutputTaps {
+    fn output(&mut self, bytes: &[u8]) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(e) = recorder.output(bytes) {
+                tracing::warn!("Stopped recording to {}: {}", recorder.path().display(), e);
+                self.recorder = None;
+            }
+        }
+        if let Some(pipe) = &self.pipe {
+            if !pipe.send(bytes) {
+                tracing::info!("Stopped piping to `{}`: it exited", pipe.command());
+                self.pipe = None;
+            }
+        }
+    }
+}
+
+/// Reads of output queued for a pipe's command before more are dropped
+const PIPE_BACKLOG: usize = 256;
+
+/// A shell command fed a pane's output on its stdin, like tmux's
+/// `pipe-pane`. Output is handed to a thread of its own so a slow reader
+/// never holds up the pane; what it can't keep up with is dropped.
+pub struct OutputPipe {
+    command: String,
+    sender: SyncSender<Vec<u8>>,
+}
+
+impl OutputPipe {
+    pub fn spawn(command: &str, dir: &Path) -> Result<Self> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Cannot run `{}`", command))?;
+        let mut stdin = child.stdin.take().context("No stdin for the pipe")?;
+
+        let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(PIPE_BACKLOG);
+        std::thread::spawn(move || {
+            for bytes in receiver {
+                if stdin.write_all(&bytes).and_then(|_| stdin.flush()).is_err() {
+                    break;
+                }
+            }
+            // Closing stdin tells the command the output has ended
+            drop(stdin);
+            let _ = child.wait();
+        });
+        Ok(Self {
+            command: command.to_string(),
+            sender,
+        })
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Queue bytes for the command, dropping them while its backlog is
+    /// full; false once it has gone
+    fn send(&self, bytes: &[u8]) -> bool {
+        match self.sender.try_send(bytes.to_vec()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                tracing::debug!("`{}` is behind; dropped {} bytes", self.command, bytes.len());
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+/// A pane's PTY, with reads copied to its taps
+pub struct TappedPty {
+    pty: Pty,
+    reader: TapReader,
+}
+
+pub struct TapReader {
+    file: File,
+    taps: Taps,
+}
+
+impl TappedPty {
+    pub fn new(mut pty: Pty, taps: Taps) -> io::Result<Self> {
+        // Another handle on the same PTY; the event loop still polls the original
+        let file = pty.reader().try_clone()?;
+        Ok(Self {
+            pty,
+            reader: TapReader { file, taps },
+        })
+    }
+}
+
+impl Read for TapReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.file.read(buf)?;
+        if read > 0 {
+            self.taps.lock().unwrap().output(&buf[..read]);
+        }
+        Ok(read)
+    }
+}
+
+impl EventedReadWrite for TappedPty {
+    type Reader = TapReader;
+    type Writer = File;
+
+    unsafe fn register(&mut self, poll: &Arc<Poller>, interest: Event, mode: PollMode) -> io::Result<()> {
+        // SAFETY: the PTY is registered as is and outlives its registration
+        // exactly as it would unwrapped
+        unsafe { self.pty.register(poll, interest, mode) }
+    }
+
+    fn reregister(&mut self, poll: &Arc<Poller>, interest: Event, mode: PollMode) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut TapReader {
+        &mut self.reader
+    }
+
+    fn writer(&mut self) -> &mut File {
+        self.pty.writer()
+    }
+}
+
+impl EventedPty for TappedPty {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl OnResize for TappedPty {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.pty.on_resize(window_size)
+    }
+}
@@ -0,0 +1,83 @@
+use crate::git::WorktreeState;
+use crate::workspace::TerminalId;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Asks what to do with a worktree that still holds work when its
+/// terminal is closed
+pub struct ClosePromptComponent {
+    target: Option<(TerminalId, String, WorktreeState)>,
+}
+
+impl Default for ClosePromptComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClosePromptComponent {
+    pub fn new() -> Self {
+        Self { target: None }
+    }
+
+    pub fn open(&mut self, terminal_id: TerminalId, branch: String, state: WorktreeState) {
+        self.target = Some((terminal_id, branch, state));
+    }
+
+    pub fn close(&mut self) -> Option<TerminalId> {
+        self.target.take().map(|(id, _, _)| id)
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.target.is_some()
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        let Some((_, branch, state)) = &self.target else {
+            return;
+        };
+
+        let width = 60.min(area.width);
+        let height = 11.min(area.height);
+        let rect = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        let key = |k: &'static str| Span::styled(k, Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
+        let lines = vec![
+            Line::from(format!("{} still has work in it:", branch)),
+            Line::from(format!("  {} uncommitted file(s)", state.dirty_files)),
+            Line::from(format!("  {} commit(s) not on main", state.unmerged_commits)),
+            Line::from(""),
+            Line::from(vec![key("k"), Span::raw("  keep worktree and branch on disk")]),
+            Line::from(vec![key("s"), Span::raw("  stash changes, remove worktree")]),
+            Line::from(vec![key("c"), Span::raw("  commit changes to the branch, remove worktree")]),
+            Line::from(vec![key("m"), Span::raw("  commit and merge into main")]),
+            Line::from(vec![
+                Span::styled("D", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::raw("  discard everything    "),
+                key("Esc"),
+                Span::raw(" cancel"),
+            ]),
+        ];
+
+        frame.render_widget(Clear, rect);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .title("Close terminal")
+                    .borders(Borders::ALL)
+                    .style(Style::default().fg(Color::Black).bg(Color::White)),
+            ),
+            rect,
+        );
+    }
+}
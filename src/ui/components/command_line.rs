@@ -0,0 +1,300 @@
+use crate::command::{common_prefix, quote, split_partial, Completion, History};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Candidate rows shown under the input at most
+const MAX_CANDIDATE_ROWS: u16 = 4;
+
+/// The `:` command line: an editable input with history and completion
+pub struct CommandLineComponent {
+    buffer: String,
+    /// Byte offset of the cursor in `buffer`
+    cursor: usize,
+    history: History,
+    /// Completions being cycled by repeated Tab: where the word starts,
+    /// the candidates and the one inserted last
+    completion: Option<(usize, Vec<String>, Option<usize>)>,
+}
+
+impl Default for CommandLineComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandLineComponent {
+    pub fn new() -> Self {
+        Self {
+            buffer: String::new(),
+            cursor: 0,
+            history: History::load(),
+            completion: None,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.buffer
+    }
+
+    /// The line up to the cursor, which is what completion looks at
+    pub fn before_cursor(&self) -> &str {
+        &self.buffer[..self.cursor]
+    }
+
+    pub fn clear(&mut self) {
+        self.set(String::new());
+        self.history.reset();
+    }
+
+    /// Take the line to run it, remembering it in the history
+    pub fn submit(&mut self) -> String {
+        let line = std::mem::take(&mut self.buffer);
+        self.history.push(&line);
+        self.cursor = 0;
+        self.completion = None;
+        line
+    }
+
+    fn set(&mut self, text: String) {
+        self.cursor = text.len();
+        self.buffer = text;
+        self.completion = None;
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.buffer.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self.completion = None;
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(c) = self.buffer[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.buffer.remove(self.cursor);
+        }
+        self.completion = None;
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.buffer.len() {
+            self.buffer.remove(self.cursor);
+        }
+        self.completion = None;
+    }
+
+    pub fn left(&mut self) {
+        if let Some(c) = self.buffer[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn right(&mut self) {
+        if let Some(c) = self.buffer[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.buffer.len();
+    }
+
+    /// Delete the word before the cursor, like Ctrl+W in a shell
+    pub fn delete_word(&mut self) {
+        let before = self.buffer[..self.cursor].trim_end();
+        let start = before.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        self.buffer.replace_range(start..self.cursor, "");
+        self.cursor = start;
+        self.completion = None;
+    }
+
+    pub fn kill_to_start(&mut self) {
+        self.buffer.replace_range(..self.cursor, "");
+        self.cursor = 0;
+        self.completion = None;
+    }
+
+    pub fn kill_to_end(&mut self) {
+        self.buffer.truncate(self.cursor);
+        self.completion = None;
+    }
+
+    /// Older history entry starting with what was typed
+    pub fn history_previous(&mut self) {
+        if let Some(entry) = self.history.older(&self.buffer).map(str::to_string) {
+            self.set(entry);
+        }
+    }
+
+    pub fn history_next(&mut self) {
+        if let Some(entry) = self.history.newer().map(str::to_string) {
+            self.set(entry);
+        }
+    }
+
+    /// Whether another Tab should step through the shown candidates rather
+    /// than compute new ones
+    pub fn is_cycling(&self) -> bool {
+        self.completion.as_ref().is_some_and(|(_, candidates, _)| candidates.len() > 1)
+    }
+
+    /// Apply a fresh completion of the word before the cursor: a single
+    /// candidate is inserted, several are narrowed to their common prefix
+    /// and listed for cycling
+    pub fn complete(&mut self, completion: Completion) {
+        let Completion { start, candidates } = completion;
+        if let [only] = candidates.as_slice() {
+            let mut text = quote(only);
+            if !only.ends_with('/') {
+                text.push(' ');
+            }
+            self.replace_word(start, &text);
+            self.completion = None;
+            return;
+        }
+
+        // The word as typed may be quoted; compare what it reads as
+        let prefix = common_prefix(&candidates);
+        let typed = split_partial(&self.buffer[start..self.cursor]).pop().map(|w| w.text).unwrap_or_default();
+        if prefix.len() > typed.len() {
+            self.replace_word(start, &quote(&prefix));
+        }
+        self.completion = Some((start, candidates, None));
+    }
+
+    /// Insert the next (or previous) listed candidate in place of the word
+    pub fn cycle(&mut self, backwards: bool) {
+        let Some((start, candidates, index)) = self.completion.take() else {
+            return;
+        };
+        let count = candidates.len();
+        let next = match (index, backwards) {
+            (None, false) => 0,
+            (None, true) => count - 1,
+            (Some(i), false) => (i + 1) % count,
+            (Some(i), true) => (i + count - 1) % count,
+        };
+        self.replace_word(start, &quote(&candidates[next]));
+        self.completion = Some((start, candidates, Some(next)));
+    }
+
+    fn replace_word(&mut self, start: usize, text: &str) {
+        self.buffer.replace_range(start..self.cursor, text);
+        self.cursor = start + text.len();
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        let width = (area.width * 3 / 5).max(40).min(area.width);
+        let candidates = self.completion.as_ref().map(|(_, c, i)| (c, *i));
+        let candidate_rows = candidates.map_or(0, |(c, _)| {
+            let total: usize = c.iter().map(|c| c.chars().count() + 2).sum();
+            (total as u16).div_ceil(width.saturating_sub(2).max(1)).min(MAX_CANDIDATE_ROWS)
+        });
+        let height = (3 + candidate_rows).min(area.height);
+        let rect = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height,
+        );
+        frame.render_widget(Clear, rect);
+
+        let block = Block::default()
+            .title("Command [Tab: complete, ↑/↓: history]")
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Blue).bg(Color::White));
+
+        let (before, after) = self.buffer.split_at(self.cursor);
+        let mut after = after.chars();
+        let under_cursor = after.next().map_or(" ".to_string(), |c| c.to_string());
+        let mut lines = vec![Line::from(vec![
+            Span::raw(format!(":{}", before)),
+            Span::styled(under_cursor, Style::default().add_modifier(Modifier::REVERSED)),
+            Span::raw(after.as_str().to_string()),
+        ])];
+
+        if let Some((candidates, index)) = candidates {
+            let spans: Vec<Span> = candidates
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    let style = if Some(i) == index {
+                        Style::default().fg(Color::White).bg(Color::Blue)
+                    } else {
+                        Style::default().fg(Color::DarkGray)
+                    };
+                    Span::styled(format!("{}  ", c), style)
+                })
+                .collect();
+            lines.push(Line::from(spans));
+        }
+
+        frame.render_widget(
+            Paragraph::new(lines).block(block).wrap(Wrap { trim: false }),
+            rect,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> CommandLineComponent {
+        let mut line = CommandLineComponent {
+            buffer: String::new(),
+            cursor: 0,
+            history: History::load_from(None),
+            completion: None,
+        };
+        text.chars().for_each(|c| line.insert(c));
+        line
+    }
+
+    fn candidates(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn common_prefix_is_quoted() {
+        let mut line = typed("edit my");
+        line.complete(Completion { start: 5, candidates: candidates(&["my file one.txt", "my file two.txt"]) });
+        assert_eq!(line.text(), "edit 'my file '");
+        assert!(line.is_cycling());
+    }
+
+    #[test]
+    fn quoted_word_is_not_completed_to_a_shorter_prefix() {
+        let mut line = typed("edit 'my file t");
+        line.complete(Completion { start: 5, candidates: candidates(&["my file three.txt", "my file two.txt"]) });
+        assert_eq!(line.text(), "edit 'my file t");
+    }
+
+    #[test]
+    fn single_candidate_is_quoted_and_finished() {
+        let mut line = typed("edit 'my file o");
+        line.complete(Completion { start: 5, candidates: candidates(&["my file one.txt"]) });
+        assert_eq!(line.text(), "edit 'my file one.txt' ");
+        assert!(!line.is_cycling());
+    }
+
+    #[test]
+    fn cycling_inserts_quoted_candidates() {
+        let mut line = typed("edit my");
+        line.complete(Completion { start: 5, candidates: candidates(&["my a", "my b"]) });
+        line.cycle(false);
+        assert_eq!(line.text(), "edit 'my a'");
+        line.cycle(false);
+        assert_eq!(line.text(), "edit 'my b'");
+        line.cycle(true);
+        assert_eq!(line.text(), "edit 'my a'");
+    }
+}
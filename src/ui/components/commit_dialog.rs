@@ -6,6 +6,12 @@ pub struct CommitDialog {
     is_open: bool,
 }
 
+impl Default for CommitDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommitDialog {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,261 @@
+use crate::git::{ConflictHunk, IntegrationStrategy, MergeRegion, PendingMerge, Resolution};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+
+/// Three-way conflict resolver for a pending merge or squash
+pub struct ConflictViewComponent {
+    pending: Option<PendingMerge>,
+    file_idx: usize,
+    conflict_idx: usize,
+}
+
+impl Default for ConflictViewComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConflictViewComponent {
+    pub fn new() -> Self {
+        Self {
+            pending: None,
+            file_idx: 0,
+            conflict_idx: 0,
+        }
+    }
+
+    pub fn open(&mut self, pending: PendingMerge) {
+        self.pending = Some(pending);
+        self.file_idx = 0;
+        self.conflict_idx = 0;
+    }
+
+    pub fn close(&mut self) -> Option<PendingMerge> {
+        self.pending.take()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn pending(&self) -> Option<&PendingMerge> {
+        self.pending.as_ref()
+    }
+
+    fn conflict_count(&self, file_idx: usize) -> usize {
+        self.pending
+            .as_ref()
+            .and_then(|p| p.files.get(file_idx))
+            .map(|f| f.regions.iter().filter(|r| matches!(r, MergeRegion::Conflict(_))).count())
+            .unwrap_or(0)
+    }
+
+    fn current_hunk_mut(&mut self) -> Option<&mut ConflictHunk> {
+        let conflict_idx = self.conflict_idx;
+        self.pending
+            .as_mut()?
+            .files
+            .get_mut(self.file_idx)?
+            .conflicts_mut()
+            .nth(conflict_idx)
+    }
+
+    /// Move to the next conflict, crossing into the next file at the end
+    pub fn next_conflict(&mut self) {
+        let file_count = self.pending.as_ref().map(|p| p.files.len()).unwrap_or(0);
+        if self.conflict_idx + 1 < self.conflict_count(self.file_idx) {
+            self.conflict_idx += 1;
+        } else if self.file_idx + 1 < file_count {
+            self.file_idx += 1;
+            self.conflict_idx = 0;
+        }
+    }
+
+    pub fn previous_conflict(&mut self) {
+        if self.conflict_idx > 0 {
+            self.conflict_idx -= 1;
+        } else if self.file_idx > 0 {
+            self.file_idx -= 1;
+            self.conflict_idx = self.conflict_count(self.file_idx).saturating_sub(1);
+        }
+    }
+
+    pub fn next_file(&mut self) {
+        let file_count = self.pending.as_ref().map(|p| p.files.len()).unwrap_or(0);
+        if self.file_idx + 1 < file_count {
+            self.file_idx += 1;
+            self.conflict_idx = 0;
+        }
+    }
+
+    /// Resolve the current conflict and advance to the next unresolved one
+    pub fn resolve(&mut self, resolution: Resolution) {
+        if let Some(hunk) = self.current_hunk_mut() {
+            hunk.resolution = Some(resolution);
+            self.next_conflict();
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        let Some(pending) = &self.pending else {
+            return;
+        };
+
+        frame.render_widget(Clear, area);
+
+        let action = match pending.strategy {
+            IntegrationStrategy::Squash => "squash",
+            _ => "merge",
+        };
+        let block = Block::default()
+            .title(format!(
+                "Conflicts: {} {} into {} ({} unresolved) [j/k:conflict o:ours t:theirs b:both a:base Enter:commit Esc:abort]",
+                action,
+                pending.worktree_branch,
+                pending.main_branch,
+                pending.unresolved_count(),
+            ))
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Black).bg(Color::White));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
+            .split(inner);
+
+        // File list with unresolved counts
+        let files: Vec<ListItem> = pending
+            .files
+            .iter()
+            .enumerate()
+            .map(|(idx, file)| {
+                let unresolved = file.unresolved_count();
+                let (marker, color) = if unresolved == 0 {
+                    ("✓", Color::Green)
+                } else {
+                    ("✗", Color::Red)
+                };
+                let style = if idx == self.file_idx {
+                    Style::default().bg(Color::LightBlue).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{} ", marker), Style::default().fg(color)),
+                    Span::raw(file.path.display().to_string()),
+                ]))
+                .style(style)
+            })
+            .collect();
+        frame.render_widget(
+            List::new(files).block(Block::default().borders(Borders::RIGHT).title("Files")),
+            columns[0],
+        );
+
+        let Some(file) = pending.files.get(self.file_idx) else {
+            return;
+        };
+        let Some(hunk) = file
+            .regions
+            .iter()
+            .filter_map(|r| match r {
+                MergeRegion::Conflict(h) => Some(h),
+                MergeRegion::Clean(_) => None,
+            })
+            .nth(self.conflict_idx)
+        else {
+            frame.render_widget(Paragraph::new("No conflicts in this file"), columns[1]);
+            return;
+        };
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(columns[1]);
+
+        // Ours | Base | Theirs side by side
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Ratio(1, 3),
+                Constraint::Ratio(1, 3),
+                Constraint::Ratio(1, 3),
+            ])
+            .split(rows[0]);
+
+        let side = |title: String, lines: &[String], present: bool, chosen: bool, color: Color| {
+            let text = if present {
+                lines.iter().map(|l| l.trim_end_matches('\n')).collect::<Vec<_>>().join("\n")
+            } else {
+                "(deleted)".to_string()
+            };
+            let border = if chosen {
+                Style::default().fg(color).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).border_style(border).title(title))
+        };
+
+        let resolution = hunk.resolution;
+        let conflicts_in_file = self.conflict_count(self.file_idx);
+        frame.render_widget(
+            side(
+                format!("Ours ({})", pending.main_branch),
+                &hunk.ours,
+                hunk.ours_present,
+                matches!(resolution, Some(Resolution::Ours | Resolution::Both)),
+                Color::Blue,
+            ),
+            panes[0],
+        );
+        frame.render_widget(
+            side(
+                format!("Base [{}/{}]", self.conflict_idx + 1, conflicts_in_file),
+                &hunk.base,
+                true,
+                resolution == Some(Resolution::Base),
+                Color::DarkGray,
+            ),
+            panes[1],
+        );
+        frame.render_widget(
+            side(
+                format!("Theirs ({})", pending.worktree_branch),
+                &hunk.theirs,
+                hunk.theirs_present,
+                matches!(resolution, Some(Resolution::Theirs | Resolution::Both)),
+                Color::Magenta,
+            ),
+            panes[2],
+        );
+
+        // Resolved file preview around the current conflict
+        let preview = match file.resolved_content() {
+            Ok(Some(content)) => content,
+            Ok(None) => "(file will be deleted)".to_string(),
+            Err(_) => {
+                let resolved = match resolution {
+                    Some(_) => "resolved",
+                    None => "unresolved",
+                };
+                format!("Current conflict is {}; resolve all conflicts to preview the file", resolved)
+            }
+        };
+        frame.render_widget(
+            Paragraph::new(preview)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::TOP).title(format!("Result: {}", file.path.display()))),
+            rows[1],
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use crate::git::{DiffHunk, DiffLineKind, DiffSide};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Scrollable per-worktree diff pane with hunk and line selection
+pub struct DiffViewComponent {
+    title: String,
+    hunks: Vec<DiffHunk>,
+    side: DiffSide,
+    selected_hunk: usize,
+    cursor_line: usize,
+    selected_lines: BTreeSet<usize>,
+    scroll: usize,
+    is_open: bool,
+}
+
+impl Default for DiffViewComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiffViewComponent {
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            hunks: Vec::new(),
+            side: DiffSide::Unstaged,
+            selected_hunk: 0,
+            cursor_line: 0,
+            selected_lines: BTreeSet::new(),
+            scroll: 0,
+            is_open: false,
+        }
+    }
+
+    pub fn open(&mut self, title: String, side: DiffSide, hunks: Vec<DiffHunk>) {
+        self.title = title;
+        self.side = side;
+        self.hunks = hunks;
+        self.selected_hunk = 0;
+        self.cursor_line = 0;
+        self.selected_lines.clear();
+        self.scroll = 0;
+        self.is_open = true;
+    }
+
+    /// Replace the hunks after staging, keeping the cursor near where it was
+    pub fn refresh(&mut self, hunks: Vec<DiffHunk>) {
+        self.hunks = hunks;
+        self.selected_hunk = self.selected_hunk.min(self.hunks.len().saturating_sub(1));
+        self.cursor_line = 0;
+        self.selected_lines.clear();
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.hunks.clear();
+        self.selected_lines.clear();
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn side(&self) -> DiffSide {
+        self.side
+    }
+
+    pub fn selected_hunk(&self) -> Option<&DiffHunk> {
+        self.hunks.get(self.selected_hunk)
+    }
+
+    /// Line indices picked with Space, or `None` when the whole hunk applies
+    pub fn line_selection(&self) -> Option<Vec<usize>> {
+        if self.selected_lines.is_empty() {
+            None
+        } else {
+            Some(self.selected_lines.iter().copied().collect())
+        }
+    }
+
+    pub fn next_hunk(&mut self) {
+        if self.selected_hunk + 1 < self.hunks.len() {
+            self.selected_hunk += 1;
+            self.cursor_line = 0;
+            self.selected_lines.clear();
+        }
+    }
+
+    pub fn previous_hunk(&mut self) {
+        if self.selected_hunk > 0 {
+            self.selected_hunk -= 1;
+            self.cursor_line = 0;
+            self.selected_lines.clear();
+        }
+    }
+
+    pub fn cursor_down(&mut self) {
+        let line_count = self.selected_hunk().map(|h| h.lines.len()).unwrap_or(0);
+        if self.cursor_line + 1 < line_count {
+            self.cursor_line += 1;
+        } else {
+            self.next_hunk();
+        }
+    }
+
+    pub fn cursor_up(&mut self) {
+        if self.cursor_line > 0 {
+            self.cursor_line -= 1;
+        } else if self.selected_hunk > 0 {
+            self.previous_hunk();
+            self.cursor_line = self.selected_hunk().map(|h| h.lines.len().saturating_sub(1)).unwrap_or(0);
+        }
+    }
+
+    /// Toggle the line under the cursor for partial staging
+    pub fn toggle_line(&mut self) {
+        let is_changed = self
+            .selected_hunk()
+            .map(|h| h.is_changed_line(self.cursor_line))
+            .unwrap_or(false);
+
+        if is_changed && !self.selected_lines.remove(&self.cursor_line) {
+            self.selected_lines.insert(self.cursor_line);
+        }
+    }
+
+    pub fn page_down(&mut self, lines: usize) {
+        for _ in 0..lines {
+            self.cursor_down();
+        }
+    }
+
+    pub fn page_up(&mut self, lines: usize) {
+        for _ in 0..lines {
+            self.cursor_up();
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+
+        let side_label = match self.side {
+            DiffSide::Unstaged => "unstaged",
+            DiffSide::Staged => "staged",
+            DiffSide::Main => "vs main",
+        };
+        let keys = match self.side {
+            DiffSide::Unstaged => "j/k:line n/p:hunk Space:select s:stage Tab:staged q:close",
+            DiffSide::Staged => "j/k:line n/p:hunk Space:select u:unstage Tab:unstaged q:close",
+            DiffSide::Main => "j/k:line n/p:hunk Tab:unstaged q:close",
+        };
+
+        let block = Block::default()
+            .title(format!("Diff: {} ({}) [{}]", self.title, side_label, keys))
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Black).bg(Color::White));
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if self.hunks.is_empty() {
+            let empty = Paragraph::new(format!("No changes ({})", side_label))
+                .style(Style::default().fg(Color::DarkGray).bg(Color::White));
+            frame.render_widget(empty, inner);
+            return;
+        }
+
+        let mut lines: Vec<Line> = Vec::new();
+        let mut cursor_row = 0;
+        let mut previous_file: Option<&Path> = None;
+
+        for (hunk_idx, hunk) in self.hunks.iter().enumerate() {
+            if previous_file != Some(hunk.file.as_path()) {
+                lines.push(Line::from(Span::styled(
+                    format!("── {} ({:?})", hunk.file.display(), hunk.file_status),
+                    Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+                )));
+                previous_file = Some(hunk.file.as_path());
+            }
+
+            let is_current = hunk_idx == self.selected_hunk;
+            let header_style = if is_current {
+                Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+            } else {
+                Style::default().fg(Color::Magenta)
+            };
+            lines.push(Line::from(Span::styled(hunk.header.clone(), header_style)));
+
+            let extension = hunk.file.extension().and_then(|e| e.to_str()).unwrap_or("");
+
+            for (line_idx, line) in hunk.lines.iter().enumerate() {
+                let is_cursor = is_current && line_idx == self.cursor_line;
+                let is_selected = is_current && self.selected_lines.contains(&line_idx);
+                if is_cursor {
+                    cursor_row = lines.len();
+                }
+
+                let (marker, gutter_style, bg) = match line.kind {
+                    DiffLineKind::Added => ("+", Style::default().fg(Color::Green), Color::Rgb(220, 255, 220)),
+                    DiffLineKind::Removed => ("-", Style::default().fg(Color::Red), Color::Rgb(255, 220, 220)),
+                    DiffLineKind::Context => (" ", Style::default().fg(Color::DarkGray), Color::White),
+                };
+
+                let lineno = match (line.old_lineno, line.new_lineno) {
+                    (_, Some(n)) | (Some(n), None) => format!("{:>5} ", n),
+                    (None, None) => "      ".to_string(),
+                };
+
+                let select_mark = if is_selected { "●" } else if is_cursor { "›" } else { " " };
+
+                let mut spans = vec![
+                    Span::styled(select_mark, Style::default().fg(Color::Blue).bg(bg)),
+                    Span::styled(lineno, Style::default().fg(Color::DarkGray).bg(bg)),
+                    Span::styled(marker, gutter_style.bg(bg)),
+                ];
+                spans.extend(highlight_line(&line.text(), extension, bg));
+
+                let mut rendered = Line::from(spans);
+                if is_cursor {
+                    rendered = rendered.style(Style::default().add_modifier(Modifier::BOLD));
+                }
+                lines.push(rendered);
+            }
+        }
+
+        // Keep the cursor visible
+        let height = inner.height as usize;
+        if cursor_row < self.scroll {
+            self.scroll = cursor_row;
+        } else if height > 0 && cursor_row >= self.scroll + height {
+            self.scroll = cursor_row + 1 - height;
+        }
+
+        let paragraph = Paragraph::new(lines)
+            .style(Style::default().bg(Color::White))
+            .scroll((self.scroll as u16, 0));
+        frame.render_widget(paragraph, inner);
+    }
+}
+
+/// Minimal keyword/string/comment highlighting keyed on file extension
+fn highlight_line(text: &str, extension: &str, bg: Color) -> Vec<Span<'static>> {
+    let keywords: &[&str] = match extension {
+        "rs" => &[
+            "fn", "let", "mut", "pub", "use", "mod", "struct", "enum", "impl", "trait", "match",
+            "if", "else", "for", "while", "loop", "return", "self", "Self", "crate", "super",
+            "async", "await", "move", "ref", "where", "const", "static", "type", "as", "in",
+        ],
+        "py" => &[
+            "def", "class", "import", "from", "return", "if", "elif", "else", "for", "while",
+            "in", "is", "not", "and", "or", "with", "as", "try", "except", "finally", "lambda",
+            "yield", "async", "await", "None", "True", "False", "self",
+        ],
+        "js" | "ts" | "tsx" | "jsx" => &[
+            "function", "const", "let", "var", "return", "if", "else", "for", "while", "class",
+            "import", "export", "from", "async", "await", "new", "this", "interface", "type",
+        ],
+        "go" => &[
+            "func", "package", "import", "return", "if", "else", "for", "range", "struct",
+            "type", "var", "const", "go", "defer", "chan", "select", "interface",
+        ],
+        "c" | "h" | "cpp" | "hpp" | "cc" => &[
+            "int", "char", "void", "return", "if", "else", "for", "while", "struct", "typedef",
+            "static", "const", "include", "define", "class", "namespace", "template",
+        ],
+        _ => &[],
+    };
+    let line_comment = match extension {
+        "py" | "sh" | "toml" | "yaml" | "yml" => "#",
+        "" | "md" | "txt" => "",
+        _ => "//",
+    };
+
+    let base = Style::default().fg(Color::Black).bg(bg);
+    if keywords.is_empty() && line_comment.is_empty() {
+        return vec![Span::styled(text.to_string(), base)];
+    }
+
+    let mut spans = Vec::new();
+    let mut word = String::new();
+    let mut chars = text.char_indices().peekable();
+
+    let flush_word = |word: &mut String, spans: &mut Vec<Span<'static>>| {
+        if word.is_empty() {
+            return;
+        }
+        let style = if keywords.contains(&word.as_str()) {
+            base.fg(Color::Blue).add_modifier(Modifier::BOLD)
+        } else if word.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+            base.fg(Color::Cyan)
+        } else {
+            base
+        };
+        spans.push(Span::styled(std::mem::take(word), style));
+    };
+
+    while let Some((idx, c)) = chars.next() {
+        if !line_comment.is_empty() && text[idx..].starts_with(line_comment) {
+            flush_word(&mut word, &mut spans);
+            spans.push(Span::styled(text[idx..].to_string(), base.fg(Color::DarkGray)));
+            return spans;
+        }
+
+        // Rust uses ' for lifetimes, so only treat it as a quote elsewhere
+        if c == '"' || (c == '\'' && extension != "rs") {
+            flush_word(&mut word, &mut spans);
+            let mut literal = String::from(c);
+            let mut escaped = false;
+            for (_, next) in chars.by_ref() {
+                literal.push(next);
+                if next == c && !escaped {
+                    break;
+                }
+                escaped = next == '\\' && !escaped;
+            }
+            spans.push(Span::styled(literal, base.fg(Color::Rgb(160, 80, 0))));
+        } else if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+        } else {
+            flush_word(&mut word, &mut spans);
+            spans.push(Span::styled(c.to_string(), base));
+        }
+    }
+    flush_word(&mut word, &mut spans);
+
+    spans
+}
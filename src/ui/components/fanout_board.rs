@@ -0,0 +1,297 @@
+use crate::git::{DiffHunk, DiffLineKind};
+use crate::workspace::{AgentActivity, Fanout, TerminalId, TestStatus};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Diffs compared side by side at most
+pub const MAX_COMPARED: usize = 4;
+
+/// Progress of the agents of a `:fanout`, and their diffs side by side
+pub struct FanoutBoardComponent {
+    fanout: Option<Fanout>,
+    selected: usize,
+    /// Agents picked for comparison
+    marked: HashSet<TerminalId>,
+    /// Title and diff of each compared agent
+    compare: Option<Vec<(String, Vec<DiffHunk>)>>,
+    scroll: usize,
+    is_open: bool,
+}
+
+impl Default for FanoutBoardComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FanoutBoardComponent {
+    pub fn new() -> Self {
+        Self {
+            fanout: None,
+            selected: 0,
+            marked: HashSet::new(),
+            compare: None,
+            scroll: 0,
+            is_open: false,
+        }
+    }
+
+    pub fn open(&mut self, fanout: Fanout) {
+        self.is_open = true;
+        self.refresh(fanout);
+    }
+
+    pub fn refresh(&mut self, fanout: Fanout) {
+        self.marked.retain(|id| fanout.agent(*id).is_some());
+        self.selected = self.selected.min(fanout.agents.len().saturating_sub(1));
+        self.fanout = Some(fanout);
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.compare = None;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn selected_agent(&self) -> Option<TerminalId> {
+        self.fanout.as_ref()?.agents.get(self.selected).map(|a| a.terminal_id)
+    }
+
+    pub fn move_down(&mut self) {
+        let count = self.fanout.as_ref().map_or(0, |f| f.agents.len());
+        if self.selected + 1 < count {
+            self.selected += 1;
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn toggle_mark(&mut self) {
+        if let Some(id) = self.selected_agent() {
+            if !self.marked.remove(&id) && self.marked.len() < MAX_COMPARED {
+                self.marked.insert(id);
+            }
+        }
+    }
+
+    /// Agents to compare: the marked ones, or the first few if none are
+    pub fn compared_agents(&self) -> Vec<TerminalId> {
+        let Some(fanout) = &self.fanout else {
+            return Vec::new();
+        };
+        fanout
+            .agents
+            .iter()
+            .map(|a| a.terminal_id)
+            .filter(|id| self.marked.is_empty() || self.marked.contains(id))
+            .take(MAX_COMPARED)
+            .collect()
+    }
+
+    pub fn show_compare(&mut self, columns: Vec<(String, Vec<DiffHunk>)>) {
+        self.compare = Some(columns);
+        self.scroll = 0;
+    }
+
+    pub fn is_comparing(&self) -> bool {
+        self.compare.is_some()
+    }
+
+    pub fn close_compare(&mut self) {
+        self.compare = None;
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll += lines;
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+        if let Some(columns) = &self.compare {
+            let longest = columns.iter().map(|(_, hunks)| diff_lines(hunks).len()).max().unwrap_or(0);
+            self.scroll = self.scroll.min(longest.saturating_sub(1));
+            draw_compare(frame, area, columns, self.scroll);
+            return;
+        }
+        let Some(fanout) = &self.fanout else {
+            return;
+        };
+
+        let block = Block::default()
+            .title(format!(
+                "Fanout: {} x{} from {}",
+                fanout.profile,
+                fanout.agents.len(),
+                &fanout.base[..fanout.base.len().min(8)]
+            ))
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Black).bg(Color::White));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(3), Constraint::Length(2)])
+            .split(inner);
+
+        frame.render_widget(
+            Paragraph::new(fanout.prompt.replace('\n', " ")).style(Style::default().fg(Color::DarkGray)),
+            chunks[0],
+        );
+
+        let header = Row::new(vec!["", "Agent", "Pane", "Branch", "State", "Files", "Tests", "Output"])
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .bottom_margin(1);
+
+        let rows: Vec<Row> = fanout
+            .agents
+            .iter()
+            .enumerate()
+            .map(|(i, agent)| {
+                let (state, state_color) = match agent.activity {
+                    AgentActivity::Starting => ("starting", Color::DarkGray),
+                    AgentActivity::Busy => ("busy", Color::Rgb(160, 80, 0)),
+                    AgentActivity::Idle => ("idle", Color::Blue),
+                    AgentActivity::Exited => ("exited", Color::Red),
+                };
+                let (tests, tests_color) = match agent.tests {
+                    TestStatus::Unknown => ("-", Color::DarkGray),
+                    TestStatus::Passing => ("passing", Color::Green),
+                    TestStatus::Failing => ("failing", Color::Red),
+                };
+                let mark = if fanout.winner == Some(agent.terminal_id) {
+                    "★"
+                } else if self.marked.contains(&agent.terminal_id) {
+                    "●"
+                } else {
+                    " "
+                };
+                let output = agent
+                    .last_output
+                    .map(|at| format!("{}s ago", at.elapsed().as_secs()))
+                    .unwrap_or_default();
+
+                Row::new(vec![
+                    Cell::from(mark).style(Style::default().fg(Color::Blue)),
+                    Cell::from(format!("#{}", i + 1)),
+                    Cell::from(agent.title.clone()),
+                    Cell::from(agent.branch.clone()).style(Style::default().fg(Color::Blue)),
+                    Cell::from(state).style(Style::default().fg(state_color)),
+                    Cell::from(agent.changed_files.len().to_string()),
+                    Cell::from(tests).style(Style::default().fg(tests_color)),
+                    Cell::from(output).style(Style::default().fg(Color::DarkGray)),
+                ])
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Length(1),
+            Constraint::Length(5),
+            Constraint::Length(14),
+            Constraint::Length(32),
+            Constraint::Length(9),
+            Constraint::Length(5),
+            Constraint::Length(8),
+            Constraint::Min(9),
+        ];
+
+        let mut state = TableState::default();
+        if !fanout.agents.is_empty() {
+            state.select(Some(self.selected));
+        }
+        frame.render_stateful_widget(
+            Table::new(rows, widths)
+                .header(header)
+                .row_highlight_style(Style::default().bg(Color::LightBlue)),
+            chunks[1],
+            &mut state,
+        );
+
+        let key = |k: &'static str| Span::styled(k, Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                key("Enter"), Span::raw(" focus  "),
+                key("Space"), Span::raw(" mark  "),
+                key("c"), Span::raw(" compare diffs  "),
+                key("d"), Span::raw(" diff  "),
+                key("m"), Span::raw(" merge winner  "),
+                key("X"), Span::raw(" discard the others  "),
+                key("q"), Span::raw(" close"),
+            ]))
+            .block(Block::default().borders(Borders::TOP)),
+            chunks[2],
+        );
+    }
+}
+
+fn draw_compare(frame: &mut Frame, area: Rect, columns: &[(String, Vec<DiffHunk>)], scroll: usize) {
+    let block = Block::default()
+        .title("Compare diffs vs main [j/k: scroll, PgUp/PgDn: page, q: back]")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Black).bg(Color::White));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let count = columns.len().max(1) as u32;
+    let areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Ratio(1, count); columns.len()])
+        .split(inner);
+
+    for ((title, hunks), column) in columns.iter().zip(areas.iter()) {
+        let files: HashSet<&Path> = hunks.iter().map(|h| h.file.as_path()).collect();
+        let block = Block::default()
+            .title(format!("{} ({} files)", title, files.len()))
+            .borders(Borders::LEFT | Borders::TOP);
+        let lines = if hunks.is_empty() {
+            vec![Line::from(Span::styled("No changes", Style::default().fg(Color::DarkGray)))]
+        } else {
+            diff_lines(hunks)
+        };
+        frame.render_widget(
+            Paragraph::new(lines).block(block).scroll((scroll as u16, 0)),
+            *column,
+        );
+    }
+}
+
+/// A diff as compact lines: file headers, hunk headers and changed lines
+fn diff_lines(hunks: &[DiffHunk]) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut previous_file: Option<&Path> = None;
+    for hunk in hunks {
+        if previous_file != Some(hunk.file.as_path()) {
+            lines.push(Line::from(Span::styled(
+                format!("── {}", hunk.file.display()),
+                Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            )));
+            previous_file = Some(hunk.file.as_path());
+        }
+        lines.push(Line::from(Span::styled(hunk.header.clone(), Style::default().fg(Color::Magenta))));
+        for line in &hunk.lines {
+            let (marker, style) = match line.kind {
+                DiffLineKind::Added => ("+", Style::default().fg(Color::Green).bg(Color::Rgb(220, 255, 220))),
+                DiffLineKind::Removed => ("-", Style::default().fg(Color::Red).bg(Color::Rgb(255, 220, 220))),
+                DiffLineKind::Context => (" ", Style::default().fg(Color::DarkGray)),
+            };
+            lines.push(Line::from(Span::styled(format!("{}{}", marker, line.text()), style)));
+        }
+    }
+    lines
+}
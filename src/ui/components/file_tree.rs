@@ -1,29 +1,516 @@
-use std::path::PathBuf;
+use crate::monitor::ConflictKind;
+use anyhow::Result;
+use git2::{Repository, Status, StatusOptions};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+    Frame,
+};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 
+/// Git state of a file, as shown next to its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileStatus {
+    Untracked,
+    Added,
+    Modified,
+    Conflicted,
+}
+
+impl FileStatus {
+    fn marker(self) -> (&'static str, Color) {
+        match self {
+            FileStatus::Untracked => ("?", Color::DarkGray),
+            FileStatus::Added => ("A", Color::Green),
+            FileStatus::Modified => ("M", Color::Rgb(150, 110, 0)),
+            FileStatus::Conflicted => ("C", Color::Red),
+        }
+    }
+}
+
+/// Per-file decorations the explorer gets from the workspace, keyed by
+/// path relative to the explorer root
+#[derive(Default)]
+pub struct TreeDecorations {
+    pub conflicts: HashMap<PathBuf, ConflictKind>,
+    /// Colour of the only terminal working on a file
+    pub owners: HashMap<PathBuf, Color>,
+}
+
+#[derive(Debug, Clone)]
+struct FileTreeItem {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+    depth: usize,
+}
+
+#[derive(Debug, Clone)]
+enum FileOperation {
+    /// Create a file, or a directory if the name ends in `/`, in a directory
+    Create(PathBuf),
+    Rename(PathBuf),
+    Delete(PathBuf),
+}
+
+/// Project file explorer: gitignore-aware tree with git status markers
 pub struct FileTreeComponent {
     root: PathBuf,
-    expanded_dirs: Vec<PathBuf>,
+    expanded_dirs: HashSet<PathBuf>,
     selected: Option<PathBuf>,
+    items: Vec<FileTreeItem>,
+    statuses: HashMap<PathBuf, FileStatus>,
+    show_hidden: bool,
+    /// Pending file operation and its typed input
+    prompt: Option<(FileOperation, String)>,
+    offset: usize,
+    area: Option<Rect>,
 }
 
 impl FileTreeComponent {
     pub fn new(root: PathBuf) -> Self {
-        Self {
+        let mut tree = Self {
             root,
-            expanded_dirs: Vec::new(),
+            expanded_dirs: HashSet::new(),
             selected: None,
+            items: Vec::new(),
+            statuses: HashMap::new(),
+            show_hidden: false,
+            prompt: None,
+            offset: 0,
+            area: None,
+        };
+        tree.refresh();
+        tree
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn set_root(&mut self, root: PathBuf) {
+        if root == self.root {
+            return;
+        }
+        self.root = root;
+        self.expanded_dirs.clear();
+        self.selected = None;
+        self.offset = 0;
+        self.refresh();
+    }
+
+    /// Re-read the tree and git status from disk
+    pub fn refresh(&mut self) {
+        let repo = Repository::discover(&self.root).ok();
+        self.statuses = repo.as_ref().map(|r| self.read_statuses(r)).unwrap_or_default();
+
+        let name = self
+            .root
+            .file_name()
+            .map(|n| format!("{}/", n.to_string_lossy()))
+            .unwrap_or_else(|| "./".to_string());
+        self.items = vec![FileTreeItem {
+            path: self.root.clone(),
+            name,
+            is_dir: true,
+            depth: 0,
+        }];
+        let root = self.root.clone();
+        self.push_children(&root, 1, repo.as_ref());
+
+        if !self.selected.as_ref().is_some_and(|s| self.items.iter().any(|i| &i.path == s)) {
+            self.selected = self.items.first().map(|i| i.path.clone());
+        }
+    }
+
+    fn push_children(&mut self, dir: &Path, depth: usize, repo: Option<&Repository>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        let mut entries: Vec<(PathBuf, String, bool)> = entries
+            .flatten()
+            .map(|e| {
+                let is_dir = e.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                (e.path(), e.file_name().to_string_lossy().to_string(), is_dir)
+            })
+            .filter(|(path, name, is_dir)| {
+                name != ".git"
+                    && (self.show_hidden || !name.starts_with('.'))
+                    && !repo.is_some_and(|r| is_ignored(r, path, *is_dir))
+            })
+            .collect();
+        // Directories first, then by name
+        entries.sort_by(|a, b| (!a.2, &a.1).cmp(&(!b.2, &b.1)));
+
+        for (path, name, is_dir) in entries {
+            let expanded = is_dir && self.expanded_dirs.contains(&path);
+            self.items.push(FileTreeItem {
+                path: path.clone(),
+                name: if is_dir { format!("{}/", name) } else { name },
+                is_dir,
+                depth,
+            });
+            if expanded {
+                self.push_children(&path, depth + 1, Some(repo).flatten());
+            }
         }
     }
 
+    /// Status of every changed path, relative to the root
+    fn read_statuses(&self, repo: &Repository) -> HashMap<PathBuf, FileStatus> {
+        let Some(workdir) = repo.workdir() else {
+            return HashMap::new();
+        };
+        let root = self.root.canonicalize().unwrap_or_else(|_| self.root.clone());
+        let workdir = workdir.canonicalize().unwrap_or_else(|_| workdir.to_path_buf());
+        let Ok(prefix) = root.strip_prefix(&workdir) else {
+            return HashMap::new();
+        };
+
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).include_ignored(false);
+        let Ok(statuses) = repo.statuses(Some(&mut options)) else {
+            return HashMap::new();
+        };
+
+        statuses
+            .iter()
+            .filter_map(|entry| {
+                let flags = entry.status();
+                let status = if flags.contains(Status::CONFLICTED) {
+                    FileStatus::Conflicted
+                } else if flags.contains(Status::INDEX_NEW) {
+                    FileStatus::Added
+                } else if flags.contains(Status::WT_NEW) {
+                    FileStatus::Untracked
+                } else if flags.intersects(
+                    Status::WT_MODIFIED
+                        | Status::INDEX_MODIFIED
+                        | Status::WT_RENAMED
+                        | Status::INDEX_RENAMED
+                        | Status::WT_TYPECHANGE
+                        | Status::INDEX_TYPECHANGE,
+                ) {
+                    FileStatus::Modified
+                } else {
+                    return None;
+                };
+
+                // Untracked directories come back with a trailing slash
+                let path = PathBuf::from(entry.path()?.trim_end_matches('/'));
+                Some((path.strip_prefix(prefix).ok()?.to_path_buf(), status))
+            })
+            .collect()
+    }
+
     pub fn toggle_expand(&mut self, path: PathBuf) {
-        if let Some(pos) = self.expanded_dirs.iter().position(|p| p == &path) {
-            self.expanded_dirs.remove(pos);
-        } else {
-            self.expanded_dirs.push(path);
+        if !self.expanded_dirs.remove(&path) {
+            self.expanded_dirs.insert(path);
         }
+        self.refresh();
     }
 
     pub fn select(&mut self, path: PathBuf) {
         self.selected = Some(path);
     }
-}
\ No newline at end of file
+
+    pub fn selected_path(&self) -> Option<&Path> {
+        self.selected.as_deref()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected
+            .as_ref()
+            .and_then(|s| self.items.iter().position(|i| &i.path == s))
+            .unwrap_or(0)
+    }
+
+    fn selected_item(&self) -> Option<&FileTreeItem> {
+        self.items.get(self.selected_index())
+    }
+
+    pub fn move_up(&mut self) {
+        let index = self.selected_index().saturating_sub(1);
+        self.selected = self.items.get(index).map(|i| i.path.clone());
+    }
+
+    pub fn move_down(&mut self) {
+        let index = (self.selected_index() + 1).min(self.items.len().saturating_sub(1));
+        self.selected = self.items.get(index).map(|i| i.path.clone());
+    }
+
+    /// Expand or collapse the selected directory
+    pub fn toggle_selected(&mut self) {
+        if let Some(item) = self.selected_item().filter(|i| i.is_dir && i.depth > 0) {
+            let path = item.path.clone();
+            self.toggle_expand(path);
+        }
+    }
+
+    /// The selected file, or `None` after toggling a selected directory
+    pub fn open_selected(&mut self) -> Option<PathBuf> {
+        let item = self.selected_item()?.clone();
+        if item.is_dir {
+            self.toggle_selected();
+            None
+        } else {
+            Some(item.path)
+        }
+    }
+
+    pub fn toggle_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        self.refresh();
+    }
+
+    pub fn area(&self) -> Option<Rect> {
+        self.area
+    }
+
+    /// Select the item drawn at screen row `y`
+    pub fn click(&mut self, y: u16) {
+        let Some(area) = self.area else {
+            return;
+        };
+        let index = self.offset + y.saturating_sub(area.y + 1) as usize;
+        if let Some(item) = self.items.get(index) {
+            self.selected = Some(item.path.clone());
+        }
+    }
+
+    /// Create inside the selected directory, or next to the selected file
+    pub fn start_create(&mut self) {
+        let dir = match self.selected_item() {
+            Some(item) if item.is_dir => item.path.clone(),
+            Some(item) => item.path.parent().map(Path::to_path_buf).unwrap_or_else(|| self.root.clone()),
+            None => self.root.clone(),
+        };
+        self.prompt = Some((FileOperation::Create(dir), String::new()));
+    }
+
+    pub fn start_rename(&mut self) {
+        if let Some(item) = self.selected_item().filter(|i| i.depth > 0) {
+            let name = item.name.trim_end_matches('/').to_string();
+            self.prompt = Some((FileOperation::Rename(item.path.clone()), name));
+        }
+    }
+
+    pub fn start_delete(&mut self) {
+        if let Some(item) = self.selected_item().filter(|i| i.depth > 0) {
+            self.prompt = Some((FileOperation::Delete(item.path.clone()), String::new()));
+        }
+    }
+
+    pub fn is_prompting(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    pub fn prompt_push(&mut self, c: char) {
+        if let Some((_, input)) = &mut self.prompt {
+            input.push(c);
+        }
+    }
+
+    pub fn prompt_backspace(&mut self) {
+        if let Some((_, input)) = &mut self.prompt {
+            input.pop();
+        }
+    }
+
+    pub fn cancel_prompt(&mut self) {
+        self.prompt = None;
+    }
+
+    /// Run the pending operation, returning a message describing it
+    pub fn confirm_prompt(&mut self) -> Result<Option<String>> {
+        let Some((operation, input)) = self.prompt.take() else {
+            return Ok(None);
+        };
+
+        let message = match operation {
+            FileOperation::Create(dir) => {
+                if input.trim().is_empty() {
+                    return Ok(None);
+                }
+                let path = dir.join(input.trim());
+                if input.ends_with('/') {
+                    fs::create_dir_all(&path)?;
+                } else {
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::File::create_new(&path)?;
+                }
+                self.expanded_dirs.insert(dir);
+                self.selected = Some(path.clone());
+                format!("Created {}", self.relative(&path).display())
+            }
+            FileOperation::Rename(from) => {
+                let Some(parent) = from.parent() else {
+                    return Ok(None);
+                };
+                if input.trim().is_empty() {
+                    return Ok(None);
+                }
+                let to = parent.join(input.trim());
+                if to.exists() {
+                    anyhow::bail!("{} already exists", self.relative(&to).display());
+                }
+                fs::rename(&from, &to)?;
+                self.selected = Some(to.clone());
+                format!("Renamed to {}", self.relative(&to).display())
+            }
+            FileOperation::Delete(path) => {
+                if !input.eq_ignore_ascii_case("y") {
+                    return Ok(None);
+                }
+                if path.is_dir() {
+                    fs::remove_dir_all(&path)?;
+                } else {
+                    fs::remove_file(&path)?;
+                }
+                format!("Deleted {}", self.relative(&path).display())
+            }
+        };
+
+        self.refresh();
+        Ok(Some(message))
+    }
+
+    pub fn relative<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+
+    /// Strongest status of a file, or of anything below a directory
+    fn status_of(&self, item: &FileTreeItem) -> Option<FileStatus> {
+        let relative = self.relative(&item.path);
+        if item.is_dir {
+            self.statuses
+                .iter()
+                .filter(|(path, _)| path.starts_with(relative))
+                .map(|(_, status)| *status)
+                .max()
+        } else {
+            self.statuses.get(relative).copied()
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect, decorations: &TreeDecorations) {
+        self.area = Some(area);
+
+        let hidden = if self.show_hidden { "" } else { " .:hidden" };
+        let block = Block::default()
+            .title(format!("Files [a:new r:rename d:delete y/Y:copy path w:worktree{}]", hidden))
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Black).bg(Color::White));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let prompt_height = u16::from(self.prompt.is_some());
+        let list_height = inner.height.saturating_sub(prompt_height) as usize;
+
+        // Keep the selection in view
+        let selected = self.selected_index();
+        if selected < self.offset {
+            self.offset = selected;
+        } else if list_height > 0 && selected >= self.offset + list_height {
+            self.offset = selected + 1 - list_height;
+        }
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .enumerate()
+            .skip(self.offset)
+            .take(list_height)
+            .map(|(index, item)| self.item(item, index == selected, decorations))
+            .collect();
+        frame.render_widget(
+            List::new(items),
+            Rect { height: list_height as u16, ..inner },
+        );
+
+        if let Some((operation, input)) = &self.prompt {
+            let label = match operation {
+                FileOperation::Create(dir) => format!("New in {}/: ", self.relative(dir).display()),
+                FileOperation::Rename(_) => "Rename to: ".to_string(),
+                FileOperation::Delete(path) => format!("Delete {}? (y/n) ", self.relative(path).display()),
+            };
+            frame.render_widget(
+                Paragraph::new(Line::from(vec![
+                    Span::styled(label, Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(input.clone()),
+                    Span::styled("█", Style::default().fg(Color::Blue)),
+                ])),
+                Rect { y: inner.y + list_height as u16, height: 1, ..inner },
+            );
+        }
+    }
+
+    fn item(&self, item: &FileTreeItem, is_selected: bool, decorations: &TreeDecorations) -> ListItem<'static> {
+        let relative = self.relative(&item.path);
+        let indent = "  ".repeat(item.depth);
+        let icon = match (item.is_dir, item.depth == 0 || self.expanded_dirs.contains(&item.path)) {
+            (true, true) => "▼",
+            (true, false) => "▶",
+            (false, _) => "•",
+        };
+
+        // Worst conflict on this file, or anywhere below this directory
+        let conflict = decorations
+            .conflicts
+            .iter()
+            .filter(|(path, _)| {
+                if item.is_dir {
+                    path.starts_with(relative)
+                } else {
+                    path.as_path() == relative
+                }
+            })
+            .map(|(_, kind)| *kind)
+            .max_by_key(|kind| *kind == ConflictKind::OverlappingLines);
+
+        let name_style = if is_selected {
+            Style::default().fg(Color::Blue).bg(Color::LightBlue).add_modifier(Modifier::BOLD)
+        } else if let Some(kind) = conflict {
+            let color = match kind {
+                ConflictKind::OverlappingLines => Color::Red,
+                ConflictKind::SameFile => Color::Rgb(160, 80, 0),
+            };
+            Style::default().fg(color)
+        } else if let Some(&color) = decorations.owners.get(relative).filter(|_| !item.is_dir) {
+            Style::default().fg(color)
+        } else if item.is_dir {
+            Style::default().fg(Color::Blue)
+        } else {
+            Style::default().fg(Color::Black)
+        };
+
+        let mut spans = vec![Span::styled(format!("{}{} {}", indent, icon, item.name), name_style)];
+        if let Some(status) = self.status_of(item) {
+            let (marker, color) = status.marker();
+            spans.push(Span::styled(format!(" {}", marker), Style::default().fg(color)));
+        }
+        if conflict.is_some() {
+            spans.push(Span::styled(" ⚠", Style::default().fg(Color::Red)));
+        }
+        ListItem::new(Line::from(spans))
+    }
+}
+
+fn is_ignored(repo: &Repository, path: &Path, is_dir: bool) -> bool {
+    let Some(workdir) = repo.workdir() else {
+        return false;
+    };
+    let Ok(relative) = path.strip_prefix(workdir) else {
+        return false;
+    };
+    // A trailing slash lets directory-only patterns such as `target/` match
+    let relative = if is_dir { relative.join("") } else { relative.to_path_buf() };
+    repo.is_path_ignored(&relative).unwrap_or(false)
+}
@@ -0,0 +1,571 @@
+use crate::workspace::TerminalId;
+use git2::{Repository, Status, StatusOptions};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+
+/// Files sent per message while indexing, so results show up early
+const INDEX_CHUNK: usize = 10_000;
+
+/// Candidates scored between checks for a newer query
+const RANK_CHUNK: usize = 4_096;
+
+/// Rows kept for display; matching still covers every candidate
+const MAX_SHOWN: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinderMode {
+    Files,
+    Panes,
+}
+
+/// A pane as the finder searches it
+#[derive(Debug, Clone)]
+pub struct FinderPane {
+    pub id: TerminalId,
+    /// Position in the workspace, as F1-F10 and Alt+G count
+    pub number: usize,
+    pub title: String,
+    pub branch: Option<String>,
+    pub working_dir: PathBuf,
+    /// Recent output lines, oldest first
+    pub output: Vec<String>,
+}
+
+pub enum FinderChoice {
+    File(PathBuf),
+    Pane(TerminalId),
+}
+
+#[derive(Debug, Clone)]
+struct Match {
+    index: usize,
+    score: i64,
+    /// Matched char positions in the displayed text
+    positions: Vec<usize>,
+    /// Output line that matched, for panes found by their output
+    snippet: Option<String>,
+}
+
+enum FinderMessage {
+    Files { generation: u64, files: Vec<String> },
+    Indexed { generation: u64 },
+    Ranked { generation: u64, query: String, matches: Vec<Match> },
+}
+
+/// Fuzzy finder popup over project files or open panes
+pub struct FuzzyFinderComponent {
+    mode: FinderMode,
+    query: String,
+    root: PathBuf,
+    files: Arc<Vec<String>>,
+    panes: Vec<FinderPane>,
+    indexing: bool,
+    /// Ranking in flight for a query the results don't reflect yet
+    ranking: bool,
+    /// Query the current matches were ranked for, and whether they cover
+    /// every file indexed so far
+    ranked: Option<(String, bool)>,
+    matches: Vec<Match>,
+    selected: usize,
+    is_open: bool,
+    /// Bumped on every new index or query; workers drop stale work
+    generation: Arc<AtomicU64>,
+    /// Generation the current file index was started under
+    index_generation: u64,
+    sender: Sender<FinderMessage>,
+    receiver: Receiver<FinderMessage>,
+}
+
+impl Default for FuzzyFinderComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FuzzyFinderComponent {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            mode: FinderMode::Files,
+            query: String::new(),
+            root: PathBuf::new(),
+            files: Arc::new(Vec::new()),
+            panes: Vec::new(),
+            indexing: false,
+            ranking: false,
+            ranked: None,
+            matches: Vec::new(),
+            selected: 0,
+            is_open: false,
+            generation: Arc::new(AtomicU64::new(0)),
+            index_generation: 0,
+            sender,
+            receiver,
+        }
+    }
+
+    /// Open in `mode`, indexing files under `root` in the background. The
+    /// index is rebuilt on every open so files created since show up
+    pub fn open(&mut self, mode: FinderMode, root: PathBuf, panes: Vec<FinderPane>) {
+        self.is_open = true;
+        self.mode = mode;
+        self.query.clear();
+        self.panes = panes;
+        self.selected = 0;
+
+        self.root = root;
+        self.files = Arc::new(Vec::new());
+        self.start_indexing();
+        self.rank();
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.query.clear();
+        self.matches.clear();
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            FinderMode::Files => FinderMode::Panes,
+            FinderMode::Panes => FinderMode::Files,
+        };
+        self.selected = 0;
+        self.rank();
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+        self.rank();
+    }
+
+    pub fn backspace(&mut self) {
+        if self.query.pop().is_some() {
+            self.selected = 0;
+            self.rank();
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.shown().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn selected_choice(&self) -> Option<FinderChoice> {
+        let index = self.shown().get(self.selected)?.index;
+        Some(match self.mode {
+            FinderMode::Files => FinderChoice::File(self.root.join(self.files.get(index)?)),
+            FinderMode::Panes => FinderChoice::Pane(self.panes.get(index)?.id),
+        })
+    }
+
+    /// Take in indexed files and finished rankings; true if anything changed
+    pub fn poll(&mut self) -> bool {
+        let current = self.generation.load(Ordering::Relaxed);
+        let mut changed = false;
+        let mut files_added = false;
+
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                FinderMessage::Files { generation, files } if generation == self.index_generation => {
+                    Arc::make_mut(&mut self.files).extend(files);
+                    files_added = true;
+                }
+                FinderMessage::Indexed { generation } if generation == self.index_generation => {
+                    self.indexing = false;
+                    changed = true;
+                }
+                FinderMessage::Ranked { generation, query, matches } if generation == current => {
+                    self.ranked = Some((query, true));
+                    self.matches = matches;
+                    self.ranking = false;
+                    self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+                    changed = true;
+                }
+                _ => {}
+            }
+        }
+
+        if files_added {
+            if let Some((_, complete)) = &mut self.ranked {
+                *complete = false;
+            }
+            if self.is_open && self.mode == FinderMode::Files {
+                self.rank();
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    fn start_indexing(&mut self) {
+        self.index_generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        self.indexing = true;
+        self.ranked = None;
+
+        let root = self.root.clone();
+        let generation = self.index_generation;
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            list_files(&root, |files| {
+                sender.send(FinderMessage::Files { generation, files }).is_ok()
+            });
+            let _ = sender.send(FinderMessage::Indexed { generation });
+        });
+    }
+
+    fn rank(&mut self) {
+        match self.mode {
+            FinderMode::Panes => {
+                self.matches = rank_panes(&self.panes, &self.query);
+                self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+            }
+            FinderMode::Files => self.rank_files(),
+        }
+    }
+
+    fn rank_files(&mut self) {
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if self.query.is_empty() {
+            self.matches = (0..self.files.len().min(MAX_SHOWN))
+                .map(|index| Match { index, score: 0, positions: Vec::new(), snippet: None })
+                .collect();
+            self.ranked = None;
+            self.ranking = false;
+            return;
+        }
+
+        // A longer query only narrows the last complete ranking
+        let subset = match &self.ranked {
+            Some((query, true)) if self.query.starts_with(query.as_str()) => {
+                Some(self.matches.iter().map(|m| m.index).collect::<Vec<_>>())
+            }
+            _ => None,
+        };
+
+        self.ranking = true;
+        let files = Arc::clone(&self.files);
+        let query = self.query.clone();
+        let latest = Arc::clone(&self.generation);
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let indices: Box<dyn Iterator<Item = usize>> = match subset {
+                Some(subset) => Box::new(subset.into_iter()),
+                None => Box::new(0..files.len()),
+            };
+
+            let mut matches = Vec::new();
+            for (n, index) in indices.enumerate() {
+                if n % RANK_CHUNK == 0 && latest.load(Ordering::Relaxed) != generation {
+                    return;
+                }
+                if let Some((score, positions)) = fuzzy_match(&query, &files[index]) {
+                    matches.push(Match { index, score, positions, snippet: None });
+                }
+            }
+            matches.sort_by(|a, b| {
+                b.score
+                    .cmp(&a.score)
+                    .then_with(|| files[a.index].len().cmp(&files[b.index].len()))
+            });
+            let _ = sender.send(FinderMessage::Ranked { generation, query, matches });
+        });
+    }
+
+    fn shown(&self) -> &[Match] {
+        &self.matches[..self.matches.len().min(MAX_SHOWN)]
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+
+        let (title, total) = match self.mode {
+            FinderMode::Files => ("Find file", self.files.len()),
+            FinderMode::Panes => ("Switch pane", self.panes.len()),
+        };
+        let mut status = format!("{}/{}", self.matches.len(), total);
+        if self.mode == FinderMode::Files && (self.indexing || self.ranking) {
+            status.push_str(if self.indexing { ", indexing…" } else { ", ranking…" });
+        }
+        let block = Block::default()
+            .title(format!("{} ({})", title, status))
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Black).bg(Color::White));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(1), Constraint::Length(2)])
+            .split(inner);
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("> ", Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)),
+                Span::raw(self.query.clone()),
+                Span::styled("█", Style::default().fg(Color::Blue)),
+            ])),
+            chunks[0],
+        );
+
+        let items: Vec<ListItem> = self.shown().iter().map(|m| self.item(m)).collect();
+        let mut state = ListState::default();
+        if !items.is_empty() {
+            state.select(Some(self.selected));
+        }
+        frame.render_stateful_widget(
+            List::new(items).highlight_style(Style::default().bg(Color::LightBlue)),
+            chunks[1],
+            &mut state,
+        );
+
+        let key = |k: &'static str| Span::styled(k, Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
+        let other = match self.mode {
+            FinderMode::Files => " panes  ",
+            FinderMode::Panes => " files  ",
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                key("Enter"), Span::raw(" open  "),
+                key("↑/↓"), Span::raw(" select  "),
+                key("Tab"), Span::raw(other),
+                key("Esc"), Span::raw(" close"),
+            ]))
+            .block(Block::default().borders(Borders::TOP)),
+            chunks[2],
+        );
+    }
+
+    fn item(&self, m: &Match) -> ListItem<'static> {
+        let text = match self.mode {
+            FinderMode::Files => self.files.get(m.index).cloned().unwrap_or_default(),
+            FinderMode::Panes => self.panes.get(m.index).map(pane_text).unwrap_or_default(),
+        };
+
+        let highlight = Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD);
+        let mut spans: Vec<Span> = text
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let style = if m.snippet.is_none() && m.positions.contains(&i) {
+                    highlight
+                } else {
+                    Style::default()
+                };
+                Span::styled(c.to_string(), style)
+            })
+            .collect();
+        if let Some(snippet) = &m.snippet {
+            spans.push(Span::styled(
+                format!("  │ {}", snippet.trim()),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        ListItem::new(Line::from(spans))
+    }
+}
+
+/// What a pane is listed and matched as
+fn pane_text(pane: &FinderPane) -> String {
+    match &pane.branch {
+        Some(branch) => format!("{}: {}  [{}]  {}", pane.number, pane.title, branch, pane.working_dir.display()),
+        None => format!("{}: {}  {}", pane.number, pane.title, pane.working_dir.display()),
+    }
+}
+
+/// Rank panes by title, branch and directory, falling back to their recent
+/// output. Panes are few, so this runs inline.
+fn rank_panes(panes: &[FinderPane], query: &str) -> Vec<Match> {
+    let mut matches: Vec<Match> = panes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, pane)| {
+            if let Some((score, positions)) = fuzzy_match(query, &pane_text(pane)) {
+                return Some(Match { index, score, positions, snippet: None });
+            }
+            // Output only counts when it holds the query as typed
+            let needle = query.to_lowercase();
+            pane.output
+                .iter()
+                .rev()
+                .find(|line| line.to_lowercase().contains(&needle))
+                .map(|line| Match {
+                    index,
+                    score: 0,
+                    positions: Vec::new(),
+                    snippet: Some(line.clone()),
+                })
+        })
+        .collect();
+    matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+    matches
+}
+
+/// Score `text` against `query` as a subsequence, or `None` if it isn't one.
+/// Matches that start words, run together or fall in the file name score
+/// higher. Lowercase queries ignore case.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let ignore_case = !query.chars().any(char::is_uppercase);
+    let fold = |c: char| if ignore_case { c.to_ascii_lowercase() } else { c };
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).map(fold).collect();
+    let chars: Vec<char> = text.chars().collect();
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    // Find the first window that holds the whole query...
+    let mut q = 0;
+    let mut end = None;
+    for (i, &c) in chars.iter().enumerate() {
+        if fold(c) == query[q] {
+            q += 1;
+            if q == query.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    // ...then walk back from its end to tighten it
+    let mut positions = Vec::with_capacity(query.len());
+    let mut q = query.len();
+    for i in (0..=end).rev() {
+        if q > 0 && fold(chars[i]) == query[q - 1] {
+            q -= 1;
+            positions.push(i);
+        }
+    }
+    positions.reverse();
+
+    let basename_start = chars.iter().rposition(|&c| c == '/').map_or(0, |i| i + 1);
+    let mut score = 0i64;
+    for (n, &i) in positions.iter().enumerate() {
+        score += 16;
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let at_boundary = match prev {
+            None => true,
+            Some(p) => matches!(p, '/' | '_' | '-' | '.' | ' ') || (p.is_lowercase() && chars[i].is_uppercase()),
+        };
+        if at_boundary {
+            score += 10;
+        }
+        if n > 0 && positions[n - 1] + 1 == i {
+            score += 8;
+        } else if n > 0 {
+            score -= (i - positions[n - 1] - 1).min(8) as i64;
+        }
+        if i >= basename_start {
+            score += 4;
+        }
+    }
+    Some((score, positions))
+}
+
+/// Every file under `root` that git doesn't ignore, relative to `root`,
+/// handed to `emit` in chunks until it returns false
+fn list_files(root: &Path, mut emit: impl FnMut(Vec<String>) -> bool) {
+    let Some((repo, prefix)) = Repository::discover(root).ok().and_then(|repo| {
+        let workdir = repo.workdir()?.canonicalize().ok()?;
+        let prefix = root.canonicalize().ok()?.strip_prefix(&workdir).ok()?.to_path_buf();
+        Some((repo, prefix))
+    }) else {
+        walk(root, root, &mut Vec::new(), &mut emit);
+        return;
+    };
+
+    let relative = |path: &str| -> Option<String> {
+        let path = Path::new(path).strip_prefix(&prefix).ok()?;
+        Some(path.to_string_lossy().to_string())
+    };
+
+    // Tracked files straight from the index, which is quick even when huge
+    let mut chunk = Vec::with_capacity(INDEX_CHUNK);
+    if let Ok(index) = repo.index() {
+        for entry in index.iter() {
+            let path = String::from_utf8_lossy(&entry.path).to_string();
+            let Some(path) = relative(&path) else {
+                continue;
+            };
+            if !root.join(&path).exists() {
+                continue;
+            }
+            chunk.push(path);
+            if chunk.len() == INDEX_CHUNK && !emit(std::mem::take(&mut chunk)) {
+                return;
+            }
+        }
+    }
+    if !chunk.is_empty() && !emit(std::mem::take(&mut chunk)) {
+        return;
+    }
+
+    // Then untracked files that aren't ignored
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false)
+        .exclude_submodules(true);
+    if let Ok(statuses) = repo.statuses(Some(&mut options)) {
+        let untracked: Vec<String> = statuses
+            .iter()
+            .filter(|entry| entry.status().contains(Status::WT_NEW))
+            .filter_map(|entry| relative(entry.path()?))
+            .collect();
+        if !untracked.is_empty() {
+            emit(untracked);
+        }
+    };
+}
+
+/// Directory walk for roots outside a repository; skips hidden entries
+fn walk(root: &Path, dir: &Path, chunk: &mut Vec<String>, emit: &mut impl FnMut(Vec<String>) -> bool) -> bool {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return true;
+    };
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if !walk(root, &path, chunk, emit) {
+                return false;
+            }
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            chunk.push(relative.to_string_lossy().to_string());
+            if chunk.len() == INDEX_CHUNK && !emit(std::mem::take(chunk)) {
+                return false;
+            }
+        }
+    }
+    if dir == root && !chunk.is_empty() {
+        return emit(std::mem::take(chunk));
+    }
+    true
+}
@@ -1,16 +1,36 @@
 use crate::git::GitStatus;
+use crate::monitor::{ChangeType, FileChange};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem},
+    Frame,
+};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+const MAX_RECENT_CHANGES: usize = 50;
 
 pub struct GitStatusComponent {
     status: GitStatus,
+    recent: VecDeque<FileChange>,
     show_staged: bool,
     show_modified: bool,
     show_untracked: bool,
 }
 
+impl Default for GitStatusComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GitStatusComponent {
     pub fn new() -> Self {
         Self {
             status: GitStatus::default(),
+            recent: VecDeque::new(),
             show_staged: true,
             show_modified: true,
             show_untracked: true,
@@ -21,6 +41,13 @@ impl GitStatusComponent {
         self.status = status;
     }
 
+    /// Newest first; repeated changes to one file collapse into the latest
+    pub fn record_change(&mut self, change: FileChange) {
+        self.recent.retain(|c| c.file_path != change.file_path);
+        self.recent.push_front(change);
+        self.recent.truncate(MAX_RECENT_CHANGES);
+    }
+
     pub fn toggle_staged(&mut self) {
         self.show_staged = !self.show_staged;
     }
@@ -32,4 +59,60 @@ impl GitStatusComponent {
     pub fn toggle_untracked(&mut self) {
         self.show_untracked = !self.show_untracked;
     }
-}
\ No newline at end of file
+
+    /// Paths in recent changes are shown relative to `root`
+    pub fn draw(&self, frame: &mut Frame, area: Rect, root: &Path) {
+        let block = Block::default()
+            .title("Git")
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Black).bg(Color::White));
+
+        let heading = |text: &'static str| {
+            ListItem::new(Span::styled(text, Style::default().add_modifier(Modifier::BOLD)))
+        };
+        let entry = |marker: &'static str, color: Color, path: String| {
+            ListItem::new(Line::from(vec![
+                Span::styled(marker, Style::default().fg(color)),
+                Span::raw(path),
+            ]))
+        };
+
+        let mut items = vec![heading("Changes:")];
+        let status = &self.status;
+        let sections: [(bool, &Vec<PathBuf>, &'static str, Color); 5] = [
+            (true, &status.conflicted_files, "U ", Color::Red),
+            (self.show_staged, &status.staged_files, "A ", Color::Green),
+            (self.show_modified, &status.modified_files, "M ", Color::Yellow),
+            (self.show_modified, &status.deleted_files, "D ", Color::Red),
+            (self.show_untracked, &status.untracked_files, "? ", Color::DarkGray),
+        ];
+        let before = items.len();
+        for (shown, files, marker, color) in sections {
+            if shown {
+                items.extend(files.iter().map(|f| entry(marker, color, f.display().to_string())));
+            }
+        }
+        if items.len() == before {
+            items.push(ListItem::new(Span::styled("  clean", Style::default().fg(Color::DarkGray))));
+        }
+
+        items.push(ListItem::new(""));
+        items.push(heading("Recent:"));
+        let relative = |path: &Path| path.strip_prefix(root).unwrap_or(path).display().to_string();
+        for change in &self.recent {
+            let item = match &change.change_type {
+                ChangeType::Created => entry("+ ", Color::Green, relative(&change.file_path)),
+                ChangeType::Modified => entry("~ ", Color::Yellow, relative(&change.file_path)),
+                ChangeType::Deleted => entry("- ", Color::Red, relative(&change.file_path)),
+                ChangeType::Renamed { from, to } => entry(
+                    "→ ",
+                    Color::Blue,
+                    format!("{} → {}", relative(from), relative(to)),
+                ),
+            };
+            items.push(item);
+        }
+
+        frame.render_widget(List::new(items).block(block), area);
+    }
+}
@@ -1,11 +1,35 @@
 // UI components module
 // This module will contain reusable UI components
 
+pub mod command_line;
+pub mod fanout_board;
 pub mod file_tree;
+pub mod fuzzy_finder;
 pub mod git_status;
 pub mod commit_dialog;
+pub mod close_prompt;
+pub mod conflict_view;
+pub mod diff_view;
+pub mod notifications;
+pub mod process_inspector;
+pub mod resource_monitor;
+pub mod timeline;
+pub mod worktree_dashboard;
+pub mod worktree_manager;
 
 // Re-exports
-pub use file_tree::FileTreeComponent;
+pub use command_line::CommandLineComponent;
+pub use fanout_board::FanoutBoardComponent;
+pub use file_tree::{FileStatus, FileTreeComponent, TreeDecorations};
+pub use fuzzy_finder::{FinderChoice, FinderMode, FinderPane, FuzzyFinderComponent};
 pub use git_status::GitStatusComponent;
-pub use commit_dialog::CommitDialog;
\ No newline at end of file
+pub use commit_dialog::CommitDialog;
+pub use close_prompt::ClosePromptComponent;
+pub use conflict_view::ConflictViewComponent;
+pub use diff_view::DiffViewComponent;
+pub use notifications::{NotificationLevel, NotificationsComponent};
+pub use process_inspector::ProcessInspectorComponent;
+pub use resource_monitor::{ResourceMonitorComponent, ResourceRow};
+pub use timeline::TimelineComponent;
+pub use worktree_dashboard::WorktreeDashboardComponent;
+pub use worktree_manager::WorktreeManagerComponent;
@@ -0,0 +1,96 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const MAX_NOTIFICATIONS: usize = 4;
+const NOTIFICATION_WIDTH: u16 = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+struct Notification {
+    level: NotificationLevel,
+    message: String,
+    created: Instant,
+}
+
+/// Short-lived toasts stacked in the top-right corner of the body
+pub struct NotificationsComponent {
+    items: VecDeque<Notification>,
+    timeout: Duration,
+}
+
+impl Default for NotificationsComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NotificationsComponent {
+    pub fn new() -> Self {
+        Self {
+            items: VecDeque::new(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    pub fn push(&mut self, level: NotificationLevel, message: String) {
+        if self.items.len() == MAX_NOTIFICATIONS {
+            self.items.pop_front();
+        }
+        self.items.push_back(Notification {
+            level,
+            message,
+            created: Instant::now(),
+        });
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let timeout = self.timeout;
+        self.items.retain(|n| n.created.elapsed() < timeout);
+
+        let width = NOTIFICATION_WIDTH.min(area.width);
+        let mut y = area.y;
+
+        for notification in self.items.iter().rev() {
+            let inner_width = width.saturating_sub(2).max(1) as usize;
+            let lines = notification.message.chars().count().div_ceil(inner_width).clamp(1, 3) as u16;
+            let height = lines + 2;
+            if y + height > area.y + area.height {
+                break;
+            }
+
+            let (title, color) = match notification.level {
+                NotificationLevel::Info => ("Info", Color::Blue),
+                NotificationLevel::Warning => ("Warning", Color::Rgb(160, 80, 0)),
+                NotificationLevel::Error => ("Error", Color::Red),
+            };
+
+            let rect = Rect::new(area.x + area.width - width, y, width, height);
+            frame.render_widget(Clear, rect);
+            frame.render_widget(
+                Paragraph::new(notification.message.as_str())
+                    .wrap(Wrap { trim: true })
+                    .style(Style::default().fg(Color::Black).bg(Color::White))
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(color))
+                            .title(title),
+                    ),
+                rect,
+            );
+
+            y += height;
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use super::resource_monitor::format_bytes;
+use crate::monitor::PaneProcesses;
+use crate::workspace::TerminalId;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
+};
+
+/// Popup of a pane's processes, with signals for its foreground job or
+/// whole tree
+pub struct ProcessInspectorComponent {
+    terminal_id: Option<TerminalId>,
+    title: String,
+    processes: PaneProcesses,
+    /// Signals go to every process of the tree rather than the foreground job
+    whole_tree: bool,
+    selected: usize,
+    is_open: bool,
+}
+
+impl Default for ProcessInspectorComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcessInspectorComponent {
+    pub fn new() -> Self {
+        Self {
+            terminal_id: None,
+            title: String::new(),
+            processes: PaneProcesses::default(),
+            whole_tree: false,
+            selected: 0,
+            is_open: false,
+        }
+    }
+
+    pub fn open(&mut self, terminal_id: TerminalId, title: String, processes: PaneProcesses) {
+        self.terminal_id = Some(terminal_id);
+        self.title = title;
+        self.whole_tree = false;
+        self.selected = 0;
+        self.is_open = true;
+        self.refresh(processes);
+    }
+
+    pub fn refresh(&mut self, processes: PaneProcesses) {
+        self.processes = processes;
+        self.selected = self.selected.min(self.processes.processes.len().saturating_sub(1));
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.terminal_id = None;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn terminal_id(&self) -> Option<TerminalId> {
+        self.terminal_id
+    }
+
+    pub fn processes(&self) -> &PaneProcesses {
+        &self.processes
+    }
+
+    pub fn is_whole_tree(&self) -> bool {
+        self.whole_tree
+    }
+
+    pub fn toggle_target(&mut self) {
+        self.whole_tree = !self.whole_tree;
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.processes.processes.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!("Processes of {}", self.title))
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Black).bg(Color::White));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(3),
+                Constraint::Length(3),
+                Constraint::Length(2),
+            ])
+            .split(inner);
+
+        let info = &self.processes;
+        let dim = Style::default().fg(Color::DarkGray);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let foreground = match info.foreground {
+            Some(group) => {
+                let leader = info
+                    .processes
+                    .iter()
+                    .find(|p| p.pid == group)
+                    .or_else(|| info.foreground_job().next());
+                match leader {
+                    Some(leader) if leader.pid == info.root => format!("{} (the pane's shell, at its prompt)", group),
+                    Some(leader) => format!("{} ({})", group, leader.command),
+                    None => group.to_string(),
+                }
+            }
+            None => "unknown".to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(vec![Span::styled("PID ", dim), Span::raw(info.root.to_string())]),
+                Line::from(vec![Span::styled("Foreground job ", dim), Span::styled(foreground, bold)]),
+                Line::from(vec![
+                    Span::styled("Directory ", dim),
+                    Span::raw(info.cwd.as_ref().map(|d| d.display().to_string()).unwrap_or_default()),
+                ]),
+            ]),
+            chunks[0],
+        );
+
+        let header = Row::new(vec!["PID", "S", "CPU%", "RSS", "Command"]).style(bold);
+        let rows: Vec<Row> = info
+            .processes
+            .iter()
+            .map(|p| {
+                let in_job = Some(p.process_group) == info.foreground;
+                let state_style = match p.state {
+                    'T' | 't' => Style::default().fg(Color::Rgb(160, 80, 0)),
+                    'Z' => Style::default().fg(Color::Red),
+                    'R' => Style::default().fg(Color::Green),
+                    _ => dim,
+                };
+                let marker = if in_job { "▶ " } else { "  " };
+                Row::new(vec![
+                    Cell::from(p.pid.to_string()),
+                    Cell::from(p.state.to_string()).style(state_style),
+                    Cell::from(p.cpu_percent.map(|c| format!("{:.1}", c)).unwrap_or_default()),
+                    Cell::from(format_bytes(p.rss_bytes)),
+                    Cell::from(format!("{}{}{}", "  ".repeat(p.depth), marker, p.command)),
+                ])
+                .style(if in_job { bold } else { Style::default() })
+            })
+            .collect();
+        let widths = [
+            Constraint::Length(8),
+            Constraint::Length(2),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Min(20),
+        ];
+        let mut state = TableState::default();
+        if !info.processes.is_empty() {
+            state.select(Some(self.selected));
+        }
+        frame.render_stateful_widget(
+            Table::new(rows, widths)
+                .header(header)
+                .row_highlight_style(Style::default().bg(Color::LightBlue)),
+            chunks[1],
+            &mut state,
+        );
+
+        let ports = if info.ports.is_empty() {
+            vec![Span::styled("No listening ports", dim)]
+        } else {
+            let mut spans = vec![Span::styled("Listening ", dim)];
+            for port in &info.ports {
+                spans.push(Span::styled(format!("{}:{}", port.address, port.port), bold));
+                spans.push(Span::styled(format!(" {} pid {}   ", port.protocol, port.pid), dim));
+            }
+            spans
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(ports))
+                .wrap(Wrap { trim: true })
+                .block(Block::default().borders(Borders::TOP)),
+            chunks[2],
+        );
+
+        let key = |k: &'static str| Span::styled(k, Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
+        let target = if self.whole_tree { "whole tree" } else { "foreground job" };
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                key("Tab"), Span::raw(" signal "), Span::styled(target, bold), Span::raw(":  "),
+                key("i"), Span::raw(" INT  "),
+                key("t"), Span::raw(" TERM  "),
+                key("K"), Span::raw(" KILL  "),
+                key("s"), Span::raw(" STOP  "),
+                key("c"), Span::raw(" CONT    "),
+                key("r"), Span::raw(" refresh  "),
+                key("q"), Span::raw(" close"),
+            ]))
+            .block(Block::default().borders(Borders::TOP)),
+            chunks[3],
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use crate::monitor::{PaneUsage, SystemLoad};
+use crate::workspace::TerminalId;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+/// One pane in `:top`
+#[derive(Debug, Clone)]
+pub struct ResourceRow {
+    pub terminal_id: TerminalId,
+    /// Position in its workspace, as F1-F10 and Alt+G count
+    pub number: usize,
+    pub title: String,
+    pub workspace: String,
+    pub branch: Option<String>,
+    pub usage: PaneUsage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortBy {
+    Pane,
+    Cpu,
+    Memory,
+}
+
+/// `:top`: CPU and memory of every pane's processes, and of the machine
+pub struct ResourceMonitorComponent {
+    rows: Vec<ResourceRow>,
+    system: SystemLoad,
+    overloaded: bool,
+    sort: SortBy,
+    selected: usize,
+    is_open: bool,
+}
+
+impl Default for ResourceMonitorComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourceMonitorComponent {
+    pub fn new() -> Self {
+        Self {
+            rows: Vec::new(),
+            system: SystemLoad::default(),
+            overloaded: false,
+            sort: SortBy::Cpu,
+            selected: 0,
+            is_open: false,
+        }
+    }
+
+    pub fn open(&mut self, rows: Vec<ResourceRow>, system: SystemLoad, overloaded: bool) {
+        self.is_open = true;
+        self.selected = 0;
+        self.refresh(rows, system, overloaded);
+    }
+
+    /// New figures, keeping the selected pane selected
+    pub fn refresh(&mut self, rows: Vec<ResourceRow>, system: SystemLoad, overloaded: bool) {
+        let selected = self.selected_terminal();
+        self.rows = rows;
+        self.system = system;
+        self.overloaded = overloaded;
+        self.sort_rows();
+        self.selected = selected
+            .and_then(|id| self.rows.iter().position(|r| r.terminal_id == id))
+            .unwrap_or(self.selected)
+            .min(self.rows.len().saturating_sub(1));
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.rows.clear();
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn selected_terminal(&self) -> Option<TerminalId> {
+        self.rows.get(self.selected).map(|r| r.terminal_id)
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.rows.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Sort by CPU, then memory, then pane order
+    pub fn cycle_sort(&mut self) {
+        self.sort = match self.sort {
+            SortBy::Cpu => SortBy::Memory,
+            SortBy::Memory => SortBy::Pane,
+            SortBy::Pane => SortBy::Cpu,
+        };
+        let selected = self.selected_terminal();
+        self.sort_rows();
+        if let Some(index) = selected.and_then(|id| self.rows.iter().position(|r| r.terminal_id == id)) {
+            self.selected = index;
+        }
+    }
+
+    fn sort_rows(&mut self) {
+        match self.sort {
+            // Rows arrive in pane order
+            SortBy::Pane => {}
+            SortBy::Cpu => self.rows.sort_by(|a, b| b.usage.cpu_percent.total_cmp(&a.usage.cpu_percent)),
+            SortBy::Memory => self.rows.sort_by_key(|r| std::cmp::Reverse(r.usage.rss_bytes)),
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+
+        let sort = match self.sort {
+            SortBy::Pane => "pane",
+            SortBy::Cpu => "CPU",
+            SortBy::Memory => "memory",
+        };
+        let block = Block::default()
+            .title(format!("Resources ({} panes, by {})", self.rows.len(), sort))
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Black).bg(Color::White));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(3), Constraint::Length(2)])
+            .split(inner);
+
+        let system = &self.system;
+        let system_style = if self.overloaded {
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let total_cpu: f64 = self.rows.iter().map(|r| r.usage.cpu_percent).sum();
+        let total_rss: u64 = self.rows.iter().map(|r| r.usage.rss_bytes).sum();
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(Span::styled(
+                    format!(
+                        "Load {:.2} on {} CPUs   Memory {} of {} used ({:.0}%)",
+                        system.load,
+                        system.cpus,
+                        format_bytes(system.memory_total - system.memory_available.min(system.memory_total)),
+                        format_bytes(system.memory_total),
+                        system.memory_used_percent(),
+                    ),
+                    system_style,
+                )),
+                Line::from(Span::styled(
+                    format!("Panes use {:.0}% CPU and {}", total_cpu, format_bytes(total_rss)),
+                    Style::default().fg(Color::DarkGray),
+                )),
+            ]),
+            chunks[0],
+        );
+
+        let header = Row::new(vec!["#", "Pane", "Workspace", "Branch", "Procs", "CPU%", "RSS"])
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .bottom_margin(1);
+
+        let rows: Vec<Row> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let usage = &row.usage;
+                // A pane busy on more than one core stands out
+                let cpu_style = match usage.cpu_percent {
+                    c if c >= 100.0 => Style::default().fg(Color::Red),
+                    c if c >= 25.0 => Style::default().fg(Color::Rgb(160, 80, 0)),
+                    _ => Style::default().fg(Color::DarkGray),
+                };
+                Row::new(vec![
+                    Cell::from(row.number.to_string()),
+                    Cell::from(row.title.clone()),
+                    Cell::from(row.workspace.clone()),
+                    Cell::from(row.branch.clone().unwrap_or_default()).style(Style::default().fg(Color::Blue)),
+                    Cell::from(usage.processes.to_string()),
+                    Cell::from(format!("{:.1}", usage.cpu_percent)).style(cpu_style),
+                    Cell::from(format_bytes(usage.rss_bytes)),
+                ])
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Length(4),
+            Constraint::Min(16),
+            Constraint::Length(14),
+            Constraint::Min(20),
+            Constraint::Length(6),
+            Constraint::Length(7),
+            Constraint::Length(10),
+        ];
+
+        let mut state = TableState::default();
+        if !self.rows.is_empty() {
+            state.select(Some(self.selected));
+        }
+        frame.render_stateful_widget(
+            Table::new(rows, widths)
+                .header(header)
+                .row_highlight_style(Style::default().bg(Color::LightBlue)),
+            chunks[1],
+            &mut state,
+        );
+
+        let key = |k: &'static str| Span::styled(k, Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                key("Enter"), Span::raw(" focus pane  "),
+                key("s"), Span::raw(" sort  "),
+                key("q"), Span::raw(" close"),
+            ]))
+            .block(Block::default().borders(Borders::TOP)),
+            chunks[2],
+        );
+    }
+}
+
+/// Bytes in the largest unit that keeps them above one
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "K", "M", "G"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}{}", bytes, UNITS[0])
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
@@ -0,0 +1,209 @@
+use crate::monitor::ChangeType;
+use crate::workspace::{TerminalId, TimelineEntry, TimelineEvent};
+use chrono::Local;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use std::path::{Path, PathBuf};
+
+/// A pane the timeline can name and filter by: id, title and working dir
+pub type TimelinePane = (TerminalId, String, PathBuf);
+
+/// `:timeline` of file changes, commits, merges and terminal events
+pub struct TimelineComponent {
+    entries: Vec<TimelineEntry>,
+    panes: Vec<TimelinePane>,
+    /// Project directory, for showing paths outside any pane's directory
+    root: PathBuf,
+    filter: Option<TerminalId>,
+    selected: usize,
+    is_open: bool,
+}
+
+impl Default for TimelineComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimelineComponent {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            panes: Vec::new(),
+            root: PathBuf::new(),
+            filter: None,
+            selected: 0,
+            is_open: false,
+        }
+    }
+
+    /// Entries come newest first
+    pub fn open(&mut self, entries: Vec<TimelineEntry>, panes: Vec<TimelinePane>, root: PathBuf) {
+        self.is_open = true;
+        self.selected = 0;
+        self.root = root;
+        self.refresh(entries, panes);
+    }
+
+    pub fn refresh(&mut self, entries: Vec<TimelineEntry>, panes: Vec<TimelinePane>) {
+        self.entries = entries;
+        self.panes = panes;
+        if self.filter.is_some_and(|id| !self.panes.iter().any(|(p, _, _)| *p == id)) {
+            self.filter = None;
+        }
+        self.selected = self.selected.min(self.visible().len().saturating_sub(1));
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.entries.clear();
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    /// Step the filter through all panes, then back to everything
+    pub fn cycle_filter(&mut self) {
+        let next = match self.filter {
+            None => 0,
+            Some(id) => self.panes.iter().position(|(p, _, _)| *p == id).map_or(0, |i| i + 1),
+        };
+        self.filter = self.panes.get(next).map(|(id, _, _)| *id);
+        self.selected = 0;
+    }
+
+    pub fn selected_entry(&self) -> Option<&TimelineEntry> {
+        self.visible().get(self.selected).copied()
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.visible().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    fn visible(&self) -> Vec<&TimelineEntry> {
+        self.entries
+            .iter()
+            .filter(|e| self.filter.is_none() || e.terminal_id == self.filter)
+            .collect()
+    }
+
+    fn pane(&self, terminal_id: Option<TerminalId>) -> Option<&TimelinePane> {
+        self.panes.iter().find(|(id, _, _)| Some(*id) == terminal_id)
+    }
+
+    /// Shortest path relative to a pane's directory or the project
+    fn display_path(&self, path: &Path) -> String {
+        self.panes
+            .iter()
+            .map(|(_, _, dir)| dir.as_path())
+            .chain(std::iter::once(self.root.as_path()))
+            .filter_map(|dir| path.strip_prefix(dir).ok())
+            .min_by_key(|relative| relative.components().count())
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+
+        let filter = match self.filter {
+            Some(id) => self.pane(Some(id)).map(|(_, t, _)| t.clone()).unwrap_or_default(),
+            None => "all panes".to_string(),
+        };
+        let visible = self.visible();
+        let block = Block::default()
+            .title(format!("Timeline ({}, {})", filter, visible.len()))
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Black).bg(Color::White));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(2)])
+            .split(inner);
+
+        let items: Vec<ListItem> = if visible.is_empty() {
+            vec![ListItem::new(Span::styled("No activity yet", Style::default().fg(Color::DarkGray)))]
+        } else {
+            visible.iter().map(|entry| self.item(entry)).collect()
+        };
+
+        let mut state = ListState::default();
+        if !visible.is_empty() {
+            state.select(Some(self.selected));
+        }
+        frame.render_stateful_widget(
+            List::new(items).highlight_style(Style::default().bg(Color::LightBlue)),
+            chunks[0],
+            &mut state,
+        );
+
+        let key = |k: &'static str| Span::styled(k, Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                key("Enter"), Span::raw(" jump  "),
+                key("d"), Span::raw(" diff  "),
+                key("f"), Span::raw(" filter by pane  "),
+                key("r"), Span::raw(" refresh  "),
+                key("q"), Span::raw(" close"),
+            ]))
+            .block(Block::default().borders(Borders::TOP)),
+            chunks[1],
+        );
+    }
+
+    fn item(&self, entry: &TimelineEntry) -> ListItem<'static> {
+        let time = Local::now() - chrono::Duration::from_std(entry.time.elapsed()).unwrap_or_default();
+        let pane = self
+            .pane(entry.terminal_id)
+            .map(|(_, title, _)| title.clone())
+            .unwrap_or_else(|| "-".to_string());
+
+        let (marker, color, text) = match &entry.event {
+            TimelineEvent::FileChanged { path, change } => match change {
+                ChangeType::Created => ("+", Color::Green, self.display_path(path)),
+                ChangeType::Modified => ("~", Color::Rgb(150, 110, 0), self.display_path(path)),
+                ChangeType::Deleted => ("-", Color::Red, self.display_path(path)),
+                ChangeType::Renamed { from, to } => (
+                    "→",
+                    Color::Blue,
+                    format!("{} → {}", self.display_path(from), self.display_path(to)),
+                ),
+            },
+            TimelineEvent::Commit { commit, summary } => {
+                ("●", Color::Magenta, format!("commit {} {}", short(commit), summary))
+            }
+            TimelineEvent::Merged { branch, commit } => {
+                ("⇡", Color::Green, format!("merged {} ({})", branch, short(commit)))
+            }
+            TimelineEvent::TerminalCreated { title } => ("▶", Color::Blue, format!("{} started", title)),
+            TimelineEvent::TerminalExited { title } => ("■", Color::DarkGray, format!("{} exited", title)),
+            TimelineEvent::TerminalClosed { title } => ("✕", Color::DarkGray, format!("{} closed", title)),
+        };
+
+        ListItem::new(Line::from(vec![
+            Span::styled(time.format("%H:%M:%S ").to_string(), Style::default().fg(Color::DarkGray)),
+            Span::styled(format!("{:<14} ", pane), Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(format!("{} ", marker), Style::default().fg(color)),
+            Span::raw(text),
+        ]))
+    }
+}
+
+fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(8)]
+}
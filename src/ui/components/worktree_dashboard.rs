@@ -0,0 +1,221 @@
+use crate::git::{DiscoveredWorktree, MergeStatus, WorktreeOverview, WorktreeState};
+use crate::workspace::TerminalId;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+use std::time::Duration;
+
+/// Full-screen overview of every terminal worktree, followed by the
+/// worktrees no terminal is attached to
+pub struct WorktreeDashboardComponent {
+    rows: Vec<(String, WorktreeOverview)>,
+    unattached: Vec<(DiscoveredWorktree, Option<WorktreeState>)>,
+    selected: usize,
+    is_open: bool,
+}
+
+impl Default for WorktreeDashboardComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorktreeDashboardComponent {
+    pub fn new() -> Self {
+        Self {
+            rows: Vec::new(),
+            unattached: Vec::new(),
+            selected: 0,
+            is_open: false,
+        }
+    }
+
+    /// Rows pair the owning pane's title with its worktree
+    pub fn open(
+        &mut self,
+        rows: Vec<(String, WorktreeOverview)>,
+        unattached: Vec<(DiscoveredWorktree, Option<WorktreeState>)>,
+    ) {
+        self.is_open = true;
+        self.refresh(rows, unattached);
+    }
+
+    pub fn refresh(
+        &mut self,
+        rows: Vec<(String, WorktreeOverview)>,
+        unattached: Vec<(DiscoveredWorktree, Option<WorktreeState>)>,
+    ) {
+        self.rows = rows;
+        self.unattached = unattached;
+        self.selected = self.selected.min(self.len().saturating_sub(1));
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.rows.clear();
+        self.unattached.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.rows.len() + self.unattached.len()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn selected_terminal(&self) -> Option<TerminalId> {
+        self.rows.get(self.selected).map(|(_, o)| o.info.terminal_id)
+    }
+
+    /// The selected worktree when no terminal is attached to it
+    pub fn selected_unattached(&self) -> Option<&DiscoveredWorktree> {
+        let index = self.selected.checked_sub(self.rows.len())?;
+        self.unattached.get(index).map(|(w, _)| w)
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!("Worktrees ({})", self.len()))
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Black).bg(Color::White));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(2)])
+            .split(inner);
+
+        let header = Row::new(vec![
+            "Pane", "Branch", "Ahead", "Behind", "Dirty", "Status", "Synced", "Last commit", "Path",
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+
+        let count = |n: usize, color: Color| {
+            let style = if n > 0 {
+                Style::default().fg(color)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Cell::from(n.to_string()).style(style)
+        };
+        let dim = Style::default().fg(Color::DarkGray);
+
+        let attached = self
+            .rows
+            .iter()
+            .map(|(pane, overview)| {
+                let info = &overview.info;
+                let (status, status_color) = match info.merge_status {
+                    MergeStatus::Unmerged => ("unmerged", Color::DarkGray),
+                    MergeStatus::Merged => ("merged", Color::Green),
+                    MergeStatus::Conflict { .. } => ("conflict", Color::Red),
+                };
+
+                Row::new(vec![
+                    Cell::from(pane.clone()),
+                    Cell::from(info.branch.clone()).style(Style::default().fg(Color::Blue)),
+                    count(info.ahead, Color::Green),
+                    count(info.behind, Color::Rgb(160, 80, 0)),
+                    count(overview.dirty_files, Color::Red),
+                    Cell::from(status).style(Style::default().fg(status_color)),
+                    Cell::from(format_elapsed(info.last_sync.elapsed())),
+                    Cell::from(overview.last_commit.clone().unwrap_or_default()),
+                    Cell::from(info.path.display().to_string()).style(dim),
+                ])
+            });
+        // Ahead is the branch's unmerged commits; behind isn't tracked
+        let unattached = self.unattached.iter().map(|(worktree, state)| {
+            let unknown = || Cell::from("?").style(dim);
+            Row::new(vec![
+                Cell::from("-").style(dim),
+                Cell::from(worktree.branch.clone().unwrap_or_else(|| "(detached)".to_string()))
+                    .style(Style::default().fg(Color::Blue)),
+                state.map_or_else(unknown, |s| count(s.unmerged_commits, Color::Green)),
+                Cell::from("-").style(dim),
+                state.map_or_else(unknown, |s| count(s.dirty_files, Color::Red)),
+                Cell::from("unattached").style(dim),
+                Cell::from("-").style(dim),
+                Cell::from(""),
+                Cell::from(worktree.path.display().to_string()).style(dim),
+            ])
+        });
+        let rows: Vec<Row> = attached.chain(unattached).collect();
+
+        let widths = [
+            Constraint::Length(14),
+            Constraint::Length(28),
+            Constraint::Length(6),
+            Constraint::Length(7),
+            Constraint::Length(6),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Min(20),
+            Constraint::Min(20),
+        ];
+
+        let mut state = TableState::default();
+        if self.len() > 0 {
+            state.select(Some(self.selected));
+        }
+        frame.render_stateful_widget(
+            Table::new(rows, widths)
+                .header(header)
+                .row_highlight_style(Style::default().bg(Color::LightBlue)),
+            chunks[0],
+            &mut state,
+        );
+
+        if self.len() == 0 {
+            let empty_area = Rect { y: chunks[0].y + 2, height: 1, ..chunks[0] };
+            frame.render_widget(
+                Paragraph::new("No terminal has a worktree").style(Style::default().fg(Color::DarkGray)),
+                empty_area,
+            );
+        }
+
+        let key = |k: &'static str| Span::styled(k, Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                key("Enter"), Span::raw(" focus pane or attach  "),
+                key("s"), Span::raw(" shell in worktree  "),
+                key("d"), Span::raw(" diff vs main  "),
+                key("m"), Span::raw(" merge  "),
+                key("x"), Span::raw(" close and delete  "),
+                key("r"), Span::raw(" refresh  "),
+                key("q"), Span::raw(" close"),
+            ]))
+            .block(Block::default().borders(Borders::TOP)),
+            chunks[1],
+        );
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        format!("{}s ago", secs)
+    } else if secs < 3600 {
+        format!("{}m ago", secs / 60)
+    } else {
+        format!("{}h ago", secs / 3600)
+    }
+}
@@ -0,0 +1,118 @@
+use crate::git::{DiscoveredWorktree, WorktreeState};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+/// `:worktrees` list of worktrees that no terminal is attached to
+pub struct WorktreeManagerComponent {
+    entries: Vec<(DiscoveredWorktree, Option<WorktreeState>)>,
+    selected: usize,
+    is_open: bool,
+}
+
+impl Default for WorktreeManagerComponent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorktreeManagerComponent {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            selected: 0,
+            is_open: false,
+        }
+    }
+
+    pub fn open(&mut self, entries: Vec<(DiscoveredWorktree, Option<WorktreeState>)>) {
+        self.is_open = true;
+        self.refresh(entries);
+    }
+
+    pub fn refresh(&mut self, entries: Vec<(DiscoveredWorktree, Option<WorktreeState>)>) {
+        self.entries = entries;
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.entries.clear();
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn selected(&self) -> Option<&DiscoveredWorktree> {
+        self.entries.get(self.selected).map(|(w, _)| w)
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.entries.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title("Kept worktrees [j/k:move Enter:attach d:remove D:force remove r:refresh q:close]")
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Black).bg(Color::White));
+
+        let items: Vec<ListItem> = if self.entries.is_empty() {
+            vec![ListItem::new(Span::styled(
+                "No kept worktrees",
+                Style::default().fg(Color::DarkGray),
+            ))]
+        } else {
+            self.entries
+                .iter()
+                .map(|(worktree, state)| {
+                    let branch = worktree.branch.clone().unwrap_or_else(|| "(detached)".to_string());
+                    let state_span = match state {
+                        Some(state) if state.is_clean() => {
+                            Span::styled("clean", Style::default().fg(Color::Green))
+                        }
+                        Some(state) => Span::styled(
+                            format!("{} dirty, {} unmerged", state.dirty_files, state.unmerged_commits),
+                            Style::default().fg(Color::Red),
+                        ),
+                        None => Span::styled("unknown", Style::default().fg(Color::DarkGray)),
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("{:<32} ", branch), Style::default().add_modifier(Modifier::BOLD)),
+                        state_span,
+                        Span::styled(
+                            format!("  {}", worktree.path.display()),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                    ]))
+                })
+                .collect()
+        };
+
+        let mut state = ListState::default();
+        if !self.entries.is_empty() {
+            state.select(Some(self.selected));
+        }
+
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(block)
+                .highlight_style(Style::default().bg(Color::LightBlue)),
+            area,
+            &mut state,
+        );
+    }
+}
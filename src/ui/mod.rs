@@ -1,70 +1,105 @@
 pub mod widgets;
 pub mod components;
+pub mod theme;
 
 use crate::app::AppState;
-use crate::config::AppConfig;
+use crate::git::{DiffHunk, DiffSide, DiscoveredWorktree, MergeStatus, PendingMerge, WorktreeOverview, WorktreeState};
 use crate::layout::LayoutEngine;
-use crate::workspace::{TerminalId, WorkspaceManager};
+use crate::monitor::{PaneProcesses, SystemLoad};
+use crate::terminal::OutputLink;
+use crate::workspace::{AlertKind, Fanout, TerminalId, WorkspaceManager};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Tabs},
+    widgets::{Block, BorderType, Borders, Paragraph},
     Frame,
 };
-use std::fs;
-use std::path::Path;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use theme::Theme;
 
 pub struct Ui {
-    command_buffer: String,
+    command_line: components::CommandLineComponent,
     error_message: Option<String>,
     show_help: bool,
     show_git_panel: bool,
     show_file_explorer: bool,
-    file_explorer_selected: usize,  // Index of selected item in file explorer
-    file_tree: Vec<FileTreeItem>,
-    file_explorer_area: Option<Rect>,  // Track the file explorer area for mouse clicks
+    file_tree: components::FileTreeComponent,
+    diff_view: components::DiffViewComponent,
+    conflict_view: components::ConflictViewComponent,
+    status_message: Option<String>,
+    notifications: components::NotificationsComponent,
+    close_prompt: components::ClosePromptComponent,
+    worktree_manager: components::WorktreeManagerComponent,
+    worktree_dashboard: components::WorktreeDashboardComponent,
+    fanout_board: components::FanoutBoardComponent,
+    resource_monitor: components::ResourceMonitorComponent,
+    process_inspector: components::ProcessInspectorComponent,
+    timeline: components::TimelineComponent,
+    finder: components::FuzzyFinderComponent,
+    git_status: components::GitStatusComponent,
+    /// Link under the mouse: terminal, screen row and columns
+    hovered_link: Option<(TerminalId, u16, Range<usize>)>,
+    /// Links labelled for picking by key in the given terminal
+    link_hints: Option<(TerminalId, Vec<LinkHint>)>,
+    /// Unseen monitor alert of each pane
+    alerts: HashMap<TerminalId, AlertKind>,
+    /// Workspace names for the tab bar, with whether one of their panes
+    /// has an alert, and the one shown
+    workspace_tabs: Vec<(String, bool)>,
+    active_workspace: usize,
+    /// Why the machine counts as overloaded, while it does
+    overload: Option<String>,
+    /// Digits typed after Alt+G, while picking a pane by number
+    pane_number: Option<String>,
+    theme: Theme,
+    border_type: BorderType,
 }
 
-#[derive(Clone, Debug)]
-struct FileTreeItem {
-    name: String,
-    is_dir: bool,
-    is_expanded: bool,
-    depth: usize,
-    path: String,
-}
+/// Key, screen row and link of one hint label
+type LinkHint = (char, u16, OutputLink);
 
-impl Ui {
-    pub fn new() -> Self {
-        // Build initial file tree - start with root directory
-        let mut file_tree = vec![
-            FileTreeItem {
-                name: "./".to_string(),
-                is_dir: true,
-                is_expanded: false,  // Start collapsed, expand on demand
-                depth: 0,
-                path: ".".to_string(),
-            },
-        ];
+/// Keys assigned to link hints, home row first
+const HINT_KEYS: &str = "asdfghjklqwertyuiopzxcvbnm";
 
-        // Try to load root directory contents initially
-        let mut ui = Self {
-            command_buffer: String::new(),
+/// Lines of each pane's output the pane finder searches
+const FINDER_OUTPUT_LINES: usize = 200;
+
+impl Ui {
+    /// `root` is the directory the file explorer starts in
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            command_line: components::CommandLineComponent::new(),
             error_message: None,
             show_help: false,
             show_git_panel: false,  // Hidden by default to save space
             show_file_explorer: true,  // Shown by default
-            file_explorer_selected: 0,
-            file_tree,
-            file_explorer_area: None,
-        };
-
-        // Expand root directory to show initial contents
-        ui.file_tree[0].is_expanded = true;
-        ui.load_directory_contents(0);
-
-        ui
+            file_tree: components::FileTreeComponent::new(root),
+            diff_view: components::DiffViewComponent::new(),
+            conflict_view: components::ConflictViewComponent::new(),
+            status_message: None,
+            notifications: components::NotificationsComponent::new(),
+            close_prompt: components::ClosePromptComponent::new(),
+            worktree_manager: components::WorktreeManagerComponent::new(),
+            worktree_dashboard: components::WorktreeDashboardComponent::new(),
+            fanout_board: components::FanoutBoardComponent::new(),
+            resource_monitor: components::ResourceMonitorComponent::new(),
+            process_inspector: components::ProcessInspectorComponent::new(),
+            timeline: components::TimelineComponent::new(),
+            finder: components::FuzzyFinderComponent::new(),
+            git_status: components::GitStatusComponent::new(),
+            hovered_link: None,
+            link_hints: None,
+            alerts: HashMap::new(),
+            workspace_tabs: Vec::new(),
+            active_workspace: 0,
+            overload: None,
+            pane_number: None,
+            theme: Theme::default(),
+            border_type: BorderType::Plain,
+        }
     }
 
     pub fn draw(
@@ -132,6 +167,39 @@ impl Ui {
             self.draw_git_panel(frame, body_chunks[git_index], workspace);
         }
 
+        // Diff pane covers the whole body while open
+        if self.diff_view.is_open() {
+            self.diff_view.draw(frame, chunks[1]);
+        }
+        if self.conflict_view.is_open() {
+            self.conflict_view.draw(frame, chunks[1]);
+        }
+        if self.worktree_dashboard.is_open() {
+            self.worktree_dashboard.draw(frame, chunks[1]);
+        }
+        if self.fanout_board.is_open() {
+            self.fanout_board.draw(frame, chunks[1]);
+        }
+        if self.resource_monitor.is_open() {
+            self.resource_monitor.draw(frame, chunks[1]);
+        }
+        if self.timeline.is_open() {
+            self.timeline.draw(frame, chunks[1]);
+        }
+        if self.worktree_manager.is_open() {
+            self.worktree_manager.draw(frame, chunks[1]);
+        }
+        if self.close_prompt.is_open() {
+            self.close_prompt.draw(frame, chunks[1]);
+        }
+        if self.finder.is_open() {
+            self.finder.draw(frame, centered_rect(70, 60, chunks[1]));
+        }
+        if self.process_inspector.is_open() {
+            self.process_inspector.draw(frame, centered_rect(80, 70, chunks[1]));
+        }
+        self.notifications.draw(frame, chunks[1]);
+
         // Draw footer
         self.draw_footer(frame, chunks[2], state);
 
@@ -156,16 +224,51 @@ impl Ui {
         let terminal_count = terminals.len();
         let active_id = workspace.active_terminal_id();
 
-        let header_text = vec![
-            Span::raw("[Project: "),
-            Span::styled("rgb-workspace", Style::default().fg(Color::Blue)),
-            Span::raw("] "),
+        let mut header_text: Vec<Span> = self
+            .workspace_tabs
+            .iter()
+            .enumerate()
+            .map(|(i, (name, alerted))| {
+                let style = if i == self.active_workspace {
+                    Style::default().fg(Color::White).bg(Color::Blue).add_modifier(Modifier::BOLD)
+                } else if *alerted {
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                Span::styled(format!(" {}:{} ", i + 1, name), style)
+            })
+            .collect();
+        header_text.extend([
+            Span::raw(" "),
             Span::raw("[Terminals: "),
             Span::styled(
                 terminal_count.to_string(),
                 Style::default().fg(Color::DarkGray),
             ),
             Span::raw("] "),
+            if self.alerts.is_empty() {
+                Span::raw("")
+            } else {
+                Span::styled(
+                    format!("[Alerts: {}] ", self.alerts.len()),
+                    Style::default().fg(Color::White).bg(Color::Red),
+                )
+            },
+            match &self.overload {
+                Some(overload) => Span::styled(
+                    format!("[{}] ", overload),
+                    Style::default().fg(Color::White).bg(Color::Red),
+                ),
+                None => Span::raw(""),
+            },
+            match terminals.iter().filter(|t| t.synchronized).count() {
+                0 => Span::raw(""),
+                synced => Span::styled(
+                    format!("[Sync: {}] ", synced),
+                    Style::default().fg(Color::White).bg(Color::Magenta),
+                ),
+            },
             if let Some(id) = active_id {
                 Span::styled(
                     format!("[Active: {}]", &id.to_string()[..8]),
@@ -174,10 +277,9 @@ impl Ui {
             } else {
                 Span::raw("")
             },
-        ];
+        ]);
 
-        let header = Paragraph::new(Line::from(header_text))
-            .style(Style::default().bg(Color::Gray).fg(Color::Black));
+        let header = Paragraph::new(Line::from(header_text)).style(self.theme.bar);
 
         frame.render_widget(header, area);
     }
@@ -202,15 +304,54 @@ impl Ui {
 
         // Draw each terminal
         for (terminal_id, rect) in terminal_rects {
+            let accent = terminal_ids
+                .iter()
+                .position(|&id| id == terminal_id)
+                .map(terminal_color)
+                .unwrap_or(Color::Gray);
+
             tracing::trace!("Drawing terminal {:?} in rect {:?}", terminal_id, rect);
 
             if let Some(emulator) = workspace.get_terminal_emulator(terminal_id) {
                 let is_active = workspace.active_terminal_id() == Some(terminal_id);
                 tracing::trace!("Terminal is_active: {}", is_active);
 
+                let mut title = terminals
+                    .iter()
+                    .position(|t| t.id == terminal_id)
+                    .map(|i| format!("{}: {}", i + 1, pane_title(&terminals[i])))
+                    .unwrap_or_default();
+                if emulator.read().recording_path().is_some() {
+                    title.push_str(" ● REC");
+                }
+                if emulator.read().pipe_command().is_some() {
+                    title.push_str(" | PIPE");
+                }
+
+                let hovered = self
+                    .hovered_link
+                    .as_ref()
+                    .filter(|(id, _, _)| *id == terminal_id)
+                    .map(|(_, row, columns)| (*row, columns.clone()));
+                let hints = match &self.link_hints {
+                    Some((id, hints)) if *id == terminal_id => hints
+                        .iter()
+                        .map(|(key, row, link)| (*row, link.columns.start, *key))
+                        .collect(),
+                    _ => Vec::new(),
+                };
+
                 // Create terminal widget
                 let terminal_widget = widgets::TerminalWidget::new(emulator.clone())
-                    .active(is_active);
+                    .active(is_active)
+                    .accent(accent)
+                    .title(title)
+                    .hovered_link(hovered)
+                    .link_hints(hints)
+                    .alert(self.alerts.get(&terminal_id).copied())
+                    .theme(self.theme)
+                    .border_type(self.border_type)
+                    .synchronized(terminals.iter().any(|t| t.id == terminal_id && t.synchronized));
 
                 frame.render_widget(terminal_widget, rect);
                 tracing::trace!("Widget rendered for terminal {:?}", terminal_id);
@@ -220,90 +361,28 @@ impl Ui {
         }
     }
 
-    fn draw_file_explorer(&mut self, frame: &mut Frame, area: Rect, _workspace: &WorkspaceManager) {
-        // Store the area for mouse click handling
-        self.file_explorer_area = Some(area);
-        // First, fill the entire area with a light background
-        let bg_block = Block::default()
-            .style(Style::default().bg(Color::White));
-        frame.render_widget(bg_block, area);
-
-        let block = Block::default()
-            .title("Files [j/k:nav, Enter:open/expand, h/l:collapse/expand]")
-            .borders(Borders::ALL)
-            .style(Style::default()
-                .fg(Color::Black)
-                .bg(Color::White));
-
-        // Build visible items from file tree
-        let mut items = Vec::new();
-        for (idx, item) in self.file_tree.iter().enumerate() {
-            let indent = "  ".repeat(item.depth);
-            let icon = if item.is_dir {
-                if item.is_expanded { "▼" } else { "▶" }
-            } else {
-                "•"
-            };
-
-            let style = if idx == self.file_explorer_selected {
-                Style::default()
-                    .fg(Color::Blue)
-                    .bg(Color::LightBlue)
-                    .add_modifier(Modifier::BOLD)
-            } else if item.is_dir {
-                Style::default()
-                    .fg(Color::Blue)
-                    .bg(Color::White)
-            } else {
-                Style::default()
-                    .fg(Color::Black)
-                    .bg(Color::White)
-            };
-
-            items.push(ListItem::new(format!("{}{} {}", indent, icon, item.name)).style(style));
-        }
-
-        let list = List::new(items)
-            .block(block)
-            .style(Style::default()
-                .fg(Color::Black)
-                .bg(Color::White))
-            .highlight_style(
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .bg(Color::Gray),
-            )
-            .highlight_symbol("> ");
+    fn draw_file_explorer(&mut self, frame: &mut Frame, area: Rect, workspace: &WorkspaceManager) {
+        let terminal_ids: Vec<TerminalId> = workspace.terminals().iter().map(|t| t.id).collect();
+        let decorations = components::TreeDecorations {
+            conflicts: workspace.file_conflicts().into_iter().map(|c| (c.file, c.kind)).collect(),
+            // Files only one terminal is working on take that terminal's colour
+            owners: workspace
+                .file_owners()
+                .into_iter()
+                .filter_map(|(path, owners)| match owners.as_slice() {
+                    [owner] => terminal_ids.iter().position(|id| id == owner).map(|i| (path, terminal_color(i))),
+                    _ => None,
+                })
+                .collect(),
+        };
 
-        frame.render_widget(list, area);
+        // Fill the whole area with the explorer's light background
+        frame.render_widget(Block::default().style(Style::default().bg(Color::White)), area);
+        self.file_tree.draw(frame, area, &decorations);
     }
 
-    fn draw_git_panel(&self, frame: &mut Frame, area: Rect, _workspace: &WorkspaceManager) {
-        let block = Block::default()
-            .title("Git")
-            .borders(Borders::ALL)
-            .style(Style::default().fg(Color::Black).bg(Color::White));
-
-        // TODO: Implement actual git status
-        let items = vec![
-            ListItem::new("Changes:"),
-            ListItem::new(Line::from(vec![
-                Span::styled("M ", Style::default().fg(Color::Yellow)),
-                Span::raw("src/main.rs"),
-            ])),
-            ListItem::new(Line::from(vec![
-                Span::styled("A ", Style::default().fg(Color::Green)),
-                Span::raw("src/test.rs"),
-            ])),
-            ListItem::new(""),
-            ListItem::new("Timeline:"),
-            ListItem::new("10:45 commit"),
-            ListItem::new("10:32 edit"),
-        ];
-
-        let list = List::new(items).block(block);
-
-        frame.render_widget(list, area);
+    fn draw_git_panel(&self, frame: &mut Frame, area: Rect, workspace: &WorkspaceManager) {
+        self.git_status.draw(frame, area, workspace.project_dir());
     }
 
     fn draw_footer(&self, frame: &mut Frame, area: Rect, state: &AppState) {
@@ -314,41 +393,41 @@ impl Ui {
             AppState::Visual => "VISUAL",
         };
 
-        let footer_text = vec![
+        let key = Style::default().fg(self.theme.key);
+        let mut footer_text = vec![
             Span::raw("["),
-            Span::styled("Alt+I", Style::default().fg(Color::Blue)),
+            Span::styled("Alt+I", key),
             Span::raw(" Insert] ["),
-            Span::styled("jj/jk", Style::default().fg(Color::Blue)),
+            Span::styled("jj/jk", key),
             Span::raw(" Normal] ["),
-            Span::styled("Ctrl+T", Style::default().fg(Color::Blue)),
+            Span::styled("Ctrl+T", key),
             Span::raw(" New] ["),
-            Span::styled("Ctrl+F", Style::default().fg(Color::Blue)),
+            Span::styled("Ctrl+F", key),
             Span::raw(" Files] ["),
-            Span::styled("?", Style::default().fg(Color::Blue)),
+            Span::styled("?", key),
             Span::raw(" Help] [Mode: "),
             Span::styled(mode_text, Style::default().fg(Color::Magenta)),
             Span::raw("]"),
         ];
+        if let Some(ref digits) = self.pane_number {
+            footer_text.push(Span::raw(" "));
+            footer_text.push(Span::styled(
+                format!("Pane: {}_", digits),
+                Style::default().fg(Color::White).bg(Color::Blue),
+            ));
+        }
+        if let Some(ref message) = self.status_message {
+            footer_text.push(Span::raw(" "));
+            footer_text.push(Span::styled(message.clone(), key));
+        }
 
-        let footer = Paragraph::new(Line::from(footer_text))
-            .style(Style::default().bg(Color::Gray).fg(Color::Black));
+        let footer = Paragraph::new(Line::from(footer_text)).style(self.theme.bar);
 
         frame.render_widget(footer, area);
     }
 
-    fn draw_command_line(&self, frame: &mut Frame, _size: Rect) {
-        let area = centered_rect(60, 3, frame.area());
-
-        let block = Block::default()
-            .title("Command")
-            .borders(Borders::ALL)
-            .style(Style::default().fg(Color::Blue).bg(Color::White));
-
-        let input = Paragraph::new(format!(":{}", self.command_buffer))
-            .block(block)
-            .style(Style::default());
-
-        frame.render_widget(input, area);
+    fn draw_command_line(&self, frame: &mut Frame, size: Rect) {
+        self.command_line.draw(frame, size);
     }
 
     fn draw_error(&self, frame: &mut Frame, _size: Rect, message: &str) {
@@ -384,13 +463,58 @@ impl Ui {
             "  Ctrl+T     - New terminal",
             "  Ctrl+W     - Close terminal/exit files",
             "  Ctrl+Q     - Quit application",
+            "  :attach    - Open a terminal in an existing worktree",
+            "  :worktree  - Dashboard of all terminal worktrees",
+            "  :worktrees - Manage worktrees kept after closing",
+            "  :timeline  - File changes, commits and terminal events",
+            "  :fanout    - Same task in n agents; compare and merge the winner",
+            "  Ctrl+P     - Find a file (:find), Tab for panes",
+            "  Ctrl+Space - Switch pane by title, branch, dir or output",
+            "  :monitor   - Alert on activity, silence, bell or a pattern",
+            "  Alt+A      - Jump to the next pane with an alert",
+            "  :record    - Record the pane to a .cast file; :play replays it",
+            "  :save-output - Save the scrollback to a file (-e keeps colours)",
+            "  :pipe-pane - Feed the pane's output to a command, e.g. cat >> log",
+            "  Alt+S      - Mirror typed keys to this pane (:sync all|profile|off)",
+            "  Alt+1..9   - Switch workspace; Alt+[ / Alt+] previous/next",
+            "  :workspace - new <name> [dir], move <ws>, rename <name>, close",
+            "  :broadcast - Type a line into synced panes, or all (-p same profile)",
+            "  F1-F10     - Pane 1-10; Alt+G then digits for any pane number",
+            "  :top       - CPU and memory of each pane's processes",
+            "  Alt+P      - Pane processes, ports and cwd; signal the job (:inspect)",
+            "  :config    - Edit the config in $EDITOR; saved changes apply live",
+            "  :config set <key> <value> - Change one setting, e.g. appearance.theme light",
+            "",
+            "Command line (:):",
+            "  Tab        - Complete commands, profiles, layouts, branches, paths",
+            "  Up/Down    - Search history by what is typed",
+            "  Ctrl+A/E/W/U/K - Start, end, delete word, kill to start/end",
+            "  :help cmd  - Usage of a command; quote arguments like a shell",
+            "",
+            "Integration (:merge, :rebase, :squash):",
+            "  o/t/b/a    - Take ours/theirs/both/base for a conflict",
+            "  Enter      - Commit once all conflicts are resolved",
+            "",
+            "Diff (:diff [staged|main]):",
+            "  j/k n/p    - Move by line / hunk",
+            "  Space      - Select line for partial staging",
+            "  s / u      - Stage / unstage hunk or selection",
+            "  Tab        - Switch staged/unstaged view",
             "",
             "File Explorer:",
             "  Ctrl+F     - Toggle focus to/from files",
             "  Ctrl+E     - Toggle file explorer visibility",
             "  j/k        - Navigate files (when focused)",
             "  h/l        - Collapse/expand folders",
-            "  Enter      - Open file in new terminal",
+            "  Enter      - Open file in the editor pane",
+            "  a / r / d  - New file (dir/ for a folder), rename, delete",
+            "  y / Y      - Copy relative / absolute path",
+            "  .          - Show/hide dotfiles",
+            "  w          - Switch root to the pane's worktree and back",
+            "",
+            "Links in terminal output:",
+            "  Click      - Open file:line in $EDITOR, or URL in browser",
+            "  Ctrl+O     - Label links, then press a label to open",
             "",
             "Modes:",
             "  Alt+I      - Insert mode (type in terminal)",
@@ -414,28 +538,36 @@ impl Ui {
         frame.render_widget(text, area);
     }
 
-    pub fn command_push(&mut self, c: char) {
-        self.command_buffer.push(c);
+    pub fn command_line_mut(&mut self) -> &mut components::CommandLineComponent {
+        &mut self.command_line
     }
 
-    pub fn command_backspace(&mut self) {
-        self.command_buffer.pop();
+    pub fn show_error(&mut self, message: &str) {
+        self.error_message = Some(message.to_string());
     }
 
-    pub fn get_command(&self) -> String {
-        self.command_buffer.clone()
+    pub fn clear_error(&mut self) {
+        self.error_message = None;
     }
 
-    pub fn clear_command(&mut self) {
-        self.command_buffer.clear();
+    pub fn has_error(&self) -> bool {
+        self.error_message.is_some()
     }
 
-    pub fn show_error(&mut self, message: &str) {
-        self.error_message = Some(message.to_string());
+    pub fn show_status(&mut self, message: &str) {
+        self.status_message = Some(message.to_string());
     }
 
-    pub fn clear_error(&mut self) {
-        self.error_message = None;
+    /// Remove `message` from the footer unless another status replaced it
+    pub fn clear_status(&mut self, message: &str) {
+        if self.status_message.as_deref() == Some(message) {
+            self.status_message = None;
+        }
+    }
+
+    /// Queue a toast in the notification area
+    pub fn notify(&mut self, level: components::NotificationLevel, message: impl Into<String>) {
+        self.notifications.push(level, message.into());
     }
 
     pub fn toggle_help(&mut self) {
@@ -450,142 +582,355 @@ impl Ui {
         self.show_git_panel = !self.show_git_panel;
     }
 
+    pub fn is_git_panel_visible(&self) -> bool {
+        self.show_git_panel
+    }
+
+    pub fn git_status_mut(&mut self) -> &mut components::GitStatusComponent {
+        &mut self.git_status
+    }
+
+    pub fn set_hovered_link(&mut self, link: Option<(TerminalId, u16, Range<usize>)>) {
+        self.hovered_link = link;
+    }
+
+    pub fn set_alerts(&mut self, alerts: HashMap<TerminalId, AlertKind>) {
+        self.alerts = alerts;
+    }
+
+    pub fn set_workspace_tabs(&mut self, tabs: Vec<(String, bool)>, active: usize) {
+        self.workspace_tabs = tabs;
+        self.active_workspace = active;
+    }
+
+    pub fn set_overload(&mut self, overload: Option<String>) {
+        self.overload = overload;
+    }
+
+    pub fn set_pane_number(&mut self, digits: Option<String>) {
+        self.pane_number = digits;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn set_border_type(&mut self, border_type: BorderType) {
+        self.border_type = border_type;
+    }
+
+    /// Show or hide the side panels, as saved in the config
+    pub fn set_panels(&mut self, file_explorer: bool, git_panel: bool) {
+        self.show_file_explorer = file_explorer;
+        self.show_git_panel = git_panel;
+    }
+
+    /// Label the links on a terminal's screen so one can be picked by key
+    pub fn show_link_hints(&mut self, terminal_id: TerminalId, links: Vec<(u16, OutputLink)>) {
+        if links.is_empty() {
+            self.show_status("No file references or URLs on screen");
+            return;
+        }
+        let hints = HINT_KEYS
+            .chars()
+            .zip(links)
+            .map(|(key, (row, link))| (key, row, link))
+            .collect();
+        self.link_hints = Some((terminal_id, hints));
+    }
+
+    pub fn is_link_hints_visible(&self) -> bool {
+        self.link_hints.is_some()
+    }
+
+    /// Close the hints, returning the link labelled `key` if any
+    pub fn pick_link_hint(&mut self, key: char) -> Option<(TerminalId, OutputLink)> {
+        let (terminal_id, hints) = self.link_hints.take()?;
+        hints
+            .into_iter()
+            .find(|(k, _, _)| *k == key)
+            .map(|(_, _, link)| (terminal_id, link))
+    }
+
     pub fn toggle_file_explorer(&mut self) {
         self.show_file_explorer = !self.show_file_explorer;
     }
 
-    pub fn show_worktree_info(&self, _workspace: &WorkspaceManager) {
-        // TODO: Implement worktree info display
+    pub fn show_worktree_info(&mut self, workspace: &WorkspaceManager) {
+        self.worktree_dashboard.open(worktree_rows(workspace), workspace.git_manager().available_worktree_states());
     }
 
-    pub fn show_commit_interface(&self) {
-        // TODO: Implement commit interface
+    pub fn refresh_worktree_info(&mut self, workspace: &WorkspaceManager) {
+        if self.worktree_dashboard.is_open() {
+            self.worktree_dashboard.refresh(worktree_rows(workspace), workspace.git_manager().available_worktree_states());
+        }
     }
 
-    pub fn show_config_editor(&self, _config: &AppConfig) {
-        // TODO: Implement config editor
+    pub fn is_worktree_info_visible(&self) -> bool {
+        self.worktree_dashboard.is_open()
     }
 
-    pub fn file_explorer_move_up(&mut self) {
-        if self.file_explorer_selected > 0 {
-            self.file_explorer_selected -= 1;
-        }
+    pub fn worktree_dashboard_mut(&mut self) -> &mut components::WorktreeDashboardComponent {
+        &mut self.worktree_dashboard
     }
 
-    pub fn file_explorer_move_down(&mut self) {
-        if self.file_explorer_selected < self.file_tree.len() - 1 {
-            self.file_explorer_selected += 1;
+    pub fn show_fanout(&mut self, fanout: &Fanout) {
+        self.fanout_board.open(fanout.clone());
+    }
+
+    pub fn refresh_fanout(&mut self, fanout: &Fanout) {
+        if self.fanout_board.is_open() {
+            self.fanout_board.refresh(fanout.clone());
         }
     }
 
-    pub fn file_explorer_toggle_expand(&mut self) {
-        if self.file_explorer_selected < self.file_tree.len() {
-            let selected_idx = self.file_explorer_selected;
-            let item = self.file_tree[selected_idx].clone();
+    pub fn is_fanout_visible(&self) -> bool {
+        self.fanout_board.is_open()
+    }
 
-            if item.is_dir {
-                let new_state = !item.is_expanded;
-                self.file_tree[selected_idx].is_expanded = new_state;
+    pub fn fanout_board_mut(&mut self) -> &mut components::FanoutBoardComponent {
+        &mut self.fanout_board
+    }
 
-                if new_state {
-                    // Expanding - load directory contents
-                    self.load_directory_contents(selected_idx);
-                } else {
-                    // Collapsing - remove child items
-                    self.collapse_directory(selected_idx);
-                }
-            }
+    pub fn show_resources(&mut self, rows: Vec<components::ResourceRow>, system: SystemLoad) {
+        let overloaded = self.overload.is_some();
+        self.resource_monitor.open(rows, system, overloaded);
+    }
+
+    pub fn refresh_resources(&mut self, rows: Vec<components::ResourceRow>, system: SystemLoad) {
+        if self.resource_monitor.is_open() {
+            let overloaded = self.overload.is_some();
+            self.resource_monitor.refresh(rows, system, overloaded);
         }
     }
 
-    fn load_directory_contents(&mut self, dir_idx: usize) {
-        let dir_item = &self.file_tree[dir_idx];
-        let dir_path = &dir_item.path;
-        let dir_depth = dir_item.depth;
+    pub fn is_resources_visible(&self) -> bool {
+        self.resource_monitor.is_open()
+    }
 
-        // Read directory contents
-        if let Ok(entries) = fs::read_dir(dir_path) {
-            let mut items_to_insert = Vec::new();
+    pub fn resource_monitor_mut(&mut self) -> &mut components::ResourceMonitorComponent {
+        &mut self.resource_monitor
+    }
 
-            // Collect and sort entries
-            let mut entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
-            entries.sort_by_key(|e| {
-                let is_dir = e.file_type().map(|t| t.is_dir()).unwrap_or(false);
-                let name = e.file_name();
-                (!is_dir, name)  // Directories first, then files
-            });
+    pub fn show_process_inspector(&mut self, terminal_id: TerminalId, title: String, processes: PaneProcesses) {
+        self.process_inspector.open(terminal_id, title, processes);
+    }
 
-            for entry in entries {
-                let path = entry.path();
-                let name = entry.file_name().to_string_lossy().to_string();
-                let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+    pub fn is_process_inspector_visible(&self) -> bool {
+        self.process_inspector.is_open()
+    }
 
-                // Skip hidden files starting with .
-                if name.starts_with('.') {
-                    continue;
-                }
+    pub fn process_inspector_mut(&mut self) -> &mut components::ProcessInspectorComponent {
+        &mut self.process_inspector
+    }
 
-                items_to_insert.push(FileTreeItem {
-                    name: if is_dir { format!("{}/", name) } else { name },
-                    is_dir,
-                    is_expanded: false,
-                    depth: dir_depth + 1,
-                    path: path.to_string_lossy().to_string(),
-                });
-            }
+    pub fn show_timeline(&mut self, workspace: &WorkspaceManager) {
+        self.timeline.open(
+            workspace.timeline(None),
+            timeline_panes(workspace),
+            workspace.project_dir().to_path_buf(),
+        );
+    }
 
-            // Insert items after the parent directory
-            let insert_pos = dir_idx + 1;
-            for (i, item) in items_to_insert.into_iter().enumerate() {
-                self.file_tree.insert(insert_pos + i, item);
-            }
+    pub fn refresh_timeline(&mut self, workspace: &WorkspaceManager) {
+        if self.timeline.is_open() {
+            self.timeline.refresh(workspace.timeline(None), timeline_panes(workspace));
         }
     }
 
-    fn collapse_directory(&mut self, dir_idx: usize) {
-        let dir_depth = self.file_tree[dir_idx].depth;
+    pub fn is_timeline_visible(&self) -> bool {
+        self.timeline.is_open()
+    }
 
-        // Remove all items with depth > dir_depth that come after dir_idx
-        let mut i = dir_idx + 1;
-        while i < self.file_tree.len() {
-            if self.file_tree[i].depth > dir_depth {
-                self.file_tree.remove(i);
-            } else {
-                break;  // Reached a sibling or parent level item
-            }
-        }
+    pub fn timeline_mut(&mut self) -> &mut components::TimelineComponent {
+        &mut self.timeline
     }
 
-    pub fn file_explorer_open(&mut self) -> Option<String> {
-        if self.file_explorer_selected < self.file_tree.len() {
-            let item = &self.file_tree[self.file_explorer_selected];
-            if !item.is_dir {
-                return Some(item.path.clone());
-            } else {
-                // Toggle expansion for directories
-                self.file_explorer_toggle_expand();
-            }
-        }
-        None
+    /// Open the fuzzy finder over the explorer's root and the open panes
+    pub fn show_finder(&mut self, mode: components::FinderMode, workspace: &WorkspaceManager) {
+        let panes = workspace
+            .terminals()
+            .into_iter()
+            .enumerate()
+            .map(|(i, t)| components::FinderPane {
+                number: i + 1,
+                output: workspace
+                    .get_terminal_emulator(t.id)
+                    .map(|em| em.read().recent_output(FINDER_OUTPUT_LINES))
+                    .unwrap_or_default(),
+                id: t.id,
+                title: t.title,
+                branch: t.branch,
+                working_dir: t.working_dir,
+            })
+            .collect();
+        self.finder.open(mode, self.file_tree.root().to_path_buf(), panes);
+    }
+
+    pub fn is_finder_visible(&self) -> bool {
+        self.finder.is_open()
+    }
+
+    pub fn finder_mut(&mut self) -> &mut components::FuzzyFinderComponent {
+        &mut self.finder
+    }
+
+    /// Pick up background indexing and ranking results
+    pub fn poll_finder(&mut self) -> bool {
+        self.finder.poll()
+    }
+
+    pub fn show_diff(&mut self, title: String, side: DiffSide, hunks: Vec<DiffHunk>) {
+        self.diff_view.open(title, side, hunks);
+    }
+
+    pub fn is_diff_visible(&self) -> bool {
+        self.diff_view.is_open()
+    }
+
+    pub fn diff_view(&self) -> &components::DiffViewComponent {
+        &self.diff_view
+    }
+
+    pub fn diff_view_mut(&mut self) -> &mut components::DiffViewComponent {
+        &mut self.diff_view
+    }
+
+    pub fn show_conflicts(&mut self, pending: PendingMerge) {
+        self.conflict_view.open(pending);
+    }
+
+    pub fn show_close_prompt(&mut self, terminal_id: TerminalId, branch: String, state: WorktreeState) {
+        self.close_prompt.open(terminal_id, branch, state);
+    }
+
+    pub fn is_close_prompt_visible(&self) -> bool {
+        self.close_prompt.is_open()
+    }
+
+    pub fn close_prompt_mut(&mut self) -> &mut components::ClosePromptComponent {
+        &mut self.close_prompt
+    }
+
+    pub fn show_worktree_manager(&mut self, entries: Vec<(DiscoveredWorktree, Option<WorktreeState>)>) {
+        self.worktree_manager.open(entries);
+    }
+
+    pub fn is_worktree_manager_visible(&self) -> bool {
+        self.worktree_manager.is_open()
+    }
+
+    pub fn worktree_manager_mut(&mut self) -> &mut components::WorktreeManagerComponent {
+        &mut self.worktree_manager
+    }
+
+    pub fn is_conflict_view_visible(&self) -> bool {
+        self.conflict_view.is_open()
+    }
+
+    pub fn conflict_view_mut(&mut self) -> &mut components::ConflictViewComponent {
+        &mut self.conflict_view
+    }
+
+    pub fn show_commit_interface(&self) {
+        // TODO: Implement commit interface
+    }
+
+    pub fn file_explorer_move_up(&mut self) {
+        self.file_tree.move_up();
+    }
+
+    pub fn file_explorer_move_down(&mut self) {
+        self.file_tree.move_down();
+    }
+
+    pub fn file_explorer_toggle_expand(&mut self) {
+        self.file_tree.toggle_selected();
+    }
+
+    /// The selected file, if any; directories toggle instead
+    pub fn file_explorer_open(&mut self) -> Option<PathBuf> {
+        self.file_tree.open_selected()
     }
 
     pub fn get_file_explorer_area(&self) -> Option<Rect> {
-        self.file_explorer_area
+        self.file_tree.area()
     }
 
-    pub fn handle_file_explorer_click(&mut self, x: u16, y: u16) {
-        if let Some(area) = self.file_explorer_area {
-            // Calculate which item was clicked based on y position
-            let relative_y = y.saturating_sub(area.y + 1);  // +1 for the border
-            let clicked_index = relative_y as usize;
+    pub fn handle_file_explorer_click(&mut self, _x: u16, y: u16) {
+        self.file_tree.click(y);
+    }
 
-            // Check if click is within the visible items
-            if clicked_index < self.file_tree.len() {
-                self.file_explorer_selected = clicked_index;
-                // Double-click logic could be added here to open files
-            }
-        }
+    pub fn is_file_explorer_visible(&self) -> bool {
+        self.show_file_explorer
+    }
+
+    pub fn file_tree_mut(&mut self) -> &mut components::FileTreeComponent {
+        &mut self.file_tree
+    }
+}
+
+/// Open panes the timeline can name and filter by
+fn timeline_panes(workspace: &WorkspaceManager) -> Vec<components::timeline::TimelinePane> {
+    workspace
+        .terminals()
+        .into_iter()
+        .map(|t| (t.id, t.title, t.working_dir))
+        .collect()
+}
+
+/// Dashboard rows: each worktree with the title of the pane that owns it
+fn worktree_rows(workspace: &WorkspaceManager) -> Vec<(String, WorktreeOverview)> {
+    let terminals = workspace.terminals();
+    workspace
+        .git_manager()
+        .worktree_overviews()
+        .into_iter()
+        .map(|overview| {
+            let pane = terminals
+                .iter()
+                .find(|t| t.id == overview.info.terminal_id)
+                .map(|t| t.title.clone())
+                .unwrap_or_default();
+            (pane, overview)
+        })
+        .collect()
+}
+
+/// Colour identifying the terminal at `index`, used for its pane title
+/// and the files it owns in the explorer
+fn terminal_color(index: usize) -> Color {
+    const PALETTE: [Color; 6] = [
+        Color::Rgb(128, 0, 128),
+        Color::Rgb(0, 120, 120),
+        Color::Rgb(0, 110, 0),
+        Color::Rgb(0, 70, 160),
+        Color::Rgb(150, 110, 0),
+        Color::Rgb(140, 60, 90),
+    ];
+    PALETTE[index % PALETTE.len()]
+}
+
+/// Pane title with the worktree branch and its merge state
+fn pane_title(info: &crate::workspace::TerminalInfo) -> String {
+    let mut title = info.title.clone();
+    if let Some(ref branch) = info.branch {
+        title.push_str(&format!(" [{}]", branch));
+    }
+    match info.merge_status {
+        Some(MergeStatus::Merged) => title.push_str(" ✓ merged"),
+        Some(MergeStatus::Conflict { .. }) => title.push_str(" ✗ conflict"),
+        Some(MergeStatus::Unmerged) | None => {}
+    }
+    if info.behind > 0 {
+        title.push_str(&format!(" ↓{}", info.behind));
+    }
+    if info.conflicting_files > 0 {
+        title.push_str(&format!(" ⚠ {} will conflict", info.conflicting_files));
+    } else if info.shared_files > 0 {
+        title.push_str(&format!(" ⚠ {} shared", info.shared_files));
     }
+    title
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
@@ -0,0 +1,61 @@
+use crate::config::BorderStyle;
+use ratatui::{
+    style::{Color, Style},
+    widgets::BorderType,
+};
+
+/// Colours of rgb's own chrome, picked by `appearance.theme`; what runs in
+/// the panes keeps its own colours
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// Header and footer bars
+    pub bar: Style,
+    /// Keys named in the bars
+    pub key: Color,
+    pub active_border: Color,
+    pub inactive_border: Color,
+}
+
+impl Theme {
+    pub const NAMES: [&'static str; 3] = ["dark", "light", "contrast"];
+
+    pub fn named(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "dark" => Some(Self::default()),
+            "light" => Some(Self {
+                bar: Style::default().bg(Color::White).fg(Color::Black),
+                key: Color::Blue,
+                active_border: Color::Blue,
+                inactive_border: Color::DarkGray,
+            }),
+            "contrast" => Some(Self {
+                bar: Style::default().bg(Color::Black).fg(Color::White),
+                key: Color::Yellow,
+                active_border: Color::Yellow,
+                inactive_border: Color::White,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            bar: Style::default().bg(Color::Gray).fg(Color::Black),
+            key: Color::Blue,
+            active_border: Color::Blue,
+            inactive_border: Color::Gray,
+        }
+    }
+}
+
+/// Pane borders for `layout.border_style`
+pub fn border_type(style: &BorderStyle) -> BorderType {
+    match style {
+        BorderStyle::Rounded => BorderType::Rounded,
+        BorderStyle::Double => BorderType::Double,
+        BorderStyle::Thick => BorderType::Thick,
+        BorderStyle::Plain => BorderType::Plain,
+    }
+}
@@ -1,17 +1,33 @@
+use super::theme::Theme;
 use crate::terminal::TerminalEmulator;
+use crate::workspace::AlertKind;
 use parking_lot::RwLock;
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Widget},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Widget},
 };
+use std::ops::Range;
 use std::sync::Arc;
 
 pub struct TerminalWidget {
     emulator: Arc<RwLock<TerminalEmulator>>,
     active: bool,
     show_cursor: bool,
+    title: String,
+    accent: Option<Color>,
+    /// Row and columns of the link under the mouse
+    hovered_link: Option<(u16, Range<usize>)>,
+    /// Hint labels drawn over link starts as (row, column, key)
+    link_hints: Vec<(u16, usize, char)>,
+    /// Unseen monitor alert, shown on the border
+    alert: Option<AlertKind>,
+    /// In the group that typed keys are mirrored to
+    synchronized: bool,
+    theme: Theme,
+    border_type: BorderType,
 }
 
 impl TerminalWidget {
@@ -20,6 +36,14 @@ impl TerminalWidget {
             emulator,
             active: false,
             show_cursor: true,
+            title: String::new(),
+            accent: None,
+            hovered_link: None,
+            link_hints: Vec::new(),
+            alert: None,
+            synchronized: false,
+            theme: Theme::default(),
+            border_type: BorderType::Plain,
         }
     }
 
@@ -28,6 +52,47 @@ impl TerminalWidget {
         self
     }
 
+    pub fn title(mut self, title: String) -> Self {
+        self.title = title;
+        self
+    }
+
+    /// Marks the title with the colour this terminal's files get elsewhere
+    pub fn accent(mut self, color: Color) -> Self {
+        self.accent = Some(color);
+        self
+    }
+
+    pub fn hovered_link(mut self, link: Option<(u16, Range<usize>)>) -> Self {
+        self.hovered_link = link;
+        self
+    }
+
+    pub fn link_hints(mut self, hints: Vec<(u16, usize, char)>) -> Self {
+        self.link_hints = hints;
+        self
+    }
+
+    pub fn alert(mut self, alert: Option<AlertKind>) -> Self {
+        self.alert = alert;
+        self
+    }
+
+    pub fn synchronized(mut self, synchronized: bool) -> Self {
+        self.synchronized = synchronized;
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn border_type(mut self, border_type: BorderType) -> Self {
+        self.border_type = border_type;
+        self
+    }
+
     pub fn show_cursor(mut self, show: bool) -> Self {
         self.show_cursor = show;
         self
@@ -39,16 +104,44 @@ impl Widget for TerminalWidget {
         tracing::debug!("TerminalWidget::render called with area: {:?}", area);
 
         // Create border
-        let border_style = if self.active {
-            Style::default().fg(Color::Blue)
-        } else {
-            Style::default().fg(Color::Gray)
+        let sync_style = Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD);
+        let border_style = match (self.alert, self.active) {
+            (Some(AlertKind::Bell | AlertKind::Match), _) => {
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+            }
+            (Some(AlertKind::Activity | AlertKind::Silence), _) => {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            }
+            (None, _) if self.synchronized => sync_style,
+            (None, true) => Style::default().fg(self.theme.active_border),
+            (None, false) => Style::default().fg(self.theme.inactive_border),
         };
 
+        let title = match (self.active, self.title.is_empty()) {
+            (true, true) => "Active Terminal".to_string(),
+            (false, true) => "Terminal".to_string(),
+            (true, false) => format!("* {}", self.title),
+            (false, false) => self.title.clone(),
+        };
+        let mut title = match self.accent {
+            Some(color) => Line::from(vec![Span::styled("■ ", Style::default().fg(color)), Span::raw(title)]),
+            None => Line::from(title),
+        };
+        if self.synchronized {
+            title.spans.push(Span::styled(" [sync]", sync_style.add_modifier(Modifier::REVERSED)));
+        }
+        if let Some(alert) = self.alert {
+            title.spans.push(Span::styled(
+                format!(" [{}]", alert.label()),
+                border_style.add_modifier(Modifier::REVERSED),
+            ));
+        }
+
         let block = Block::default()
             .borders(Borders::ALL)
+            .border_type(self.border_type)
             .border_style(border_style)
-            .title(if self.active { "Active Terminal" } else { "Terminal" });
+            .title(title);
 
         let inner_area = block.inner(area);
         block.render(area, buf);
@@ -137,7 +230,28 @@ impl Widget for TerminalWidget {
             }
         }
 
-        // Remove the debug message - no longer needed
+        if let Some((row, columns)) = &self.hovered_link {
+            for x in columns.clone() {
+                if *row < inner_area.height && (x as u16) < inner_area.width {
+                    if let Some(cell) = buf.cell_mut((inner_area.x + x as u16, inner_area.y + row)) {
+                        cell.set_style(cell.style().add_modifier(Modifier::UNDERLINED));
+                    }
+                }
+            }
+        }
+        for &(row, column, key) in &self.link_hints {
+            if row < inner_area.height && (column as u16) < inner_area.width {
+                if let Some(cell) = buf.cell_mut((inner_area.x + column as u16, inner_area.y + row)) {
+                    cell.set_char(key);
+                    cell.set_style(
+                        Style::default()
+                            .fg(Color::Black)
+                            .bg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    );
+                }
+            }
+        }
 
         // Draw cursor if active and show_cursor is true
         if self.active && self.show_cursor {
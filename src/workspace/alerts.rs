@@ -0,0 +1,147 @@
+use regex::Regex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    /// New output
+    Activity,
+    /// No output for the configured time
+    Silence,
+    Bell,
+    /// Output matched a watched pattern
+    Match,
+}
+
+impl AlertKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertKind::Activity => "activity",
+            AlertKind::Silence => "silence",
+            AlertKind::Bell => "bell",
+            AlertKind::Match => "match",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub kind: AlertKind,
+    /// What happened, e.g. the matching line
+    pub detail: String,
+    pub at: Instant,
+}
+
+/// What a pane is watched for, and what has been seen of it so far
+#[derive(Debug, Clone, Default)]
+pub struct PaneMonitor {
+    pub activity: bool,
+    pub silence: Option<Duration>,
+    pub bell: bool,
+    pub patterns: Vec<Regex>,
+    /// Raised and not yet looked at
+    pub alert: Option<Alert>,
+    /// The first check only records where the pane stands
+    primed: bool,
+    seen_output: Option<Instant>,
+    seen_bell: Option<Instant>,
+    silence_alerted: bool,
+    /// Lines that matched a pattern in the last scan
+    matched: Vec<String>,
+}
+
+impl PaneMonitor {
+    /// Watched conditions in words, e.g. `bell, silence 30s`
+    pub fn describe(&self) -> String {
+        let mut watched = Vec::new();
+        if self.activity {
+            watched.push("activity".to_string());
+        }
+        if let Some(after) = self.silence {
+            watched.push(format!("silence {}s", after.as_secs()));
+        }
+        if self.bell {
+            watched.push("bell".to_string());
+        }
+        for pattern in &self.patterns {
+            watched.push(format!("match /{}/", pattern.as_str()));
+        }
+        if watched.is_empty() {
+            "nothing".to_string()
+        } else {
+            watched.join(", ")
+        }
+    }
+
+    /// Compare the pane with what was seen last time and raise an alert if
+    /// a watched condition has occurred. `recent_output` is only read when
+    /// there is new output to search.
+    pub fn check(
+        &mut self,
+        last_output: Option<Instant>,
+        last_bell: Option<Instant>,
+        recent_output: impl FnOnce() -> Vec<String>,
+    ) -> Option<Alert> {
+        let new_output = last_output != self.seen_output;
+        let new_bell = last_bell != self.seen_bell;
+        self.seen_output = last_output;
+        self.seen_bell = last_bell;
+        if new_output {
+            self.silence_alerted = false;
+        }
+
+        let matched = if new_output && !self.patterns.is_empty() {
+            let lines: Vec<String> = recent_output()
+                .into_iter()
+                .filter(|line| self.patterns.iter().any(|p| p.is_match(line)))
+                .collect();
+            let fresh = lines
+                .iter()
+                .find(|line| !self.matched.contains(line))
+                .or_else(|| lines.last().filter(|_| lines.len() > self.matched.len()))
+                .cloned();
+            self.matched = lines;
+            fresh
+        } else {
+            None
+        };
+
+        if !self.primed {
+            self.primed = true;
+            return None;
+        }
+
+        let (kind, detail) = if let Some(line) = matched {
+            (AlertKind::Match, line.chars().filter(|c| !c.is_control()).collect::<String>().trim().to_string())
+        } else if self.bell && new_bell {
+            (AlertKind::Bell, "rang the bell".to_string())
+        } else if let (Some(after), Some(at)) = (self.silence, last_output) {
+            if self.silence_alerted || at.elapsed() < after {
+                return self.activity_alert(new_output);
+            }
+            self.silence_alerted = true;
+            (AlertKind::Silence, format!("silent for {}s", after.as_secs()))
+        } else {
+            return self.activity_alert(new_output);
+        };
+        self.raise(kind, detail)
+    }
+
+    fn activity_alert(&mut self, new_output: bool) -> Option<Alert> {
+        // Activity is only news while the pane isn't already flagged
+        if self.activity && new_output && self.alert.is_none() {
+            return self.raise(AlertKind::Activity, "new output".to_string());
+        }
+        None
+    }
+
+    fn raise(&mut self, kind: AlertKind, detail: String) -> Option<Alert> {
+        let alert = Alert { kind, detail, at: Instant::now() };
+        self.alert = Some(alert.clone());
+        Some(alert)
+    }
+
+    /// The pane has been looked at
+    pub fn acknowledge(&mut self) {
+        self.alert = None;
+    }
+}
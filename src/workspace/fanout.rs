@@ -0,0 +1,130 @@
+use super::TerminalId;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Output quiet for this long means an agent is waiting for input
+pub const IDLE_AFTER: Duration = Duration::from_secs(5);
+
+/// Summary lines of common test runners and what they report, failures
+/// first so a line mentioning both counts as failing
+static TEST_SUMMARIES: Lazy<Vec<(Regex, TestStatus)>> = Lazy::new(|| {
+    [
+        // cargo test
+        (r"test result: FAILED", TestStatus::Failing),
+        (r"test result: ok", TestStatus::Passing),
+        // pytest
+        (r"^=+ .*\b\d+ (failed|error)", TestStatus::Failing),
+        (r"^=+ .*\b\d+ passed", TestStatus::Passing),
+        // jest and vitest
+        (r"^\s*Tests?:?\s+.*\b\d+ failed", TestStatus::Failing),
+        (r"^\s*Tests?:?\s+.*\b\d+ passed", TestStatus::Passing),
+        // mocha
+        (r"^\s*\d+ failing\b", TestStatus::Failing),
+        (r"^\s*\d+ passing\b", TestStatus::Passing),
+        // go test
+        (r"^(FAIL|--- FAIL)\b", TestStatus::Failing),
+        (r"^(ok|PASS)\s", TestStatus::Passing),
+    ]
+    .into_iter()
+    .map(|(pattern, status)| (Regex::new(pattern).unwrap(), status))
+    .collect()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentActivity {
+    /// Nothing printed yet
+    Starting,
+    Busy,
+    Idle,
+    Exited,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestStatus {
+    /// No test run seen in the output
+    Unknown,
+    Passing,
+    Failing,
+}
+
+/// One agent of a fanout, in its own worktree and pane
+#[derive(Debug, Clone)]
+pub struct Agent {
+    pub terminal_id: TerminalId,
+    pub title: String,
+    pub branch: String,
+    pub activity: AgentActivity,
+    pub last_output: Option<Instant>,
+    /// Outcome of the latest test run in the agent's output
+    pub tests: TestStatus,
+    /// Files the worktree changes against main, committed or not
+    pub changed_files: Vec<PathBuf>,
+    /// Prompt still to be typed, for profiles that read it from stdin
+    pub pending_prompt: Option<String>,
+}
+
+/// Agents started together on one task by `:fanout`
+#[derive(Debug, Clone)]
+pub struct Fanout {
+    pub profile: String,
+    pub prompt: String,
+    /// Commit every agent's worktree starts from
+    pub base: String,
+    pub started: Instant,
+    pub agents: Vec<Agent>,
+    /// Agent whose work was merged into main
+    pub winner: Option<TerminalId>,
+}
+
+impl Agent {
+    pub fn new(terminal_id: TerminalId, title: String, branch: String, pending_prompt: Option<String>) -> Self {
+        Self {
+            terminal_id,
+            title,
+            branch,
+            activity: AgentActivity::Starting,
+            last_output: None,
+            tests: TestStatus::Unknown,
+            changed_files: Vec::new(),
+            pending_prompt,
+        }
+    }
+
+    /// Update activity from whether the process runs and when it last
+    /// printed something
+    pub fn observe(&mut self, alive: bool, last_output: Option<Instant>) {
+        self.last_output = last_output;
+        self.activity = match (alive, last_output) {
+            (false, _) => AgentActivity::Exited,
+            (true, None) => AgentActivity::Starting,
+            (true, Some(at)) if at.elapsed() < IDLE_AFTER => AgentActivity::Busy,
+            (true, Some(_)) => AgentActivity::Idle,
+        };
+    }
+}
+
+impl Fanout {
+    pub fn agent(&self, terminal_id: TerminalId) -> Option<&Agent> {
+        self.agents.iter().find(|a| a.terminal_id == terminal_id)
+    }
+
+    pub fn agent_mut(&mut self, terminal_id: TerminalId) -> Option<&mut Agent> {
+        self.agents.iter_mut().find(|a| a.terminal_id == terminal_id)
+    }
+}
+
+/// Result of the most recent test run summarised in `lines`, oldest first
+pub fn test_status(lines: &[String]) -> TestStatus {
+    lines
+        .iter()
+        .rev()
+        .find_map(|line| {
+            TEST_SUMMARIES
+                .iter()
+                .find(|(pattern, _)| pattern.is_match(line))
+                .map(|(_, status)| *status)
+        })
+        .unwrap_or(TestStatus::Unknown)
+}
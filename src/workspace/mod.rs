@@ -1,43 +1,77 @@
-use crate::git::GitManager;
-use crate::monitor::FileTracker;
+mod alerts;
+mod fanout;
+mod timeline;
+
+use crate::config::GitConfig;
+use crate::git::{ChangedFile, GitEvent, GitManager, GitWorker, MergeStatus, WorktreeDisposition};
+use crate::monitor::{ChangeType, ConflictKind, ConflictResolution, FileChange, FileConflict, FileTracker};
 use crate::terminal::TerminalEmulator;
 use anyhow::Result;
 use crossterm::event::KeyEvent;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use uuid::Uuid;
 
+pub use alerts::{Alert, AlertKind, PaneMonitor};
+pub use fanout::{test_status, Agent, AgentActivity, Fanout, TestStatus, IDLE_AFTER};
+pub use timeline::{TimelineEntry, TimelineEvent};
+
 pub type TerminalId = Uuid;
 
+/// Terminal lifecycle events kept for the timeline
+const MAX_TERMINAL_EVENTS: usize = 500;
+
 pub struct WorkspaceManager {
     terminals: Arc<RwLock<Vec<TerminalSession>>>,
     active_terminal: Arc<RwLock<Option<TerminalId>>>,
     project_dir: PathBuf,
     git_manager: Arc<GitManager>,
     file_tracker: Arc<FileTracker>,
+    /// Most panes the workspace holds; 0 for no limit
     max_terminals: usize,
     redraw_tx: Arc<RwLock<Option<mpsc::UnboundedSender<()>>>>,
+    git_worker: RwLock<Option<GitWorker>>,
+    /// Terminal created, exited and closed events for the timeline
+    terminal_events: RwLock<Vec<TimelineEntry>>,
+    /// Attributed file changes, folded into each session's `active_files`
+    file_changes: Mutex<broadcast::Receiver<FileChange>>,
+    auto_worktree: bool,
+    /// Panes that receive keys typed into any one of them
+    synchronized: RwLock<HashSet<TerminalId>>,
+    /// Control socket handed to new panes as `RGB_SOCKET`
+    control_socket: RwLock<Option<PathBuf>>,
 }
 
 pub struct TerminalSession {
     pub id: TerminalId,
     pub title: String,
+    /// Profile the pane was opened with, else the program it runs or
+    /// `shell`; the `{profile}` of its branch
+    pub profile: String,
     pub emulator: Arc<RwLock<TerminalEmulator>>,
     pub working_dir: PathBuf,
     pub active_files: HashSet<PathBuf>,
     pub worktree_path: Option<PathBuf>,
+    /// The process has exited and that was recorded on the timeline
+    pub exited: bool,
 }
 
 impl WorkspaceManager {
-    pub fn new(project_dir: PathBuf) -> Result<Self> {
-        let git_manager = Arc::new(GitManager::new(&project_dir)?);
-        // Skip file tracker for now - it might be blocking
-        // let file_tracker = Arc::new(FileTracker::new(&project_dir)?);
-        let file_tracker = Arc::new(FileTracker::new_disabled());
+    pub fn new(project_dir: PathBuf, git_config: &GitConfig, max_terminals: usize) -> Result<Self> {
+        // Watcher events carry canonical paths
+        let project_dir = project_dir.canonicalize().unwrap_or(project_dir);
+        let git_manager = Arc::new(GitManager::new(&project_dir, git_config)?);
+        let file_tracker = Arc::new(FileTracker::new(&project_dir).unwrap_or_else(|e| {
+            tracing::warn!("File watching disabled: {}", e);
+            FileTracker::new_disabled()
+        }));
+        file_tracker.set_conflict_resolution(git_config.conflict_resolution);
+
+        let file_changes = Mutex::new(file_tracker.subscribe());
 
         Ok(Self {
             terminals: Arc::new(RwLock::new(Vec::new())),
@@ -45,49 +79,125 @@ impl WorkspaceManager {
             project_dir,
             git_manager,
             file_tracker,
-            max_terminals: 10,
+            max_terminals,
             redraw_tx: Arc::new(RwLock::new(None)),
+            git_worker: RwLock::new(None),
+            terminal_events: RwLock::new(Vec::new()),
+            file_changes,
+            auto_worktree: git_config.auto_worktree,
+            synchronized: RwLock::new(HashSet::new()),
+            control_socket: RwLock::new(None),
         })
     }
 
-    pub async fn create_terminal(&self, command: Option<String>) -> Result<TerminalId> {
-        let terminals = self.terminals.read();
-        if terminals.len() >= self.max_terminals {
-            anyhow::bail!("Maximum number of terminals ({}) reached", self.max_terminals);
+    /// `profile` is the configured profile the command comes from, if any;
+    /// it names the pane's branch
+    pub async fn create_terminal(&self, command: Option<String>, profile: Option<&str>) -> Result<TerminalId> {
+        self.spawn_terminal(command, profile, Placement::NewWorktree).await
+    }
+
+    /// Open a terminal in a new worktree branched from `base`, so several
+    /// terminals can start from the same commit
+    pub async fn create_terminal_at(
+        &self,
+        base: &str,
+        command: Option<String>,
+        profile: Option<&str>,
+    ) -> Result<TerminalId> {
+        self.spawn_terminal(command, profile, Placement::Fork(base)).await
+    }
+
+    /// Open a terminal in `dir` without giving it a worktree of its own
+    pub async fn create_terminal_in(&self, dir: PathBuf, command: Option<String>) -> Result<TerminalId> {
+        self.spawn_terminal(command, None, Placement::Directory(dir)).await
+    }
+
+    /// Open a terminal in an existing worktree or on an existing branch
+    pub async fn attach_terminal(
+        &self,
+        target: &str,
+        command: Option<String>,
+        profile: Option<&str>,
+    ) -> Result<TerminalId> {
+        if !self.git_manager.is_git_repo() {
+            anyhow::bail!("Not a git repository");
         }
-        drop(terminals);
+        self.spawn_terminal(command, profile, Placement::Attach(target)).await
+    }
+
+    async fn spawn_terminal(
+        &self,
+        command: Option<String>,
+        profile: Option<&str>,
+        placement: Placement<'_>,
+    ) -> Result<TerminalId> {
+        self.check_room()?;
 
         let id = Uuid::new_v4();
         let title = format!("Terminal {}", self.terminals.read().len() + 1);
 
         // If no command specified, pass empty string to let TerminalEmulator handle shell setup
-        let cmd = command.unwrap_or_else(|| String::new());
+        let cmd = command.unwrap_or_default();
+        let profile = profile.map_or_else(|| profile_name(&cmd), str::to_string);
 
         tracing::info!("Creating terminal with command: {:?}", cmd);
 
         // Create worktree if git is enabled
-        let worktree_path = if self.git_manager.is_git_repo() {
-            self.git_manager.create_worktree(id).await.ok()
-        } else {
-            None
+        let mut working_dir = self.project_dir.clone();
+        let worktree_path = match placement {
+            Placement::Attach(target) => Some(self.git_manager.attach_worktree(id, target).await?),
+            Placement::Fork(base) => Some(self.git_manager.create_worktree(id, &profile, Some(base)).await?),
+            Placement::NewWorktree if self.auto_worktree && self.git_manager.is_git_repo() => {
+                match self.git_manager.create_worktree(id, &profile, None).await {
+                    Ok(path) => Some(path),
+                    Err(e) => {
+                        tracing::warn!("Failed to create worktree: {}", e);
+                        None
+                    }
+                }
+            }
+            Placement::NewWorktree => None,
+            Placement::Directory(dir) => {
+                working_dir = dir;
+                None
+            }
         };
 
         // Determine working directory
-        let working_dir = worktree_path.clone().unwrap_or_else(|| self.project_dir.clone());
+        if let Some(ref path) = worktree_path {
+            working_dir = path.clone();
+        }
+
+        // Panes inherit the socket so `rgb ctl` in them reaches this rgb
+        let mut env = HashMap::new();
+        if let Some(path) = self.control_socket.read().as_ref() {
+            env.insert(crate::control::SOCKET_ENV.to_string(), path.display().to_string());
+        }
 
         // Create terminal emulator
-        let emulator = TerminalEmulator::new(&cmd, &working_dir, (80, 24))?;
+        let emulator = match TerminalEmulator::new(&cmd, &working_dir, (80, 24), env) {
+            Ok(emulator) => emulator,
+            Err(e) => {
+                if worktree_path.is_some() {
+                    self.git_manager.cleanup_worktree(id).await.ok();
+                }
+                return Err(e);
+            }
+        };
 
         // Create Arc for the emulator
+        let pid = emulator.pid();
         let emulator_arc = Arc::new(RwLock::new(emulator));
 
         let session = TerminalSession {
             id,
-            title,
+            title: title.clone(),
+            profile,
             emulator: emulator_arc,
             working_dir,
             active_files: HashSet::new(),
             worktree_path,
+            exited: false,
         };
 
         // Add to terminals list
@@ -98,60 +208,228 @@ impl WorkspaceManager {
             *self.active_terminal.write() = Some(id);
         }
 
+        self.record_event(id, TimelineEvent::TerminalCreated { title: title.clone() });
+
         // Start file tracking for this terminal
-        self.file_tracker.start_tracking_terminal(id);
+        self.file_tracker.start_tracking_terminal(id, Some(pid));
+        if let Some(path) = self.worktree_path(id) {
+            if let Err(e) = self.file_tracker.watch_root(&path, Some(id)) {
+                tracing::warn!("Failed to watch {}: {}", path.display(), e);
+            }
+        }
 
         Ok(id)
     }
 
+    /// Close a terminal, removing its worktree only if that loses no work
     pub async fn close_terminal(&self, id: TerminalId) -> Result<()> {
-        // Stop file tracking
-        self.file_tracker.stop_tracking_terminal(id);
+        if self.git_manager.is_git_repo() {
+            self.release_watched(id, self.git_manager.cleanup_worktree(id)).await?;
+        }
+        self.remove_terminal(id);
+        Ok(())
+    }
 
-        // Clean up worktree if exists
+    /// Close a terminal, handling its worktree's work as chosen by the user
+    pub async fn close_terminal_with(&self, id: TerminalId, disposition: WorktreeDisposition) -> Result<()> {
         if self.git_manager.is_git_repo() {
-            self.git_manager.cleanup_worktree(id).await?;
+            self.release_watched(id, self.git_manager.release_worktree(id, disposition))
+                .await?;
+        }
+        self.remove_terminal(id);
+        Ok(())
+    }
+
+    /// Stop watching a worktree while it is released so its removal does
+    /// not flood subscribers; watch it again if the worktree stays
+    async fn release_watched(&self, id: TerminalId, release: impl std::future::Future<Output = Result<()>>) -> Result<()> {
+        let path = self.worktree_path(id);
+        if let Some(path) = &path {
+            self.file_tracker.unwatch_root(path);
+        }
+
+        let result = release.await;
+        if let Some(path) = path.filter(|p| p.exists()) {
+            self.file_tracker.watch_root(&path, Some(id)).ok();
         }
+        result
+    }
+
+    pub fn worktree_path(&self, id: TerminalId) -> Option<PathBuf> {
+        self.terminals
+            .read()
+            .iter()
+            .find(|t| t.id == id)
+            .and_then(|t| t.worktree_path.clone())
+    }
+
+    /// Debounced file changes across the project and every worktree
+    pub fn subscribe_file_changes(&self) -> broadcast::Receiver<FileChange> {
+        self.file_tracker.subscribe()
+    }
+
+    fn remove_terminal(&self, id: TerminalId) {
+        // Stop file tracking
+        self.file_tracker.stop_tracking_terminal(id);
 
         // Shutdown the terminal emulator properly
         let mut terminals = self.terminals.write();
         if let Some(terminal) = terminals.iter_mut().find(|t| t.id == id) {
             terminal.emulator.write().shutdown();
+            self.record_event(id, TimelineEvent::TerminalClosed { title: terminal.title.clone() });
         }
 
         // Remove from terminals list
         terminals.retain(|t| t.id != id);
+        self.synchronized.write().remove(&id);
 
         // Update active terminal if needed
         let mut active = self.active_terminal.write();
         if active.as_ref() == Some(&id) {
             *active = terminals.first().map(|t| t.id);
         }
+    }
+
+    /// Fails when `general.max_terminals` panes are open
+    fn check_room(&self) -> Result<()> {
+        if self.max_terminals > 0 && self.terminals.read().len() >= self.max_terminals {
+            anyhow::bail!("Maximum number of terminals ({}) reached", self.max_terminals);
+        }
+        Ok(())
+    }
+
+    /// Move a pane, still running, to another workspace. Its worktree
+    /// moves with it within one repository; between repositories the
+    /// worktree is kept and the pane carries on in it unmanaged.
+    pub fn move_terminal(&self, id: TerminalId, to: &WorkspaceManager) -> Result<()> {
+        to.check_room()?;
+        let mut session = {
+            let mut terminals = self.terminals.write();
+            let index = terminals
+                .iter()
+                .position(|t| t.id == id)
+                .ok_or_else(|| anyhow::anyhow!("No such terminal"))?;
+            let session = terminals.remove(index);
+            let mut active = self.active_terminal.write();
+            if active.as_ref() == Some(&id) {
+                *active = terminals.get(index.min(terminals.len().saturating_sub(1))).map(|t| t.id);
+            }
+            session
+        };
+        self.synchronized.write().remove(&id);
+        self.file_tracker.stop_tracking_terminal(id);
+        if let Some(path) = &session.worktree_path {
+            self.file_tracker.unwatch_root(path);
+        }
 
+        if !self.git_manager.hand_over_worktree(id, &to.git_manager) {
+            session.worktree_path = None;
+        }
+        let pid = session.emulator.read().pid();
+        to.file_tracker.start_tracking_terminal(id, Some(pid));
+        if let Some(path) = &session.worktree_path {
+            if let Err(e) = to.file_tracker.watch_root(path, Some(id)) {
+                tracing::warn!("Failed to watch {}: {}", path.display(), e);
+            }
+        }
+        to.terminals.write().push(session);
+        *to.active_terminal.write() = Some(id);
         Ok(())
     }
 
     pub async fn close_active_terminal(&self) -> Result<()> {
-        if let Some(id) = *self.active_terminal.read() {
+        let active_id = *self.active_terminal.read();
+        if let Some(id) = active_id {
             self.close_terminal(id).await?;
         }
         Ok(())
     }
 
     pub fn terminals(&self) -> Vec<TerminalInfo> {
+        let conflicts = self.file_conflicts();
+        let count = |id: TerminalId, kind: ConflictKind| {
+            conflicts
+                .iter()
+                .filter(|c| c.kind == kind && c.terminal_ids.contains(&id))
+                .count()
+        };
+
         self.terminals
             .read()
             .iter()
-            .map(|t| TerminalInfo {
-                id: t.id,
-                title: t.title.clone(),
-                working_dir: t.working_dir.clone(),
-                active_files_count: t.active_files.len(),
-                has_worktree: t.worktree_path.is_some(),
+            .map(|t| {
+                let worktree = self.git_manager.worktree_info(t.id);
+                TerminalInfo {
+                    id: t.id,
+                    title: t.title.clone(),
+                    profile: t.profile.clone(),
+                    synchronized: self.synchronized.read().contains(&t.id),
+                    working_dir: t.working_dir.clone(),
+                    active_files_count: t.active_files.len(),
+                    has_worktree: t.worktree_path.is_some(),
+                    branch: worktree.as_ref().map(|w| w.branch.clone()),
+                    behind: worktree.as_ref().map(|w| w.behind).unwrap_or(0),
+                    shared_files: count(t.id, ConflictKind::SameFile),
+                    conflicting_files: count(t.id, ConflictKind::OverlappingLines),
+                    merge_status: worktree.map(|w| w.merge_status),
+                }
             })
             .collect()
     }
 
+    pub fn project_dir(&self) -> &Path {
+        &self.project_dir
+    }
+
+    pub fn git_manager(&self) -> Arc<GitManager> {
+        self.git_manager.clone()
+    }
+
+    /// Start syncing worktrees onto main and scanning them for conflicting
+    /// changes; a zero interval disables that part
+    pub fn start_git_worker(
+        &self,
+        sync_interval: Duration,
+        scan_interval: Duration,
+        events: mpsc::UnboundedSender<GitEvent>,
+    ) {
+        if !self.git_manager.is_git_repo() {
+            return;
+        }
+        *self.git_worker.write() = Some(GitWorker::spawn(
+            self.git_manager.clone(),
+            sync_interval,
+            scan_interval,
+            events,
+        ));
+    }
+
+    /// Record a scan of what each worktree changes; returns conflicts that
+    /// were not there before
+    pub fn apply_worktree_changes(&self, changes: Vec<(TerminalId, Vec<ChangedFile>)>) -> Vec<FileConflict> {
+        let before = self.file_conflicts();
+        for (terminal_id, files) in changes {
+            // Skip terminals closed while the scan ran
+            if self.terminals.read().iter().any(|t| t.id == terminal_id) {
+                self.file_tracker.set_worktree_changes(terminal_id, files);
+            }
+        }
+
+        self.file_conflicts()
+            .into_iter()
+            .filter(|c| !before.iter().any(|b| b.file == c.file && b.kind == c.kind))
+            .collect()
+    }
+
+    /// Files more than one terminal is changing
+    pub fn file_conflicts(&self) -> Vec<FileConflict> {
+        self.file_tracker.detect_conflicts()
+    }
+
+    pub fn conflict_resolution(&self) -> ConflictResolution {
+        self.file_tracker.conflict_resolution()
+    }
+
     pub fn active_terminal_id(&self) -> Option<TerminalId> {
         *self.active_terminal.read()
     }
@@ -214,6 +492,10 @@ impl WorkspaceManager {
         *self.redraw_tx.write() = Some(tx);
     }
 
+    pub fn set_control_socket(&self, path: PathBuf) {
+        *self.control_socket.write() = Some(path);
+    }
+
     fn signal_redraw(&self) {
         if let Some(ref tx) = *self.redraw_tx.read() {
             let _ = tx.send(());
@@ -224,7 +506,7 @@ impl WorkspaceManager {
         // Get the active terminal ID first, then drop the lock immediately
         let active_id = {
             let guard = self.active_terminal.read();
-            guard.clone()
+            *guard
         };
 
         if let Some(id) = active_id {
@@ -240,6 +522,69 @@ impl WorkspaceManager {
             if let Some(emulator) = emulator {
                 emulator.write().handle_key_event(key)?;
             }
+
+            // Mirrored to the rest of its synchronized group. Each pane
+            // encodes the key itself, as its modes may differ.
+            if self.is_synchronized(id) {
+                for (other, emulator) in self.synchronized_emulators() {
+                    if other == id {
+                        continue;
+                    }
+                    let mut emulator = emulator.write();
+                    if emulator.is_alive() {
+                        if let Err(e) = emulator.handle_key_event(key) {
+                            tracing::warn!("Failed to mirror a key to {}: {}", other, e);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_synchronized(&self, id: TerminalId) -> bool {
+        self.synchronized.read().contains(&id)
+    }
+
+    /// Add a pane to the synchronized group or take it out; true if it is
+    /// now in the group
+    pub fn toggle_synchronized(&self, id: TerminalId) -> bool {
+        let mut synchronized = self.synchronized.write();
+        if synchronized.remove(&id) {
+            return false;
+        }
+        synchronized.insert(id);
+        true
+    }
+
+    /// Make `ids` the synchronized group; empty turns synchronizing off
+    pub fn set_synchronized(&self, ids: impl IntoIterator<Item = TerminalId>) {
+        *self.synchronized.write() = ids.into_iter().collect();
+    }
+
+    /// Open panes running the same profile as `id`, `id` included
+    pub fn panes_with_profile_of(&self, id: TerminalId) -> Vec<TerminalId> {
+        let terminals = self.terminals.read();
+        let Some(profile) = terminals.iter().find(|t| t.id == id).map(|t| t.profile.clone()) else {
+            return Vec::new();
+        };
+        terminals.iter().filter(|t| t.profile == profile).map(|t| t.id).collect()
+    }
+
+    fn synchronized_emulators(&self) -> Vec<(TerminalId, Arc<RwLock<TerminalEmulator>>)> {
+        let synchronized = self.synchronized.read();
+        self.terminals
+            .read()
+            .iter()
+            .filter(|t| synchronized.contains(&t.id))
+            .map(|t| (t.id, t.emulator.clone()))
+            .collect()
+    }
+
+    /// Write raw bytes to a terminal as if typed
+    pub fn send_input(&self, id: TerminalId, data: &[u8]) -> Result<()> {
+        if let Some(emulator) = self.get_terminal_emulator(id) {
+            emulator.write().write(data)?;
         }
         Ok(())
     }
@@ -248,21 +593,23 @@ impl WorkspaceManager {
         tracing::trace!("WorkspaceManager::update start");
 
         // Get terminal emulator references first, then drop the lock
-        let emulators: Vec<Arc<RwLock<TerminalEmulator>>> = {
+        let emulators: Vec<(TerminalId, Arc<RwLock<TerminalEmulator>>)> = {
             let terminals = self.terminals.read();
-            terminals.iter().map(|t| t.emulator.clone()).collect()
+            terminals.iter().map(|t| (t.id, t.emulator.clone())).collect()
         };
         // terminals lock is now dropped
 
         let mut had_output = false;
 
         // Update each terminal emulator without holding the terminals lock
-        for emulator in emulators {
+        for (id, emulator) in emulators {
             // Try to get a write lock - if we can't, skip this update
             if let Some(mut em) = emulator.try_write() {
                 // Skip dead terminals to avoid infinite EOF reading
                 if !em.is_alive() {
                     tracing::trace!("Skipping update for dead terminal");
+                    drop(em);
+                    self.record_exit(id);
                     continue;
                 }
 
@@ -289,56 +636,87 @@ impl WorkspaceManager {
         }
         tracing::debug!("All terminal emulators updated");
 
-        // Skip file tracking for now - might be blocking
-        // self.file_tracker.update()?;
-        tracing::trace!("File tracker skipped");
+        self.apply_file_changes();
 
-        // Check for file conflicts
-        let conflicts = self.detect_file_conflicts();
-        if !conflicts.is_empty() {
-            // TODO: Handle conflicts (show warnings, etc.)
-            tracing::warn!("File conflicts detected: {:?}", conflicts);
-        }
+        Ok(())
+    }
 
-        // Skip git worktree sync for now - it might be blocking
-        // TODO: Fix git worktree sync
-        /*
-        if self.git_manager.is_git_repo() {
-            for terminal in self.terminals.read().iter() {
-                if terminal.worktree_path.is_some() {
-                    self.git_manager.sync_worktree(terminal.id).await.ok();
+    fn record_event(&self, terminal_id: TerminalId, event: TimelineEvent) {
+        let mut events = self.terminal_events.write();
+        events.push(TimelineEntry::terminal(terminal_id, event));
+        let excess = events.len().saturating_sub(MAX_TERMINAL_EVENTS);
+        events.drain(..excess);
+    }
+
+    /// Record a terminal's process exiting, once
+    fn record_exit(&self, id: TerminalId) {
+        let title = {
+            let mut terminals = self.terminals.write();
+            match terminals.iter_mut().find(|t| t.id == id && !t.exited) {
+                Some(session) => {
+                    session.exited = true;
+                    session.title.clone()
                 }
+                None => return,
             }
-        }
-        */
-
-        Ok(())
+        };
+        self.record_event(id, TimelineEvent::TerminalExited { title });
     }
 
-    fn detect_file_conflicts(&self) -> Vec<FileConflict> {
-        let mut file_terminals: HashMap<PathBuf, Vec<TerminalId>> = HashMap::new();
-        let terminals = self.terminals.read();
+    /// File changes, commits, merges and terminal lifecycle events, newest
+    /// first, optionally only those of one terminal
+    pub fn timeline(&self, terminal_id: Option<TerminalId>) -> Vec<TimelineEntry> {
+        let mut entries: Vec<TimelineEntry> = self
+            .file_tracker
+            .get_file_changes(None)
+            .into_iter()
+            .map(TimelineEntry::from)
+            .chain(self.git_manager.activity().into_iter().map(TimelineEntry::from))
+            .chain(self.terminal_events.read().iter().cloned())
+            .filter(|entry| terminal_id.is_none() || entry.terminal_id == terminal_id)
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.time));
+        entries
+    }
 
-        for terminal in terminals.iter() {
-            for file in &terminal.active_files {
-                file_terminals
-                    .entry(file.clone())
-                    .or_default()
-                    .push(terminal.id);
-            }
-        }
+    fn apply_file_changes(&self) {
+        let mut rx = self.file_changes.lock();
+        loop {
+            let change = match rx.try_recv() {
+                Ok(change) => change,
+                Err(broadcast::error::TryRecvError::Lagged(missed)) => {
+                    tracing::debug!("Missed {} file change(s) for active files", missed);
+                    continue;
+                }
+                Err(_) => break,
+            };
+            let Some(terminal_id) = change.terminal_id else {
+                continue;
+            };
 
-        let mut conflicts = Vec::new();
-        for (file, terminal_ids) in file_terminals {
-            if terminal_ids.len() > 1 {
-                conflicts.push(FileConflict {
-                    file,
-                    terminal_ids,
-                });
+            let mut terminals = self.terminals.write();
+            let Some(session) = terminals.iter_mut().find(|t| t.id == terminal_id) else {
+                continue;
+            };
+            match change.change_type {
+                ChangeType::Created | ChangeType::Modified => {
+                    session.active_files.insert(change.file_path);
+                }
+                ChangeType::Deleted => {
+                    session.active_files.remove(&change.file_path);
+                }
+                ChangeType::Renamed { from, to } => {
+                    session.active_files.remove(&from);
+                    session.active_files.insert(to);
+                }
             }
         }
+    }
 
-        conflicts
+    /// Terminals working on each file, keyed by path relative to the
+    /// project (or worktree) root
+    pub fn file_owners(&self) -> HashMap<PathBuf, Vec<TerminalId>> {
+        self.file_tracker.file_owners()
     }
 
     pub fn get_terminal_emulator(&self, id: TerminalId) -> Option<Arc<RwLock<TerminalEmulator>>> {
@@ -356,7 +734,7 @@ impl WorkspaceManager {
         // Get active ID and drop lock immediately
         let active_id = {
             let guard = self.active_terminal.read();
-            guard.clone()
+            *guard
         }?;
 
         // Now get the emulator without holding the active_terminal lock
@@ -376,13 +754,38 @@ impl WorkspaceManager {
 pub struct TerminalInfo {
     pub id: TerminalId,
     pub title: String,
+    pub profile: String,
+    /// Keys typed into the pane go to the whole synchronized group
+    pub synchronized: bool,
     pub working_dir: PathBuf,
     pub active_files_count: usize,
     pub has_worktree: bool,
+    pub branch: Option<String>,
+    pub merge_status: Option<MergeStatus>,
+    /// Commits on main not yet synced into the worktree
+    pub behind: usize,
+    /// Files another terminal also changes, in separate places
+    pub shared_files: usize,
+    /// Files whose changes overlap another terminal's and will conflict
+    pub conflicting_files: usize,
 }
 
-#[derive(Debug, Clone)]
-pub struct FileConflict {
-    pub file: PathBuf,
-    pub terminal_ids: Vec<TerminalId>,
-}
\ No newline at end of file
+/// Where a new terminal runs
+enum Placement<'a> {
+    NewWorktree,
+    /// A new worktree starting at this commit, regardless of `auto_worktree`
+    Fork(&'a str),
+    Attach(&'a str),
+    Directory(PathBuf),
+}
+
+/// Profile of a pane not opened from a configured one: the command's
+/// program name, or `shell`
+fn profile_name(command: &str) -> String {
+    command
+        .split_whitespace()
+        .next()
+        .and_then(|program| Path::new(program).file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "shell".to_string())
+}
@@ -0,0 +1,60 @@
+use super::TerminalId;
+use crate::git::{GitActivity, GitActivityKind};
+use crate::monitor::{ChangeType, FileChange};
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// One entry of the activity timeline
+#[derive(Debug, Clone)]
+pub struct TimelineEntry {
+    pub time: Instant,
+    pub terminal_id: Option<TerminalId>,
+    pub event: TimelineEvent,
+}
+
+#[derive(Debug, Clone)]
+pub enum TimelineEvent {
+    FileChanged { path: PathBuf, change: ChangeType },
+    Commit { commit: String, summary: String },
+    Merged { branch: String, commit: String },
+    TerminalCreated { title: String },
+    TerminalExited { title: String },
+    TerminalClosed { title: String },
+}
+
+impl TimelineEntry {
+    pub fn terminal(terminal_id: TerminalId, event: TimelineEvent) -> Self {
+        Self {
+            time: Instant::now(),
+            terminal_id: Some(terminal_id),
+            event,
+        }
+    }
+}
+
+impl From<FileChange> for TimelineEntry {
+    fn from(change: FileChange) -> Self {
+        Self {
+            time: change.timestamp,
+            terminal_id: change.terminal_id,
+            event: TimelineEvent::FileChanged {
+                path: change.file_path,
+                change: change.change_type,
+            },
+        }
+    }
+}
+
+impl From<GitActivity> for TimelineEntry {
+    fn from(activity: GitActivity) -> Self {
+        let event = match activity.kind {
+            GitActivityKind::Commit { commit, summary } => TimelineEvent::Commit { commit, summary },
+            GitActivityKind::Merged { branch, commit } => TimelineEvent::Merged { branch, commit },
+        };
+        Self {
+            time: activity.time,
+            terminal_id: activity.terminal_id,
+            event,
+        }
+    }
+}